@@ -0,0 +1,157 @@
+//! Plays seeded four player random games, once timed and once counting heap
+//! allocations, so changes to the hot path show up in both.
+//!
+//! `cargo bench --bench step`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use catanatron_rs::game::{GameConfig, GamePhase, GameState};
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+/// Counts every allocation and reallocation made through the system
+/// allocator.
+struct Counting;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Measures heap allocations instead of time.
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> u64 {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: u64) -> u64 {
+        ALLOCATIONS.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        a + b
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationFormatter
+    }
+}
+
+struct AllocationFormatter;
+
+impl ValueFormatter for AllocationFormatter {
+    fn scale_values(&self, _typical: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        let per = match throughput {
+            Throughput::Elements(count) | Throughput::Bytes(count) => *count as f64,
+            Throughput::BytesDecimal(count) => *count as f64,
+        };
+        for value in values {
+            *value /= per;
+        }
+        "allocs/step"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+const TURNS_LIMIT: u32 = 500;
+
+fn new_game(seed: u64) -> GameState {
+    GameState::new(GameConfig {
+        num_players: 4,
+        seed,
+        ..GameConfig::default()
+    })
+}
+
+/// Plays random legal actions until the game ends, returning the number of
+/// steps taken.
+fn play_out(state: &mut GameState, rng: &mut StdRng) -> u64 {
+    let mut steps = 0;
+    while !matches!(state.phase, GamePhase::Completed { .. }) && state.turn < TURNS_LIMIT {
+        let action = state.legal_actions().choose(rng).cloned().unwrap();
+        state.step(action).unwrap();
+        steps += 1;
+    }
+    steps
+}
+
+fn random_games<M: Measurement>(c: &mut Criterion<M>, name: &str) {
+    let mut group = c.benchmark_group(name);
+    for seed in [1, 2] {
+        // Per step figures, from the length of this seed's game.
+        let steps = play_out(&mut new_game(seed), &mut StdRng::seed_from_u64(seed));
+        group.throughput(Throughput::Elements(steps));
+        group.bench_function(format!("seed_{seed}"), |b| {
+            b.iter_batched(
+                || (new_game(seed), StdRng::seed_from_u64(seed)),
+                |(mut state, mut rng)| {
+                    play_out(&mut state, &mut rng);
+                    state
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn timed(c: &mut Criterion) {
+    random_games(c, "random_game");
+}
+
+fn counted(c: &mut Criterion<Allocations>) {
+    random_games(c, "random_game_allocations");
+}
+
+criterion_group!(time, timed);
+criterion_group! {
+    name = allocations;
+    config = Criterion::default().with_measurement(Allocations);
+    targets = counted
+}
+criterion_main!(time, allocations);
@@ -0,0 +1,262 @@
+use std::str::FromStr;
+use std::time::Instant;
+
+use catanatron_rs::MapType;
+use catanatron_rs::cli::players::PlayerInstance;
+use catanatron_rs::cli::{create_player, print_player_help};
+use catanatron_rs::game::action::{ActionPayload, GameAction};
+use catanatron_rs::game::{DiceConfig, Game, GameConfig, RngStream};
+use catanatron_rs::types::{ActionType, Color};
+use clap::Parser;
+
+#[derive(Debug, Parser, Clone)]
+#[command(name = "catanatron-analyze-openings")]
+#[command(
+    about = "Rank every legal first settlement by rolling out games with a bot from each placement"
+)]
+struct Args {
+    /// Bot player code used for every seat during rollouts
+    #[arg(short = 'b', long, default_value = "R")]
+    bot: String,
+
+    /// Bot-specific parameters (comma-separated)
+    #[arg(long, default_value = "")]
+    bot_params: String,
+
+    /// Number of players
+    #[arg(short = 'p', long, default_value_t = 4)]
+    num_players: usize,
+
+    /// Rollouts per candidate placement
+    #[arg(short = 'n', long, default_value_t = 20)]
+    rollouts: u32,
+
+    /// Seed fixing the map; rollout i of every candidate uses stream i + 1
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
+    #[arg(long, default_value = "BASE")]
+    map: String,
+
+    /// Victory points needed to win
+    #[arg(long, default_value_t = 10)]
+    vps_to_win: u8,
+
+    /// Dice model: FAIR, BALANCED[:RESHUFFLE_AT], or SCRIPTED:D1-D2,D1-D2,...
+    #[arg(long, default_value = "FAIR")]
+    dice: String,
+
+    /// Only print the best N placements (0 prints all)
+    #[arg(long, default_value_t = 0)]
+    top: usize,
+
+    /// Number of worker threads; candidates are split between them
+    #[arg(long, default_value_t = 1)]
+    workers: usize,
+
+    /// Show player codes and exit
+    #[arg(long)]
+    help_players: bool,
+}
+
+struct OpeningResult {
+    action: GameAction,
+    wins: u32,
+    total_vps: u32,
+    rollouts: u32,
+}
+
+impl OpeningResult {
+    fn win_rate(&self) -> f64 {
+        if self.rollouts == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.rollouts as f64
+    }
+
+    fn avg_vps(&self) -> f64 {
+        if self.rollouts == 0 {
+            return 0.0;
+        }
+        self.total_vps as f64 / self.rollouts as f64
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if args.help_players {
+        print_player_help();
+        return;
+    }
+
+    if args.num_players < 2 || args.num_players > 4 {
+        eprintln!("Error: Must specify 2-4 players");
+        std::process::exit(1);
+    }
+
+    let bot_params: Vec<&str> = if args.bot_params.is_empty() {
+        Vec::new()
+    } else {
+        args.bot_params.split(',').collect()
+    };
+
+    let mut players: Vec<PlayerInstance> = Vec::new();
+    for color in Color::ORDERED.iter().take(args.num_players) {
+        match create_player(&args.bot, *color, bot_params.clone()) {
+            Some(player) => players.push(player),
+            None => {
+                eprintln!("Error: Unknown bot code '{}'", args.bot);
+                eprintln!("Use --help-players to see available codes");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let dice = DiceConfig::from_str(&args.dice).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let config = GameConfig {
+        num_players: args.num_players,
+        map_type,
+        vps_to_win: args.vps_to_win,
+        seed: args.seed,
+        dice,
+        ..GameConfig::default()
+    };
+
+    let game = Game::new(config);
+    let seat = game.state.current_player;
+    let color = game.state.players[seat].color;
+    let candidates: Vec<GameAction> = game
+        .state
+        .legal_actions()
+        .iter()
+        .filter(|action| action.action_type == ActionType::BuildSettlement)
+        .cloned()
+        .collect();
+
+    println!(
+        "Analyzing {} opening placements for {:?} ({} rollouts each, bot {})",
+        candidates.len(),
+        color,
+        args.rollouts,
+        args.bot
+    );
+
+    let start = Instant::now();
+    let workers = args.workers.max(1);
+    let chunk_size = candidates.len().div_ceil(workers).max(1);
+    let mut results: Vec<OpeningResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let game = &game;
+                let mut players = players.clone();
+                let args = &args;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|action| evaluate_opening(game, &mut players, action, color, args))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    results.sort_by(|a, b| {
+        b.win_rate()
+            .total_cmp(&a.win_rate())
+            .then(b.avg_vps().total_cmp(&a.avg_vps()))
+    });
+
+    print_results(&game, &results, args.top);
+    println!("\nFinished in {:.2?}", start.elapsed());
+}
+
+fn evaluate_opening(
+    game: &Game,
+    players: &mut [PlayerInstance],
+    action: &GameAction,
+    color: Color,
+    args: &Args,
+) -> OpeningResult {
+    let mut result = OpeningResult {
+        action: action.clone(),
+        wins: 0,
+        total_vps: 0,
+        rollouts: 0,
+    };
+
+    for rollout in 0..args.rollouts {
+        let mut rollout_game = game.copy();
+        // Every candidate sees the same rollout streams, so differences
+        // between placements aren't drowned out by dice luck.
+        rollout_game
+            .state
+            .reseed(RngStream::new(args.seed, rollout as u64 + 1));
+        rollout_game.execute(action.clone());
+        let outcome = rollout_game.play(players);
+
+        if outcome.winner == Some(color) {
+            result.wins += 1;
+        }
+        if let Some(player) = outcome.players.iter().find(|p| p.color == color) {
+            result.total_vps += player.victory_points as u32;
+        }
+        result.rollouts += 1;
+    }
+
+    result
+}
+
+fn print_results(game: &Game, results: &[OpeningResult], top: usize) {
+    println!(
+        "\n{:<6} {:<8} {:<10} {:<8} {:<8} Resources",
+        "Rank", "Node", "Win Rate", "Avg VP", "Prod"
+    );
+    println!("{}", "-".repeat(70));
+
+    let shown = if top == 0 {
+        results.len()
+    } else {
+        top.min(results.len())
+    };
+    for (rank, result) in results.iter().take(shown).enumerate() {
+        let ActionPayload::Node(node) = result.action.payload else {
+            continue;
+        };
+        let production = game.state.map.node_production.get(&node);
+        let total: f32 = production.map(|p| p.values().sum()).unwrap_or(0.0);
+        let resources = production
+            .map(|p| {
+                p.iter()
+                    .map(|(resource, value)| format!("{:?}:{:.3}", resource, value))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+
+        println!(
+            "{:<6} {:<8} {:<9.1}% {:<8.2} {:<8.3} {}",
+            rank + 1,
+            node,
+            result.win_rate() * 100.0,
+            result.avg_vps(),
+            total,
+            resources
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+
+use catanatron_rs::MapType;
+use catanatron_rs::cli::players::PlayerInstance;
+use catanatron_rs::cli::{create_player, print_player_help};
+use catanatron_rs::game::action::GameAction;
+use catanatron_rs::game::{Game, GameConfig, RngStream};
+use catanatron_rs::players::opening_book::{BookEntry, BookKey, OpeningBook, layout_hash};
+use catanatron_rs::types::{ActionType, Color};
+use clap::Parser;
+
+#[derive(Debug, Parser, Clone)]
+#[command(name = "catanatron-build-opening-book")]
+#[command(
+    about = "Build an opening book from the setup placements of self-play games and how they fared"
+)]
+struct Args {
+    /// Bot player code used for every seat during self-play
+    #[arg(short = 'b', long, default_value = "R")]
+    bot: String,
+
+    /// Bot-specific parameters (comma-separated)
+    #[arg(long, default_value = "")]
+    bot_params: String,
+
+    /// Number of players
+    #[arg(short = 'p', long, default_value_t = 4)]
+    num_players: usize,
+
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
+    #[arg(long, default_value = "BASE")]
+    map: String,
+
+    /// First map seed; layouts are generated from seeds SEED..SEED+MAPS
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+
+    /// Number of map layouts to cover
+    #[arg(long, default_value_t = 1)]
+    maps: u64,
+
+    /// Self-play games per layout
+    #[arg(short = 'n', long, default_value_t = 200)]
+    games: u32,
+
+    /// Placements seen fewer times than this are left out of the book
+    #[arg(long, default_value_t = 5)]
+    min_samples: u32,
+
+    /// Victory points needed to win
+    #[arg(long, default_value_t = 10)]
+    vps_to_win: u8,
+
+    /// Where to write the book (JSON)
+    #[arg(short = 'o', long, default_value = "opening_book.json")]
+    output: PathBuf,
+
+    /// Number of worker threads; layouts are split between them
+    #[arg(long, default_value_t = 1)]
+    workers: usize,
+
+    /// Show player codes and exit
+    #[arg(long)]
+    help_players: bool,
+}
+
+#[derive(Default)]
+struct Tally {
+    games: u32,
+    wins: u32,
+}
+
+type Stats = HashMap<(BookKey, GameAction), Tally>;
+
+fn main() {
+    let args = Args::parse();
+
+    if args.help_players {
+        print_player_help();
+        return;
+    }
+
+    if args.num_players < 2 || args.num_players > 4 {
+        eprintln!("Error: Must specify 2-4 players");
+        std::process::exit(1);
+    }
+
+    let bot_params: Vec<&str> = if args.bot_params.is_empty() {
+        Vec::new()
+    } else {
+        args.bot_params.split(',').collect()
+    };
+
+    let mut players: Vec<PlayerInstance> = Vec::new();
+    for color in Color::ORDERED.iter().take(args.num_players) {
+        match create_player(&args.bot, *color, bot_params.clone()) {
+            Some(player) => players.push(player),
+            None => {
+                eprintln!("Error: Unknown bot code '{}'", args.bot);
+                eprintln!("Use --help-players to see available codes");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    println!(
+        "Building opening book from {} layouts x {} games (bot {})",
+        args.maps, args.games, args.bot
+    );
+
+    let start = Instant::now();
+    let seeds: Vec<u64> = (args.seed..args.seed + args.maps).collect();
+    let workers = args.workers.max(1);
+    let chunk_size = seeds.len().div_ceil(workers).max(1);
+    let stats: Stats = std::thread::scope(|scope| {
+        let handles: Vec<_> = seeds
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let map_type = &map_type;
+                let mut players = players.clone();
+                let args = &args;
+                scope.spawn(move || {
+                    let mut stats = Stats::new();
+                    for &seed in chunk {
+                        play_layout(seed, map_type, &mut players, args, &mut stats);
+                    }
+                    stats
+                })
+            })
+            .collect();
+        let mut merged = Stats::new();
+        for handle in handles {
+            for (key, tally) in handle.join().unwrap() {
+                let entry = merged.entry(key).or_default();
+                entry.games += tally.games;
+                entry.wins += tally.wins;
+            }
+        }
+        merged
+    });
+
+    let book = select_entries(stats, args.min_samples);
+    if let Err(err) = book.save(&args.output) {
+        eprintln!("Error: could not write {}: {err}", args.output.display());
+        std::process::exit(1);
+    }
+    println!(
+        "Wrote {} entries to {} in {:.2?}",
+        book.len(),
+        args.output.display(),
+        start.elapsed()
+    );
+}
+
+fn play_layout(
+    seed: u64,
+    map_type: &MapType,
+    players: &mut [PlayerInstance],
+    args: &Args,
+    stats: &mut Stats,
+) {
+    let config = GameConfig {
+        num_players: args.num_players,
+        map_type: map_type.clone(),
+        vps_to_win: args.vps_to_win,
+        seed,
+        ..GameConfig::default()
+    };
+    let base = Game::new(config);
+    let layout = layout_hash(&base.state.map);
+
+    for game_idx in 0..args.games {
+        let mut game = base.copy();
+        game.state.reseed(RngStream::new(seed, game_idx as u64 + 1));
+        let winner = game.play(players).winner;
+
+        let mut placements = vec![0u8; args.num_players];
+        for action in game.state.action_log() {
+            if !matches!(
+                action.action_type,
+                ActionType::BuildSettlement | ActionType::BuildRoad
+            ) {
+                break;
+            }
+            let seat = action.player_index;
+            let key = BookKey {
+                layout,
+                seat,
+                placement: placements[seat],
+            };
+            placements[seat] += 1;
+            let tally = stats.entry((key, action.clone())).or_default();
+            tally.games += 1;
+            if winner == Some(game.state.players[seat].color) {
+                tally.wins += 1;
+            }
+        }
+    }
+}
+
+/// Keeps the placement with the best win rate for every key, among those
+/// seen at least `min_samples` times.
+fn select_entries(stats: Stats, min_samples: u32) -> OpeningBook {
+    let mut best: HashMap<BookKey, BookEntry> = HashMap::new();
+    for ((key, action), tally) in stats {
+        if tally.games < min_samples {
+            continue;
+        }
+        let candidate = BookEntry {
+            key,
+            action,
+            games: tally.games,
+            wins: tally.wins,
+        };
+        match best.get(&key) {
+            Some(current)
+                if current
+                    .win_rate()
+                    .total_cmp(&candidate.win_rate())
+                    .then(current.games.cmp(&candidate.games))
+                    .is_ge() => {}
+            _ => {
+                best.insert(key, candidate);
+            }
+        }
+    }
+
+    let mut book = OpeningBook::new();
+    for entry in best.into_values() {
+        book.insert(entry);
+    }
+    book
+}
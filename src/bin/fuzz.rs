@@ -0,0 +1,83 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use catanatron_rs::game::fuzz::{FuzzOptions, fuzz_game, random_config};
+use catanatron_rs::game::{GameConfig, RngStream};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+#[command(name = "catanatron-fuzz")]
+#[command(about = "Play random games from the engine's own legal actions and report rule bugs")]
+struct Args {
+    /// Number of games to play
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    games: u32,
+
+    /// Seed for choosing configurations and actions; game i uses stream i
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Steps before an unfinished game counts as stuck
+    #[arg(long, default_value_t = 50_000)]
+    max_steps: usize,
+
+    /// Skip the invariant check after every step
+    #[arg(long)]
+    no_invariants: bool,
+
+    /// Keep going after the first failure
+    #[arg(long)]
+    keep_going: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+    let options = FuzzOptions {
+        max_steps: args.max_steps,
+        check_invariants: !args.no_invariants,
+    };
+
+    let start = Instant::now();
+    let mut failures = 0;
+    let mut total_steps = 0;
+    for game in 0..args.games {
+        let mut rng = RngStream::new(args.seed, game as u64).to_rng();
+        let config = random_config(&mut rng);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            fuzz_game(config.clone(), &options, &mut rng)
+        }));
+        let message = match result {
+            Ok(Ok(report)) => {
+                total_steps += report.steps;
+                continue;
+            }
+            Ok(Err(failure)) => failure.to_string(),
+            Err(_) => "engine panicked".to_string(),
+        };
+        failures += 1;
+        report_failure(game, &args, &config, &message);
+        if !args.keep_going {
+            break;
+        }
+    }
+
+    println!(
+        "{} games, {} steps, {} failures in {:.2?}",
+        args.games,
+        total_steps,
+        failures,
+        start.elapsed()
+    );
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn report_failure(game: u32, args: &Args, config: &GameConfig, message: &str) {
+    eprintln!("Game {game} failed: {message}");
+    eprintln!("  reproduce with --seed {} (game index {game})", args.seed);
+    match serde_json::to_string(config) {
+        Ok(json) => eprintln!("  config: {json}"),
+        Err(err) => eprintln!("  config could not be serialized: {err}"),
+    }
+}
@@ -1,27 +1,47 @@
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
-use catanatron_rs::MapType;
 use catanatron_rs::cli::players::PlayerInstance;
-use catanatron_rs::cli::{HumanPlayer, create_player, print_player_help};
+use catanatron_rs::cli::{
+    AnnotationLog, HumanPlayer, TuiApp, notes_path, print_player_help, try_create_player,
+};
 use catanatron_rs::game::action::GameAction;
-use catanatron_rs::game::{Game, GameConfig};
-use catanatron_rs::players::BasePlayer;
+use catanatron_rs::game::codec::encode_actions;
+use catanatron_rs::game::{DiceConfig, Game, GameConfig, GameSnapshot, SetupMode};
+use catanatron_rs::players::{BasePlayer, WinProbEstimator};
 use catanatron_rs::types::Color;
+use catanatron_rs::{MapLayout, MapType};
 use clap::Parser;
+use rand::rngs::StdRng;
 
 #[derive(Clone)]
 enum UnifiedPlayer {
     Human(HumanPlayer),
-    Bot(PlayerInstance),
+    Bot(Box<PlayerInstance>),
 }
 
 impl BasePlayer for UnifiedPlayer {
-    fn decide(&self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
         match self {
             UnifiedPlayer::Human(p) => p.decide(game, actions),
             UnifiedPlayer::Bot(p) => p.decide(game, actions),
         }
     }
+
+    fn reseed(&mut self, rng: StdRng) {
+        match self {
+            UnifiedPlayer::Human(p) => p.reseed(rng),
+            UnifiedPlayer::Bot(p) => p.reseed(rng),
+        }
+    }
+
+    fn last_search_stats(&self) -> Option<&str> {
+        match self {
+            UnifiedPlayer::Human(_) => None,
+            UnifiedPlayer::Bot(p) => p.last_search_stats(),
+        }
+    }
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -40,14 +60,61 @@ struct Args {
     #[arg(long, default_value_t = 42)]
     seed: u64,
 
-    /// Map type: BASE, MINI, or TOURNAMENT
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
     #[arg(long, default_value = "BASE")]
     map: String,
 
+    /// Play this exact board: a board code of tiles, numbers and
+    /// optionally harbors, as printed at the start of a game (e.g.
+    /// DOWOBOHHSBSBHWHWSSW/6,3,11,9,4,5,9,12,11,4,8,10,5,2,6,3,8,10)
+    #[arg(long)]
+    board_code: Option<String>,
+
     /// Victory points needed to win
     #[arg(long, default_value_t = 10)]
     vps_to_win: u8,
 
+    /// Dice model: FAIR, BALANCED[:RESHUFFLE_AT], or SCRIPTED:D1-D2,D1-D2,...
+    #[arg(long, default_value = "FAIR")]
+    dice: String,
+
+    /// Initial placement: SNAKE (standard), FIXED (same order both rounds)
+    /// or RANDOM (placed by the engine)
+    #[arg(long, default_value = "SNAKE")]
+    setup: String,
+
+    /// Bot player code whose top picks are shown on your turns, without
+    /// being played for you
+    #[arg(long)]
+    advisor: Option<String>,
+
+    /// How many times you may press 'u' to take back your last action and
+    /// the bot's replies to it
+    #[arg(long, default_value_t = 3)]
+    takebacks: usize,
+
+    /// Show each player's win probability, estimated every turn from this
+    /// many random rollouts
+    #[arg(long)]
+    win_prob: Option<usize>,
+
+    /// Watch bots play both seats in the TUI instead of playing yourself
+    #[arg(long)]
+    spectate: bool,
+
+    /// Bot player code for Red when spectating (defaults to --bot)
+    #[arg(long)]
+    red_bot: Option<String>,
+
+    /// Delay between actions when spectating, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    delay_ms: u64,
+
+    /// Write the game's actions here when it ends. Notes taken with 'a'
+    /// go next to it, as <stem>.notes.json
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
     /// Show player codes and exit
     #[arg(long)]
     help_players: bool,
@@ -68,46 +135,107 @@ fn main() {
         args.bot_params.split(',').collect()
     };
 
-    let bot = match create_player(&args.bot, Color::Blue, bot_params) {
-        Some(player) => UnifiedPlayer::Bot(player),
-        None => {
-            eprintln!("Error: Unknown bot code '{}'", args.bot);
+    let bot = create_bot(&args.bot, Color::Blue, &bot_params, args.spectate);
+
+    // Create human player (always Red), or a second bot when spectating
+    let mut human_player = HumanPlayer::new(Color::Red);
+    if let Some(code) = &args.advisor {
+        let advisor = try_create_player(code, Color::Red, Vec::new()).unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
             eprintln!("Use --help-players to see available codes");
             std::process::exit(1);
-        }
+        });
+        human_player = human_player.with_advisor(code.clone(), advisor.boxed());
+    }
+    if let Some(rollouts) = args.win_prob {
+        human_player = human_player.with_estimator(WinProbEstimator::new(rollouts));
+    }
+    let human = if args.spectate {
+        let code = args.red_bot.as_deref().unwrap_or(&args.bot);
+        create_bot(code, Color::Red, &bot_params, true)
+    } else {
+        UnifiedPlayer::Human(human_player.clone())
     };
 
-    // Create human player (always Red)
-    let human = UnifiedPlayer::Human(HumanPlayer::new(Color::Red));
-
     // Create players array: human is always player 0 (Red), bot is player 1 (Blue)
-    let players = vec![human, bot];
+    let mut players = vec![human, bot];
 
-    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|_| {
-        eprintln!(
-            "Error: Invalid map type '{}'. Use BASE, MINI, or TOURNAMENT",
-            args.map
-        );
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let map_layout = args.board_code.as_ref().map(|code| {
+        let layout = MapLayout::from_board_code(code).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        });
+        if let Err(err) = layout.validate(map_type.template()) {
+            eprintln!("Error: board code does not fit the {map_type} map: {err}");
+            std::process::exit(1);
+        }
+        layout
+    });
+
+    let dice = DiceConfig::from_str(&args.dice).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let setup = SetupMode::from_str(&args.setup).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
         std::process::exit(1);
     });
 
     // Create game config for 2 players
     let config = GameConfig {
         num_players: 2,
-        map_type,
+        map_type: map_type.clone(),
+        map_layout,
         vps_to_win: args.vps_to_win,
         seed: args.seed,
+        dice,
+        setup,
+        ..GameConfig::default()
     };
 
+    if args.spectate {
+        let game = Game::new(config).with_event_log();
+        let mut app = TuiApp::spectator(game, Duration::from_millis(args.delay_ms));
+        if let Some(rollouts) = args.win_prob {
+            app = app.with_estimator(WinProbEstimator::new(rollouts));
+        }
+        if let Err(err) = app.spectate(&mut players) {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+        let game = app.into_game();
+        match game.winning_color() {
+            Some(winner_color) => println!("{:?} wins!", winner_color),
+            None => println!("No winner declared."),
+        }
+        print_final_stats(&game, &["RED BOT", "BLUE BOT"]);
+        return;
+    }
+
     println!("Starting game: You (Red) vs Bot (Blue)");
     println!(
         "Map: {:?}, Victory Points to Win: {}",
         map_type, args.vps_to_win
     );
-    println!("{}", "=".repeat(80));
 
     // Create game
-    let mut game = Game::new(config);
+    let mut game = Game::new(config).with_event_log();
+    game.seed_players(&mut players);
+    if let Some(code) = MapLayout::of_map(&game.state.map).board_code() {
+        println!("Board code: {code}");
+    }
+    println!("{}", "=".repeat(80));
+
+    // The game at the human's latest decisions, oldest first; the last one
+    // is the decision being made. Only as many as can still be taken back.
+    let mut takebacks_left = args.takebacks;
+    let mut decision_points: Vec<GameSnapshot> = Vec::new();
 
     // Game loop
     loop {
@@ -134,12 +262,40 @@ fn main() {
 
         if is_human_turn {
             // Human player's turn - show nothing before, display happens in HumanPlayer
+            // Positions behind a roll, card draw or steal can't be taken back.
+            decision_points.retain(|point| game.can_restore(point));
+            decision_points.push(game.snapshot());
+            if decision_points.len() > takebacks_left + 1 {
+                decision_points.remove(0);
+            }
+            human_player.offer_takebacks(takebacks_left.min(decision_points.len() - 1));
         } else {
             // Bot player's turn
             println!("\n🤖 Bot is thinking...");
         }
 
-        if let Some(action) = game.play_tick(&players) {
+        let played = game.play_tick(&mut players);
+        if human_player.quit_requested() {
+            println!("\nYou left the game.");
+            break;
+        }
+        if human_player.take_undo_request() {
+            decision_points.pop();
+            if let Some(snapshot) = decision_points.pop() {
+                match game.restore(snapshot) {
+                    Ok(()) => {
+                        takebacks_left -= 1;
+                        println!(
+                            "\n↶ Took back your last action ({takebacks_left} takebacks left)"
+                        );
+                    }
+                    Err(err) => println!("\nCan't take that back: {err}"),
+                }
+            }
+            continue;
+        }
+
+        if let Some(action) = played {
             if is_human_turn {
                 println!("\n→ You played: {:?}", action.action_type);
             } else {
@@ -148,15 +304,58 @@ fn main() {
         }
     }
 
-    // Final stats
+    print_final_stats(&game, &["YOU", "BOT"]);
+
+    if let Some(path) = &args.replay {
+        match save_replay(&game, human_player.notes(), path) {
+            Ok(()) => println!("\nReplay saved to {}", path.display()),
+            Err(err) => eprintln!("Error: could not save replay: {err}"),
+        }
+    }
+}
+
+/// Writes the encoded action log to `path` and any notes beside it.
+fn save_replay(game: &Game, notes: &AnnotationLog, path: &Path) -> std::io::Result<()> {
+    std::fs::write(path, encode_actions(game.state.action_log()))?;
+    if !notes.is_empty() {
+        let notes_file = notes_path(path);
+        notes.save(&notes_file)?;
+        println!("Notes saved to {}", notes_file.display());
+    }
+    Ok(())
+}
+
+/// `search_stats` keeps what searching bots looked at, for the spectator's
+/// search overlay.
+fn create_bot(code: &str, color: Color, params: &[&str], search_stats: bool) -> UnifiedPlayer {
+    match try_create_player(code, color, params.to_vec()) {
+        Ok(player) if search_stats => UnifiedPlayer::Bot(Box::new(player.with_search_stats())),
+        Ok(player) => UnifiedPlayer::Bot(Box::new(player)),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            eprintln!("Use --help-players to see available codes");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_final_stats(game: &Game, labels: &[&str]) {
     println!("\n{}", "=".repeat(80));
     println!("FINAL STATS:");
     println!("{}", "=".repeat(80));
 
-    for (idx, player) in game.state.players.iter().enumerate() {
-        let label = if idx == 0 { "YOU" } else { "BOT" };
+    for (player, label) in game.state.players.iter().zip(labels) {
         println!("\n{} ({:?}):", label, player.color);
-        println!("  Victory Points: {}", player.total_points());
+        let vps = player.vp_breakdown();
+        println!("  Victory Points: {}", vps.total());
+        println!(
+            "    settlements {}, cities {}, VP cards {}, longest road {}, largest army {}",
+            vps.settlements,
+            vps.cities,
+            vps.victory_point_cards,
+            vps.longest_road,
+            vps.largest_army
+        );
         println!("  Resources: {}", player.resources);
         println!("  Settlements: {}", player.settlements.len());
         println!("  Cities: {}", player.cities.len());
@@ -1 +1,94 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
+use catanatron_rs::MapType;
+use catanatron_rs::features::{FeatureCollection, build_board_tensor, collect_features};
+use catanatron_rs::game::{GameConfig, GameState};
+
+use clap::Parser;
+
+#[derive(Debug, Parser, Clone)]
+#[command(name = "settle-rs-profile-features")]
+#[command(about = "Profile feature extraction")]
+struct Args {
+    /// Number of positions to extract features from
+    #[arg(long, default_value = "10000")]
+    num_samples: u32,
+
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
+    #[arg(long, default_value = "BASE")]
+    map: String,
+
+    #[arg(long, default_value = "4")]
+    num_players: usize,
+
+    #[arg(long, default_value = "42")]
+    seed: u64,
+}
+
+fn print_stats(label: &str, durations: &[Duration]) {
+    if durations.is_empty() {
+        return;
+    }
+    let total: Duration = durations.iter().sum();
+    println!("{label} timing statistics:");
+    println!("  Calls: {}", durations.len());
+    println!("  Average: {:?}", total / durations.len() as u32);
+    println!("  Min: {:?}", durations.iter().min().unwrap());
+    println!("  Max: {:?}", durations.iter().max().unwrap());
+}
+
+fn main() {
+    let args = Args::parse();
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let mut config = GameConfig {
+        num_players: args.num_players,
+        map_type,
+        seed: args.seed,
+        ..GameConfig::default()
+    };
+    let mut state = GameState::new(config.clone());
+
+    let mut features = Vec::new();
+    let mut updates = Vec::new();
+    let mut tensors = Vec::new();
+    let mut num_features = 0;
+    // Seat 0's features, kept up to date with `update_from`.
+    let mut tracked = collect_features(&state, 0);
+    for _ in 0..args.num_samples {
+        let action = state.random_legal_action();
+        let finished = match action.clone() {
+            Some(action) => state.step(action).map_or(true, |outcome| outcome.done),
+            None => true,
+        };
+        if finished {
+            config.seed += 1;
+            state = GameState::new(config.clone());
+            tracked = collect_features(&state, 0);
+        } else if let Some(action) = action {
+            let start = Instant::now();
+            tracked = FeatureCollection::update_from(&tracked, &action, &state);
+            updates.push(start.elapsed());
+            debug_assert_eq!(tracked.values, collect_features(&state, 0).values);
+        }
+
+        let perspective = state.current_player;
+        let start = Instant::now();
+        let collection = collect_features(&state, perspective);
+        features.push(start.elapsed());
+        num_features = collection.values.len();
+
+        let start = Instant::now();
+        let _ = build_board_tensor(&state, perspective);
+        tensors.push(start.elapsed());
+    }
+
+    println!("Features per call: {num_features}");
+    print_stats("collect_features", &features);
+    print_stats("update_from", &updates);
+    print_stats("build_board_tensor", &tensors);
+}
@@ -17,7 +17,7 @@ struct Args {
     #[arg(long, default_value = "1000")]
     num_games: u32,
 
-    /// Map type: BASE, MINI, or TOURNAMENT
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
     #[arg(long, default_value = "BASE")]
     map: String,
 
@@ -32,8 +32,8 @@ struct Args {
 }
 
 fn profile_steps(map: String, num_players: usize, num_steps: u32, turns_limit: u32, seed: u64) {
-    let map_type = MapType::from_str(&map.to_uppercase()).unwrap_or_else(|_| {
-        eprintln!("Error: Invalid map type '{}'. Use BASE, MINI, or TOURNAMENT", map);
+    let map_type = MapType::from_str(&map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
         std::process::exit(1);
     });
 
@@ -42,22 +42,25 @@ fn profile_steps(map: String, num_players: usize, num_steps: u32, turns_limit: u
         map_type,
         vps_to_win: 10,
         seed,
+        ..GameConfig::default()
     };
 
     let mut game = Game::new(config.clone());
     let mut players = Vec::new();
     for _i in 0..num_players {
-        players.push(RandomPlayer);
+        players.push(RandomPlayer::default());
     }
+    game.seed_players(&mut players);
 
     let mut durations = Vec::new();
     for _ in 0..num_steps {
         if game.winning_color().is_some() || game.state.turn >= turns_limit {
             config.seed += 1;
             game = Game::new(config.clone());
+            game.seed_players(&mut players);
         }
         let start = Instant::now();
-        let _ = game.play_tick(&players);
+        let _ = game.play_tick(&mut players);
         durations.push(start.elapsed());
     }
 
@@ -78,8 +81,8 @@ fn profile_steps(map: String, num_players: usize, num_steps: u32, turns_limit: u
 }
 
 fn profile_games(map: String, num_players: usize, num_games: u32, seed: u64) {
-    let map_type = MapType::from_str(&map.to_uppercase()).unwrap_or_else(|_| {
-        eprintln!("Error: Invalid map type '{}'. Use BASE, MINI, or TOURNAMENT", map);
+    let map_type = MapType::from_str(&map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
         std::process::exit(1);
     });
 
@@ -88,11 +91,12 @@ fn profile_games(map: String, num_players: usize, num_games: u32, seed: u64) {
         map_type,
         vps_to_win: 10,
         seed,
+        ..GameConfig::default()
     };
 
     let mut players = Vec::new();
     for _i in 0..num_players {
-        players.push(RandomPlayer);
+        players.push(RandomPlayer::default());
     }
 
     let mut durations = Vec::new();
@@ -100,7 +104,7 @@ fn profile_games(map: String, num_players: usize, num_games: u32, seed: u64) {
     for _ in 0..num_games {
         let start = Instant::now();
         let mut game = Game::new(config.clone());
-        let _ = game.play(&players);
+        let _ = game.play(&mut players);
         durations.push(start.elapsed());
         turns.push(game.state.turn);
     }
@@ -1,38 +1,137 @@
+use std::ops::Range;
 use std::str::FromStr;
-use std::time::Instant;
-
-use catanatron_rs::MapType;
-use catanatron_rs::cli::{StatisticsAccumulator, create_player, print_player_help};
-use catanatron_rs::game::{Game, GameConfig};
-use catanatron_rs::types::Color;
+use std::sync::{Arc, Mutex};
+
+use catanatron_rs::cli::{
+    BroadcastTarget, Broadcaster, DICE_CHI_SQUARED_CRITICAL, DiceStats, RunConfig, SimCheckpoint,
+    StatisticsAccumulator, print_player_help, try_create_player,
+};
+use catanatron_rs::game::{
+    DiceConfig, Game, GameConfig, GameResult, HouseRules, SetupMode, TimeControl, VictoryCondition,
+};
+use catanatron_rs::types::probability::{ROLL_SUMS, number_probability};
+use catanatron_rs::types::{Color, DevelopmentCard, Resource};
+use catanatron_rs::{HarborLayout, MapLayout, MapType};
 use clap::Parser;
 
 #[derive(Debug, Parser, Clone)]
 #[command(name = "catanatron-sim")]
 #[command(about = "Catan Bot Simulator - Simulate games between different player strategies")]
+#[command(args_override_self = true)]
 struct Args {
+    /// Read settings from this TOML file; flags on the command line
+    /// override it
+    #[arg(long)]
+    config: Option<String>,
+
     /// Number of games to play
     #[arg(short = 'n', long, default_value_t = 5)]
     num: u32,
 
     /// Comma-separated player codes (e.g., R,R,R,R or F,F,R,R)
-    /// Use ':' to set player-specific params (e.g., F:0.1 for epsilon)
+    /// Use ':' to set player-specific params (e.g., F:0.1 for epsilon,
+    /// F:weights.json for tuned weights)
     /// Codes: R=Random, F=ValueFunction
     #[arg(long, default_value = "R,R,R,R")]
     players: String,
 
-    /// Random seed for reproducibility
+    /// Random seed for reproducibility (game i draws from stream i of this seed)
     #[arg(long, default_value_t = 42)]
     seed: u64,
 
-    /// Map type: BASE, MINI, or TOURNAMENT
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
     #[arg(long, default_value = "BASE")]
     map: String,
 
+    /// Play this exact board: a board code of tiles, numbers and
+    /// optionally harbors, as printed by `play` (e.g.
+    /// DOWOBOHHSBSBHWHWSSW/6,3,11,9,4,5,9,12,11,4,8,10,5,2,6,3,8,10)
+    #[arg(long)]
+    board_code: Option<String>,
+
     /// Victory points needed to win
     #[arg(long, default_value_t = 10)]
     vps_to_win: u8,
 
+    /// Dice model: FAIR, BALANCED[:RESHUFFLE_AT], or SCRIPTED:D1-D2,D1-D2,...
+    #[arg(long, default_value = "FAIR")]
+    dice: String,
+
+    /// Victory condition: POINTS, TURNS:N (most VPs after N turns) or
+    /// NODES:K:N1,N2,... (first to build on K of the listed nodes)
+    #[arg(long, default_value = "POINTS")]
+    victory: String,
+
+    /// Comma-separated team id per seat for team play (e.g. 0,1,0,1)
+    #[arg(long)]
+    teams: Option<String>,
+
+    /// Let teammates gift single cards to each other
+    #[arg(long)]
+    team_gifts: bool,
+
+    /// Harbors: SHUFFLED (random ports every game) or OFFICIAL (the printed
+    /// board's ports, with tiles and numbers still shuffled)
+    #[arg(long, default_value = "SHUFFLED")]
+    harbors: String,
+
+    /// Let players offer 1-for-1 and 2-for-1 domestic trades
+    #[arg(long)]
+    domestic_trades: bool,
+
+    /// Prompt all over-limit players to discard at once after a 7
+    #[arg(long)]
+    simultaneous_discard: bool,
+
+    /// Initial placement: SNAKE (standard), FIXED (same order both rounds)
+    /// or RANDOM (placed by the engine, games start at the first roll)
+    #[arg(long, default_value = "SNAKE")]
+    setup: String,
+
+    /// Apply moves that are the only legal option without asking the bots
+    #[arg(long)]
+    auto_forced: bool,
+
+    /// House rule: players with 2 or fewer public VPs can't be robbed
+    #[arg(long)]
+    friendly_robber: bool,
+
+    /// House rule: the robber can't be moved back onto the desert
+    #[arg(long)]
+    robber_avoids_desert: bool,
+
+    /// Per-player time bank as SECONDS[+INCREMENT][:FORFEIT|RANDOM|RESIGN],
+    /// e.g. 30+0.5:RANDOM
+    #[arg(long)]
+    time_control: Option<String>,
+
+    /// Write each player's public VPs per turn to this CSV file
+    #[arg(long)]
+    vp_timeline: Option<String>,
+
+    /// Write per-game rows and the summary to this file: .json for one
+    /// document, .csv for game rows plus a .summary.csv next to it
+    #[arg(long)]
+    output: Option<String>,
+
+    /// Save progress to this file and resume from it when it already exists
+    #[arg(long)]
+    checkpoint: Option<String>,
+
+    /// Games each worker plays between checkpoint saves
+    #[arg(long, default_value_t = 10)]
+    checkpoint_every: u32,
+
+    /// Stream this game (1-based) as JSON lines, one per action, while it
+    /// is played
+    #[arg(long, value_name = "GAME")]
+    broadcast: Option<u32>,
+
+    /// Where --broadcast writes: stdout (implies --quiet) or unix:PATH to
+    /// connect to a listening socket
+    #[arg(long, default_value = "stdout")]
+    broadcast_to: String,
+
     /// Show player codes and exit
     #[arg(long)]
     help_players: bool,
@@ -46,8 +145,22 @@ struct Args {
     workers: usize,
 }
 
+/// The game being broadcast, shared with whichever worker ends up playing it.
+type SharedBroadcast = Arc<Mutex<Option<Broadcaster>>>;
+
 fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    if let Some(path) = &args.config {
+        let config = RunConfig::from_file(path).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        });
+        // The file's settings go first so the command line overrides them.
+        let mut argv: Vec<std::ffi::OsString> = std::env::args_os().take(1).collect();
+        argv.extend(config.to_args().into_iter().map(Into::into));
+        argv.extend(std::env::args_os().skip(1));
+        args = Args::parse_from(argv);
+    }
 
     if args.help_players {
         print_player_help();
@@ -73,31 +186,213 @@ fn main() {
             Vec::new()
         };
 
-        match create_player(code, colors[i], params) {
-            Some(player) => players.push(player),
-            None => {
-                eprintln!("Error: Unknown player code '{}'", code);
+        match try_create_player(code, colors[i], params) {
+            Ok(player) => players.push(player),
+            Err(err) => {
+                eprintln!("Error: {}", err);
                 eprintln!("Use --help-players to see available codes");
                 std::process::exit(1);
             }
         }
     }
 
-    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|_| {
-        eprintln!(
-            "Error: Invalid map type '{}'. Use BASE, MINI, or TOURNAMENT",
-            args.map
-        );
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let map_layout = args.board_code.as_ref().map(|code| {
+        let layout = MapLayout::from_board_code(code).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        });
+        if let Err(err) = layout.validate(map_type.template()) {
+            eprintln!("Error: board code does not fit the {map_type} map: {err}");
+            std::process::exit(1);
+        }
+        layout
+    });
+
+    let dice = DiceConfig::from_str(&args.dice).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let victory = VictoryCondition::from_str(&args.victory).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let harbor_layout = HarborLayout::from_str(&args.harbors).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let setup = SetupMode::from_str(&args.setup).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
         std::process::exit(1);
     });
 
+    let time_control = args.time_control.as_ref().map(|control| {
+        TimeControl::from_str(control).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        })
+    });
+
+    let teams = args.teams.as_ref().map(|teams| {
+        let teams: Vec<usize> = teams
+            .split(',')
+            .map(|team| team.trim().parse().ok())
+            .collect::<Option<_>>()
+            .unwrap_or_else(|| {
+                eprintln!("Error: Invalid team list '{}'", teams);
+                std::process::exit(1);
+            });
+        if teams.len() != players.len() || teams.iter().any(|team| *team >= players.len()) {
+            eprintln!("Error: --teams needs one team id below the player count per seat");
+            std::process::exit(1);
+        }
+        teams
+    });
+
+    let base_config = GameConfig {
+        num_players: players.len(),
+        map_type,
+        map_layout,
+        harbor_layout,
+        vps_to_win: args.vps_to_win,
+        seed: args.seed,
+        dice,
+        domestic_trades: args.domestic_trades,
+        victory,
+        teams,
+        team_gifts: args.team_gifts,
+        time_control,
+        simultaneous_discard: args.simultaneous_discard,
+        setup,
+        auto_forced_actions: args.auto_forced,
+        house_rules: HouseRules {
+            friendly_robber: args.friendly_robber,
+            robber_avoids_desert: args.robber_avoids_desert,
+        },
+        ..GameConfig::default()
+    };
+
+    let output = args.output.as_deref().map(|path| {
+        OutputFormat::from_path(path).unwrap_or_else(|| {
+            eprintln!("Error: --output must end in .json or .csv, got '{}'", path);
+            std::process::exit(1);
+        })
+    });
+
+    let broadcast: SharedBroadcast = Arc::new(Mutex::new(None));
+    if let Some(game) = args.broadcast {
+        if game == 0 || game > args.num {
+            eprintln!("Error: --broadcast must be between 1 and {}", args.num);
+            std::process::exit(1);
+        }
+        let target = BroadcastTarget::from_str(&args.broadcast_to).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        });
+        // Keep stdout a clean JSON-lines stream.
+        if target == BroadcastTarget::Stdout {
+            args.quiet = true;
+        }
+        match Broadcaster::open(&target, game) {
+            Ok(broadcaster) => *broadcast.lock().unwrap() = Some(broadcaster),
+            Err(err) => {
+                eprintln!("Error: Failed to open broadcast target: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Run simulations
-    let mut stats = StatisticsAccumulator::new();
+    let mut stats = new_accumulator(&args);
+    let mut next_game = 0;
+
+    if let Some(path) = &args.checkpoint {
+        match SimCheckpoint::load(path) {
+            Ok(Some(checkpoint)) => {
+                if !checkpoint.matches(&base_config, &args.players) {
+                    eprintln!(
+                        "Error: Checkpoint '{}' was made with other game settings (--players {} --seed {})",
+                        path, checkpoint.players, checkpoint.config.seed
+                    );
+                    std::process::exit(1);
+                }
+                if !args.quiet {
+                    println!(
+                        "Resuming from checkpoint '{}' at game {}",
+                        path,
+                        checkpoint.next_game + 1
+                    );
+                }
+                next_game = checkpoint.next_game;
+                stats = checkpoint.stats;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("Error: Failed to read checkpoint '{}': {}", path, err);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    if args.workers > 1 {
-        run_parallel_simulations(&args, &players, &mut stats, map_type);
+    // With a checkpoint, play in batches and save after each one
+    let batch_size = if args.checkpoint.is_some() {
+        args.checkpoint_every.max(1) * args.workers.max(1) as u32
     } else {
-        run_sequential_simulations(&args, &players, &mut stats, map_type);
+        args.num
+    };
+    while next_game < args.num {
+        let games = next_game..next_game.saturating_add(batch_size).min(args.num);
+        next_game = games.end;
+
+        if args.workers > 1 {
+            run_parallel_simulations(&args, &players, &mut stats, &base_config, games, &broadcast);
+        } else {
+            run_sequential_simulations(
+                &args,
+                &mut players,
+                &mut stats,
+                &base_config,
+                games,
+                &broadcast,
+            );
+        }
+
+        if let Some(path) = &args.checkpoint {
+            let checkpoint = SimCheckpoint {
+                next_game,
+                config: base_config.clone(),
+                players: args.players.clone(),
+                stats,
+            };
+            if let Err(err) = checkpoint.save(path) {
+                eprintln!("Error: Failed to write checkpoint '{}': {}", path, err);
+                std::process::exit(1);
+            }
+            stats = checkpoint.stats;
+        }
+    }
+
+    if let Some(path) = &args.vp_timeline {
+        let result = std::fs::File::create(path)
+            .and_then(|file| stats.write_vp_timeline_csv(std::io::BufWriter::new(file)));
+        if let Err(err) = result {
+            eprintln!("Error: Failed to write VP timeline to '{}': {}", path, err);
+            std::process::exit(1);
+        }
+    }
+
+    if let (Some(path), Some(format)) = (&args.output, output)
+        && let Err(err) = write_output(&stats, path, format)
+    {
+        eprintln!("Error: Failed to write results to '{}': {}", path, err);
+        std::process::exit(1);
     }
 
     // Print summary
@@ -106,47 +401,150 @@ fn main() {
     }
 }
 
+/// Spec of each seat as given to --players, which is what results are
+/// keyed by.
+fn player_specs(args: &Args) -> Vec<String> {
+    args.players
+        .split(',')
+        .map(|spec| spec.trim().to_string())
+        .collect()
+}
+
+fn new_accumulator(args: &Args) -> StatisticsAccumulator {
+    let mut stats = StatisticsAccumulator::new().with_player_specs(player_specs(args));
+    if args.vp_timeline.is_some() {
+        stats = stats.with_vp_timeline();
+    }
+    if args.output.is_some() {
+        stats = stats.with_game_records();
+    }
+    stats
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Writes --output; CSV puts the summary in `<stem>.summary.csv` beside it.
+fn write_output(
+    stats: &StatisticsAccumulator,
+    path: &str,
+    format: OutputFormat,
+) -> std::io::Result<()> {
+    use std::io::{BufWriter, Write};
+
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    match format {
+        OutputFormat::Json => stats.write_results_json(&mut writer)?,
+        OutputFormat::Csv => {
+            stats.write_results_csv(&mut writer)?;
+            let summary_path = std::path::Path::new(path).with_extension("summary.csv");
+            let mut summary = BufWriter::new(std::fs::File::create(summary_path)?);
+            stats.write_summary_csv(&mut summary)?;
+            summary.flush()?;
+        }
+    }
+    writer.flush()
+}
+
+/// Plays one game, streaming it when it is the one picked by `--broadcast`.
+/// A broadcast that fails (e.g. the listener went away) is dropped with a
+/// warning and the simulation carries on.
+fn play_game(
+    args: &Args,
+    players: &mut [catanatron_rs::cli::players::PlayerInstance],
+    stats: &mut StatisticsAccumulator,
+    config: GameConfig,
+    game_idx: u32,
+    broadcast: &SharedBroadcast,
+) -> (Game, GameResult) {
+    if args.broadcast != Some(game_idx + 1) {
+        let mut game = Game::new(config);
+        stats.before(&game);
+        let result = game.play_with(players, |game| stats.step(game));
+        return (game, result);
+    }
+
+    let mut slot = broadcast.lock().unwrap();
+    let mut game = Game::new(config).with_event_log();
+    stats.before(&game);
+    let mut streamed = slot.as_mut().map_or(Ok(()), |b| b.start(&game));
+    let result = game.play_with(players, |game| {
+        stats.step(game);
+        if streamed.is_ok()
+            && let Some(broadcaster) = slot.as_mut()
+        {
+            streamed = broadcaster.tick(game);
+        }
+    });
+    if streamed.is_ok()
+        && let Some(broadcaster) = slot.as_mut()
+    {
+        streamed = broadcaster.finish(&game);
+    }
+    if let Err(err) = streamed {
+        eprintln!(
+            "Warning: broadcast of game {} stopped: {}",
+            game_idx + 1,
+            err
+        );
+    }
+    *slot = None;
+    (game, result)
+}
+
 fn run_sequential_simulations(
     args: &Args,
-    players: &[catanatron_rs::cli::players::PlayerInstance],
+    players: &mut [catanatron_rs::cli::players::PlayerInstance],
     stats: &mut StatisticsAccumulator,
-    map_type: MapType,
+    base_config: &GameConfig,
+    games: Range<u32>,
+    broadcast: &SharedBroadcast,
 ) {
-    for game_idx in 0..args.num {
+    for game_idx in games {
         let config = GameConfig {
-            num_players: players.len(),
-            map_type,
-            vps_to_win: args.vps_to_win,
-            seed: args.seed + game_idx as u64,
+            stream: game_idx as u64,
+            ..base_config.clone()
         };
 
-        let start = Instant::now();
-        let mut game = Game::new(config);
-        let winner = game.play(players);
-        let duration = start.elapsed();
+        let (game, result) = play_game(args, players, stats, config, game_idx, broadcast);
 
-        stats.after(&game, duration);
+        stats.after(&game, result.duration);
 
         if !args.quiet {
             let last_n = 10;
             if game_idx < last_n || game_idx >= args.num.saturating_sub(last_n) {
-                let winner_str = winner
+                let winner_str = result
+                    .winner
                     .map(|c| format!("{:?}", c))
                     .unwrap_or_else(|| "None".to_string());
-                let seating: String = game
-                    .state
+                let seating: String = result
                     .players
                     .iter()
                     .map(|p| format!("{:?}", p.color))
                     .collect::<Vec<_>>()
                     .join(",");
                 println!(
-                    "Game {:>4}: Seating=[{}], Winner={:>6}, Turns={:>4}, Duration={:?}",
+                    "Game {:>4}: Seating=[{}], Winner={:>6}, Turns={:>4}, Ticks={:>5}, Duration={:?}",
                     game_idx + 1,
                     seating,
                     winner_str,
-                    game.state.turn,
-                    duration
+                    result.turns,
+                    result.ticks,
+                    result.duration
                 );
             } else if (game_idx + 1) % 100 == 0 {
                 print!(".");
@@ -161,24 +559,24 @@ fn run_parallel_simulations(
     args: &Args,
     players: &[catanatron_rs::cli::players::PlayerInstance],
     stats: &mut StatisticsAccumulator,
-    map_type: MapType,
+    base_config: &GameConfig,
+    games: Range<u32>,
+    broadcast: &SharedBroadcast,
 ) {
-    use std::sync::Arc;
     use std::thread;
 
-    // Clone players for each thread (they need to be owned)
-    let players_vec: Vec<_> = players.iter().cloned().collect();
-    let players = Arc::new(players_vec);
     let args = Arc::new(args.clone());
 
     let mut handles = Vec::new();
-    let games_per_worker = args.num as usize / args.workers;
-    let remainder = args.num as usize % args.workers;
+    let games_per_worker = games.len() / args.workers;
+    let remainder = games.len() % args.workers;
 
     for worker_id in 0..args.workers {
-        let players_clone = Arc::clone(&players);
-        let args_clone = Arc::clone(&args);
-        let map_type_clone = map_type;
+        // Each worker plays with its own copies, since players keep state
+        let mut players = players.to_vec();
+        let base_config = base_config.clone();
+        let args = Arc::clone(&args);
+        let broadcast = Arc::clone(broadcast);
 
         let num_games = if worker_id < remainder {
             games_per_worker + 1
@@ -187,24 +585,27 @@ fn run_parallel_simulations(
         };
 
         let handle = thread::spawn(move || {
-            let mut local_stats = StatisticsAccumulator::new();
-            let start_idx = worker_id * games_per_worker + worker_id.min(remainder);
+            let mut local_stats = new_accumulator(&args);
+            let start_idx =
+                games.start as usize + worker_id * games_per_worker + worker_id.min(remainder);
 
             for local_idx in 0..num_games {
                 let game_idx = start_idx + local_idx;
                 let config = GameConfig {
-                    num_players: players_clone.len(),
-                    map_type: map_type_clone,
-                    vps_to_win: args_clone.vps_to_win,
-                    seed: args_clone.seed + game_idx as u64,
+                    stream: game_idx as u64,
+                    ..base_config.clone()
                 };
 
-                let start = Instant::now();
-                let mut game = Game::new(config);
-                let _winner = game.play(&**players_clone);
-                let duration = start.elapsed();
+                let (game, result) = play_game(
+                    &args,
+                    &mut players,
+                    &mut local_stats,
+                    config,
+                    game_idx as u32,
+                    &broadcast,
+                );
 
-                local_stats.after(&game, duration);
+                local_stats.after(&game, result.duration);
             }
 
             local_stats
@@ -216,22 +617,7 @@ fn run_parallel_simulations(
     // Collect and merge results
     for handle in handles {
         let worker_stats = handle.join().unwrap();
-        // Merge stats
-        for (color, wins) in worker_stats.stats.wins {
-            *stats.stats.wins.entry(color).or_insert(0) += wins;
-        }
-        for (color, vps) in worker_stats.stats.results_by_player {
-            stats
-                .stats
-                .results_by_player
-                .entry(color)
-                .or_insert_with(Vec::new)
-                .extend(vps);
-        }
-        stats.stats.games += worker_stats.stats.games;
-        stats.stats.total_ticks += worker_stats.stats.total_ticks;
-        stats.stats.total_turns += worker_stats.stats.total_turns;
-        stats.stats.total_duration += worker_stats.stats.total_duration;
+        stats.merge(worker_stats);
     }
 }
 
@@ -246,59 +632,208 @@ fn print_summary(
     // Player Summary
     println!("\nPlayer Summary:");
     println!(
-        "{:<15} {:<10} {:<12} {:<12}",
-        "Player", "Wins", "Win Rate", "Avg VP"
+        "{:<24} {:<6} {:<10} {:<12} {:<12}",
+        "Player", "Seats", "Wins", "Win Rate", "Avg VP"
     );
-    println!("{}", "-".repeat(50));
-
-    for (idx, player) in players.iter().enumerate() {
-        let color = match player {
-            catanatron_rs::cli::players::PlayerInstance::Random(_) => {
-                [Color::Red, Color::Blue, Color::Orange, Color::White][idx]
-            }
-            catanatron_rs::cli::players::PlayerInstance::ValueFunction(p) => p.color,
-            catanatron_rs::cli::players::PlayerInstance::MCTS(p) => p.color,
-        };
-
-        let wins = stats.stats.wins.get(&color).copied().unwrap_or(0);
-        let win_rate = if stats.stats.games > 0 {
-            (wins as f64 / stats.stats.games as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let avg_vps = stats
-            .stats
-            .results_by_player
-            .get(&color)
-            .map(|vps| {
-                if vps.is_empty() {
-                    0.0
-                } else {
-                    vps.iter().sum::<u8>() as f64 / vps.len() as f64
-                }
-            })
-            .unwrap_or(0.0);
-
-        let player_name = match player {
-            catanatron_rs::cli::players::PlayerInstance::Random(_) => "Random",
-            catanatron_rs::cli::players::PlayerInstance::ValueFunction(_) => "ValueFunction",
-            catanatron_rs::cli::players::PlayerInstance::MCTS(_) => "MCTS",
-        };
+    println!("{}", "-".repeat(64));
 
+    for (spec, label, seats) in spec_rows(stats, players) {
+        let wins = stats.stats.wins.get(&spec).copied().unwrap_or(0);
         println!(
-            "{:<15} {:<10} {:<11.1}% {:<12.2}",
-            format!("{} ({:?})", player_name, color),
+            "{:<24} {:<6} {:<10} {:<11.1}% {:<12.2}",
+            label,
+            seats,
             wins,
-            win_rate,
-            avg_vps
+            stats.stats.win_rate(&spec) * 100.0,
+            stats.stats.avg_vps(&spec)
         );
     }
 
+    print_player_breakdown(stats, players);
+
+    if !stats.stats.team_wins.is_empty() {
+        println!("\nTeam Summary:");
+        let mut teams: Vec<_> = stats.stats.team_wins.iter().collect();
+        teams.sort();
+        for (team, wins) in teams {
+            let win_rate = (*wins as f64 / stats.stats.games as f64) * 100.0;
+            println!("  Team {}: {} wins ({:.1}%)", team, wins, win_rate);
+        }
+    }
+
     // Game Summary
     println!("\nGame Summary:");
     println!("  Total Games: {}", stats.stats.games);
     println!("  Avg Turns: {:.2}", stats.stats.get_avg_turns());
     println!("  Avg Ticks: {:.2}", stats.stats.get_avg_ticks());
+    println!(
+        "  Avg Ticks/Turn: {:.2}",
+        stats.stats.get_avg_ticks_per_turn()
+    );
+    println!("  Longest Turn: {} ticks", stats.stats.longest_turn_ticks);
     println!("  Avg Duration: {:.2?}", stats.stats.get_avg_duration());
+
+    print_dice_summary(&stats.stats.dice, stats.stats.games);
+}
+
+fn print_dice_summary(dice: &DiceStats, games: u32) {
+    let total = dice.total_rolls();
+    if total == 0 {
+        return;
+    }
+    println!(
+        "\nDice Rolls: {} ({:.1} per game)",
+        total,
+        total as f64 / games.max(1) as f64
+    );
+    println!(
+        "{:>4} {:>8} {:>7} {:>8}",
+        "Sum", "Rolls", "Share", "Expected"
+    );
+    println!("{}", "-".repeat(64));
+    let widest = ROLL_SUMS.map(|sum| dice.share(sum)).fold(0.0, f64::max);
+    for sum in ROLL_SUMS {
+        let share = dice.share(sum);
+        let bar = "#".repeat((share / widest * 32.0).round() as usize);
+        println!(
+            "{:>4} {:>8} {:>6.2}% {:>7.2}% {}",
+            sum,
+            dice.sums[sum as usize],
+            share * 100.0,
+            number_probability(sum) * 100.0,
+            bar
+        );
+    }
+    let chi_squared = dice.chi_squared();
+    println!(
+        "  Chi-squared vs fair dice: {:.2} (flagged above {:.2}){}",
+        chi_squared,
+        DICE_CHI_SQUARED_CRITICAL,
+        if dice.is_unlikely() {
+            " -- UNLIKELY"
+        } else {
+            ""
+        }
+    );
+    if let Some(streak) = dice.unlikeliest_streak {
+        println!(
+            "  Unlikeliest streak: {} x{} (p <= {:.2e}){}",
+            streak.sum,
+            streak.length,
+            streak.p_value,
+            if streak.is_unlikely() {
+                " -- UNLIKELY"
+            } else {
+                ""
+            }
+        );
+    }
+    if dice.unlikely_streak_games > 0 {
+        println!(
+            "  Games with unlikely streaks: {}",
+            dice.unlikely_streak_games
+        );
+    }
+}
+
+fn player_name(player: &catanatron_rs::cli::players::PlayerInstance) -> &'static str {
+    match player {
+        catanatron_rs::cli::players::PlayerInstance::Random(_) => "Random",
+        catanatron_rs::cli::players::PlayerInstance::ValueFunction(_) => "ValueFunction",
+        catanatron_rs::cli::players::PlayerInstance::SameTurnExpand(_) => "SameTurnExpand",
+        catanatron_rs::cli::players::PlayerInstance::MCTS(_) => "MCTS",
+    }
+}
+
+/// `(spec, label, seats)` for each distinct player spec, in the order the
+/// specs first appear in --players.
+fn spec_rows(
+    stats: &StatisticsAccumulator,
+    players: &[catanatron_rs::cli::players::PlayerInstance],
+) -> Vec<(String, String, usize)> {
+    let mut rows: Vec<(String, String, usize)> = Vec::new();
+    for (spec, player) in stats.player_specs().iter().zip(players) {
+        if let Some(row) = rows.iter_mut().find(|row| &row.0 == spec) {
+            row.2 += 1;
+        } else {
+            let label = format!("{} [{}]", player_name(player), spec);
+            rows.push((spec.clone(), label, 1));
+        }
+    }
+    rows
+}
+
+fn print_player_breakdown(
+    stats: &StatisticsAccumulator,
+    players: &[catanatron_rs::cli::players::PlayerInstance],
+) {
+    println!("\nPer-Game Averages:");
+    println!(
+        "{:<24} {:>8} {:>8} {:>8} {:>6} {:>6} {:>6} {:>7} {:>9}",
+        "Player", "DiceRes", "DevBuy", "DevPlay", "Sett", "City", "Road", "Robbed", "Discards"
+    );
+    println!("{}", "-".repeat(92));
+    let rows = spec_rows(stats, players);
+    for (spec, label, _) in &rows {
+        let Some(ps) = stats.stats.player_stats.get(spec) else {
+            continue;
+        };
+        println!(
+            "{:<24} {:>8.2} {:>8.2} {:>8.2} {:>6.2} {:>6.2} {:>6.2} {:>7.2} {:>9.2}",
+            label,
+            ps.per_game(ps.total_dice_resources() as f64),
+            ps.per_game(ps.total_dev_cards_bought() as f64),
+            ps.per_game(ps.total_dev_cards_played() as f64),
+            ps.per_game(ps.settlements_built as f64),
+            ps.per_game(ps.cities_built as f64),
+            ps.per_game(ps.roads_built as f64),
+            ps.per_game(ps.times_robbed as f64),
+            ps.per_game(ps.cards_discarded as f64),
+        );
+    }
+
+    println!("\nDice Income by Resource (per game):");
+    print!("{:<24}", "Player");
+    for resource in Resource::ALL {
+        print!(" {:>7}", format!("{resource:?}"));
+    }
+    println!();
+    println!("{}", "-".repeat(64));
+    for (spec, label, _) in &rows {
+        let Some(ps) = stats.stats.player_stats.get(spec) else {
+            continue;
+        };
+        print!("{:<24}", label);
+        for amount in ps.dice_resources {
+            print!(" {:>7.2}", ps.per_game(amount as f64));
+        }
+        println!();
+    }
+
+    println!("\nDevelopment Cards Bought / Played (per game):");
+    print!("{:<24}", "Player");
+    for card in DevelopmentCard::ALL {
+        print!(" {:>13}", format!("{card:?}"));
+    }
+    println!();
+    println!("{}", "-".repeat(94));
+    for (spec, label, _) in &rows {
+        let Some(ps) = stats.stats.player_stats.get(spec) else {
+            continue;
+        };
+        print!("{:<24}", label);
+        for card in DevelopmentCard::ALL {
+            let bought = ps.dev_cards_bought.get(&card).copied().unwrap_or(0);
+            let played = ps.dev_cards_played.get(&card).copied().unwrap_or(0);
+            print!(
+                " {:>13}",
+                format!(
+                    "{:.2}/{:.2}",
+                    ps.per_game(bought as f64),
+                    ps.per_game(played as f64)
+                )
+            );
+        }
+        println!();
+    }
 }
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+
+use catanatron_rs::MapType;
+use catanatron_rs::cli::players::PlayerInstance;
+use catanatron_rs::cli::{print_player_help, try_create_player};
+use catanatron_rs::game::{Game, GameConfig, RngStream};
+use catanatron_rs::players::{ValueFunctionParams, ValueFunctionPlayer};
+use catanatron_rs::types::Color;
+use clap::Parser;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+#[derive(Debug, Parser, Clone)]
+#[command(name = "catanatron-tune")]
+#[command(
+    about = "Tune ValueFunctionPlayer weights with the cross-entropy method against fixed opponents"
+)]
+struct Args {
+    /// Player spec for every opponent seat (e.g. F, R, M:50, F:weights.json)
+    #[arg(long, default_value = "F")]
+    opponent: String,
+
+    /// Number of players; the candidate takes one seat, rotating every game
+    #[arg(short = 'p', long, default_value_t = 2)]
+    num_players: usize,
+
+    /// Starting weights file (.json or .toml); defaults to the built-in weights
+    #[arg(long)]
+    init: Option<PathBuf>,
+
+    /// Number of generations
+    #[arg(short = 'g', long, default_value_t = 10)]
+    generations: u32,
+
+    /// Candidates sampled per generation
+    #[arg(long, default_value_t = 16)]
+    population: usize,
+
+    /// Best candidates the next generation is fitted to
+    #[arg(long, default_value_t = 4)]
+    elite: usize,
+
+    /// Games played by each candidate
+    #[arg(short = 'n', long, default_value_t = 20)]
+    games: u32,
+
+    /// Initial spread of each weight, in powers of ten
+    #[arg(long, default_value_t = 1.0)]
+    sigma: f64,
+
+    /// Smallest spread kept, so the search never stops exploring
+    #[arg(long, default_value_t = 0.05)]
+    min_sigma: f64,
+
+    /// Seed for sampling candidates and for the games
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+
+    /// Map type: BASE, MINI, TOURNAMENT, HEX<N>, or a registered custom map
+    #[arg(long, default_value = "BASE")]
+    map: String,
+
+    /// Victory points needed to win
+    #[arg(long, default_value_t = 10)]
+    vps_to_win: u8,
+
+    /// Where to write the best weights (.json or .toml)
+    #[arg(short = 'o', long, default_value = "tuned_weights.json")]
+    output: PathBuf,
+
+    /// Number of worker threads; candidates are split between them
+    #[arg(long, default_value_t = 1)]
+    workers: usize,
+
+    /// Show player codes and exit
+    #[arg(long)]
+    help_players: bool,
+}
+
+/// The weights are searched in log space: each nonzero weight keeps its sign
+/// and varies as `sign * 10^x`. Zero weights stay switched off.
+struct SearchSpace {
+    names: Vec<String>,
+    signs: Vec<f64>,
+    fixed: BTreeMap<String, f64>,
+}
+
+impl SearchSpace {
+    fn new(params: &ValueFunctionParams) -> (Self, Vec<f64>) {
+        let mut space = SearchSpace {
+            names: Vec::new(),
+            signs: Vec::new(),
+            fixed: BTreeMap::new(),
+        };
+        let mut mean = Vec::new();
+        for (name, value) in weights(params) {
+            if value == 0.0 {
+                space.fixed.insert(name, value);
+            } else {
+                space.names.push(name);
+                space.signs.push(value.signum());
+                mean.push(value.abs().log10());
+            }
+        }
+        (space, mean)
+    }
+
+    fn params(&self, point: &[f64]) -> ValueFunctionParams {
+        let mut map: serde_json::Map<String, serde_json::Value> = self
+            .fixed
+            .iter()
+            .map(|(name, value)| (name.clone(), (*value).into()))
+            .collect();
+        for ((name, sign), x) in self.names.iter().zip(&self.signs).zip(point) {
+            map.insert(name.clone(), (sign * 10f64.powf(*x)).into());
+        }
+        serde_json::from_value(map.into()).expect("weights map back onto the params")
+    }
+}
+
+fn weights(params: &ValueFunctionParams) -> BTreeMap<String, f64> {
+    serde_json::from_value(serde_json::to_value(params).unwrap()).unwrap()
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if args.help_players {
+        print_player_help();
+        return;
+    }
+
+    if args.num_players < 2 || args.num_players > 4 {
+        eprintln!("Error: Must specify 2-4 players");
+        std::process::exit(1);
+    }
+    if args.elite == 0 || args.elite > args.population {
+        eprintln!("Error: --elite must be between 1 and --population");
+        std::process::exit(1);
+    }
+
+    let map_type = MapType::from_str(&args.map.to_uppercase()).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+
+    let opponents: Vec<PlayerInstance> = Color::ORDERED
+        .iter()
+        .take(args.num_players)
+        .map(|color| {
+            let mut parts = args.opponent.split(':');
+            let code = parts.next().unwrap_or_default();
+            try_create_player(code, *color, parts.collect()).unwrap_or_else(|err| {
+                eprintln!("Error: {err}");
+                eprintln!("Use --help-players to see available codes");
+                std::process::exit(1);
+            })
+        })
+        .collect();
+
+    let init = match &args.init {
+        Some(path) => ValueFunctionParams::from_file(path).unwrap_or_else(|err| {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }),
+        None => ValueFunctionParams::default(),
+    };
+    let (space, mut mean) = SearchSpace::new(&init);
+    let mut sigma = vec![args.sigma; mean.len()];
+
+    let base_config = GameConfig {
+        num_players: args.num_players,
+        map_type,
+        vps_to_win: args.vps_to_win,
+        seed: args.seed,
+        ..GameConfig::default()
+    };
+
+    println!(
+        "Tuning {} weights: {} generations x {} candidates x {} games vs {}",
+        mean.len(),
+        args.generations,
+        args.population,
+        args.games,
+        args.opponent
+    );
+
+    let start = Instant::now();
+    let mut rng = RngStream::new(args.seed, u64::MAX).to_rng();
+    let mut best: Option<(f64, ValueFunctionParams)> = None;
+    for generation in 0..args.generations {
+        let points: Vec<Vec<f64>> = (0..args.population)
+            .map(|_| sample(&mut rng, &mean, &sigma))
+            .collect();
+        let candidates: Vec<ValueFunctionParams> =
+            points.iter().map(|point| space.params(point)).collect();
+
+        // Every candidate plays the same games so they are compared fairly.
+        let first_stream = generation as u64 * args.games as u64;
+        let scores = evaluate_all(&candidates, &opponents, &base_config, first_stream, &args);
+
+        let mut ranked: Vec<usize> = (0..points.len()).collect();
+        ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+        let elite: Vec<&Vec<f64>> = ranked[..args.elite]
+            .iter()
+            .map(|idx| &points[*idx])
+            .collect();
+        for i in 0..mean.len() {
+            let values: Vec<f64> = elite.iter().map(|point| point[i]).collect();
+            mean[i] = values.iter().sum::<f64>() / values.len() as f64;
+            let variance =
+                values.iter().map(|v| (v - mean[i]).powi(2)).sum::<f64>() / values.len() as f64;
+            sigma[i] = variance.sqrt().max(args.min_sigma);
+        }
+
+        let top = ranked[0];
+        if best.as_ref().is_none_or(|(score, _)| scores[top] > *score) {
+            best = Some((scores[top], candidates[top].clone()));
+        }
+        let elite_mean = ranked[..args.elite]
+            .iter()
+            .map(|idx| scores[*idx])
+            .sum::<f64>()
+            / args.elite as f64;
+        println!(
+            "Generation {:>3}: best {:>5.1}%, elite mean {:>5.1}%, spread {:.3} ({:.2?})",
+            generation + 1,
+            scores[top] * 100.0,
+            elite_mean * 100.0,
+            sigma.iter().sum::<f64>() / sigma.len().max(1) as f64,
+            start.elapsed()
+        );
+    }
+
+    let Some((score, params)) = best else {
+        eprintln!("Error: no generations were run");
+        std::process::exit(1);
+    };
+    if let Err(err) = write_params(&args.output, &params) {
+        eprintln!("Error: could not write {}: {err}", args.output.display());
+        std::process::exit(1);
+    }
+    println!(
+        "Best candidate won {:.1}% of its games; weights written to {}",
+        score * 100.0,
+        args.output.display()
+    );
+    for (name, value) in weights(&params) {
+        println!("  {:<24} {:e}", name, value);
+    }
+}
+
+fn sample(rng: &mut StdRng, mean: &[f64], sigma: &[f64]) -> Vec<f64> {
+    mean.iter()
+        .zip(sigma)
+        .map(|(mu, sd)| mu + sd * standard_normal(rng))
+        .collect()
+}
+
+/// Box-Muller transform, to avoid pulling in rand_distr for one function.
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.r#gen::<f64>().max(f64::MIN_POSITIVE);
+    let u2: f64 = rng.r#gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn evaluate_all(
+    candidates: &[ValueFunctionParams],
+    opponents: &[PlayerInstance],
+    base_config: &GameConfig,
+    first_stream: u64,
+    args: &Args,
+) -> Vec<f64> {
+    let workers = args.workers.max(1);
+    let chunk_size = candidates.len().div_ceil(workers).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|params| {
+                            evaluate(params, opponents, base_config, first_stream, args.games)
+                        })
+                        .collect::<Vec<f64>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Fraction of `games` won by a value player with `params`, moving it to
+/// the next seat every game.
+fn evaluate(
+    params: &ValueFunctionParams,
+    opponents: &[PlayerInstance],
+    base_config: &GameConfig,
+    first_stream: u64,
+    games: u32,
+) -> f64 {
+    let mut wins = 0;
+    for game_idx in 0..games {
+        let seat = game_idx as usize % opponents.len();
+        let color = Color::ORDERED[seat];
+        let mut players = opponents.to_vec();
+        players[seat] = PlayerInstance::ValueFunction(ValueFunctionPlayer::new(
+            color,
+            Some(params.clone()),
+            None,
+        ));
+
+        let config = GameConfig {
+            stream: first_stream + game_idx as u64,
+            ..base_config.clone()
+        };
+        let mut game = Game::new(config);
+        if game.play(&mut players).winner == Some(color) {
+            wins += 1;
+        }
+    }
+    wins as f64 / games.max(1) as f64
+}
+
+fn write_params(path: &PathBuf, params: &ValueFunctionParams) -> std::io::Result<()> {
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let text = if is_toml {
+        toml::to_string_pretty(params).map_err(std::io::Error::other)?
+    } else {
+        serde_json::to_string_pretty(params).map_err(std::io::Error::other)?
+    };
+    std::fs::write(path, text)
+}
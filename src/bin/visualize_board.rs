@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use catanatron_rs::board::{CatanMap, MapType, Tile};
+use catanatron_rs::board::{CatanMap, MapType, Point, Tile, tile_center, tile_corners};
 use catanatron_rs::coords::{CubeCoord, Direction};
 use catanatron_rs::types::{NodeRef, Resource};
 use plotters::prelude::*;
@@ -28,7 +28,6 @@ fn render_map(
 
     // Gather geometry
     let mut centers: Vec<(f64, f64, TileKind, Option<String>)> = Vec::new();
-    let mut node_positions: HashMap<u16, Vec<(f64, f64)>> = HashMap::new();
     let mut all_points: Vec<(f64, f64)> = Vec::new();
 
     for (coord, tile) in &map.tiles {
@@ -41,20 +40,9 @@ fn render_map(
         centers.push((cx, cy, kind, label));
 
         // collect corners for bounding box
-        for (x, y) in hexagon_corners((cx, cy), hex_size) {
+        for (x, y) in hexagon_corners(*coord, hex_size) {
             all_points.push((x, y));
         }
-
-        // collect node positions for labels (average if shared)
-        let node_iter = match tile {
-            Tile::Land(t) => &t.nodes,
-            Tile::Port(t) => &t.nodes,
-            Tile::Water(t) => &t.nodes,
-        };
-        for (node_ref, node_id) in node_iter.iter() {
-            let pos = node_position((cx, cy), hex_size, *node_ref);
-            node_positions.entry(*node_id).or_default().push(pos);
-        }
     }
 
     // Compute bounding box
@@ -74,8 +62,8 @@ fn render_map(
     };
 
     // Draw tiles
-    for (cx, cy, kind, label) in &centers {
-        let corners: Vec<(i32, i32)> = hexagon_corners((*cx, *cy), hex_size)
+    for ((coord, _), (cx, cy, kind, label)) in map.tiles.iter().zip(&centers) {
+        let corners: Vec<(i32, i32)> = hexagon_corners(*coord, hex_size)
             .into_iter()
             .map(to_canvas)
             .collect();
@@ -98,11 +86,11 @@ fn render_map(
     }
 
     // Draw node circles + labels
-    let mut node_centers: HashMap<u16, (f64, f64)> = HashMap::new();
-    for (node_id, positions) in node_positions {
-        let center = average(&positions);
-        node_centers.insert(node_id, center);
-    }
+    let node_centers: HashMap<u16, Point> = map
+        .node_coordinates()
+        .into_iter()
+        .map(|(node_id, (x, y))| (node_id, (x * hex_size, y * hex_size)))
+        .collect();
 
     let mut port_infos: Vec<PortInfo> = map
         .ports_by_id
@@ -187,39 +175,17 @@ fn render_map(
 }
 
 fn cube_to_pixel(cube: CubeCoord, size: f64) -> (f64, f64) {
-    let x = size * ((3.0_f64).sqrt() * cube.x as f64 + (3.0_f64).sqrt() / 2.0 * cube.z as f64);
-    let y = size * (1.5 * cube.z as f64);
-    (x, y)
+    let (x, y) = tile_center(cube);
+    (x * size, y * size)
 }
 
-fn hexagon_corners(center: (f64, f64), size: f64) -> Vec<(f64, f64)> {
-    let node_order = [
-        NodeRef::North,
-        NodeRef::NorthEast,
-        NodeRef::SouthEast,
-        NodeRef::South,
-        NodeRef::SouthWest,
-        NodeRef::NorthWest,
-    ];
-    node_order
-        .iter()
-        .map(|nr| node_position(center, size, *nr))
+fn hexagon_corners(coord: CubeCoord, size: f64) -> Vec<(f64, f64)> {
+    tile_corners(coord)
+        .into_iter()
+        .map(|(x, y)| (x * size, y * size))
         .collect()
 }
 
-fn node_position(center: (f64, f64), size: f64, node_ref: NodeRef) -> (f64, f64) {
-    let angle = match node_ref {
-        NodeRef::North => -std::f64::consts::FRAC_PI_2,
-        NodeRef::NorthEast => -std::f64::consts::FRAC_PI_6,
-        NodeRef::SouthEast => std::f64::consts::FRAC_PI_6,
-        NodeRef::South => std::f64::consts::FRAC_PI_2,
-        NodeRef::SouthWest => 5.0 * std::f64::consts::FRAC_PI_6,
-        NodeRef::NorthWest => -5.0 * std::f64::consts::FRAC_PI_6,
-    };
-    let (cx, cy) = center;
-    (cx + size * angle.cos(), cy + size * angle.sin())
-}
-
 fn bounds(points: &[(f64, f64)]) -> Result<(f64, f64, f64, f64), &'static str> {
     if points.is_empty() {
         return Err("no points");
@@ -0,0 +1,133 @@
+//! Board codes: a whole board in one line that can be pasted on the command
+//! line to play it again. A code lists the land tiles, their numbers and
+//! optionally the harbors, all in template order and separated by `/`:
+//!
+//! ```text
+//! DOWOBOHHSBSBHWHWSSW/6,3,11,9,4,5,9,12,11,4,8,10,5,2,6,3,8,10/3BW3HO3S3
+//! ```
+//!
+//! Tiles and harbors are one letter each: `W`ood, `B`rick, `S`heep, w`H`eat,
+//! `O`re, `D`esert for a tile without resource and `3` for a 3:1 harbor.
+//! Numbers skip the deserts. The one above is the tournament board.
+
+use super::{CatanMap, MapLayout};
+use crate::types::Resource;
+
+const SEPARATOR: char = '/';
+
+impl MapLayout {
+    /// Pins exactly the board `map` shows.
+    pub fn of_map(map: &CatanMap) -> Self {
+        let mut tiles: Vec<_> = map.tiles_by_id.values().collect();
+        tiles.sort_unstable_by_key(|tile| tile.id);
+        let mut ports: Vec<_> = map.ports_by_id.values().collect();
+        ports.sort_unstable_by_key(|port| port.id);
+        // Layout lists are consumed from the back.
+        Self {
+            numbers: Some(tiles.iter().rev().filter_map(|tile| tile.number).collect()),
+            port_resources: Some(ports.iter().rev().map(|port| port.resource).collect()),
+            tile_resources: Some(tiles.iter().rev().map(|tile| tile.resource).collect()),
+        }
+    }
+
+    /// Reads a board code. Whether it fits a map type is left to
+    /// `validate`.
+    pub fn from_board_code(code: &str) -> Result<Self, String> {
+        let sections: Vec<&str> = code.trim().split(SEPARATOR).collect();
+        let (tiles, numbers, ports) = match sections.as_slice() {
+            [tiles, numbers] => (*tiles, *numbers, None),
+            [tiles, numbers, ports] => (*tiles, *numbers, Some(*ports)),
+            _ => {
+                return Err(format!(
+                    "board code needs tiles{SEPARATOR}numbers[{SEPARATOR}harbors], got '{code}'"
+                ));
+            }
+        };
+
+        let mut tile_resources = tiles
+            .chars()
+            .map(|letter| match letter.to_ascii_uppercase() {
+                'D' => Ok(None),
+                letter => resource_from_letter(letter).map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut numbers = numbers
+            .split(',')
+            .map(|number| {
+                number
+                    .trim()
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid tile number '{number}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let port_resources = ports
+            .map(|ports| {
+                ports
+                    .chars()
+                    .map(|letter| match letter.to_ascii_uppercase() {
+                        '3' => Ok(None),
+                        letter => resource_from_letter(letter).map(Some),
+                    })
+                    .rev()
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+
+        tile_resources.reverse();
+        numbers.reverse();
+        Ok(Self {
+            numbers: Some(numbers),
+            port_resources,
+            tile_resources: Some(tile_resources),
+        })
+    }
+
+    /// The board code of this layout, or `None` unless it pins both tiles
+    /// and numbers. Harbors are included when pinned and there are any.
+    pub fn board_code(&self) -> Option<String> {
+        let tiles: String = self
+            .tile_resources
+            .as_ref()?
+            .iter()
+            .rev()
+            .map(|resource| resource.map_or('D', resource_letter))
+            .collect();
+        let numbers: Vec<String> = self
+            .numbers
+            .as_ref()?
+            .iter()
+            .rev()
+            .map(|number| number.to_string())
+            .collect();
+        let mut code = format!("{tiles}{SEPARATOR}{}", numbers.join(","));
+        if let Some(ports) = &self.port_resources
+            && !ports.is_empty()
+        {
+            code.push(SEPARATOR);
+            code.extend(
+                ports
+                    .iter()
+                    .rev()
+                    .map(|resource| resource.map_or('3', resource_letter)),
+            );
+        }
+        Some(code)
+    }
+}
+
+fn resource_letter(resource: Resource) -> char {
+    match resource {
+        Resource::Wood => 'W',
+        Resource::Brick => 'B',
+        Resource::Sheep => 'S',
+        Resource::Wheat => 'H',
+        Resource::Ore => 'O',
+    }
+}
+
+fn resource_from_letter(letter: char) -> Result<Resource, String> {
+    Resource::ALL
+        .into_iter()
+        .find(|resource| resource_letter(*resource) == letter)
+        .ok_or_else(|| format!("unknown board code letter '{letter}'"))
+}
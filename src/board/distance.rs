@@ -0,0 +1,112 @@
+//! Shortest paths over the node graph, for reachability features, placement
+//! heuristics and road planning.
+//!
+//! Boards have at most a few hundred nodes, so all pairs are computed with
+//! one BFS per node the first time a distance is asked for, and shared by
+//! every clone of the map after that.
+
+use std::collections::VecDeque;
+
+use super::{CatanMap, EdgeId, NodeId, normalize_edge};
+
+const UNREACHABLE: u8 = u8::MAX;
+
+/// All-pairs hop counts between the nodes of one map.
+#[derive(Debug)]
+pub(crate) struct DistanceTable {
+    /// Sorted node ids; a node's index here is its row in `hops`.
+    nodes: Vec<NodeId>,
+    /// Sorted neighbors of each node, by index into `nodes`.
+    neighbors: Vec<Vec<usize>>,
+    hops: Vec<u8>,
+}
+
+impl DistanceTable {
+    fn new(map: &CatanMap) -> Self {
+        let mut nodes: Vec<NodeId> = map.node_neighbors.keys().copied().collect();
+        nodes.sort_unstable();
+        let index = |node: &NodeId| nodes.binary_search(node).ok();
+        let neighbors: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|node| {
+                let mut adjacent: Vec<usize> =
+                    map.node_neighbors[node].iter().filter_map(index).collect();
+                adjacent.sort_unstable();
+                adjacent
+            })
+            .collect();
+
+        let count = nodes.len();
+        let mut hops = vec![UNREACHABLE; count * count];
+        let mut queue = VecDeque::new();
+        for start in 0..count {
+            let row = &mut hops[start * count..(start + 1) * count];
+            row[start] = 0;
+            queue.push_back(start);
+            while let Some(node) = queue.pop_front() {
+                for &next in &neighbors[node] {
+                    if row[next] == UNREACHABLE {
+                        row[next] = row[node] + 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        Self {
+            nodes,
+            neighbors,
+            hops,
+        }
+    }
+
+    fn index(&self, node: NodeId) -> Option<usize> {
+        self.nodes.binary_search(&node).ok()
+    }
+
+    fn hops(&self, a: usize, b: usize) -> Option<u8> {
+        let hops = self.hops[a * self.nodes.len() + b];
+        (hops != UNREACHABLE).then_some(hops)
+    }
+}
+
+impl CatanMap {
+    fn distances(&self) -> &DistanceTable {
+        self.distances.get_or_init(|| DistanceTable::new(self))
+    }
+
+    /// Number of edges on a shortest path from `a` to `b`, ignoring who owns
+    /// what. `None` if either node isn't on this map or they aren't connected.
+    pub fn node_distance(&self, a: NodeId, b: NodeId) -> Option<u8> {
+        let table = self.distances();
+        table.hops(table.index(a)?, table.index(b)?)
+    }
+
+    /// Nodes of a shortest path from `a` to `b`, both included. Ties go to the
+    /// lowest node id at each step, so the path is the same on every call.
+    pub fn node_path(&self, a: NodeId, b: NodeId) -> Option<Vec<NodeId>> {
+        let table = self.distances();
+        let (mut current, end) = (table.index(a)?, table.index(b)?);
+        let mut remaining = table.hops(current, end)?;
+        let mut path = Vec::with_capacity(remaining as usize + 1);
+        path.push(table.nodes[current]);
+        while remaining > 0 {
+            remaining -= 1;
+            current = *table.neighbors[current]
+                .iter()
+                .find(|next| table.hops(**next, end) == Some(remaining))?;
+            path.push(table.nodes[current]);
+        }
+        Some(path)
+    }
+
+    /// Normalized edges of `node_path(a, b)`, in order from `a`. Empty when
+    /// `a == b`.
+    pub fn edge_path(&self, a: NodeId, b: NodeId) -> Option<Vec<EdgeId>> {
+        let path = self.node_path(a, b)?;
+        Some(
+            path.windows(2)
+                .map(|pair| normalize_edge((pair[0], pair[1])))
+                .collect(),
+        )
+    }
+}
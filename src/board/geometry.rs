@@ -0,0 +1,88 @@
+//! 2D positions of tiles, nodes and edges for renderers, so frontends and
+//! image exports don't each redo the hex math.
+//!
+//! Tiles are pointy-topped with a circumradius of 1: the distance from a
+//! tile center to each of its nodes, and the length of every edge. `x` grows
+//! to the east and `y` to the south (screen coordinates), with the center
+//! tile at the origin. Scale and flip to taste.
+
+use std::collections::BTreeMap;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_6};
+
+use crate::coords::CubeCoord;
+use crate::types::NodeRef;
+
+use super::{CatanMap, EdgeId, NodeId, normalize_edge};
+
+/// A position on the board plane.
+pub type Point = (f64, f64);
+
+/// Center of the tile at `coord`.
+pub fn tile_center(coord: CubeCoord) -> Point {
+    let sqrt3 = 3.0_f64.sqrt();
+    (
+        sqrt3 * coord.x as f64 + sqrt3 / 2.0 * coord.z as f64,
+        1.5 * coord.z as f64,
+    )
+}
+
+/// Offset of a tile's `node_ref` corner from the tile center.
+pub fn node_offset(node_ref: NodeRef) -> Point {
+    let angle = match node_ref {
+        NodeRef::North => -FRAC_PI_2,
+        NodeRef::NorthEast => -FRAC_PI_6,
+        NodeRef::SouthEast => FRAC_PI_6,
+        NodeRef::South => FRAC_PI_2,
+        NodeRef::SouthWest => 5.0 * FRAC_PI_6,
+        NodeRef::NorthWest => -5.0 * FRAC_PI_6,
+    };
+    (angle.cos(), angle.sin())
+}
+
+/// Corners of the tile at `coord`, clockwise from `North`.
+pub fn tile_corners(coord: CubeCoord) -> [Point; 6] {
+    let (cx, cy) = tile_center(coord);
+    [
+        NodeRef::North,
+        NodeRef::NorthEast,
+        NodeRef::SouthEast,
+        NodeRef::South,
+        NodeRef::SouthWest,
+        NodeRef::NorthWest,
+    ]
+    .map(|node_ref| {
+        let (dx, dy) = node_offset(node_ref);
+        (cx + dx, cy + dy)
+    })
+}
+
+impl CatanMap {
+    /// Position of every node, including the ones on water and port tiles.
+    pub fn node_coordinates(&self) -> BTreeMap<NodeId, Point> {
+        let mut coordinates = BTreeMap::new();
+        for (coord, tile) in &self.tiles {
+            let (cx, cy) = tile_center(*coord);
+            for (node_ref, node_id) in tile.nodes() {
+                coordinates.entry(*node_id).or_insert_with(|| {
+                    let (dx, dy) = node_offset(*node_ref);
+                    (cx + dx, cy + dy)
+                });
+            }
+        }
+        coordinates
+    }
+
+    /// End points of every edge, keyed by its normalized id and in the same
+    /// order as the id's nodes.
+    pub fn edge_coordinates(&self) -> BTreeMap<EdgeId, (Point, Point)> {
+        let nodes = self.node_coordinates();
+        self.node_edges
+            .values()
+            .flatten()
+            .map(|edge| {
+                let edge = normalize_edge(*edge);
+                (edge, (nodes[&edge.0], nodes[&edge.1]))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,219 @@
+//! Hexagonal maps of any radius, built from the base game's proportions so
+//! training curricula can grow the board gradually.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use strum::IntoEnumIterator;
+
+use crate::coords::{CubeCoord, Direction, UNIT_VECTORS};
+use crate::types::{NodeRef, Resource};
+
+use super::{CanonicalNodeId, CatanMap, MapShuffleOverrides, MapTemplate, NodeId, TileTemplate};
+
+type NodeLookup = HashMap<(CubeCoord, NodeRef), NodeId>;
+
+/// Generated node tables, one per radius, leaked so templates can borrow
+/// them for `'static` like the hand-written base and mini tables.
+static HEX_NODE_IDS: Lazy<Mutex<HashMap<u8, &'static NodeLookup>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Number tokens of the base game by how often they appear, best first so
+/// small boards keep the high-yield numbers.
+const NUMBER_WEIGHTS: [(u8, u32); 10] = [
+    (6, 2),
+    (8, 2),
+    (5, 2),
+    (9, 2),
+    (4, 2),
+    (10, 2),
+    (3, 2),
+    (11, 2),
+    (2, 1),
+    (12, 1),
+];
+
+const RESOURCE_WEIGHTS: [(Resource, u32); 5] = [
+    (Resource::Wood, 4),
+    (Resource::Sheep, 4),
+    (Resource::Wheat, 4),
+    (Resource::Brick, 3),
+    (Resource::Ore, 3),
+];
+
+const PORT_WEIGHTS: [(Option<Resource>, u32); 6] = [
+    (None, 4),
+    (Some(Resource::Wood), 1),
+    (Some(Resource::Brick), 1),
+    (Some(Resource::Sheep), 1),
+    (Some(Resource::Wheat), 1),
+    (Some(Resource::Ore), 1),
+];
+
+/// Land tiles, and ports, on the base board.
+const BASE_LAND_TILES: usize = 19;
+const BASE_PORTS: usize = 9;
+
+impl MapTemplate {
+    /// A hexagon of land `radius` rings around the centre tile, surrounded by
+    /// a ring of water with ports spread evenly along it. Radius 1 is the
+    /// size of MINI and radius 2 the size of BASE. Resource, number and port
+    /// pools keep the base game's proportions.
+    ///
+    /// Node ids are numbered ring by ring from the centre, the same way the
+    /// base and mini tables are, so a node keeps its id at every radius that
+    /// contains it and radii 1 and 2 agree with MINI and BASE.
+    pub fn hex_radius(radius: u8) -> MapTemplate {
+        assert!(radius >= 1, "hex maps need at least one ring of land");
+        let radius_i = radius as i32;
+
+        let mut topology: Vec<(CubeCoord, TileTemplate)> = (0..=radius_i)
+            .flat_map(ring)
+            .map(|coord| (coord, TileTemplate::Land))
+            .collect();
+        let land_tiles = topology.len();
+
+        let coast = ring(radius_i + 1);
+        // Never more than every other coastal tile, as on the base board.
+        let port_count = ((BASE_PORTS * land_tiles + BASE_LAND_TILES / 2) / BASE_LAND_TILES)
+            .min(coast.len() / 2);
+        let port_slots: Vec<usize> = (0..port_count)
+            .map(|i| i * coast.len() / port_count)
+            .collect();
+        for (index, coord) in coast.into_iter().enumerate() {
+            let kind = if port_slots.contains(&index) {
+                TileTemplate::Port(facing_land(coord, radius_i))
+            } else {
+                TileTemplate::Water
+            };
+            topology.push((coord, kind));
+        }
+
+        let deserts = ((land_tiles + BASE_LAND_TILES / 2) / BASE_LAND_TILES).max(1);
+        let producing = land_tiles - deserts;
+        let mut tile_resources: Vec<Option<Resource>> = apportion(&RESOURCE_WEIGHTS, producing)
+            .into_iter()
+            .map(Some)
+            .collect();
+        tile_resources.extend(std::iter::repeat_n(None, deserts));
+
+        MapTemplate {
+            numbers: apportion(&NUMBER_WEIGHTS, producing),
+            port_resources: apportion(&PORT_WEIGHTS, port_count),
+            official_port_resources: None,
+            tile_resources,
+            node_lookup: Some(hex_node_ids(radius, &topology)),
+            topology,
+        }
+    }
+
+    /// Checks that every node of the template comes from `node_lookup` and
+    /// that the ids run from 0 without gaps, i.e. that maps built from it
+    /// number their nodes the same way every time.
+    pub fn validate_node_ids(&self) -> Result<(), String> {
+        let Some(lookup) = self.node_lookup else {
+            return Err("template has no node id table".to_string());
+        };
+        let map = CatanMap::from_template(self, MapShuffleOverrides::default());
+        let mut ids: Vec<NodeId> = Vec::new();
+        for (coord, _) in &self.topology {
+            let tile = map
+                .tiles
+                .get(coord)
+                .ok_or_else(|| format!("tile {coord:?} missing from the built map"))?;
+            for (node_ref, node) in tile.nodes() {
+                if lookup.get(&(*coord, *node_ref)) != Some(node) {
+                    return Err(format!(
+                        "node {node_ref:?} of tile {coord:?} is not numbered by the table"
+                    ));
+                }
+                ids.push(*node);
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.iter().enumerate().any(|(i, id)| i != *id as usize) {
+            return Err("node ids are not contiguous from 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The tiles `k` steps from the centre, starting east and walking the ring
+/// in the order the base topology lists them.
+fn ring(k: i32) -> Vec<CubeCoord> {
+    if k == 0 {
+        return vec![CubeCoord::new(0, 0, 0)];
+    }
+    const STEPS: [(i32, i32, i32); 6] = [
+        (-1, 0, 1),
+        (-1, 1, 0),
+        (0, 1, -1),
+        (1, 0, -1),
+        (1, -1, 0),
+        (0, -1, 1),
+    ];
+    let mut coord = CubeCoord::new(k, -k, 0);
+    let mut tiles = Vec::with_capacity(6 * k as usize);
+    for (dx, dy, dz) in STEPS {
+        for _ in 0..k {
+            tiles.push(coord);
+            coord = coord.add(CubeCoord::new(dx, dy, dz));
+        }
+    }
+    tiles
+}
+
+fn distance_from_centre(coord: CubeCoord) -> i32 {
+    coord.x.abs().max(coord.y.abs()).max(coord.z.abs())
+}
+
+/// A direction from a coastal tile towards the land, so the port's nodes
+/// are on the island.
+fn facing_land(coord: CubeCoord, radius: i32) -> Direction {
+    Direction::iter()
+        .find(|direction| distance_from_centre(coord.add(UNIT_VECTORS[direction])) <= radius)
+        .expect("coastal tile borders the land")
+}
+
+/// Splits `total` items across the weighted values by largest remainder,
+/// settling ties in list order.
+fn apportion<T: Copy>(weights: &[(T, u32)], total: usize) -> Vec<T> {
+    let weight_sum: usize = weights.iter().map(|(_, weight)| *weight as usize).sum();
+    let mut counts: Vec<usize> = weights
+        .iter()
+        .map(|(_, weight)| total * *weight as usize / weight_sum)
+        .collect();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(total * weights[i].1 as usize % weight_sum));
+    let missing = total - counts.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(missing) {
+        counts[i] += 1;
+    }
+    weights
+        .iter()
+        .zip(counts)
+        .flat_map(|((value, _), count)| std::iter::repeat_n(*value, count))
+        .collect()
+}
+
+/// Numbers nodes tile by tile in topology order, corners in `NodeRef` order,
+/// giving a shared corner the id of the first tile that reached it.
+fn hex_node_ids(radius: u8, topology: &[(CubeCoord, TileTemplate)]) -> &'static NodeLookup {
+    let mut cache = HEX_NODE_IDS.lock().unwrap();
+    cache.entry(radius).or_insert_with(|| {
+        let mut ids: HashMap<CanonicalNodeId, NodeId> = HashMap::new();
+        let mut lookup = NodeLookup::new();
+        for (coord, _) in topology {
+            for node_ref in NodeRef::iter() {
+                let next = ids.len() as NodeId;
+                let id = *ids
+                    .entry(CanonicalNodeId::new(*coord, node_ref))
+                    .or_insert(next);
+                lookup.insert((*coord, node_ref), id);
+            }
+        }
+        Box::leak(Box::new(lookup))
+    })
+}
@@ -1,21 +1,105 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 use crate::coords::{CubeCoord, Direction, UNIT_VECTORS, add};
+use crate::types::probability::{number_pips, number_probability, pips_to_probability};
 use crate::types::{EdgeRef, NodeRef, Resource};
 
+mod code;
+mod distance;
+mod geometry;
+mod hex;
 mod node_ids;
+mod registry;
+
+pub use geometry::{Point, node_offset, tile_center, tile_corners};
+pub use registry::MapRegistry;
 
 pub type NodeId = u16;
 pub type EdgeId = (NodeId, NodeId);
 
+/// The canonical spelling of an edge: lower node id first.
+pub fn normalize_edge(edge: EdgeId) -> EdgeId {
+    if edge.0 <= edge.1 {
+        edge
+    } else {
+        (edge.1, edge.0)
+    }
+}
+
+/// A node label that depends only on board geometry, not on the order tiles
+/// were numbered in, so the same intersection gets the same label on every
+/// map that contains it. Every intersection is the North or South corner of
+/// exactly one hex, and that hex plus corner is the label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CanonicalNodeId {
+    pub coord: CubeCoord,
+    /// Always `NodeRef::North` or `NodeRef::South`.
+    pub corner: NodeRef,
+}
+
+impl CanonicalNodeId {
+    /// Labels corner `node_ref` of the hex at `coord`.
+    pub fn new(coord: CubeCoord, node_ref: NodeRef) -> Self {
+        let (direction, corner) = match node_ref {
+            NodeRef::North | NodeRef::South => {
+                return Self {
+                    coord,
+                    corner: node_ref,
+                };
+            }
+            NodeRef::NorthEast => (Direction::NorthEast, NodeRef::South),
+            NodeRef::NorthWest => (Direction::NorthWest, NodeRef::South),
+            NodeRef::SouthEast => (Direction::SouthEast, NodeRef::North),
+            NodeRef::SouthWest => (Direction::SouthWest, NodeRef::North),
+        };
+        Self {
+            coord: add(coord, UNIT_VECTORS[&direction]),
+            corner,
+        }
+    }
+
+    /// Orders by hex row from the top, then west to east, North corner first.
+    fn sort_key(&self) -> (i32, i32, bool) {
+        (self.coord.z, self.coord.x, self.corner == NodeRef::South)
+    }
+}
+
+impl Ord for CanonicalNodeId {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for CanonicalNodeId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for CanonicalNodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let corner = if self.corner == NodeRef::North {
+            'N'
+        } else {
+            'S'
+        };
+        write!(
+            f,
+            "({},{},{}){}",
+            self.coord.x, self.coord.y, self.coord.z, corner
+        )
+    }
+}
+
 type NodeMap = HashMap<NodeRef, NodeId>;
 type EdgeMap = HashMap<EdgeRef, EdgeId>;
 
@@ -50,6 +134,19 @@ pub enum Tile {
     Water(Water),
 }
 
+impl Port {
+    /// The two coastal nodes that get to trade at this port.
+    pub fn land_nodes(&self) -> [NodeId; 2] {
+        let (first_ref, second_ref) = PORT_DIRECTION_TO_NODE_REFS
+            .get(&self.direction)
+            .expect("missing port");
+        [
+            *self.nodes.get(first_ref).expect("node missing"),
+            *self.nodes.get(second_ref).expect("node missing"),
+        ]
+    }
+}
+
 impl Tile {
     fn nodes(&self) -> &NodeMap {
         match self {
@@ -79,6 +176,9 @@ pub enum TileTemplate {
 pub struct MapTemplate {
     pub numbers: Vec<u8>,
     pub port_resources: Vec<Option<Resource>>,
+    /// Port resources of the printed board, consumed from the back like a
+    /// `MapLayout` list. `None` deals `port_resources` in the listed order.
+    pub official_port_resources: Option<Vec<Option<Resource>>>,
     pub tile_resources: Vec<Option<Resource>>,
     pub topology: Vec<(CubeCoord, TileTemplate)>,
     pub node_lookup: Option<&'static HashMap<(CubeCoord, NodeRef), NodeId>>,
@@ -92,13 +192,119 @@ impl MapTemplate {
     pub fn mini() -> &'static MapTemplate {
         &MINI_TEMPLATE
     }
+
+    pub fn land_tile_count(&self) -> usize {
+        self.topology
+            .iter()
+            .filter(|(_, kind)| matches!(kind, TileTemplate::Land))
+            .count()
+    }
+
+    pub fn port_count(&self) -> usize {
+        self.topology
+            .iter()
+            .filter(|(_, kind)| matches!(kind, TileTemplate::Port(_)))
+            .count()
+    }
+
+    /// Port resources for `HarborLayout::Official`.
+    pub fn official_ports(&self) -> Vec<Option<Resource>> {
+        self.official_port_resources
+            .clone()
+            .unwrap_or_else(|| self.port_resources.clone())
+    }
+
+    /// Checks that the pools fit the topology and that every port faces a
+    /// land tile, so its two nodes can actually be settled.
+    pub fn validate(&self) -> Result<(), String> {
+        let land: HashSet<CubeCoord> = self
+            .topology
+            .iter()
+            .filter(|(_, kind)| matches!(kind, TileTemplate::Land))
+            .map(|(coord, _)| *coord)
+            .collect();
+        for (coord, kind) in &self.topology {
+            if let TileTemplate::Port(direction) = kind
+                && !land.contains(&add(*coord, UNIT_VECTORS[direction]))
+            {
+                return Err(format!(
+                    "port at {coord:?} faces {direction:?}, away from any land tile"
+                ));
+            }
+        }
+        if self.tile_resources.len() != land.len() {
+            return Err(format!(
+                "expected {} tile resources, got {}",
+                land.len(),
+                self.tile_resources.len()
+            ));
+        }
+        // Spare numbers are fine (the mini pool has one); they're never dealt.
+        let numbered = self
+            .tile_resources
+            .iter()
+            .filter(|res| res.is_some())
+            .count();
+        if self.numbers.len() < numbered {
+            return Err(format!(
+                "expected at least {numbered} numbers for non-desert tiles, got {}",
+                self.numbers.len()
+            ));
+        }
+        for ports in std::iter::once(&self.port_resources).chain(&self.official_port_resources) {
+            if ports.len() != self.port_count() {
+                return Err(format!(
+                    "expected {} port resources, got {}",
+                    self.port_count(),
+                    ports.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How port resources are dealt when a `MapLayout` doesn't pin them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HarborLayout {
+    /// Shuffled independently of the tiles on every board.
+    #[default]
+    Shuffled,
+    /// The printed board's harbors (`MapTemplate::official_ports`), while
+    /// tiles and numbers are still shuffled.
+    Official,
+}
+
+impl fmt::Display for HarborLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HarborLayout::Shuffled => write!(f, "SHUFFLED"),
+            HarborLayout::Official => write!(f, "OFFICIAL"),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl FromStr for HarborLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "shuffled" | "random" => Ok(HarborLayout::Shuffled),
+            "official" => Ok(HarborLayout::Official),
+            _ => Err(format!("unknown harbor layout: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MapType {
     Base,
+    /// The base board with the fixed tiles, numbers and harbors of the
+    /// catanatron tournament layout; the same whatever the seed.
     Tournament,
     Mini,
+    /// A template looked up in the `MapRegistry` by name.
+    Custom(String),
 }
 
 impl Default for MapType {
@@ -113,11 +319,38 @@ impl fmt::Display for MapType {
             MapType::Base => "BASE",
             MapType::Tournament => "TOURNAMENT",
             MapType::Mini => "MINI",
+            MapType::Custom(name) => name,
         };
         write!(f, "{label}")
     }
 }
 
+impl MapType {
+    /// Panics for a custom map that isn't registered in this process.
+    pub fn template(&self) -> &'static MapTemplate {
+        match self {
+            MapType::Base | MapType::Tournament => MapTemplate::base(),
+            MapType::Mini => MapTemplate::mini(),
+            MapType::Custom(name) => {
+                MapRegistry::get(name).unwrap_or_else(|| panic!("map '{name}' is not registered"))
+            }
+        }
+    }
+
+    /// The pools this map type never shuffles. Only the tournament map pins
+    /// anything, and it pins everything.
+    pub fn fixed_layout(&self) -> MapShuffleOverrides<'static> {
+        match self {
+            MapType::Tournament => MapShuffleOverrides {
+                numbers: Some(&TOURNAMENT_NUMBERS),
+                port_resources: MapTemplate::base().official_port_resources.as_deref(),
+                tile_resources: Some(&TOURNAMENT_TILES),
+            },
+            _ => MapShuffleOverrides::default(),
+        }
+    }
+}
+
 impl FromStr for MapType {
     type Err = String;
 
@@ -126,7 +359,11 @@ impl FromStr for MapType {
             "base" => Ok(MapType::Base),
             "tournament" => Ok(MapType::Tournament),
             "mini" => Ok(MapType::Mini),
-            _ => Err(format!("unknown map type: {s}")),
+            _ if MapRegistry::contains(s) => Ok(MapType::Custom(registry::normalize(s))),
+            _ => Err(format!(
+                "unknown map type: {s} (available: {})",
+                MapRegistry::names().join(", ")
+            )),
         }
     }
 }
@@ -138,6 +375,119 @@ pub struct MapShuffleOverrides<'a> {
     pub tile_resources: Option<&'a [Option<Resource>]>,
 }
 
+impl<'a> MapShuffleOverrides<'a> {
+    /// These overrides, with the pools they leave open taken from
+    /// `fallback`.
+    pub fn or(self, fallback: MapShuffleOverrides<'a>) -> Self {
+        Self {
+            numbers: self.numbers.or(fallback.numbers),
+            port_resources: self.port_resources.or(fallback.port_resources),
+            tile_resources: self.tile_resources.or(fallback.tile_resources),
+        }
+    }
+}
+
+/// Owned, serializable counterpart of `MapShuffleOverrides` used to pin an
+/// exact board through `GameConfig`. Like the template pools, each list is
+/// consumed from the back while walking the template topology.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MapLayout {
+    pub numbers: Option<Vec<u8>>,
+    pub port_resources: Option<Vec<Option<Resource>>>,
+    pub tile_resources: Option<Vec<Option<Resource>>>,
+}
+
+impl MapLayout {
+    pub fn as_overrides(&self) -> MapShuffleOverrides<'_> {
+        MapShuffleOverrides {
+            numbers: self.numbers.as_deref(),
+            port_resources: self.port_resources.as_deref(),
+            tile_resources: self.tile_resources.as_deref(),
+        }
+    }
+
+    /// Pins the template's official harbors unless ports are already pinned.
+    pub fn with_official_harbors(mut self, template: &MapTemplate) -> Self {
+        if self.port_resources.is_none() {
+            self.port_resources = Some(template.official_ports());
+        }
+        self
+    }
+
+    pub fn validate(&self, template: &MapTemplate) -> Result<(), String> {
+        let tiles = self
+            .tile_resources
+            .as_ref()
+            .unwrap_or(&template.tile_resources);
+        if tiles.len() != template.land_tile_count() {
+            return Err(format!(
+                "expected {} tile resources, got {}",
+                template.land_tile_count(),
+                tiles.len()
+            ));
+        }
+        let numbered = tiles.iter().filter(|res| res.is_some()).count();
+        let numbers = self.numbers.as_ref().unwrap_or(&template.numbers);
+        // Only a pinned list has to match exactly; template pools may carry spares.
+        if numbers.len() < numbered || (self.numbers.is_some() && numbers.len() != numbered) {
+            return Err(format!(
+                "expected {numbered} numbers for non-desert tiles, got {}",
+                numbers.len()
+            ));
+        }
+        if let Some(number) = numbers.iter().find(|n| !(2..=12).contains(*n) || **n == 7) {
+            return Err(format!("invalid tile number {number}"));
+        }
+        let ports = self
+            .port_resources
+            .as_ref()
+            .unwrap_or(&template.port_resources);
+        if ports.len() != template.port_count() {
+            return Err(format!(
+                "expected {} port resources, got {}",
+                template.port_count(),
+                ports.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Expected yield of one or more buildings from a single dice roll.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Production {
+    /// Dots printed on the number tokens, i.e. ways out of 36 to hit them.
+    pub pips: u32,
+    /// Expected cards per roll for each resource.
+    pub resources: BTreeMap<Resource, f64>,
+}
+
+impl Production {
+    pub fn get(&self, resource: Resource) -> f64 {
+        self.resources.get(&resource).copied().unwrap_or(0.0)
+    }
+
+    pub fn total(&self) -> f64 {
+        self.resources.values().sum()
+    }
+
+    pub fn add(&mut self, other: &Production) {
+        self.pips += other.pips;
+        for (resource, amount) in &other.resources {
+            *self.resources.entry(*resource).or_default() += amount;
+        }
+    }
+
+    /// Scales the production, e.g. by 2 for a city.
+    pub fn scaled(mut self, factor: u32) -> Self {
+        self.pips *= factor;
+        for amount in self.resources.values_mut() {
+            *amount *= factor as f64;
+        }
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CatanMap {
     pub tiles: HashMap<CubeCoord, Tile>,
@@ -149,7 +499,12 @@ pub struct CatanMap {
     pub node_edges: HashMap<NodeId, Vec<EdgeId>>,
     pub node_production: HashMap<NodeId, BTreeMap<Resource, f32>>,
     pub tiles_by_id: HashMap<u16, LandTile>,
+    /// Where each land tile sits, by id.
+    pub tile_coords: HashMap<u16, CubeCoord>,
     pub ports_by_id: HashMap<u16, Port>,
+    pub canonical_nodes: HashMap<NodeId, CanonicalNodeId>,
+    /// Filled by the first `node_distance`/`edge_path` call, shared by clones.
+    distances: Arc<OnceCell<distance::DistanceTable>>,
 }
 
 impl CatanMap {
@@ -180,21 +535,18 @@ impl CatanMap {
             .values()
             .map(|tile| (tile.id, tile.clone()))
             .collect();
+        let tile_coords: HashMap<u16, CubeCoord> = land_tiles
+            .iter()
+            .map(|(coord, tile)| (tile.id, *coord))
+            .collect();
 
         let mut port_nodes: HashMap<Option<Resource>, HashSet<NodeId>> = HashMap::new();
         for tile in tiles.values() {
             if let Tile::Port(port) = tile {
-                let (first_ref, second_ref) = PORT_DIRECTION_TO_NODE_REFS
-                    .get(&port.direction)
-                    .expect("missing port");
-                port_nodes
-                    .entry(port.resource)
-                    .or_default()
-                    .insert(*port.nodes.get(first_ref).expect("node missing"));
                 port_nodes
                     .entry(port.resource)
                     .or_default()
-                    .insert(*port.nodes.get(second_ref).expect("node missing"));
+                    .extend(port.land_nodes());
             }
         }
 
@@ -230,7 +582,7 @@ impl CatanMap {
                     if let Some(tile) = tiles_by_id.get(tile_id) {
                         if let (Some(resource), Some(number)) = (tile.resource, tile.number) {
                             let entry = production.entry(resource).or_default();
-                            *entry += number_probability(number);
+                            *entry += number_probability(number) as f32;
                         }
                     }
                 }
@@ -244,6 +596,14 @@ impl CatanMap {
                 _ => None,
             })
             .collect();
+        let canonical_nodes = tiles
+            .iter()
+            .flat_map(|(coord, tile)| {
+                tile.nodes()
+                    .iter()
+                    .map(|(node_ref, node)| (*node, CanonicalNodeId::new(*coord, *node_ref)))
+            })
+            .collect();
 
         Self {
             tiles,
@@ -255,83 +615,117 @@ impl CatanMap {
             node_edges,
             node_neighbors,
             tiles_by_id,
+            tile_coords,
             ports_by_id,
+            canonical_nodes,
+            distances: Arc::default(),
         }
     }
 
+    /// The geometry-derived label of `node`, stable across maps and engine
+    /// versions. `None` for ids not on this map.
+    pub fn canonical_node_id(&self, node: NodeId) -> Option<CanonicalNodeId> {
+        self.canonical_nodes.get(&node).copied()
+    }
+
+    /// Inverse of `canonical_node_id`.
+    pub fn node_from_canonical(&self, canonical: CanonicalNodeId) -> Option<NodeId> {
+        self.canonical_nodes
+            .iter()
+            .find(|(_, label)| **label == canonical)
+            .map(|(node, _)| *node)
+    }
+
+    /// Land nodes sorted by canonical label, for feature vectors whose layout
+    /// should not depend on how this map happened to number its nodes.
+    pub fn canonical_land_nodes(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = self.land_nodes.iter().copied().collect();
+        nodes.sort_by_key(|node| self.canonical_nodes[node]);
+        nodes
+    }
+
+    /// What a settlement on `node` is expected to collect per roll.
+    pub fn expected_production(&self, node: NodeId) -> Production {
+        self.expected_production_with_robber(node, None)
+    }
+
+    /// Like `expected_production`, leaving out the tile the robber sits on.
+    pub fn expected_production_with_robber(
+        &self,
+        node: NodeId,
+        robber_tile: Option<u16>,
+    ) -> Production {
+        let mut production = Production::default();
+        let Some(tile_ids) = self.adjacent_tiles.get(&node) else {
+            return production;
+        };
+        for tile_id in tile_ids {
+            if Some(*tile_id) == robber_tile {
+                continue;
+            }
+            if let Some(LandTile {
+                resource: Some(resource),
+                number: Some(number),
+                ..
+            }) = self.tiles_by_id.get(tile_id)
+            {
+                let pips = number_pips(*number);
+                production.pips += pips;
+                *production.resources.entry(*resource).or_default() += pips_to_probability(pips);
+            }
+        }
+        production
+    }
+
     pub fn build(map_type: MapType) -> Self {
         let mut rng = thread_rng();
         Self::build_with_rng(map_type, &mut rng)
     }
 
     pub fn build_with_rng(map_type: MapType, rng: &mut impl rand::Rng) -> Self {
-        match map_type {
-            MapType::Base => CatanMap::from_template_with_rng(
-                MapTemplate::base(),
-                MapShuffleOverrides::default(),
-                rng,
-            ),
-            MapType::Mini => CatanMap::from_template_with_rng(
-                MapTemplate::mini(),
-                MapShuffleOverrides::default(),
-                rng,
-            ),
-            MapType::Tournament => build_tournament_map(),
-        }
+        CatanMap::from_template_with_rng(map_type.template(), map_type.fixed_layout(), rng)
     }
-}
 
-fn build_tournament_map() -> CatanMap {
-    static TOURNAMENT_NUMBERS: Lazy<Vec<u8>> =
-        Lazy::new(|| vec![10, 8, 3, 6, 2, 5, 10, 8, 4, 11, 12, 9, 5, 4, 9, 11, 3, 6]);
-    static TOURNAMENT_PORTS: Lazy<Vec<Option<Resource>>> = Lazy::new(|| {
-        vec![
-            None,
-            Some(Resource::Sheep),
-            None,
-            Some(Resource::Ore),
-            Some(Resource::Wheat),
-            None,
-            Some(Resource::Wood),
-            Some(Resource::Brick),
-            None,
-        ]
-    });
-    static TOURNAMENT_TILES: Lazy<Vec<Option<Resource>>> = Lazy::new(|| {
-        vec![
-            None,
-            Some(Resource::Wood),
-            Some(Resource::Sheep),
-            Some(Resource::Sheep),
-            Some(Resource::Wood),
-            Some(Resource::Wheat),
-            Some(Resource::Wood),
-            Some(Resource::Wheat),
-            Some(Resource::Brick),
-            Some(Resource::Sheep),
-            Some(Resource::Brick),
-            Some(Resource::Sheep),
-            Some(Resource::Wheat),
-            Some(Resource::Wheat),
-            Some(Resource::Ore),
-            Some(Resource::Brick),
-            Some(Resource::Ore),
-            Some(Resource::Wood),
-            Some(Resource::Ore),
-            None,
-        ]
-    });
-
-    CatanMap::from_template(
-        MapTemplate::base(),
-        MapShuffleOverrides {
-            numbers: Some(&TOURNAMENT_NUMBERS),
-            port_resources: Some(&TOURNAMENT_PORTS),
-            tile_resources: Some(&TOURNAMENT_TILES),
-        },
-    )
+    /// Builds `map_type` with any fields pinned by `layout`; fields left as
+    /// `None` keep the map type's fixed layout, if any, or are shuffled from
+    /// the template pools as usual.
+    pub fn build_with_layout(
+        map_type: MapType,
+        layout: &MapLayout,
+        rng: &mut impl rand::Rng,
+    ) -> Self {
+        let overrides = layout.as_overrides().or(map_type.fixed_layout());
+        CatanMap::from_template_with_rng(map_type.template(), overrides, rng)
+    }
 }
 
+static TOURNAMENT_NUMBERS: Lazy<Vec<u8>> =
+    Lazy::new(|| vec![10, 8, 3, 6, 2, 5, 10, 8, 4, 11, 12, 9, 5, 4, 9, 11, 3, 6]);
+static TOURNAMENT_TILES: Lazy<Vec<Option<Resource>>> = Lazy::new(|| {
+    vec![
+        None,
+        Some(Resource::Wood),
+        Some(Resource::Sheep),
+        Some(Resource::Sheep),
+        Some(Resource::Wood),
+        Some(Resource::Wheat),
+        Some(Resource::Wood),
+        Some(Resource::Wheat),
+        Some(Resource::Brick),
+        Some(Resource::Sheep),
+        Some(Resource::Brick),
+        Some(Resource::Sheep),
+        Some(Resource::Wheat),
+        Some(Resource::Wheat),
+        Some(Resource::Ore),
+        Some(Resource::Brick),
+        Some(Resource::Ore),
+        Some(Resource::Wood),
+        Some(Resource::Ore),
+        None,
+    ]
+});
+
 fn initialize_tiles(
     template: &MapTemplate,
     overrides: MapShuffleOverrides<'_>,
@@ -579,21 +973,6 @@ static PORT_DIRECTION_TO_NODE_REFS: Lazy<HashMap<Direction, (NodeRef, NodeRef)>>
         ])
     });
 
-fn number_probability(number: u8) -> f32 {
-    *DICE_PROBABILITIES.get(&number).unwrap_or(&0.0)
-}
-
-static DICE_PROBABILITIES: Lazy<HashMap<u8, f32>> = Lazy::new(|| {
-    let mut probas: HashMap<u8, f32> = HashMap::new();
-    for i in 1..=6 {
-        for j in 1..=6 {
-            let total = (i + j) as u8;
-            *probas.entry(total).or_insert(0.0) += 1.0 / 36.0;
-        }
-    }
-    probas
-});
-
 static BASE_TEMPLATE: Lazy<MapTemplate> = Lazy::new(|| MapTemplate {
     numbers: vec![2, 3, 3, 4, 4, 5, 5, 6, 6, 8, 8, 9, 9, 10, 10, 11, 11, 12],
     port_resources: vec![
@@ -607,6 +986,17 @@ static BASE_TEMPLATE: Lazy<MapTemplate> = Lazy::new(|| MapTemplate {
         None,
         None,
     ],
+    official_port_resources: Some(vec![
+        None,
+        Some(Resource::Sheep),
+        None,
+        Some(Resource::Ore),
+        Some(Resource::Wheat),
+        None,
+        Some(Resource::Wood),
+        Some(Resource::Brick),
+        None,
+    ]),
     tile_resources: vec![
         Some(Resource::Wood),
         Some(Resource::Wood),
@@ -635,6 +1025,7 @@ static BASE_TEMPLATE: Lazy<MapTemplate> = Lazy::new(|| MapTemplate {
 static MINI_TEMPLATE: Lazy<MapTemplate> = Lazy::new(|| MapTemplate {
     numbers: vec![3, 4, 5, 6, 8, 9, 10],
     port_resources: vec![],
+    official_port_resources: None,
     tile_resources: vec![
         Some(Resource::Wood),
         None,
@@ -0,0 +1,91 @@
+//! Named map templates that can be chosen by string, e.g. from the CLI or a
+//! serialized `GameConfig`, alongside the built-in `MapType`s.
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+use super::MapTemplate;
+
+/// Names taken by the built-in map types.
+const BUILT_IN: [&str; 3] = ["base", "tournament", "mini"];
+
+/// Largest radius `hex<N>` names are generated for on demand.
+const MAX_HEX_RADIUS: u8 = 8;
+
+static REGISTRY: Lazy<RwLock<BTreeMap<String, &'static MapTemplate>>> =
+    Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Process-wide table of custom map templates. Names are case-insensitive.
+/// Besides whatever is registered explicitly, `hex1` to `hex8` resolve to
+/// `MapTemplate::hex_radius` boards.
+pub struct MapRegistry;
+
+impl MapRegistry {
+    /// Registers `template` under `name` after checking it with
+    /// `MapTemplate::validate`. Templates live for the rest of the process,
+    /// so a name can't be reused for a different template.
+    pub fn register(name: &str, template: MapTemplate) -> Result<(), String> {
+        let key = normalize(name);
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("invalid map name '{name}'"));
+        }
+        template
+            .validate()
+            .map_err(|err| format!("invalid map '{name}': {err}"))?;
+        if BUILT_IN.contains(&key.as_str()) || hex_radius(&key).is_some() {
+            return Err(format!("map name '{name}' is reserved"));
+        }
+        let mut registry = REGISTRY.write().unwrap();
+        if registry.contains_key(&key) {
+            return Err(format!("map '{name}' is already registered"));
+        }
+        registry.insert(key, Box::leak(Box::new(template)));
+        Ok(())
+    }
+
+    /// The template registered as `name`, or a generated hex board for
+    /// `hex<N>`. Built-in names are resolved through `MapType` instead.
+    pub fn get(name: &str) -> Option<&'static MapTemplate> {
+        let key = normalize(name);
+        if let Some(template) = REGISTRY.read().unwrap().get(&key) {
+            return Some(template);
+        }
+        let radius = hex_radius(&key)?;
+        let mut registry = REGISTRY.write().unwrap();
+        let template = registry
+            .entry(key)
+            .or_insert_with(|| Box::leak(Box::new(MapTemplate::hex_radius(radius))));
+        Some(*template)
+    }
+
+    pub fn contains(name: &str) -> bool {
+        Self::get(name).is_some()
+    }
+
+    /// Every name `MapType::from_str` accepts, built-ins first, for help and
+    /// error messages. Generated hex boards are listed as `HEX<N>`.
+    pub fn names() -> Vec<String> {
+        let mut names: Vec<String> = BUILT_IN.iter().map(|name| name.to_uppercase()).collect();
+        names.push(format!("HEX1..HEX{MAX_HEX_RADIUS}"));
+        names.extend(
+            REGISTRY
+                .read()
+                .unwrap()
+                .keys()
+                .filter(|name| hex_radius(name).is_none())
+                .cloned(),
+        );
+        names
+    }
+}
+
+pub(super) fn normalize(name: &str) -> String {
+    name.trim().to_ascii_lowercase()
+}
+
+fn hex_radius(key: &str) -> Option<u8> {
+    let radius: u8 = key.strip_prefix("hex")?.parse().ok()?;
+    (1..=MAX_HEX_RADIUS).contains(&radius).then_some(radius)
+}
@@ -0,0 +1,51 @@
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Color;
+
+/// A note the human wrote during a game, for reviewing their decisions
+/// afterwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub turn: u32,
+    /// How many actions had been played when the note was written; the note
+    /// follows action `action_index - 1` in the replay.
+    pub action_index: usize,
+    pub color: Color,
+    pub text: String,
+}
+
+/// The notes of one game, shared between clones so the TUI can add to them
+/// while the game loop owns the player.
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationLog(Arc<Mutex<Vec<Annotation>>>);
+
+impl AnnotationLog {
+    pub fn push(&self, annotation: Annotation) {
+        self.0.lock().unwrap().push(annotation);
+    }
+
+    /// Every note so far, in the order they were written.
+    pub fn entries(&self) -> Vec<Annotation> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+
+    /// Writes the notes as a JSON array.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(writer, &self.entries()).map_err(io::Error::other)
+    }
+}
+
+/// Where the notes of the game replayed from `replay` live: next to it, as
+/// `<stem>.notes.json`.
+pub fn notes_path(replay: &Path) -> PathBuf {
+    replay.with_extension("notes.json")
+}
@@ -0,0 +1,159 @@
+//! Live feed of one game as JSON lines, so dashboards can follow a long
+//! benchmark run without the TUI.
+
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::game::action::GameAction;
+use crate::game::game::Game;
+use crate::game::state::GameEvent;
+use crate::types::Color;
+
+/// Where the feed goes: `-` or `stdout`, or `unix:PATH` to connect to a
+/// listening Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastTarget {
+    Stdout,
+    UnixSocket(PathBuf),
+}
+
+impl FromStr for BroadcastTarget {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "-" | "stdout" => Ok(BroadcastTarget::Stdout),
+            value => match value.strip_prefix("unix:") {
+                Some(path) if !path.is_empty() => Ok(BroadcastTarget::UnixSocket(path.into())),
+                _ => Err(format!(
+                    "invalid broadcast target '{value}', expected stdout or unix:PATH"
+                )),
+            },
+        }
+    }
+}
+
+/// One line of the feed.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message<'a> {
+    Start {
+        game: u32,
+        seed: u64,
+        players: Vec<Color>,
+    },
+    Action {
+        game: u32,
+        index: usize,
+        turn: u32,
+        action: &'a GameAction,
+        events: Vec<&'a GameEvent>,
+        victory_points: Vec<u8>,
+    },
+    End {
+        game: u32,
+        winner: Option<Color>,
+        turns: u32,
+    },
+}
+
+/// Writes a `start` line, one `action` line per executed action with the
+/// events it caused, and an `end` line. The game must be created with
+/// `Game::with_event_log` for events to show up.
+pub struct Broadcaster {
+    game: u32,
+    writer: Box<dyn Write + Send>,
+    actions_sent: usize,
+    events_sent: usize,
+}
+
+impl Broadcaster {
+    pub fn new(writer: Box<dyn Write + Send>, game: u32) -> Self {
+        Self {
+            game,
+            writer,
+            actions_sent: 0,
+            events_sent: 0,
+        }
+    }
+
+    pub fn open(target: &BroadcastTarget, game: u32) -> io::Result<Self> {
+        let writer: Box<dyn Write + Send> = match target {
+            BroadcastTarget::Stdout => Box::new(io::stdout()),
+            #[cfg(unix)]
+            BroadcastTarget::UnixSocket(path) => Box::new(BufWriter::new(
+                std::os::unix::net::UnixStream::connect(path)?,
+            )),
+            #[cfg(not(unix))]
+            BroadcastTarget::UnixSocket(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unix sockets are not available on this platform",
+                ));
+            }
+        };
+        Ok(Self::new(writer, game))
+    }
+
+    pub fn start(&mut self, game: &Game) -> io::Result<()> {
+        self.actions_sent = game.state.action_log().len();
+        self.events_sent = game.events().len();
+        self.send(&Message::Start {
+            game: self.game,
+            seed: game.seed,
+            players: game.state.players.iter().map(|p| p.color).collect(),
+        })
+    }
+
+    /// Sends every action executed since the last call.
+    pub fn tick(&mut self, game: &Game) -> io::Result<()> {
+        let actions = game.state.action_log();
+        let events = game.events();
+        let victory_points: Vec<u8> = game
+            .state
+            .players
+            .iter()
+            .map(|p| p.total_points())
+            .collect();
+        while self.actions_sent < actions.len() {
+            let index = self.actions_sent;
+            let start = self.events_sent;
+            while self.events_sent < events.len() && events[self.events_sent].action_index == index
+            {
+                self.events_sent += 1;
+            }
+            self.send(&Message::Action {
+                game: self.game,
+                index,
+                turn: game.state.turn,
+                action: &actions[index],
+                events: events[start..self.events_sent]
+                    .iter()
+                    .map(|logged| &logged.event)
+                    .collect(),
+                victory_points: victory_points.clone(),
+            })?;
+            self.actions_sent += 1;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self, game: &Game) -> io::Result<()> {
+        self.tick(game)?;
+        self.send(&Message::End {
+            game: self.game,
+            winner: game.winning_color(),
+            turns: game.state.turn,
+        })
+    }
+
+    fn send(&mut self, message: &Message<'_>) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, message).map_err(io::Error::other)?;
+        self.writer.write_all(b"\n")?;
+        // Flush every line so watchers see the game as it happens.
+        self.writer.flush()
+    }
+}
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::board::{CatanMap, NodeId};
 use crate::game::action::{ActionPayload, GameAction};
 use crate::types::ActionType;
 
@@ -87,6 +88,13 @@ fn group_key(action: &GameAction) -> String {
             }
         }
         ActionType::OfferTrade => "OfferTrade".to_string(),
+        ActionType::GiftResource => {
+            if let ActionPayload::Gift { partner, .. } = &action.payload {
+                format!("GiftResource:{}", partner)
+            } else {
+                "GiftResource".to_string()
+            }
+        }
         _ => format!("{:?}", action.action_type),
     }
 }
@@ -95,6 +103,8 @@ fn group_description(action: &GameAction) -> String {
     match action.action_type {
         ActionType::Roll => "Roll Dice".to_string(),
         ActionType::EndTurn => "End Turn".to_string(),
+        ActionType::Resign => "Resign".to_string(),
+        ActionType::Forfeit => "Forfeit".to_string(),
         ActionType::BuildRoad => "Build Road".to_string(),
         ActionType::BuildSettlement => "Build Settlement".to_string(),
         ActionType::BuildCity => "Build City".to_string(),
@@ -147,6 +157,13 @@ fn group_description(action: &GameAction) -> String {
         ActionType::RejectTrade => "Reject Trade".to_string(),
         ActionType::ConfirmTrade => "Confirm Trade".to_string(),
         ActionType::CancelTrade => "Cancel Trade".to_string(),
+        ActionType::GiftResource => {
+            if let ActionPayload::Gift { resource, partner } = &action.payload {
+                format!("Gift {:?} to player {}", resource, partner)
+            } else {
+                "Gift Resource".to_string()
+            }
+        }
     }
 }
 
@@ -208,12 +225,79 @@ pub fn expand_group(group: &CompressedActionGroup, start_index: usize) -> HashMa
     index_map
 }
 
+/// Placement groups with more options than this are split by board area.
+pub const SUBGROUP_THRESHOLD: usize = 8;
+
+/// Splits a large road, settlement or city group into one sub-group per land
+/// tile so placements can be browsed by area instead of by raw node id. Each
+/// placement is filed under the lowest-id tile it touches (for roads, a tile
+/// both ends touch). Returns `None` when the group doesn't need splitting.
+pub fn subgroup_by_tile(
+    group: &CompressedActionGroup,
+    map: &CatanMap,
+) -> Option<Vec<CompressedActionGroup>> {
+    if group.actions.len() <= SUBGROUP_THRESHOLD
+        || !matches!(
+            group.action_type,
+            ActionType::BuildRoad | ActionType::BuildSettlement | ActionType::BuildCity
+        )
+    {
+        return None;
+    }
+
+    let tiles_of = |node: &NodeId| map.adjacent_tiles.get(node).cloned().unwrap_or_default();
+    let mut by_tile: BTreeMap<Option<u16>, Vec<(usize, GameAction)>> = BTreeMap::new();
+    for (idx, action) in &group.actions {
+        let tile = match &action.payload {
+            ActionPayload::Node(node) => tiles_of(node).into_iter().min(),
+            ActionPayload::Edge((a, b)) => {
+                let b_tiles = tiles_of(b);
+                let a_tiles = tiles_of(a);
+                a_tiles
+                    .iter()
+                    .filter(|tile| b_tiles.contains(tile))
+                    .min()
+                    .or_else(|| a_tiles.iter().min())
+                    .copied()
+            }
+            _ => None,
+        };
+        by_tile
+            .entry(tile)
+            .or_default()
+            .push((*idx, action.clone()));
+    }
+    if by_tile.len() < 2 {
+        return None;
+    }
+
+    let subgroups = by_tile
+        .into_iter()
+        .map(|(tile, actions)| {
+            let description = match tile.and_then(|id| map.tiles_by_id.get(&id)) {
+                Some(tile) => match (tile.resource, tile.number) {
+                    (Some(resource), Some(number)) => {
+                        format!("Tile {} ({:?} {})", tile.id, resource, number)
+                    }
+                    _ => format!("Tile {} (Desert)", tile.id),
+                },
+                None => "Other".to_string(),
+            };
+            CompressedActionGroup {
+                action_type: group.action_type,
+                description,
+                actions,
+            }
+        })
+        .collect();
+    Some(subgroups)
+}
+
 pub fn action_detail_label(action: &GameAction) -> String {
     match action.action_type {
         ActionType::Roll => {
-            if let ActionPayload::Dice(d1, d2) = &action.payload {
-                let sum = (*d1 as u16) + (*d2 as u16);
-                format!("Rolled {} + {} = {}", d1, d2, sum)
+            if let ActionPayload::Dice(roll) = &action.payload {
+                format!("Rolled {} + {} = {}", roll.d1, roll.d2, roll.sum())
             } else {
                 group_description(action)
             }
@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A simulation run described in a TOML file, for `sim --config`. Every
+/// setting is optional; the ones left out keep their command-line default,
+/// and flags given on the command line override the file.
+///
+/// ```toml
+/// num = 1000
+/// players = ["F", "F:weights.json", "R", "R"]
+/// seed = 7
+/// map = "BASE"
+///
+/// [house_rules]
+/// friendly_robber = true
+///
+/// [output]
+/// results = "runs/baseline.json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    pub num: Option<u32>,
+    /// One player spec per seat, as in `--players`.
+    pub players: Option<Vec<String>>,
+    pub seed: Option<u64>,
+    pub map: Option<String>,
+    pub board_code: Option<String>,
+    pub vps_to_win: Option<u8>,
+    pub dice: Option<String>,
+    pub victory: Option<String>,
+    pub harbors: Option<String>,
+    pub setup: Option<String>,
+    /// Team id per seat.
+    pub teams: Option<Vec<usize>>,
+    pub team_gifts: Option<bool>,
+    pub domestic_trades: Option<bool>,
+    pub simultaneous_discard: Option<bool>,
+    pub auto_forced: Option<bool>,
+    pub time_control: Option<String>,
+    pub workers: Option<usize>,
+    pub house_rules: HouseRulesConfig,
+    pub output: OutputConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HouseRulesConfig {
+    pub friendly_robber: Option<bool>,
+    pub robber_avoids_desert: Option<bool>,
+}
+
+/// Where a run writes its artifacts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// As `--output`.
+    pub results: Option<String>,
+    pub vp_timeline: Option<String>,
+    pub checkpoint: Option<String>,
+    pub checkpoint_every: Option<u32>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RunConfigError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid TOML in {path}: {source}")]
+    Toml {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl RunConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RunConfigError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let text = std::fs::read_to_string(path).map_err(|source| RunConfigError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| RunConfigError::Toml {
+            path: display,
+            source,
+        })
+    }
+
+    /// The settings as `sim` flags, so they can be parsed ahead of the real
+    /// command line and be overridden by it.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut value = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(format!("--{flag}"));
+                args.push(value);
+            }
+        };
+        value("num", self.num.map(|num| num.to_string()));
+        value(
+            "players",
+            self.players.as_ref().map(|players| players.join(",")),
+        );
+        value("seed", self.seed.map(|seed| seed.to_string()));
+        value("map", self.map.clone());
+        value("board-code", self.board_code.clone());
+        value("vps-to-win", self.vps_to_win.map(|vps| vps.to_string()));
+        value("dice", self.dice.clone());
+        value("victory", self.victory.clone());
+        value("harbors", self.harbors.clone());
+        value("setup", self.setup.clone());
+        value(
+            "teams",
+            self.teams.as_ref().map(|teams| {
+                let teams: Vec<String> = teams.iter().map(|team| team.to_string()).collect();
+                teams.join(",")
+            }),
+        );
+        value("time-control", self.time_control.clone());
+        value("workers", self.workers.map(|workers| workers.to_string()));
+        value("output", self.output.results.clone());
+        value("vp-timeline", self.output.vp_timeline.clone());
+        value("checkpoint", self.output.checkpoint.clone());
+        value(
+            "checkpoint-every",
+            self.output.checkpoint_every.map(|every| every.to_string()),
+        );
+
+        let switches = [
+            ("team-gifts", self.team_gifts),
+            ("domestic-trades", self.domestic_trades),
+            ("simultaneous-discard", self.simultaneous_discard),
+            ("auto-forced", self.auto_forced),
+            ("friendly-robber", self.house_rules.friendly_robber),
+            (
+                "robber-avoids-desert",
+                self.house_rules.robber_avoids_desert,
+            ),
+        ];
+        for (flag, on) in switches {
+            if on == Some(true) {
+                args.push(format!("--{flag}"));
+            }
+        }
+        args
+    }
+}
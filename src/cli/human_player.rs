@@ -1,31 +1,181 @@
-use crate::cli::tui::TuiApp;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rand::rngs::StdRng;
+
+use crate::cli::annotations::AnnotationLog;
+use crate::cli::tui::{Advice, TuiApp, TuiOutcome};
 use crate::game::action::GameAction;
 use crate::game::game::Game;
-use crate::players::BasePlayer;
+use crate::players::{BasePlayer, WinEstimate, WinProbEstimator};
 use crate::types::Color;
 
 #[derive(Clone)]
 pub struct HumanPlayer {
     pub color: Color,
+    // Shared between clones so the game loop can see the user quit.
+    quit: Arc<AtomicBool>,
+    // Takebacks the game loop can honour right now, and whether the user
+    // asked for one.
+    takebacks: Arc<AtomicUsize>,
+    undo: Arc<AtomicBool>,
+    notes: AnnotationLog,
+    advisor: Option<Arc<Mutex<Advisor>>>,
+    win_tracker: Option<Arc<Mutex<WinTracker>>>,
 }
 
+/// Win probabilities for the game-state panel, estimated once per turn.
+struct WinTracker {
+    estimator: WinProbEstimator,
+    latest: Option<(u32, WinEstimate)>,
+}
+
+/// A bot asked about each of the human's positions. Its picks are shown,
+/// never played.
+struct Advisor {
+    name: String,
+    bot: Box<dyn BasePlayer + Send>,
+}
+
+/// How many of the advisor's picks the TUI lists.
+const ADVICE_MOVES: usize = 3;
+
 impl HumanPlayer {
     pub fn new(color: Color) -> Self {
-        Self { color }
+        Self {
+            color,
+            quit: Arc::new(AtomicBool::new(false)),
+            takebacks: Arc::new(AtomicUsize::new(0)),
+            undo: Arc::new(AtomicBool::new(false)),
+            notes: AnnotationLog::default(),
+            advisor: None,
+            win_tracker: None,
+        }
+    }
+
+    /// Shows every player's win probability, as `estimator` puts it at the
+    /// start of each turn.
+    pub fn with_estimator(mut self, estimator: WinProbEstimator) -> Self {
+        self.win_tracker = Some(Arc::new(Mutex::new(WinTracker {
+            estimator,
+            latest: None,
+        })));
+        self
+    }
+
+    /// Has `bot`, playing the human's color, recommend moves every time the
+    /// human has a choice. `name` labels its panel.
+    pub fn with_advisor(
+        mut self,
+        name: impl Into<String>,
+        bot: Box<dyn BasePlayer + Send>,
+    ) -> Self {
+        self.advisor = Some(Arc::new(Mutex::new(Advisor {
+            name: name.into(),
+            bot,
+        })));
+        self
+    }
+
+    /// Notes written with 'a' during the game, shared between clones.
+    pub fn notes(&self) -> &AnnotationLog {
+        &self.notes
+    }
+
+    /// True once the user has asked to leave the game from the TUI.
+    pub fn quit_requested(&self) -> bool {
+        self.quit.load(Ordering::Relaxed)
+    }
+
+    /// Lets the TUI offer `takebacks` undos on the next decisions. The game
+    /// loop keeps this in step with how far back it can go.
+    pub fn offer_takebacks(&self, takebacks: usize) {
+        self.takebacks.store(takebacks, Ordering::Relaxed);
+    }
+
+    /// True once after the user pressed 'u'; `decide` returned `None` for
+    /// that decision.
+    pub fn take_undo_request(&self) -> bool {
+        self.undo.swap(false, Ordering::Relaxed)
+    }
+
+    /// This turn's win probabilities, estimating them if the turn is new.
+    fn win_estimate(&self, game: &Game) -> Option<(u32, WinEstimate)> {
+        let mut tracker = self.win_tracker.as_ref()?.lock().unwrap();
+        let turn = game.state.turn;
+        if tracker.latest.as_ref().is_none_or(|(on, _)| *on != turn) {
+            let estimate = tracker.estimator.estimate(game);
+            tracker.latest = Some((turn, estimate));
+        }
+        tracker.latest.clone()
+    }
+
+    /// The advisor's best few actions with their scores, or just its pick
+    /// when it doesn't score actions.
+    fn advise(&self, game: &Game, actions: &[GameAction]) -> Option<Advice> {
+        let advisor = self.advisor.as_ref()?;
+        if actions.len() < 2 {
+            return None;
+        }
+        let mut advisor = advisor.lock().unwrap();
+        let moves = match advisor.bot.score_actions(game, actions) {
+            Some(scored) => scored
+                .into_iter()
+                .take(ADVICE_MOVES)
+                .map(|(action, score)| (action, Some(score)))
+                .collect(),
+            None => advisor
+                .bot
+                .decide(game, actions)
+                .map(|action| (action, None))
+                .into_iter()
+                .collect(),
+        };
+        Some(Advice {
+            advisor: advisor.name.clone(),
+            moves,
+        })
     }
 }
 
 impl BasePlayer for HumanPlayer {
-    fn decide(&self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
         if actions.is_empty() {
             return None;
         }
 
         // Use TUI for beautiful interactive interface
-        let mut app = TuiApp::new(game.copy(), self.color, actions.to_vec());
+        let mut app = TuiApp::new(game.copy(), self.color, actions.to_vec())
+            .with_notes(self.notes.clone())
+            .with_takebacks(self.takebacks.load(Ordering::Relaxed));
+        if let Some(advice) = self.advise(game, actions) {
+            app = app.with_advice(advice);
+        }
+        if let Some((turn, estimate)) = self.win_estimate(game) {
+            app = app.with_win_estimate(turn, estimate);
+        }
         match app.run() {
-            Ok(action) => action,
+            Ok(TuiOutcome::Selected(action)) => Some(action),
+            Ok(TuiOutcome::Quit) => {
+                self.quit.store(true, Ordering::Relaxed);
+                None
+            }
+            Ok(TuiOutcome::Undo) => {
+                self.undo.store(true, Ordering::Relaxed);
+                None
+            }
             Err(_) => None,
         }
     }
+
+    fn reseed(&mut self, rng: StdRng) {
+        if let Some(tracker) = &self.win_tracker {
+            let mut tracker = tracker.lock().unwrap();
+            tracker.estimator.reseed(rng.clone());
+            tracker.latest = None;
+        }
+        if let Some(advisor) = &self.advisor {
+            advisor.lock().unwrap().bot.reseed(rng);
+        }
+    }
 }
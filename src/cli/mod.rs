@@ -1,15 +1,29 @@
+pub mod annotations;
 pub mod board_display;
+pub mod broadcast;
 pub mod compressed_actions;
+pub mod config;
+#[cfg(feature = "tui")]
 pub mod human_player;
 pub mod players;
 pub mod stats;
+#[cfg(feature = "tui")]
 pub mod tui;
 
+pub use annotations::{Annotation, AnnotationLog, notes_path};
 pub use board_display::{display_board, render_board_to_string};
+pub use broadcast::{BroadcastTarget, Broadcaster};
 pub use compressed_actions::{
-    CompressedActionGroup, action_detail_label, compress_actions, expand_group,
+    CompressedActionGroup, SUBGROUP_THRESHOLD, action_detail_label, compress_actions, expand_group,
+    subgroup_by_tile,
 };
+pub use config::{HouseRulesConfig, OutputConfig, RunConfig, RunConfigError};
+#[cfg(feature = "tui")]
 pub use human_player::HumanPlayer;
-pub use players::{CLI_PLAYERS, CliPlayer, create_player, print_player_help};
-pub use stats::{GameStats, StatisticsAccumulator};
-pub use tui::TuiApp;
+pub use players::{CLI_PLAYERS, CliPlayer, create_player, print_player_help, try_create_player};
+pub use stats::{
+    DICE_CHI_SQUARED_CRITICAL, DiceStats, GameRecord, GameStats, PlayerStats, ResultsSummary,
+    STREAK_SIGNIFICANCE, SimCheckpoint, SpecSummary, StatisticsAccumulator, SumStreak, VpTimeline,
+};
+#[cfg(feature = "tui")]
+pub use tui::{Advice, TerminalGuard, TuiApp, TuiOutcome};
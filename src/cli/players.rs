@@ -1,9 +1,11 @@
 use crate::game::action::GameAction;
 use crate::game::game::Game;
 use crate::players::{
-    BasePlayer, MCTSPlayer, RandomPlayer, ValueFunctionParams, ValueFunctionPlayer,
+    BasePlayer, MCTSPlayer, ProgressiveWidening, RandomPlayer, SameTurnExpandPlayer,
+    ValueFunctionParams, ValueFunctionPlayer,
 };
 use crate::types::Color;
+use rand::rngs::StdRng;
 
 pub struct CliPlayer {
     pub code: &'static str,
@@ -20,12 +22,17 @@ pub const CLI_PLAYERS: &[CliPlayer] = &[
     CliPlayer {
         code: "F",
         name: "ValueFunctionPlayer",
-        description: "Chooses the action that leads to the most immediate reward, based on a hand-crafted value function.",
+        description: "Chooses the action that leads to the most immediate reward, based on a hand-crafted value function. Params: EPSILON and/or a weights file (.json or .toml).",
+    },
+    CliPlayer {
+        code: "F2",
+        name: "SameTurnExpandPlayer",
+        description: "Like F, but follows each candidate with the best moves left in the same turn before evaluating. Param: a weights file (.json or .toml).",
     },
     CliPlayer {
         code: "M",
         name: "MCTSPlayer",
-        description: "Decides according to the MCTS algorithm. First param is NUM_SIMULATIONS.",
+        description: "Decides according to the MCTS algorithm. Params: NUM_SIMULATIONS, PRUNNING, then the progressive widening COEFFICIENT (or 'off' to expand every action) and EXPONENT. A DET param anywhere searches over sampled opponent hands instead of the true ones.",
     },
 ];
 
@@ -33,45 +40,150 @@ pub const CLI_PLAYERS: &[CliPlayer] = &[
 pub enum PlayerInstance {
     Random(RandomPlayer),
     ValueFunction(ValueFunctionPlayer),
+    SameTurnExpand(SameTurnExpandPlayer),
     MCTS(MCTSPlayer),
 }
 
+impl PlayerInstance {
+    /// The player as a trait object, to sit alongside players that aren't
+    /// in the CLI registry.
+    pub fn boxed(self) -> Box<dyn BasePlayer + Send> {
+        Box::new(self)
+    }
+
+    /// Turns on `last_search_stats` for players that search; the others
+    /// are returned as they are.
+    pub fn with_search_stats(self) -> Self {
+        match self {
+            PlayerInstance::MCTS(p) => PlayerInstance::MCTS(p.with_search_stats()),
+            other => other,
+        }
+    }
+}
+
 impl BasePlayer for PlayerInstance {
-    fn decide(&self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
         match self {
             PlayerInstance::Random(p) => p.decide(game, actions),
             PlayerInstance::ValueFunction(p) => p.decide(game, actions),
+            PlayerInstance::SameTurnExpand(p) => p.decide(game, actions),
             PlayerInstance::MCTS(p) => p.decide(game, actions),
         }
     }
+
+    fn reseed(&mut self, rng: StdRng) {
+        match self {
+            PlayerInstance::Random(p) => p.reseed(rng),
+            PlayerInstance::ValueFunction(p) => p.reseed(rng),
+            PlayerInstance::SameTurnExpand(p) => p.reseed(rng),
+            PlayerInstance::MCTS(p) => p.reseed(rng),
+        }
+    }
+
+    fn last_search_stats(&self) -> Option<&str> {
+        match self {
+            PlayerInstance::Random(p) => p.last_search_stats(),
+            PlayerInstance::ValueFunction(p) => p.last_search_stats(),
+            PlayerInstance::SameTurnExpand(p) => p.last_search_stats(),
+            PlayerInstance::MCTS(p) => p.last_search_stats(),
+        }
+    }
+
+    fn score_actions(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> Option<Vec<(GameAction, f64)>> {
+        match self {
+            PlayerInstance::Random(p) => p.score_actions(game, actions),
+            PlayerInstance::ValueFunction(p) => p.score_actions(game, actions),
+            PlayerInstance::SameTurnExpand(p) => p.score_actions(game, actions),
+            PlayerInstance::MCTS(p) => p.score_actions(game, actions),
+        }
+    }
 }
 
 pub fn create_player(code: &str, color: Color, params: Vec<&str>) -> Option<PlayerInstance> {
+    try_create_player(code, color, params).ok()
+}
+
+/// Like `create_player`, but says what was wrong with the spec: an unknown
+/// code or a weights file that couldn't be loaded.
+pub fn try_create_player(
+    code: &str,
+    color: Color,
+    params: Vec<&str>,
+) -> Result<PlayerInstance, String> {
     match code {
-        "R" => Some(PlayerInstance::Random(RandomPlayer)),
+        "R" => Ok(PlayerInstance::Random(RandomPlayer::default())),
         "F" => {
-            let value_params = if params.is_empty() {
-                ValueFunctionParams::default()
-            } else {
-                // For now, use default params. Could parse custom params later
-                ValueFunctionParams::default()
+            // A number is the exploration epsilon, anything else a weights file.
+            let mut value_params = ValueFunctionParams::default();
+            let mut epsilon = None;
+            for param in params {
+                match param.parse::<f64>() {
+                    Ok(value) => epsilon = Some(value.clamp(0.0, 1.0)),
+                    Err(_) => {
+                        value_params =
+                            ValueFunctionParams::from_file(param).map_err(|err| err.to_string())?
+                    }
+                }
+            }
+            Ok(PlayerInstance::ValueFunction(ValueFunctionPlayer::new(
+                color,
+                Some(value_params),
+                epsilon,
+            )))
+        }
+        "F2" => {
+            let value_params = match params.first() {
+                Some(path) => {
+                    ValueFunctionParams::from_file(path).map_err(|err| err.to_string())?
+                }
+                None => ValueFunctionParams::default(),
             };
-            Some(PlayerInstance::ValueFunction(ValueFunctionPlayer::new(
+            Ok(PlayerInstance::SameTurnExpand(SameTurnExpandPlayer::new(
                 color,
                 Some(value_params),
-                None,
             )))
         }
         "M" => {
+            // DET may appear anywhere; the rest are positional.
+            let (det, params): (Vec<&str>, Vec<&str>) = params
+                .into_iter()
+                .partition(|param| param.eq_ignore_ascii_case("det"));
             // First param: number of simulations, default SIMULATIONS
             let num_sims = params.get(0).and_then(|s| s.parse::<usize>().ok());
             // Second param (optional): prunning flag (any value other than explicit "false" is treated as true)
             let prunning = params.get(1).map(|s| s.to_lowercase() != "false");
-            Some(PlayerInstance::MCTS(MCTSPlayer::new(
-                color, num_sims, prunning,
-            )))
+            // Third and fourth (optional): widening coefficient or "off", and exponent
+            let mut widening = Some(ProgressiveWidening::default());
+            match params.get(2).map(|s| s.to_lowercase()) {
+                Some(off) if off == "off" || off == "false" => widening = None,
+                Some(coefficient) => {
+                    let coefficient = coefficient
+                        .parse::<f64>()
+                        .map_err(|_| format!("Invalid widening coefficient '{}'", params[2]))?;
+                    let exponent = match params.get(3) {
+                        Some(exponent) => exponent
+                            .parse::<f64>()
+                            .map_err(|_| format!("Invalid widening exponent '{}'", exponent))?,
+                        None => ProgressiveWidening::default().exponent,
+                    };
+                    widening = Some(ProgressiveWidening {
+                        coefficient,
+                        exponent,
+                    });
+                }
+                None => {}
+            }
+            let mut player = MCTSPlayer::new(color, num_sims, prunning).with_widening(widening);
+            if !det.is_empty() {
+                player = player.with_determinization();
+            }
+            Ok(PlayerInstance::MCTS(player))
         }
-        _ => None,
+        _ => Err(format!("Unknown player code '{}'", code)),
     }
 }
 
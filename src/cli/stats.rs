@@ -1,17 +1,245 @@
 use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+
+use crate::game::action::{ActionPayload, GameAction};
 use crate::game::game::Game;
-use crate::types::Color;
+use crate::game::{GameConfig, GamePhase};
+use crate::types::probability::{ROLL_SUMS, number_probability};
+use crate::types::{ActionType, Color, DevelopmentCard, Resource};
+
+/// Chi-squared value above which the sum distribution of a run has less than
+/// a 0.1% chance of coming from two fair dice (10 degrees of freedom).
+pub const DICE_CHI_SQUARED_CRITICAL: f64 = 29.588;
+
+/// Streaks less likely than this to show up in a game of fair rolls are
+/// flagged.
+pub const STREAK_SIGNIFICANCE: f64 = 0.001;
 
-#[derive(Debug, Default, Clone)]
+/// Totals over a run of games. Per-player results are keyed by player spec
+/// (the `code[:params]` a bot was created from) rather than by color, so a
+/// strategy's results stay together whichever seats it played from, and
+/// seats sharing a spec are pooled.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct GameStats {
-    pub wins: HashMap<Color, u32>,
-    pub results_by_player: HashMap<Color, Vec<u8>>,
+    /// Games won by each spec.
+    pub wins: HashMap<String, u32>,
+    /// Final VPs of each spec, one entry per seat per game.
+    pub results_by_player: HashMap<String, Vec<u8>>,
+    pub player_stats: HashMap<String, PlayerStats>,
+    /// Wins per team id, only filled in for team games.
+    pub team_wins: HashMap<usize, u32>,
     pub games: u32,
     pub total_ticks: u64,
     pub total_turns: u64,
+    /// Most actions taken within a single turn, over every game.
+    #[serde(default)]
+    pub longest_turn_ticks: u32,
     pub total_duration: Duration,
+    #[serde(default)]
+    pub dice: DiceStats,
+}
+
+/// Per-player totals summed over every recorded game; `games` counts one
+/// per seat played.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub games: u32,
+    pub dice_resources: [u64; Resource::ALL.len()],
+    pub dev_cards_bought: HashMap<DevelopmentCard, u32>,
+    pub dev_cards_played: HashMap<DevelopmentCard, u32>,
+    pub settlements_built: u32,
+    pub cities_built: u32,
+    pub roads_built: u32,
+    pub times_robbed: u32,
+    pub cards_discarded: u32,
+}
+
+impl PlayerStats {
+    pub fn merge(&mut self, other: &PlayerStats) {
+        self.games += other.games;
+        for (total, value) in self.dice_resources.iter_mut().zip(other.dice_resources) {
+            *total += value;
+        }
+        for (card, count) in &other.dev_cards_bought {
+            *self.dev_cards_bought.entry(*card).or_default() += count;
+        }
+        for (card, count) in &other.dev_cards_played {
+            *self.dev_cards_played.entry(*card).or_default() += count;
+        }
+        self.settlements_built += other.settlements_built;
+        self.cities_built += other.cities_built;
+        self.roads_built += other.roads_built;
+        self.times_robbed += other.times_robbed;
+        self.cards_discarded += other.cards_discarded;
+    }
+
+    pub fn total_dice_resources(&self) -> u64 {
+        self.dice_resources.iter().sum()
+    }
+
+    pub fn total_dev_cards_bought(&self) -> u32 {
+        self.dev_cards_bought.values().sum()
+    }
+
+    pub fn total_dev_cards_played(&self) -> u32 {
+        self.dev_cards_played.values().sum()
+    }
+
+    /// Divides a summed counter by the number of games recorded.
+    pub fn per_game(&self, total: f64) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        total / self.games as f64
+    }
+
+    fn record_action(&mut self, action: &GameAction) {
+        match action.action_type {
+            ActionType::BuildSettlement => self.settlements_built += 1,
+            ActionType::BuildCity => self.cities_built += 1,
+            ActionType::BuildRoad => self.roads_built += 1,
+            ActionType::Discard => self.cards_discarded += 1,
+            ActionType::BuyDevelopmentCard => {
+                if let ActionPayload::DevelopmentCard(card) = action.payload {
+                    *self.dev_cards_bought.entry(card).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rolled sums over every recorded game, to check the dice against two fair
+/// six-sided dice.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DiceStats {
+    /// Rolls of each sum, indexed by the sum; 0 and 1 stay empty.
+    pub sums: [u64; 13],
+    /// Games with a streak less likely than `STREAK_SIGNIFICANCE`.
+    pub unlikely_streak_games: u32,
+    /// The least likely streak seen in any game.
+    pub unlikeliest_streak: Option<SumStreak>,
+}
+
+/// The same sum rolled several times in a row within one game.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SumStreak {
+    pub sum: u8,
+    pub length: u32,
+    /// Upper bound on the chance of fair dice rolling a streak of this sum at
+    /// least this long somewhere in the game.
+    pub p_value: f64,
+}
+
+impl SumStreak {
+    fn new(sum: u8, length: u32, rolls: usize) -> Self {
+        // Expected number of maximal runs of `length` or more: one can start
+        // on the first roll, or on any later roll that breaks the previous run.
+        let p = number_probability(sum);
+        let later_starts = rolls.saturating_sub(length as usize) as f64;
+        let p_value = (p.powi(length as i32) * (1.0 + later_starts * (1.0 - p))).min(1.0);
+        Self {
+            sum,
+            length,
+            p_value,
+        }
+    }
+
+    pub fn is_unlikely(&self) -> bool {
+        self.p_value < STREAK_SIGNIFICANCE
+    }
+}
+
+impl DiceStats {
+    /// Sums rolled in one game, in order.
+    pub fn game_sums(game: &Game) -> Vec<u8> {
+        game.state
+            .actions
+            .iter()
+            .filter_map(|action| match action.payload {
+                ActionPayload::Dice(roll) => Some(roll.sum()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Adds one game's sums, in the order they were rolled.
+    pub fn record_game(&mut self, sums: &[u8]) {
+        for sum in sums {
+            self.sums[*sum as usize] += 1;
+        }
+        let Some(streak) = Self::unlikeliest_streak_in(sums) else {
+            return;
+        };
+        if streak.is_unlikely() {
+            self.unlikely_streak_games += 1;
+        }
+        self.keep_unlikeliest(streak);
+    }
+
+    /// The streak of one game least likely under fair dice.
+    pub fn unlikeliest_streak_in(sums: &[u8]) -> Option<SumStreak> {
+        sums.chunk_by(|a, b| a == b)
+            .map(|run| SumStreak::new(run[0], run.len() as u32, sums.len()))
+            .min_by(|a, b| a.p_value.total_cmp(&b.p_value))
+    }
+
+    fn keep_unlikeliest(&mut self, streak: SumStreak) {
+        if self
+            .unlikeliest_streak
+            .is_none_or(|kept| streak.p_value < kept.p_value)
+        {
+            self.unlikeliest_streak = Some(streak);
+        }
+    }
+
+    pub fn merge(&mut self, other: &DiceStats) {
+        for (total, count) in self.sums.iter_mut().zip(other.sums) {
+            *total += count;
+        }
+        self.unlikely_streak_games += other.unlikely_streak_games;
+        if let Some(streak) = other.unlikeliest_streak {
+            self.keep_unlikeliest(streak);
+        }
+    }
+
+    pub fn total_rolls(&self) -> u64 {
+        self.sums.iter().sum()
+    }
+
+    /// Share of all rolls that came up `sum`.
+    pub fn share(&self, sum: u8) -> f64 {
+        let total = self.total_rolls();
+        if total == 0 {
+            return 0.0;
+        }
+        self.sums[sum as usize] as f64 / total as f64
+    }
+
+    /// Pearson's chi-squared of the sums against two fair dice.
+    pub fn chi_squared(&self) -> f64 {
+        let total = self.total_rolls() as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        ROLL_SUMS
+            .map(|sum| {
+                let expected = total * number_probability(sum);
+                let diff = self.sums[sum as usize] as f64 - expected;
+                diff * diff / expected
+            })
+            .sum()
+    }
+
+    /// Whether the sums stray further from fair dice than chance explains.
+    pub fn is_unlikely(&self) -> bool {
+        self.chi_squared() > DICE_CHI_SQUARED_CRITICAL
+    }
 }
 
 impl GameStats {
@@ -19,30 +247,146 @@ impl GameStats {
         Self {
             wins: HashMap::new(),
             results_by_player: HashMap::new(),
+            player_stats: HashMap::new(),
+            team_wins: HashMap::new(),
             total_ticks: 0,
             total_turns: 0,
+            longest_turn_ticks: 0,
             total_duration: Duration::ZERO,
             games: 0,
+            dice: DiceStats::default(),
         }
     }
 
-    pub fn record_game(&mut self, game: &Game, duration: Duration) {
+    /// Spec of the player in `seat`: `specs[seat]`, or the seat's color name
+    /// when no spec was given for it.
+    pub fn seat_spec(game: &Game, specs: &[String], seat: usize) -> String {
+        specs
+            .get(seat)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", game.state.players[seat].color))
+    }
+
+    /// Adds a finished game; `specs` holds the spec of each seat in order.
+    pub fn record_game(&mut self, game: &Game, specs: &[String], duration: Duration) {
         self.games += 1;
         self.total_duration += duration;
         self.total_turns += game.state.turn as u64;
-        self.total_ticks += game.state.actions.len() as u64;
+        self.total_ticks += game.state.tick as u64;
 
-        if let Some(winner) = game.winning_color() {
-            *self.wins.entry(winner).or_insert(0) += 1;
+        if let Some(winner) = game.winning_color()
+            && let Some(seat) = game.state.players.iter().position(|p| p.color == winner)
+        {
+            *self
+                .wins
+                .entry(Self::seat_spec(game, specs, seat))
+                .or_insert(0) += 1;
         }
 
-        for player in &game.state.players {
+        if game.state.config.teams.is_some()
+            && let GamePhase::Completed {
+                winner: Some(winner),
+            } = game.state.phase
+        {
+            *self
+                .team_wins
+                .entry(game.state.team_of(winner))
+                .or_insert(0) += 1;
+        }
+
+        for (seat, player) in game.state.players.iter().enumerate() {
             let vps = player.total_points();
             self.results_by_player
-                .entry(player.color)
+                .entry(Self::seat_spec(game, specs, seat))
                 .or_insert_with(Vec::new)
                 .push(vps);
         }
+
+        self.record_player_stats(game, specs);
+        self.dice.record_game(&DiceStats::game_sums(game));
+    }
+
+    fn record_player_stats(&mut self, game: &Game, specs: &[String]) {
+        let mut per_player: Vec<PlayerStats> = game
+            .state
+            .players
+            .iter()
+            .map(|player| {
+                let mut stats = PlayerStats {
+                    games: 1,
+                    ..PlayerStats::default()
+                };
+                for (total, value) in stats.dice_resources.iter_mut().zip(player.dice_income) {
+                    *total = value as u64;
+                }
+                for (card, count) in &player.played_dev_cards {
+                    stats.dev_cards_played.insert(*card, *count);
+                }
+                stats
+            })
+            .collect();
+
+        for action in &game.state.actions {
+            if let Some(stats) = per_player.get_mut(action.player_index) {
+                stats.record_action(action);
+            }
+            if let ActionPayload::Robber {
+                victim: Some(victim),
+                ..
+            } = action.payload
+                && let Some(stats) = per_player.get_mut(victim)
+            {
+                stats.times_robbed += 1;
+            }
+        }
+
+        for (seat, stats) in per_player.into_iter().enumerate() {
+            self.player_stats
+                .entry(Self::seat_spec(game, specs, seat))
+                .or_default()
+                .merge(&stats);
+        }
+    }
+
+    pub fn merge(&mut self, other: GameStats) {
+        for (spec, wins) in other.wins {
+            *self.wins.entry(spec).or_insert(0) += wins;
+        }
+        for (spec, vps) in other.results_by_player {
+            self.results_by_player.entry(spec).or_default().extend(vps);
+        }
+        for (spec, stats) in other.player_stats {
+            self.player_stats.entry(spec).or_default().merge(&stats);
+        }
+        for (team, wins) in other.team_wins {
+            *self.team_wins.entry(team).or_insert(0) += wins;
+        }
+        self.games += other.games;
+        self.total_ticks += other.total_ticks;
+        self.total_turns += other.total_turns;
+        self.longest_turn_ticks = self.longest_turn_ticks.max(other.longest_turn_ticks);
+        self.total_duration += other.total_duration;
+        self.dice.merge(&other.dice);
+    }
+
+    /// Share of the seats played by `spec` that won, so a spec filling two
+    /// seats isn't credited with twice the games.
+    pub fn win_rate(&self, spec: &str) -> f64 {
+        let seats = self.results_by_player.get(spec).map_or(0, Vec::len);
+        if seats == 0 {
+            return 0.0;
+        }
+        self.wins.get(spec).copied().unwrap_or(0) as f64 / seats as f64
+    }
+
+    /// Average final VPs of `spec` per seat played.
+    pub fn avg_vps(&self, spec: &str) -> f64 {
+        match self.results_by_player.get(spec) {
+            Some(vps) if !vps.is_empty() => {
+                vps.iter().map(|vp| *vp as f64).sum::<f64>() / vps.len() as f64
+            }
+            _ => 0.0,
+        }
     }
 
     pub fn get_avg_ticks(&self) -> f64 {
@@ -52,6 +396,15 @@ impl GameStats {
         self.total_ticks as f64 / self.games as f64
     }
 
+    /// Average actions per turn, counting every player's actions (rolls,
+    /// discards, trade responses) towards the turn they happen in.
+    pub fn get_avg_ticks_per_turn(&self) -> f64 {
+        if self.total_turns == 0 {
+            return 0.0;
+        }
+        self.total_ticks as f64 / self.total_turns as f64
+    }
+
     pub fn get_avg_turns(&self) -> f64 {
         if self.games == 0 {
             return 0.0;
@@ -67,18 +420,355 @@ impl GameStats {
     }
 }
 
+/// Public VPs of every player at the end of each turn of a single game.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct VpTimeline {
+    pub colors: Vec<Color>,
+    /// `(turn, public VPs indexed like colors)`
+    pub points: Vec<(u32, Vec<u8>)>,
+}
+
+impl VpTimeline {
+    fn push(&mut self, turn: u32, game: &Game) {
+        let vps = game
+            .state
+            .players
+            .iter()
+            .map(|player| player.public_points())
+            .collect();
+        self.points.push((turn, vps));
+    }
+}
+
+/// How one game went, kept per game for exporting results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub stream: u64,
+    /// Spec of the player in each seat.
+    pub seats: Vec<String>,
+    pub colors: Vec<Color>,
+    /// Seat of the winner; `None` for draws and abandoned games.
+    pub winner: Option<usize>,
+    pub turns: u32,
+    pub ticks: u32,
+    pub duration_ms: f64,
+    /// Final VPs of each seat.
+    pub vps: Vec<u8>,
+}
+
+impl GameRecord {
+    fn new(game: &Game, specs: &[String], duration: Duration) -> Self {
+        let players = &game.state.players;
+        Self {
+            seed: game.state.config.seed,
+            stream: game.state.config.stream,
+            seats: (0..players.len())
+                .map(|seat| GameStats::seat_spec(game, specs, seat))
+                .collect(),
+            colors: players.iter().map(|p| p.color).collect(),
+            winner: game
+                .winning_color()
+                .and_then(|winner| players.iter().position(|p| p.color == winner)),
+            turns: game.state.turn,
+            ticks: game.state.tick,
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            vps: players.iter().map(|p| p.total_points()).collect(),
+        }
+    }
+}
+
+/// Aggregate results of a run, in the form written by `write_results_*`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsSummary {
+    pub games: u32,
+    pub avg_turns: f64,
+    pub avg_ticks: f64,
+    pub avg_duration_ms: f64,
+    pub players: Vec<SpecSummary>,
+}
+
+/// Results of one player spec over a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecSummary {
+    pub spec: String,
+    /// Seats the spec filled in each game.
+    pub seats: usize,
+    pub wins: u32,
+    pub win_rate: f64,
+    pub avg_vps: f64,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct StatisticsAccumulator {
     pub stats: GameStats,
+    pub vp_timelines: Vec<VpTimeline>,
+    /// Spec of the player in each seat, see `GameStats`.
+    #[serde(default)]
+    player_specs: Vec<String>,
+    record_vp_timeline: bool,
+    #[serde(skip)]
+    current_timeline: Option<VpTimeline>,
+    /// One entry per game, only kept after `with_game_records`.
+    #[serde(default)]
+    pub game_records: Vec<GameRecord>,
+    #[serde(default)]
+    record_games: bool,
 }
 
 impl StatisticsAccumulator {
     pub fn new() -> Self {
         Self {
             stats: GameStats::new(),
+            vp_timelines: Vec::new(),
+            player_specs: Vec::new(),
+            record_vp_timeline: false,
+            current_timeline: None,
+            game_records: Vec::new(),
+            record_games: false,
+        }
+    }
+
+    /// Also keep a `GameRecord` of every game.
+    pub fn with_game_records(mut self) -> Self {
+        self.record_games = true;
+        self
+    }
+
+    /// Keys per-player results by these specs, one per seat in order.
+    /// Without them, results are keyed by color name.
+    pub fn with_player_specs(mut self, specs: Vec<String>) -> Self {
+        self.player_specs = specs;
+        self
+    }
+
+    pub fn player_specs(&self) -> &[String] {
+        &self.player_specs
+    }
+
+    /// Also record each player's public VPs turn by turn.
+    pub fn with_vp_timeline(mut self) -> Self {
+        self.record_vp_timeline = true;
+        self
+    }
+
+    pub fn before(&mut self, game: &Game) {
+        if self.record_vp_timeline {
+            self.current_timeline = Some(VpTimeline {
+                colors: game.state.players.iter().map(|p| p.color).collect(),
+                points: Vec::new(),
+            });
+        }
+    }
+
+    /// Call after every tick; tracks the longest turn and records a
+    /// timeline row whenever a turn has finished.
+    pub fn step(&mut self, game: &Game) {
+        self.stats.longest_turn_ticks = self
+            .stats
+            .longest_turn_ticks
+            .max(game.state.ticks_this_turn());
+        let Some(timeline) = self.current_timeline.as_mut() else {
+            return;
+        };
+        let next_turn = timeline.points.last().map_or(0, |(turn, _)| turn + 1);
+        if game.state.turn > next_turn {
+            timeline.push(game.state.turn - 1, game);
         }
     }
 
     pub fn after(&mut self, game: &Game, duration: Duration) {
-        self.stats.record_game(game, duration);
+        self.stats.record_game(game, &self.player_specs, duration);
+        if self.record_games {
+            self.game_records
+                .push(GameRecord::new(game, &self.player_specs, duration));
+        }
+        if let Some(mut timeline) = self.current_timeline.take() {
+            timeline.push(game.state.turn, game);
+            self.vp_timelines.push(timeline);
+        }
+    }
+
+    pub fn merge(&mut self, other: StatisticsAccumulator) {
+        self.stats.merge(other.stats);
+        self.vp_timelines.extend(other.vp_timelines);
+        self.game_records.extend(other.game_records);
+    }
+
+    /// Totals per distinct player spec, in the order the specs first appear
+    /// in the seats.
+    pub fn summary(&self) -> ResultsSummary {
+        let mut players: Vec<SpecSummary> = Vec::new();
+        for spec in &self.player_specs {
+            if let Some(row) = players.iter_mut().find(|row| &row.spec == spec) {
+                row.seats += 1;
+                continue;
+            }
+            players.push(SpecSummary {
+                spec: spec.clone(),
+                seats: 1,
+                wins: self.stats.wins.get(spec).copied().unwrap_or(0),
+                win_rate: self.stats.win_rate(spec),
+                avg_vps: self.stats.avg_vps(spec),
+            });
+        }
+        ResultsSummary {
+            games: self.stats.games,
+            avg_turns: self.stats.get_avg_turns(),
+            avg_ticks: self.stats.get_avg_ticks(),
+            avg_duration_ms: self.stats.get_avg_duration().as_secs_f64() * 1000.0,
+            players,
+        }
+    }
+
+    /// Game records ordered by stream, so parallel runs list games in the
+    /// order they were numbered rather than the order workers finished.
+    fn sorted_game_records(&self) -> Vec<&GameRecord> {
+        let mut records: Vec<&GameRecord> = self.game_records.iter().collect();
+        records.sort_by_key(|record| (record.seed, record.stream));
+        records
+    }
+
+    /// Writes `{"summary": ..., "games": [...]}`.
+    pub fn write_results_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Results<'a> {
+            summary: ResultsSummary,
+            games: Vec<&'a GameRecord>,
+        }
+        let results = Results {
+            summary: self.summary(),
+            games: self.sorted_game_records(),
+        };
+        serde_json::to_writer_pretty(writer, &results).map_err(io::Error::other)
+    }
+
+    /// Writes one row per game, with a `seatN`/`vpN` column pair per seat.
+    pub fn write_results_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let records = self.sorted_game_records();
+        let seats = records.iter().map(|r| r.seats.len()).max().unwrap_or(0);
+        write!(
+            writer,
+            "seed,stream,winner_seat,winner,turns,ticks,duration_ms"
+        )?;
+        for seat in 1..=seats {
+            write!(writer, ",seat{seat},vp{seat}")?;
+        }
+        writeln!(writer)?;
+        for record in records {
+            let (winner_seat, winner) = match record.winner {
+                Some(seat) => ((seat + 1).to_string(), record.seats[seat].as_str()),
+                None => (String::new(), ""),
+            };
+            write!(
+                writer,
+                "{},{},{},{},{},{},{:.3}",
+                record.seed,
+                record.stream,
+                winner_seat,
+                csv_field(winner),
+                record.turns,
+                record.ticks,
+                record.duration_ms
+            )?;
+            for (spec, vps) in record.seats.iter().zip(&record.vps) {
+                write!(writer, ",{},{}", csv_field(spec), vps)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes `summary()` as one row per player spec, repeating the run
+    /// totals on each row.
+    pub fn write_summary_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let summary = self.summary();
+        writeln!(
+            writer,
+            "spec,seats,wins,win_rate,avg_vps,games,avg_turns,avg_ticks,avg_duration_ms"
+        )?;
+        for player in &summary.players {
+            writeln!(
+                writer,
+                "{},{},{},{:.4},{:.3},{},{:.3},{:.3},{:.3}",
+                csv_field(&player.spec),
+                player.seats,
+                player.wins,
+                player.win_rate,
+                player.avg_vps,
+                summary.games,
+                summary.avg_turns,
+                summary.avg_ticks,
+                summary.avg_duration_ms
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the recorded timelines as `game,turn,color,public_vps` rows,
+    /// numbering games in the order they were recorded.
+    pub fn write_vp_timeline_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "game,turn,color,public_vps")?;
+        for (game_idx, timeline) in self.vp_timelines.iter().enumerate() {
+            for (turn, vps) in &timeline.points {
+                for (color, vp) in timeline.colors.iter().zip(vps) {
+                    writeln!(writer, "{},{},{:?},{}", game_idx + 1, turn, color, vp)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field when it contains a separator or a quote.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Progress of a long simulation run, saved between games so an interrupted
+/// run can pick up where it left off.
+#[derive(Serialize, Deserialize)]
+pub struct SimCheckpoint {
+    /// Index of the first game that hasn't been played yet.
+    pub next_game: u32,
+    /// What every game was created from, before its stream is set.
+    pub config: GameConfig,
+    pub players: String,
+    pub stats: StatisticsAccumulator,
+}
+
+impl SimCheckpoint {
+    /// Whether the run was started with `config` and `players`, so resuming
+    /// it with them adds up games of the same kind.
+    pub fn matches(&self, config: &GameConfig, players: &str) -> bool {
+        self.players == players
+            && serde_json::to_value(&self.config).ok() == serde_json::to_value(config).ok()
+    }
+
+    /// Reads a checkpoint, or `None` if there is no file at `path` yet.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Writes to a temporary file first and renames it over `path`, so a
+    /// crash mid-write never leaves a truncated checkpoint behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let data = serde_json::to_vec(self).map_err(io::Error::other)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)
     }
 }
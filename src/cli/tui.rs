@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::io::{self, Stdout, stdout};
-use std::process;
-use std::time::Duration;
+use std::ops::{Deref, DerefMut};
+use std::panic;
+use std::sync::Once;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{
     self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
@@ -14,32 +16,151 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
 
 use crate::board::NodeId;
-use crate::cli::board_display::{NodeSpan, render_board as render_ascii_board};
+use crate::cli::annotations::{Annotation, AnnotationLog};
+use crate::cli::board_display::{NodeSpan, render_board_fitted};
 use crate::cli::compressed_actions::{
-    CompressedActionGroup, action_detail_label, compress_actions, expand_group,
+    CompressedActionGroup, action_detail_label, compress_actions, expand_group, subgroup_by_tile,
 };
-use crate::game::action::GameAction;
-use crate::game::game::Game;
-use crate::game::state::Structure;
-use crate::types::{Color as PlayerColor, DevelopmentCard};
+use crate::game::action::{ActionPayload, GameAction};
+use crate::game::game::{Game, LoggedEvent, TURNS_LIMIT};
+use crate::game::resources::ResourceBundle;
+use crate::game::state::{GameEvent, GamePhase, Structure};
+use crate::players::ordering::order_groups;
+use crate::players::{BasePlayer, SearchStats, WinEstimate, WinProbEstimator};
+use crate::types::probability::number_pips;
+use crate::types::{ActionType, Color as PlayerColor, DevelopmentCard, Resource};
 
 pub type Terminal = ratatui::Terminal<CrosstermBackend<Stdout>>;
 
+/// Owns the terminal while the TUI is up: raw mode and mouse capture are
+/// switched on by `new` and always switched off again, whether the guard is
+/// dropped normally, by an early `?` return or while unwinding a panic.
+pub struct TerminalGuard {
+    terminal: Terminal,
+}
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        let mut guard = Self {
+            terminal: Terminal::new(CrosstermBackend::new(stdout()))?,
+        };
+        execute!(guard.terminal.backend_mut(), EnableMouseCapture)?;
+        guard.terminal.clear()?; // clear cargo/run output before first draw
+        Ok(guard)
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal;
+
+    fn deref(&self) -> &Terminal {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Terminal {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = self.terminal.clear();
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), DisableMouseCapture, crossterm::cursor::Show);
+}
+
+/// Restores the terminal before the default hook prints a panic message, so
+/// the message isn't mangled by raw mode.
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// How a `TuiApp::run` session ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TuiOutcome {
+    Selected(GameAction),
+    /// The user asked to leave the game.
+    Quit,
+    /// The user asked to take back their last action.
+    Undo,
+}
+
+/// Guided bank trade: pick the resource to give (at the player's best rate),
+/// then the resource to receive.
+struct MaritimeDialog {
+    give: Option<Resource>,
+    selected: usize,
+}
+
+/// What an advisor bot would play in the human's position, best first, with
+/// its score for each move when it gives one.
+pub struct Advice {
+    pub advisor: String,
+    pub moves: Vec<(GameAction, Option<f64>)>,
+}
+
+/// Playback state while watching a game between bots.
+struct SpectateState {
+    delay: Duration,
+    paused: bool,
+    fast_forward: bool,
+    step_requested: bool,
+    last_tick: Instant,
+    /// Seat and JSON `SearchStats` of the latest decision that came with
+    /// them, shown by the search overlay.
+    search_stats: Option<(usize, String)>,
+    show_search: bool,
+}
+
 pub struct TuiApp {
     game: Game,
-    human_color: PlayerColor,
+    human_color: Option<PlayerColor>,
     actions: Vec<GameAction>,
     compressed_groups: Vec<CompressedActionGroup>,
     selected_action_idx: usize,
-    expanded_group: Option<usize>,       // Group index if expanded
-    expanded_map: HashMap<usize, usize>, // Maps expanded index -> original index
+    expanded_group: Option<usize>,         // Group index if expanded
+    expanded_map: HashMap<usize, usize>,   // Maps expanded index -> original index
+    subgroups: Vec<CompressedActionGroup>, // By-tile split of the expanded group, if any
+    expanded_subgroup: Option<usize>,
+    maritime_dialog: Option<MaritimeDialog>,
+    /// Text of the note being typed, while the note prompt is open.
+    note_input: Option<String>,
+    notes: AnnotationLog,
+    advice: Option<Advice>,
+    /// Takebacks the human may still use; 'u' does nothing at zero.
+    takebacks: usize,
+    undo_requested: bool,
+    /// 'R' was pressed once; pressing it again resigns.
+    resign_armed: bool,
+    /// Latest win probabilities and the turn they were estimated on.
+    win_estimate: Option<(u32, WinEstimate)>,
+    /// Re-estimates every turn while spectating.
+    estimator: Option<WinProbEstimator>,
+    spectate: Option<SpectateState>,
     show_help: bool,
     should_quit: bool,
     selected_action: Option<GameAction>,
     history: Vec<GameAction>,
+    events: Vec<LoggedEvent>,
     game_state_scroll: u16,
     history_scroll: u16,
     game_state_max_scroll: u16,
@@ -48,23 +169,38 @@ pub struct TuiApp {
 
 impl TuiApp {
     pub fn new(game: Game, human_color: PlayerColor, actions: Vec<GameAction>) -> Self {
-        let compressed_groups = compress_actions(&actions);
+        let mut compressed_groups = Self::collapse_maritime_groups(compress_actions(&actions));
+        order_groups(&game.state, &mut compressed_groups);
         let expanded_map = HashMap::new();
 
         let history = game.state.actions.clone();
+        let events = game.events().to_vec();
 
         Self {
             game,
-            human_color,
+            human_color: Some(human_color),
             actions,
             compressed_groups,
             selected_action_idx: 0,
             expanded_group: None,
             expanded_map,
+            subgroups: Vec::new(),
+            expanded_subgroup: None,
+            maritime_dialog: None,
+            note_input: None,
+            notes: AnnotationLog::default(),
+            advice: None,
+            takebacks: 0,
+            undo_requested: false,
+            resign_armed: false,
+            win_estimate: None,
+            estimator: None,
+            spectate: None,
             show_help: false,
             should_quit: false,
             selected_action: None,
             history,
+            events,
             game_state_scroll: 0,
             history_scroll: 0,
             game_state_max_scroll: 0,
@@ -72,43 +208,216 @@ impl TuiApp {
         }
     }
 
-    pub fn run(&mut self) -> io::Result<Option<GameAction>> {
-        enable_raw_mode()?;
-        let mut stdout = stdout();
-        execute!(stdout, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-        terminal.clear()?; // clear cargo/run output before first draw
+    /// A read-only view for watching bots play `game`, advancing one action
+    /// every `delay`.
+    pub fn spectator(game: Game, delay: Duration) -> Self {
+        let mut app = Self::new(game, PlayerColor::Red, Vec::new());
+        app.human_color = None;
+        app.spectate = Some(SpectateState {
+            delay,
+            paused: false,
+            fast_forward: false,
+            step_requested: false,
+            last_tick: Instant::now(),
+            search_stats: None,
+            show_search: false,
+        });
+        app
+    }
+
+    /// Keeps the human's notes in `notes`, and shows the ones already
+    /// there in the history.
+    pub fn with_notes(mut self, notes: AnnotationLog) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Shows `advice` in a panel above the actions.
+    pub fn with_advice(mut self, advice: Advice) -> Self {
+        self.advice = Some(advice);
+        self
+    }
+
+    /// Lets the human press 'u' to take back their last action, with
+    /// `takebacks` left to use.
+    pub fn with_takebacks(mut self, takebacks: usize) -> Self {
+        self.takebacks = takebacks;
+        self
+    }
+
+    /// Shows each player's chance to win, as estimated on `turn`.
+    pub fn with_win_estimate(mut self, turn: u32, estimate: WinEstimate) -> Self {
+        self.win_estimate = Some((turn, estimate));
+        self
+    }
+
+    /// Has spectating keep the win probabilities up to date with
+    /// `estimator`, once per turn.
+    pub fn with_estimator(mut self, estimator: WinProbEstimator) -> Self {
+        self.estimator = Some(estimator);
+        self
+    }
 
-        let result = loop {
-            if self.should_quit {
-                break Ok(self.selected_action.take());
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    pub fn run(&mut self) -> io::Result<TuiOutcome> {
+        let mut terminal = TerminalGuard::new()?;
+
+        while !self.should_quit {
+            terminal.draw(|f| self.render(f))?;
+
+            // handle_key returns true once an action was selected or the user quit
+            if crossterm::event::poll(Duration::from_millis(50))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_key(key)
+            {
+                break;
             }
+        }
 
+        Ok(match self.selected_action.take() {
+            Some(action) => TuiOutcome::Selected(action),
+            None if self.undo_requested => TuiOutcome::Undo,
+            None => TuiOutcome::Quit,
+        })
+    }
+
+    /// Plays the game out with `players`, rendering after every action.
+    /// Space pauses, 'n' steps one action while paused, 'f' toggles
+    /// fast-forward, +/- change the delay and 's' shows what the last
+    /// searching bot looked at. Returns when the user quits.
+    pub fn spectate<P: BasePlayer>(&mut self, players: &mut [P]) -> io::Result<()> {
+        let mut terminal = TerminalGuard::new()?;
+        self.game.seed_players(players);
+
+        loop {
+            self.refresh_win_estimate();
             terminal.draw(|f| self.render(f))?;
 
-            if crossterm::event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        if self.handle_key(key) {
-                            // handle_key returned true, meaning we should quit or action was selected
-                            break Ok(self.selected_action.take());
-                        }
-                    }
-                }
+            if event::poll(Duration::from_millis(20))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_spectate_key(key)
+            {
+                return Ok(());
+            }
+
+            if self.spectate_due() {
+                self.spectate_tick(players);
+            }
+        }
+    }
+
+    /// Re-estimates win probabilities once the turn has moved on since the
+    /// last estimate, or the game has ended.
+    fn refresh_win_estimate(&mut self) {
+        let Some(estimator) = self.estimator.as_mut() else {
+            return;
+        };
+        let turn = self.game.state.turn;
+        let stale = match &self.win_estimate {
+            None => true,
+            Some((estimated_on, estimate)) => {
+                *estimated_on != turn || (self.game.is_over() && estimate.rollouts > 0)
             }
         };
+        if stale {
+            self.win_estimate = Some((turn, estimator.estimate(&self.game)));
+        }
+    }
 
-        // Always cleanup terminal state
-        let _ = terminal.clear();
-        let _ = disable_raw_mode();
-        let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
-        let _ = terminal.show_cursor();
+    fn spectate_finished(&self) -> bool {
+        self.game.is_over() || self.game.state.turn >= TURNS_LIMIT
+    }
 
-        result
+    fn spectate_due(&self) -> bool {
+        let Some(spectate) = &self.spectate else {
+            return false;
+        };
+        if self.spectate_finished() {
+            return false;
+        }
+        if spectate.paused {
+            return spectate.step_requested;
+        }
+        spectate.fast_forward || spectate.last_tick.elapsed() >= spectate.delay
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> bool {
+    fn spectate_tick<P: BasePlayer>(&mut self, players: &mut [P]) {
+        let seat = self.game.state.current_player;
+        let action = self.game.play_tick(players);
+        if let Some(spectate) = self.spectate.as_mut() {
+            if action.is_some()
+                && let Some(stats) = players.get(seat).and_then(|p| p.last_search_stats())
+            {
+                spectate.search_stats = Some((seat, stats.to_string()));
+            }
+            spectate.step_requested = false;
+            spectate.last_tick = Instant::now();
+            // A player with nothing to say would stall playback forever.
+            if action.is_none() {
+                spectate.paused = true;
+            }
+        }
+        self.history = self.game.state.actions.clone();
+        self.events = self.game.events().to_vec();
+    }
+
+    fn handle_spectate_key(&mut self, key: KeyEvent) -> bool {
+        if self.handle_scroll_key(key) {
+            return false;
+        }
+        let Some(spectate) = self.spectate.as_mut() else {
+            return true;
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => return true,
+            KeyCode::Char('h') => self.show_help = !self.show_help,
+            KeyCode::Char(' ') => spectate.paused = !spectate.paused,
+            KeyCode::Char('n') | KeyCode::Right => {
+                spectate.paused = true;
+                spectate.step_requested = true;
+            }
+            KeyCode::Char('f') => spectate.fast_forward = !spectate.fast_forward,
+            KeyCode::Char('s') => spectate.show_search = !spectate.show_search,
+            KeyCode::Char('+') => spectate.delay /= 2,
+            KeyCode::Char('-') => {
+                spectate.delay = (spectate.delay * 2).max(Duration::from_millis(10))
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Replaces the one-group-per-trade maritime entries with a single group
+    /// that opens the maritime dialog.
+    fn collapse_maritime_groups(groups: Vec<CompressedActionGroup>) -> Vec<CompressedActionGroup> {
+        let (maritime, mut groups): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|group| group.action_type == ActionType::MaritimeTrade);
+        if maritime.is_empty() {
+            return groups;
+        }
+        let mut actions: Vec<(usize, GameAction)> = maritime
+            .into_iter()
+            .flat_map(|group| group.actions)
+            .collect();
+        actions.sort_by_key(|(idx, _)| *idx);
+        groups.push(CompressedActionGroup {
+            action_type: ActionType::MaritimeTrade,
+            description: "Maritime Trade...".to_string(),
+            actions,
+        });
+        groups.sort_by(|a, b| a.description.cmp(&b.description));
+        groups
+    }
+
+    /// Ctrl(+Shift)+arrows scroll the game state (history) panel; returns
+    /// whether the key was one of them.
+    fn handle_scroll_key(&mut self, key: KeyEvent) -> bool {
         if key.modifiers.contains(KeyModifiers::CONTROL) {
             let is_shift = key.modifiers.contains(KeyModifiers::SHIFT);
             match key.code {
@@ -118,7 +427,7 @@ impl TuiApp {
                     } else {
                         self.adjust_game_state_scroll(-1);
                     }
-                    return false;
+                    return true;
                 }
                 KeyCode::Down => {
                     if is_shift {
@@ -126,7 +435,7 @@ impl TuiApp {
                     } else {
                         self.adjust_game_state_scroll(1);
                     }
-                    return false;
+                    return true;
                 }
                 KeyCode::PageUp => {
                     if is_shift {
@@ -134,7 +443,7 @@ impl TuiApp {
                     } else {
                         self.adjust_game_state_scroll(-5);
                     }
-                    return false;
+                    return true;
                 }
                 KeyCode::PageDown => {
                     if is_shift {
@@ -142,29 +451,76 @@ impl TuiApp {
                     } else {
                         self.adjust_game_state_scroll(5);
                     }
-                    return false;
+                    return true;
                 }
                 _ => {}
             }
         }
+        false
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.note_input.is_some() {
+            self.handle_note_key(key);
+            return false;
+        }
+        if self.handle_scroll_key(key) {
+            return false;
+        }
+        let resign_armed = std::mem::take(&mut self.resign_armed);
+        if self.maritime_dialog.is_some()
+            && matches!(
+                key.code,
+                KeyCode::Up | KeyCode::Down | KeyCode::Enter | KeyCode::Backspace | KeyCode::Left
+            )
+        {
+            return self.handle_maritime_key(key);
+        }
         match key.code {
             KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
-                // User wants to quit the game entirely - exit the program
-                // Cleanup terminal first
-                let _ = disable_raw_mode();
-                let _ = execute!(io::stdout(), DisableMouseCapture);
-                process::exit(0);
+                // User wants to quit the game entirely
+                self.selected_action = None;
+                self.should_quit = true;
+                return true;
             }
             KeyCode::Char('h') => {
                 self.show_help = !self.show_help;
             }
+            KeyCode::Char('a') => {
+                self.note_input = Some(String::new());
+            }
+            KeyCode::Char('u') if self.takebacks > 0 => {
+                self.selected_action = None;
+                self.undo_requested = true;
+                self.should_quit = true;
+                return true;
+            }
+            KeyCode::Char('R') if !matches!(self.game.state.phase, GamePhase::Setup(_)) => {
+                let seat = self
+                    .game
+                    .state
+                    .players
+                    .iter()
+                    .position(|player| Some(player.color) == self.human_color);
+                match seat {
+                    Some(seat) if resign_armed => {
+                        self.selected_action = Some(GameAction::resign(seat));
+                        self.should_quit = true;
+                        return true;
+                    }
+                    Some(_) => self.resign_armed = true,
+                    None => {}
+                }
+            }
             KeyCode::Up => {
                 if self.selected_action_idx > 0 {
                     self.selected_action_idx -= 1;
                 }
             }
             KeyCode::Down => {
-                let max_idx = if self.expanded_group.is_some() {
+                let max_idx = if self.showing_subgroups() {
+                    self.subgroups.len()
+                } else if self.expanded_group.is_some() {
                     self.expanded_map.len()
                 } else {
                     self.compressed_groups.len()
@@ -174,7 +530,14 @@ impl TuiApp {
                 }
             }
             KeyCode::Enter => {
-                if let Some(_expanded_idx) = self.expanded_group {
+                if self.showing_subgroups() {
+                    // Picked an area - list its placements
+                    if let Some(subgroup) = self.subgroups.get(self.selected_action_idx) {
+                        self.expanded_map = expand_group(subgroup, 0);
+                        self.expanded_subgroup = Some(self.selected_action_idx);
+                        self.selected_action_idx = 0;
+                    }
+                } else if let Some(_expanded_idx) = self.expanded_group {
                     // In expanded mode - select from expanded actions
                     if let Some(&original_idx) = self.expanded_map.get(&self.selected_action_idx) {
                         if original_idx < self.actions.len() {
@@ -186,15 +549,26 @@ impl TuiApp {
                     // Normal mode - check if it's a group or single action
                     if self.selected_action_idx < self.compressed_groups.len() {
                         let group = &self.compressed_groups[self.selected_action_idx];
-                        if group.actions.len() == 1 {
+                        if group.action_type == ActionType::MaritimeTrade {
+                            self.maritime_dialog = Some(MaritimeDialog {
+                                give: None,
+                                selected: 0,
+                            });
+                        } else if group.actions.len() == 1 {
                             // Single action - select it
                             let (original_idx, _) = &group.actions[0];
                             self.selected_action = Some(self.actions[*original_idx].clone());
                             return true;
                         } else {
-                            // Expand the group
+                            // Expand the group, by area first if it is large
+                            self.subgroups =
+                                subgroup_by_tile(group, &self.game.state.map).unwrap_or_default();
+                            self.expanded_map = if self.subgroups.is_empty() {
+                                expand_group(group, 0)
+                            } else {
+                                HashMap::new()
+                            };
                             self.expanded_group = Some(self.selected_action_idx);
-                            self.expanded_map = expand_group(group, 0);
                             self.selected_action_idx = 0;
                         }
                     }
@@ -202,9 +576,13 @@ impl TuiApp {
             }
             KeyCode::Backspace | KeyCode::Left => {
                 // Go back from expanded view
-                if self.expanded_group.is_some() {
+                if let Some(subgroup_idx) = self.expanded_subgroup.take() {
+                    self.expanded_map.clear();
+                    self.selected_action_idx = subgroup_idx;
+                } else if self.expanded_group.is_some() {
                     self.expanded_group = None;
                     self.expanded_map.clear();
+                    self.subgroups.clear();
                     self.selected_action_idx = 0;
                 }
             }
@@ -213,6 +591,124 @@ impl TuiApp {
         false
     }
 
+    /// Typing into the note prompt: Enter keeps the note, Esc drops it.
+    fn handle_note_key(&mut self, key: KeyEvent) {
+        let Some(text) = self.note_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => self.note_input = None,
+            KeyCode::Enter => {
+                let text = self.note_input.take().unwrap_or_default();
+                let text = text.trim();
+                if let (false, Some(color)) = (text.is_empty(), self.human_color) {
+                    self.notes.push(Annotation {
+                        turn: self.game.state.turn,
+                        action_index: self.history.len(),
+                        color,
+                        text: text.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// True while an expanded group is split by area and no area is open.
+    fn showing_subgroups(&self) -> bool {
+        self.expanded_group.is_some()
+            && !self.subgroups.is_empty()
+            && self.expanded_subgroup.is_none()
+    }
+
+    /// The group whose individual actions are currently listed.
+    fn expanded_actions(&self) -> Option<&CompressedActionGroup> {
+        let expanded_idx = self.expanded_group?;
+        if self.subgroups.is_empty() {
+            self.compressed_groups.get(expanded_idx)
+        } else {
+            self.subgroups.get(self.expanded_subgroup?)
+        }
+    }
+
+    fn handle_maritime_key(&mut self, key: KeyEvent) -> bool {
+        let Some(dialog) = self.maritime_dialog.as_mut() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => {
+                dialog.selected = dialog.selected.saturating_sub(1);
+            }
+            KeyCode::Down if dialog.selected + 1 < Resource::ALL.len() => {
+                dialog.selected += 1;
+            }
+            KeyCode::Backspace | KeyCode::Left => {
+                if let Some(give) = dialog.give.take() {
+                    dialog.selected = give as usize;
+                } else {
+                    self.maritime_dialog = None;
+                }
+            }
+            KeyCode::Enter => {
+                let resource = Resource::ALL[dialog.selected];
+                match dialog.give {
+                    None => {
+                        if self.can_give(resource) {
+                            self.maritime_dialog = Some(MaritimeDialog {
+                                give: Some(resource),
+                                selected: 0,
+                            });
+                        }
+                    }
+                    Some(give) => {
+                        if let Some(action) = self.maritime_action(give, resource) {
+                            self.selected_action = Some(action);
+                            self.maritime_dialog = None;
+                            return true;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn maritime_player(&self) -> usize {
+        self.actions
+            .first()
+            .map(|action| action.player_index)
+            .unwrap_or(self.game.state.current_player)
+    }
+
+    fn maritime_rate(&self, resource: Resource) -> u8 {
+        self.game
+            .state
+            .maritime_rate(self.maritime_player(), resource)
+    }
+
+    fn can_give(&self, resource: Resource) -> bool {
+        Resource::ALL
+            .iter()
+            .any(|receive| self.maritime_action(resource, *receive).is_some())
+    }
+
+    /// Builds the bank trade at the player's rate, provided it is legal.
+    fn maritime_action(&self, give: Resource, receive: Resource) -> Option<GameAction> {
+        let mut bundle = ResourceBundle::zero();
+        bundle.add(give, self.maritime_rate(give));
+        let action = GameAction::new(self.maritime_player(), ActionType::MaritimeTrade)
+            .with_payload(ActionPayload::MaritimeTrade {
+                give: bundle,
+                receive,
+            });
+        self.actions.contains(&action).then_some(action)
+    }
+
     fn render(&mut self, f: &mut Frame<'_>) {
         let area = f.size();
         let chunks = Layout::default()
@@ -239,15 +735,81 @@ impl TuiApp {
 
         // Render status/help bar at bottom
         self.render_status_bar(f, chunks[1]);
+
+        if self.spectate.as_ref().is_some_and(|s| s.show_search) {
+            self.render_search_overlay(f, main_chunks[0]);
+        }
+    }
+
+    /// Root children of the last search over the board: visits, Q (win
+    /// rate for the searching bot) and prior, most visited first.
+    fn render_search_overlay(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(spectate) = &self.spectate else {
+            return;
+        };
+        let mut lines: Vec<Line<'_>> = Vec::new();
+        let parsed = spectate
+            .search_stats
+            .as_ref()
+            .map(|(seat, json)| (seat, serde_json::from_str::<SearchStats>(json)));
+        match parsed {
+            None => lines.push(Line::from("No search recorded yet.")),
+            Some((_, Err(err))) => lines.push(Line::from(format!("Unreadable stats: {err}"))),
+            Some((seat, Ok(stats))) => {
+                let color = self.game.state.players[*seat].color;
+                lines.push(Line::from(format!(
+                    "{:?}: {} simulations, {} root visits, {} actions unexpanded",
+                    color, stats.simulations, stats.root_visits, stats.unexpanded
+                )));
+                lines.push(Line::from(format!(
+                    "{:>7} {:>6} {:>6}  Action",
+                    "Visits", "Q", "Prior"
+                )));
+                for child in &stats.children {
+                    let style = if stats.chosen.as_ref() == Some(&child.action) {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    lines.push(Line::styled(
+                        format!(
+                            "{:>7} {:>6.3} {:>6.3}  {}",
+                            child.visits,
+                            child.q,
+                            child.prior,
+                            action_detail_label(&child.action)
+                        ),
+                        style,
+                    ));
+                }
+            }
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Search (s to close)");
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
     }
 
     fn render_board(&self, f: &mut Frame<'_>, area: Rect) {
-        let rendered_board = render_ascii_board(&self.game);
+        // Less the block's borders.
+        let width = area.width.saturating_sub(2) as usize;
+        let rendered_board = render_board_fitted(&self.game, width);
         let mut span_lookup: HashMap<(usize, usize), (NodeId, NodeSpan)> = HashMap::new();
         for (node_id, span) in &rendered_board.node_spans {
             span_lookup.insert((span.row, span.col_start), (*node_id, *span));
         }
 
+        let hovered = self.hovered_nodes();
+
         let lines: Vec<Line<'_>> = rendered_board
             .text
             .lines()
@@ -258,7 +820,13 @@ impl TuiApp {
                 let mut col = 0;
                 while col < chars.len() {
                     if let Some((node_id, span)) = span_lookup.get(&(row_idx, col)) {
-                        if let Some(style) = self.style_for_node(*node_id) {
+                        let style = self.style_for_node(*node_id);
+                        let style = if hovered.contains(node_id) {
+                            Some(style.unwrap_or_default().add_modifier(Modifier::REVERSED))
+                        } else {
+                            style
+                        };
+                        if let Some(style) = style {
                             let segment: String = chars[col..col + span.len].iter().collect();
                             spans.push(Span::styled(segment, style));
                             col += span.len;
@@ -290,9 +858,146 @@ impl TuiApp {
             .wrap(Wrap { trim: false });
 
         f.render_widget(paragraph, area);
+
+        if let Some(span) = hovered
+            .first()
+            .and_then(|node| rendered_board.node_spans.get(node))
+        {
+            self.render_node_tooltip(f, area, &hovered, *span);
+        }
+    }
+
+    /// Nodes the highlighted action builds on: the node of a settlement or
+    /// city, both ends of a road.
+    fn hovered_nodes(&self) -> Vec<NodeId> {
+        if self.spectate.is_some() || self.showing_subgroups() {
+            return Vec::new();
+        }
+        let Some((_, action)) = self
+            .expanded_actions()
+            .and_then(|group| group.actions.get(self.selected_action_idx))
+        else {
+            return Vec::new();
+        };
+        match action.payload {
+            ActionPayload::Node(node) => vec![node],
+            ActionPayload::Edge((a, b)) => vec![a, b],
+            _ => Vec::new(),
+        }
+    }
+
+    /// What each of `nodes` is worth, in a box just below the first one.
+    fn render_node_tooltip(&self, f: &mut Frame<'_>, area: Rect, nodes: &[NodeId], at: NodeSpan) {
+        let lines: Vec<Line<'_>> = nodes
+            .iter()
+            .flat_map(|node| self.node_tooltip_lines(*node))
+            .collect();
+        let width = lines
+            .iter()
+            .map(|line| line.width() as u16)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(2)
+            .min(area.width);
+        let height = (lines.len() as u16).saturating_add(2).min(area.height);
+        // Inside the board's border, one row under the node, kept on screen.
+        let x = (area.x + 1 + at.col_start as u16).min(area.right().saturating_sub(width));
+        let below = area.y + 2 + at.row as u16;
+        let y = if below + height <= area.bottom() {
+            below
+        } else {
+            (area.y + at.row as u16).saturating_sub(height)
+        };
+        let tooltip = Rect::new(x, y.max(area.y), width, height);
+        f.render_widget(Clear, tooltip);
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL)),
+            tooltip,
+        );
+    }
+
+    /// Adjacent tiles with their pips, the node's harbor and whether the
+    /// human could settle there.
+    fn node_tooltip_lines(&self, node: NodeId) -> Vec<Line<'static>> {
+        let state = &self.game.state;
+        let map = &state.map;
+        let mut lines = vec![Line::styled(
+            format!("Node {node}"),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        let mut tile_ids = map.adjacent_tiles.get(&node).cloned().unwrap_or_default();
+        tile_ids.sort_unstable();
+        for tile in tile_ids.iter().filter_map(|id| map.tiles_by_id.get(id)) {
+            let label = match (tile.resource, tile.number) {
+                (Some(resource), Some(number)) => {
+                    let robber = if tile.id == state.robber_tile {
+                        ", robber"
+                    } else {
+                        ""
+                    };
+                    format!(
+                        "  {number:>2} {resource:?} ({} pips{robber})",
+                        number_pips(number)
+                    )
+                }
+                _ => "     Desert".to_string(),
+            };
+            lines.push(Line::from(label));
+        }
+        let production: f32 = map
+            .node_production
+            .get(&node)
+            .map(|production| production.values().sum())
+            .unwrap_or(0.0);
+        lines.push(Line::from(format!(
+            "  Production: {:.2} cards/roll",
+            production
+        )));
+        let port = map
+            .port_nodes
+            .iter()
+            .find(|(_, nodes)| nodes.contains(&node))
+            .map(|(resource, _)| match resource {
+                Some(resource) => format!("2:1 {resource:?}"),
+                None => "3:1".to_string(),
+            });
+        lines.push(Line::from(format!(
+            "  Port: {}",
+            port.as_deref().unwrap_or("none")
+        )));
+        if let Some(human_idx) = state
+            .players
+            .iter()
+            .position(|player| Some(player.color) == self.human_color)
+        {
+            let buildable = state.buildable_settlement_nodes(human_idx).contains(&node);
+            lines.push(Line::from(format!(
+                "  You can settle: {}",
+                if buildable { "yes" } else { "no" }
+            )));
+        }
+        lines
     }
 
     fn render_right_panel(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if let Some(advice) = &self.advice {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(30),                        // Game state
+                    Constraint::Length(advice.moves.len() as u16 + 2), // Advisor
+                    Constraint::Min(5),                                // Actions
+                    Constraint::Percentage(25),                        // History
+                ])
+                .split(area);
+
+            self.render_game_state(f, chunks[0]);
+            self.render_advice(f, chunks[1]);
+            self.render_actions(f, chunks[2]);
+            self.render_history_panel(f, chunks[3]);
+            return;
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -307,14 +1012,54 @@ impl TuiApp {
         self.render_history_panel(f, chunks[2]);
     }
 
+    fn render_advice(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(advice) = &self.advice else {
+            return;
+        };
+        let lines: Vec<Line<'_>> = advice
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(rank, (action, score))| {
+                let mut spans = vec![Span::styled(
+                    format!("{}. ", rank + 1),
+                    Style::default().fg(Color::DarkGray),
+                )];
+                spans.push(Span::styled(
+                    action_detail_label(action),
+                    if rank == 0 {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    },
+                ));
+                if let Some(score) = score {
+                    spans.push(Span::styled(
+                        format!("  {}", format_score(*score)),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Advisor ({})", advice.advisor)),
+        );
+        f.render_widget(paragraph, area);
+    }
+
     fn render_game_state(&mut self, f: &mut Frame<'_>, area: Rect) {
         let human_idx = self
             .game
             .state
             .players
             .iter()
-            .position(|p| p.color == self.human_color)
-            .unwrap_or(0);
+            .position(|p| Some(p.color) == self.human_color);
 
         let mut lines: Vec<Line<'_>> = vec![];
         lines.push(Line::from(vec![
@@ -328,9 +1073,10 @@ impl TuiApp {
         ]));
 
         // Player info
+        let game_over = matches!(self.game.state.phase, GamePhase::Completed { .. });
         for (idx, player) in self.game.state.players.iter().enumerate() {
             let is_current = idx == self.game.state.current_player;
-            let is_human = idx == human_idx;
+            let is_human = human_idx == Some(idx);
             let color = self.color_for_player(player.color);
             let marker = if is_current { "→ " } else { "  " };
             let label = if is_human { "YOU" } else { "BOT" };
@@ -343,14 +1089,42 @@ impl TuiApp {
                     Style::default().fg(color).add_modifier(Modifier::BOLD),
                 ),
             ]));
+            // Opponents' victory point cards stay hidden until the game ends.
+            let vps = if is_human || game_over {
+                player.total_points()
+            } else {
+                player.public_points()
+            };
             lines.push(Line::from(vec![
                 Span::raw("  VP: "),
-                Span::styled(
-                    format!("{}", player.total_points()),
-                    Style::default().fg(Color::Green),
-                ),
+                Span::styled(format!("{vps}"), Style::default().fg(Color::Green)),
             ]));
 
+            if let Some(probability) = self
+                .win_estimate
+                .as_ref()
+                .and_then(|(_, estimate)| estimate.probabilities.get(idx))
+            {
+                lines.push(Line::from(vec![
+                    Span::raw("  Win chance: "),
+                    Span::styled(
+                        format!("{:.0}%", probability * 100.0),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                ]));
+            }
+
+            if let Some(clock) = &self.game.state.clock {
+                let remaining = clock.remaining(idx).as_secs();
+                lines.push(Line::from(vec![
+                    Span::raw("  Clock: "),
+                    Span::styled(
+                        format!("{}:{:02}", remaining / 60, remaining % 60),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                ]));
+            }
+
             // Resources
             let resources = format!("{}", player.resources);
             lines.push(Line::from(vec![
@@ -395,12 +1169,12 @@ impl TuiApp {
         }
 
         // Last roll
-        if let Some((d1, d2)) = self.game.state.last_roll {
+        if let Some(roll) = self.game.state.last_roll {
             lines.push(Line::from(""));
             lines.push(Line::from(vec![
                 Span::raw("Last Roll: "),
                 Span::styled(
-                    format!("{} + {} = {}", d1, d2, d1 + d2),
+                    format!("{} + {} = {}", roll.d1, roll.d2, roll.sum()),
                     Style::default().fg(Color::Yellow),
                 ),
             ]));
@@ -425,11 +1199,39 @@ impl TuiApp {
     }
 
     fn render_actions(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.spectate.is_some() {
+            self.render_playback(f, area);
+            return;
+        }
+        if self.maritime_dialog.is_some() {
+            self.render_maritime_dialog(f, area);
+            return;
+        }
+
         let mut items: Vec<ListItem<'_>> = vec![];
 
-        if let Some(expanded_idx) = self.expanded_group {
+        if self.showing_subgroups() {
+            // Show the areas of a split group
+            for (idx, subgroup) in self.subgroups.iter().enumerate() {
+                let style = if idx == self.selected_action_idx {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+
+                items.push(
+                    ListItem::new(format!(
+                        "  {} ({} options)",
+                        subgroup.description,
+                        subgroup.actions.len()
+                    ))
+                    .style(style),
+                );
+            }
+        } else if let Some(group) = self.expanded_actions() {
             // Show expanded actions
-            let group = &self.compressed_groups[expanded_idx];
             for (exp_idx, (_original_idx, action)) in group.actions.iter().enumerate() {
                 let details = action_detail_label(action);
                 let style = if exp_idx == self.selected_action_idx {
@@ -463,7 +1265,9 @@ impl TuiApp {
             }
         }
 
-        let title = if self.expanded_group.is_some() {
+        let title = if self.showing_subgroups() {
+            "Available Actions (By Area)"
+        } else if self.expanded_group.is_some() {
             "Available Actions (Expanded)"
         } else {
             "Available Actions"
@@ -483,18 +1287,135 @@ impl TuiApp {
         f.render_stateful_widget(list, area, &mut state);
     }
 
+    fn render_playback(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(spectate) = &self.spectate else {
+            return;
+        };
+
+        let status = if self.spectate_finished() {
+            match self.game.winning_color() {
+                Some(color) => format!("Game over - {:?} wins", color),
+                None => "Game over - no winner".to_string(),
+            }
+        } else if spectate.paused {
+            "Paused".to_string()
+        } else if spectate.fast_forward {
+            "Fast-forward".to_string()
+        } else {
+            "Playing".to_string()
+        };
+
+        let mut lines: Vec<Line<'_>> = vec![
+            Line::from(vec![
+                Span::raw("Status: "),
+                Span::styled(
+                    status,
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            Line::from(format!("Delay: {} ms", spectate.delay.as_millis())),
+            Line::from(""),
+        ];
+        if !self.history.is_empty() {
+            lines.push(Line::from("Last action:"));
+            lines.extend(self.history_entry_lines(self.history.len() - 1));
+        }
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Playback"))
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(paragraph, area);
+    }
+
+    fn render_maritime_dialog(&self, f: &mut Frame<'_>, area: Rect) {
+        let Some(dialog) = &self.maritime_dialog else {
+            return;
+        };
+        let hand = &self.game.state.players[self.maritime_player()].resources;
+
+        let items: Vec<ListItem<'_>> = Resource::ALL
+            .iter()
+            .enumerate()
+            .map(|(idx, resource)| {
+                let (text, enabled) = match dialog.give {
+                    None => {
+                        let rate = self.maritime_rate(*resource);
+                        (
+                            format!(
+                                "{:<6} {}:1  (have {})",
+                                format!("{:?}", resource),
+                                rate,
+                                hand.get(*resource)
+                            ),
+                            self.can_give(*resource),
+                        )
+                    }
+                    Some(give) => (
+                        format!("{:?}", resource),
+                        self.maritime_action(give, *resource).is_some(),
+                    ),
+                };
+                let style = if idx == dialog.selected {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else if enabled {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                };
+                ListItem::new(format!("  {}", text)).style(style)
+            })
+            .collect();
+
+        let title = match dialog.give {
+            None => "Maritime Trade - give which resource?".to_string(),
+            Some(give) => format!(
+                "Maritime Trade - {} {:?} for which resource?",
+                self.maritime_rate(give),
+                give
+            ),
+        };
+
+        let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+
+        let mut state = ListState::default();
+        state.select(Some(dialog.selected));
+
+        f.render_stateful_widget(list, area, &mut state);
+    }
+
     fn render_history_panel(&mut self, f: &mut Frame<'_>, area: Rect) {
         let block = Block::default()
             .borders(Borders::ALL)
             .title("Action History");
 
+        let notes = self.notes.entries();
+        let note_lines = |after: usize| {
+            notes
+                .iter()
+                .filter(move |note| note.action_index == after)
+                .map(|note| {
+                    Line::styled(
+                        format!("    Note: {}", note.text),
+                        Style::default()
+                            .fg(Color::Cyan)
+                            .add_modifier(Modifier::ITALIC),
+                    )
+                })
+        };
+
         let mut lines: Vec<Line<'_>> = Vec::new();
         if self.history.is_empty() {
             lines.push(Line::from("No actions have been taken yet."));
-        } else {
-            for (idx, action) in self.history.iter().enumerate() {
-                lines.push(Line::from(self.format_history_entry(idx, action)));
-            }
+        }
+        lines.extend(note_lines(0));
+        for idx in 0..self.history.len() {
+            lines.extend(self.history_entry_lines(idx));
+            lines.extend(note_lines(idx + 1));
         }
 
         let viewport_height = area.height.saturating_sub(2);
@@ -512,11 +1433,34 @@ impl TuiApp {
     }
 
     fn render_status_bar(&self, f: &mut Frame<'_>, area: Rect) {
-        let help_text = if self.show_help {
-            "↑/↓: Navigate | Enter: Select/Expand | ←/Backspace: Back | Ctrl+↑/↓: Scroll Game | Ctrl+Shift+↑/↓: Scroll History | h: Toggle Help | q/Esc: Quit"
+        if let Some(text) = &self.note_input {
+            let paragraph = Paragraph::new(format!("{text}_")).block(
+                Block::default().borders(Borders::ALL).title(format!(
+                    "Note for turn {} (Enter: save, Esc: cancel)",
+                    self.game.state.turn
+                )),
+            );
+            f.render_widget(paragraph, area);
+            return;
+        }
+        let help_text = if self.spectate.is_some() {
+            if self.show_help {
+                "Space: Pause/Resume | n/→: Step | f: Fast-forward | +/-: Speed | s: Search | Ctrl+↑/↓: Scroll Game | Ctrl+Shift+↑/↓: Scroll History | h: Toggle Help | q/Esc: Quit"
+            } else {
+                "Press 'h' for help | Space pause | n step | f fast-forward | q quit"
+            }
+        } else if self.show_help {
+            "↑/↓: Navigate | Enter: Select/Expand/Trade | ←/Backspace: Back | a: Add Note | R: Resign | Ctrl+↑/↓: Scroll Game | Ctrl+Shift+↑/↓: Scroll History | h: Toggle Help | q/Esc: Quit"
         } else {
             "Press 'h' for help | Ctrl+↑/↓ game scroll | Ctrl+Shift+↑/↓ history scroll"
         };
+        let help_text = if self.resign_armed {
+            "Press R again to resign, any other key to cancel".to_string()
+        } else if self.takebacks > 0 {
+            format!("{help_text} | u: Undo ({} left)", self.takebacks)
+        } else {
+            help_text.to_string()
+        };
 
         let paragraph = Paragraph::new(help_text)
             .block(Block::default().borders(Borders::ALL))
@@ -591,31 +1535,298 @@ impl TuiApp {
         }
     }
 
-    fn format_history_entry(&self, idx: usize, action: &GameAction) -> String {
-        let player_label = self
-            .game
+    /// The history lines for action `idx`: one line per thing that happened,
+    /// with player names in their colors and hidden cards blanked for the
+    /// human. Rolls are folded into a single "rolled 8: Blue +2 Wheat" line.
+    fn history_entry_lines(&self, idx: usize) -> Vec<Line<'static>> {
+        let action = &self.history[idx];
+        let events = self.events_for_action(idx);
+
+        let mut rows: Vec<Vec<Span<'static>>> = Vec::new();
+        if action.action_type == ActionType::Roll
+            && let Some(GameEvent::DiceRolled { player, sum, .. }) = events
+                .iter()
+                .find(|event| matches!(event, GameEvent::DiceRolled { .. }))
+        {
+            let mut row = vec![
+                self.player_span(*player),
+                Span::raw(format!(" rolled {}", sum)),
+            ];
+            let gains: Vec<(usize, &ResourceBundle)> = events
+                .iter()
+                .filter_map(|event| match event {
+                    GameEvent::ResourcesDistributed { player, bundle, .. } => {
+                        Some((*player, bundle))
+                    }
+                    _ => None,
+                })
+                .collect();
+            for (i, (player, bundle)) in gains.iter().enumerate() {
+                row.push(Span::raw(if i == 0 { ": " } else { ", " }));
+                row.push(self.player_span(*player));
+                row.push(Span::raw(format!(" {}", format_gains(bundle))));
+            }
+            rows.push(row);
+            rows.extend(events.iter().filter_map(|event| match event {
+                GameEvent::DiceRolled { .. } | GameEvent::ResourcesDistributed { .. } => None,
+                event => self.event_spans(event),
+            }));
+        } else {
+            rows.extend(events.iter().filter_map(|event| self.event_spans(event)));
+        }
+
+        if rows.is_empty() {
+            let action_type = format!("{:?}", action.action_type);
+            let detail = action_detail_label(action);
+            let text = if detail == action_type {
+                format!(" {}", action_type)
+            } else {
+                format!(" {} – {}", action_type, detail)
+            };
+            rows.push(vec![self.player_span(action.player_index), Span::raw(text)]);
+        }
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(row_idx, mut row)| {
+                let prefix = if row_idx == 0 {
+                    format!("#{} ", idx + 1)
+                } else {
+                    "    ".to_string()
+                };
+                row.insert(
+                    0,
+                    Span::styled(prefix, Style::default().fg(Color::DarkGray)),
+                );
+                Line::from(row)
+            })
+            .collect()
+    }
+
+    /// Events logged for action `idx`, as the human is allowed to see them.
+    fn events_for_action(&self, idx: usize) -> Vec<GameEvent> {
+        let start = self
+            .events
+            .partition_point(|logged| logged.action_index < idx);
+        let end = self
+            .events
+            .partition_point(|logged| logged.action_index <= idx);
+        let viewer = self.human_index();
+        self.events[start..end]
+            .iter()
+            .map(|logged| match viewer {
+                Some(viewer) => logged.event.visible_to(viewer),
+                None => logged.event.clone(),
+            })
+            .collect()
+    }
+
+    fn human_index(&self) -> Option<usize> {
+        self.game
             .state
             .players
-            .get(action.player_index)
-            .map(|player| {
-                if player.color == self.human_color {
-                    format!("YOU ({:?})", player.color)
+            .iter()
+            .position(|player| Some(player.color) == self.human_color)
+    }
+
+    fn player_span(&self, player_idx: usize) -> Span<'static> {
+        match self.game.state.players.get(player_idx) {
+            Some(player) => {
+                let name = if Some(player.color) == self.human_color {
+                    "You".to_string()
                 } else {
                     format!("{:?}", player.color)
-                }
-            })
-            .unwrap_or_else(|| format!("Player {}", action.player_index));
-
-        let action_type = format!("{:?}", action.action_type);
-        let detail = action_detail_label(action);
+                };
+                Span::styled(
+                    name,
+                    Style::default()
+                        .fg(self.color_for_player(player.color))
+                        .add_modifier(Modifier::BOLD),
+                )
+            }
+            None => Span::raw(format!("Player {}", player_idx)),
+        }
+    }
 
-        if detail == action_type {
-            format!("#{} {} {}", idx + 1, player_label, action_type)
-        } else {
-            format!("#{} {} {} – {}", idx + 1, player_label, action_type, detail)
+    fn award_spans(
+        &self,
+        award: &str,
+        previous: Option<usize>,
+        holder: Option<usize>,
+    ) -> Vec<Span<'static>> {
+        match (holder, previous) {
+            (Some(holder), Some(previous)) => vec![
+                self.player_span(holder),
+                Span::raw(format!(" took {award} from ")),
+                self.player_span(previous),
+            ],
+            (Some(holder), None) => vec![
+                self.player_span(holder),
+                Span::raw(format!(" claimed {award}")),
+            ],
+            (None, Some(previous)) => vec![
+                self.player_span(previous),
+                Span::raw(format!(" lost {award}")),
+            ],
+            (None, None) => Vec::new(),
         }
     }
 
+    /// One history line for `event`, or `None` for events that add nothing
+    /// to the action line (turn changes).
+    fn event_spans(&self, event: &GameEvent) -> Option<Vec<Span<'static>>> {
+        let spans = match event {
+            GameEvent::DiceRolled { player, sum, .. } => {
+                vec![
+                    self.player_span(*player),
+                    Span::raw(format!(" rolled {}", sum)),
+                ]
+            }
+            GameEvent::ResourcesDistributed { player, bundle, .. } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" received {}", format_gains(bundle))),
+            ],
+            GameEvent::BuiltRoad { player, edge } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" built a road on edge ({}, {})", edge.0, edge.1)),
+            ],
+            GameEvent::BuiltSettlement { player, node } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" built a settlement at node {}", node)),
+            ],
+            GameEvent::BuiltCity { player, node } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" built a city at node {}", node)),
+            ],
+            GameEvent::TurnAdvanced { .. } => return None,
+            GameEvent::DevelopmentCardBought { player, card } => vec![
+                self.player_span(*player),
+                Span::raw(match card {
+                    Some(card) => format!(" bought a development card ({:?})", card),
+                    None => " bought a development card".to_string(),
+                }),
+            ],
+            GameEvent::RobberMoved { player, tile, .. } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" moved the robber to tile {}", tile)),
+            ],
+            GameEvent::ResourceStolen {
+                thief,
+                victim,
+                amount,
+                resource,
+            } => vec![
+                self.player_span(*thief),
+                Span::raw(match resource {
+                    Some(resource) => format!(" stole {} {:?} from ", amount, resource),
+                    None => format!(" stole {} card(s) from ", amount),
+                }),
+                self.player_span(*victim),
+            ],
+            GameEvent::MonopolyPlayed {
+                player,
+                resource,
+                taken,
+            } => {
+                let mut spans = vec![
+                    self.player_span(*player),
+                    Span::raw(format!(" played Monopoly on {:?}", resource)),
+                ];
+                for (i, (victim, amount)) in taken.iter().enumerate() {
+                    spans.push(Span::raw(if i == 0 { ": " } else { ", " }));
+                    spans.push(self.player_span(*victim));
+                    spans.push(Span::raw(format!(" -{}", amount)));
+                }
+                spans
+            }
+            GameEvent::YearOfPlentyPlayed { player, resources } => vec![
+                self.player_span(*player),
+                Span::raw(format!(
+                    " played Year of Plenty: {}",
+                    format_gains(resources)
+                )),
+            ],
+            GameEvent::RoadBuildingPlayed { player } => {
+                vec![
+                    self.player_span(*player),
+                    Span::raw(" played Road Building"),
+                ]
+            }
+            GameEvent::TradeOffered {
+                player,
+                give,
+                receive,
+            } => vec![
+                self.player_span(*player),
+                Span::raw(format!(
+                    " offered {} for {}",
+                    format_bundle(give),
+                    format_bundle(receive)
+                )),
+            ],
+            GameEvent::TradeAccepted { player, offerer } => vec![
+                self.player_span(*player),
+                Span::raw(" accepted the offer from "),
+                self.player_span(*offerer),
+            ],
+            GameEvent::TradeRejected { player, offerer } => vec![
+                self.player_span(*player),
+                Span::raw(" rejected the offer from "),
+                self.player_span(*offerer),
+            ],
+            GameEvent::TradeCancelled { player } => {
+                vec![self.player_span(*player), Span::raw(" cancelled the offer")]
+            }
+            GameEvent::TradeExecuted {
+                player,
+                partner,
+                give,
+                receive,
+            } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" traded {} to ", format_bundle(give))),
+                match partner {
+                    Some(partner) => self.player_span(*partner),
+                    None => Span::raw("the bank"),
+                },
+                Span::raw(format!(" for {}", format_bundle(receive))),
+            ],
+            GameEvent::ResourceGifted {
+                player,
+                partner,
+                resource,
+            } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" gave 1 {:?} to ", resource)),
+                self.player_span(*partner),
+            ],
+            GameEvent::LongestRoadChanged {
+                previous, holder, ..
+            } => self.award_spans("Longest Road", *previous, *holder),
+            GameEvent::LargestArmyChanged {
+                previous, holder, ..
+            } => self.award_spans("Largest Army", *previous, *holder),
+            GameEvent::VictoryPointsRevealed { player, cards } => vec![
+                self.player_span(*player),
+                Span::raw(format!(" revealed {} victory point card(s)", cards)),
+            ],
+            GameEvent::GameWon { winner } => vec![
+                self.player_span(*winner),
+                Span::styled(
+                    " won the game!",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ],
+            GameEvent::PlayerResigned { player } => {
+                vec![self.player_span(*player), Span::raw(" resigned")]
+            }
+            GameEvent::PlayerForfeited { player } => {
+                vec![self.player_span(*player), Span::raw(" forfeited")]
+            }
+        };
+        Some(spans)
+    }
+
     fn ensure_history_scroll_from_bottom(&mut self, viewport_height: u16, content_height: u16) {
         if content_height <= viewport_height {
             self.history_scroll = 0;
@@ -626,3 +1837,37 @@ impl TuiApp {
         }
     }
 }
+
+/// Advisor scores: win rates to three places, the value function's large
+/// weighted sums in whole units so close moves still tell apart.
+fn format_score(score: f64) -> String {
+    if score.abs() >= 1e3 {
+        format!("{score:.0}")
+    } else {
+        format!("{score:.3}")
+    }
+}
+
+/// "+2 Wheat, +1 Ore" style listing of cards gained.
+fn format_gains(bundle: &ResourceBundle) -> String {
+    bundle
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(resource, count)| format!("+{} {:?}", count, resource))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "2 Wood + 1 Ore" style listing of a bundle.
+fn format_bundle(bundle: &ResourceBundle) -> String {
+    let parts: Vec<String> = bundle
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(resource, count)| format!("{} {:?}", count, resource))
+        .collect();
+    if parts.is_empty() {
+        "nothing".to_string()
+    } else {
+        parts.join(" + ")
+    }
+}
@@ -0,0 +1,228 @@
+//! Fixed-size integer encoding of actions, so a policy can have one output
+//! per index and mask out whatever is illegal instead of enumerating
+//! `GameAction`s. The layout depends only on the map and the player count.
+//!
+//! Indices are laid out in blocks, in this order:
+//!
+//! | Block                | Size                     | Index within the block             |
+//! |----------------------|--------------------------|------------------------------------|
+//! | `Roll`               | 1                        |                                    |
+//! | `EndTurn`            | 1                        |                                    |
+//! | `MoveRobber`         | tiles × (players + 1)    | tile × (players + 1) + victim + 1, 0 for no victim |
+//! | `Discard`            | 5                        | resource                           |
+//! | `BuildRoad`          | edges                    | edge                               |
+//! | `BuildSettlement`    | nodes                    | node                               |
+//! | `BuildCity`          | nodes                    | node                               |
+//! | `BuyDevelopmentCard` | 1                        |                                    |
+//! | `PlayKnightCard`     | 1                        |                                    |
+//! | `PlayYearOfPlenty`   | 20                       | one card: resource; two: 5 + pair  |
+//! | `PlayMonopoly`       | 5                        | resource                           |
+//! | `PlayRoadBuilding`   | 1                        |                                    |
+//! | `MaritimeTrade`      | 5 × 5                    | given × 5 + received               |
+//! | `OfferTrade`         | 5 × 5 × 2                | (given × 5 + asked) × 2 + amount given − 1 |
+//! | `AcceptTrade`        | 1                        |                                    |
+//! | `RejectTrade`        | 1                        |                                    |
+//! | `ConfirmTrade`       | players                  | partner                            |
+//! | `CancelTrade`        | 1                        |                                    |
+//! | `GiftResource`       | 5 × players              | resource × players + partner       |
+//!
+//! Resources count in `Resource::ALL` order, tiles by id, edges and nodes in
+//! ascending order. Year of Plenty pairs are numbered over `(a, b)` with
+//! `a <= b`, walking `a` then `b`. A few indices (e.g. trading a resource for
+//! itself) are never legal.
+
+use crate::board::{EdgeId, NodeId, normalize_edge};
+use crate::game::action::{ActionPayload, GameAction};
+use crate::game::resources::ResourceBundle;
+use crate::game::state::GameState;
+use crate::types::{ActionType, Resource};
+
+const RESOURCES: usize = Resource::ALL.len();
+/// Single cards plus unordered pairs with repetition.
+const YEAR_OF_PLENTY_CHOICES: usize = RESOURCES + RESOURCES * (RESOURCES + 1) / 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionSpace {
+    num_players: usize,
+    tiles: Vec<u16>,
+    edges: Vec<EdgeId>,
+    nodes: Vec<NodeId>,
+}
+
+impl ActionSpace {
+    pub fn new(state: &GameState) -> Self {
+        let mut tiles: Vec<u16> = state.map.tiles_by_id.keys().copied().collect();
+        tiles.sort_unstable();
+        let mut edges: Vec<EdgeId> = state
+            .map
+            .node_edges
+            .values()
+            .flatten()
+            .map(|edge| normalize_edge(*edge))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        let mut nodes: Vec<NodeId> = state.map.land_nodes.iter().copied().collect();
+        nodes.sort_unstable();
+        Self {
+            num_players: state.players.len(),
+            tiles,
+            edges,
+            nodes,
+        }
+    }
+
+    /// Number of indices, legal or not.
+    pub fn size(&self) -> usize {
+        self.blocks().iter().map(|(_, size)| size).sum()
+    }
+
+    /// The index of `action`, or `None` if it has no place in the layout
+    /// (e.g. a payload naming a tile that isn't on the map).
+    pub fn encode(&self, action: &GameAction) -> Option<usize> {
+        let local = self.local_index(action)?;
+        let mut start = 0;
+        for (action_type, size) in self.blocks() {
+            if action_type == action.action_type {
+                return (local < size).then_some(start + local);
+            }
+            start += size;
+        }
+        None
+    }
+
+    /// Sorted indices of `actions`.
+    pub fn indices(&self, actions: &[GameAction]) -> Vec<usize> {
+        let mut indices: Vec<usize> = actions
+            .iter()
+            .filter_map(|action| self.encode(action))
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// `size()` flags, set for the indices of `actions`.
+    pub fn mask(&self, actions: &[GameAction]) -> Vec<bool> {
+        let mut mask = vec![false; self.size()];
+        for index in self.indices(actions) {
+            mask[index] = true;
+        }
+        mask
+    }
+
+    fn blocks(&self) -> [(ActionType, usize); 19] {
+        let players = self.num_players;
+        [
+            (ActionType::Roll, 1),
+            (ActionType::EndTurn, 1),
+            (ActionType::MoveRobber, self.tiles.len() * (players + 1)),
+            (ActionType::Discard, RESOURCES),
+            (ActionType::BuildRoad, self.edges.len()),
+            (ActionType::BuildSettlement, self.nodes.len()),
+            (ActionType::BuildCity, self.nodes.len()),
+            (ActionType::BuyDevelopmentCard, 1),
+            (ActionType::PlayKnightCard, 1),
+            (ActionType::PlayYearOfPlenty, YEAR_OF_PLENTY_CHOICES),
+            (ActionType::PlayMonopoly, RESOURCES),
+            (ActionType::PlayRoadBuilding, 1),
+            (ActionType::MaritimeTrade, RESOURCES * RESOURCES),
+            (ActionType::OfferTrade, RESOURCES * RESOURCES * 2),
+            (ActionType::AcceptTrade, 1),
+            (ActionType::RejectTrade, 1),
+            (ActionType::ConfirmTrade, players),
+            (ActionType::CancelTrade, 1),
+            (ActionType::GiftResource, RESOURCES * players),
+        ]
+    }
+
+    /// Position of `action` within its block.
+    fn local_index(&self, action: &GameAction) -> Option<usize> {
+        match (action.action_type, &action.payload) {
+            (
+                ActionType::Roll
+                | ActionType::EndTurn
+                | ActionType::BuyDevelopmentCard
+                | ActionType::PlayKnightCard
+                | ActionType::PlayRoadBuilding
+                | ActionType::AcceptTrade
+                | ActionType::RejectTrade
+                | ActionType::CancelTrade,
+                _,
+            ) => Some(0),
+            (
+                ActionType::MoveRobber,
+                ActionPayload::Robber {
+                    tile_id, victim, ..
+                },
+            ) => {
+                let tile = self.tiles.binary_search(tile_id).ok()?;
+                let victim = match victim {
+                    Some(victim) if *victim < self.num_players => victim + 1,
+                    Some(_) => return None,
+                    None => 0,
+                };
+                Some(tile * (self.num_players + 1) + victim)
+            }
+            (ActionType::Discard | ActionType::PlayMonopoly, ActionPayload::Resource(resource)) => {
+                Some(*resource as usize)
+            }
+            (ActionType::BuildRoad, ActionPayload::Edge(edge)) => {
+                self.edges.binary_search(&normalize_edge(*edge)).ok()
+            }
+            (ActionType::BuildSettlement | ActionType::BuildCity, ActionPayload::Node(node)) => {
+                self.nodes.binary_search(node).ok()
+            }
+            (ActionType::PlayYearOfPlenty, ActionPayload::Resources(bundle)) => {
+                year_of_plenty_index(bundle)
+            }
+            (ActionType::MaritimeTrade, ActionPayload::MaritimeTrade { give, receive }) => {
+                let (given, _) = single_resource(give)?;
+                Some(given * RESOURCES + *receive as usize)
+            }
+            (ActionType::OfferTrade, ActionPayload::Trade { give, receive, .. }) => {
+                let (given, amount) = single_resource(give)?;
+                let (asked, asked_amount) = single_resource(receive)?;
+                if asked_amount != 1 || !(1..=2).contains(&amount) {
+                    return None;
+                }
+                Some((given * RESOURCES + asked) * 2 + amount as usize - 1)
+            }
+            (
+                ActionType::ConfirmTrade,
+                ActionPayload::Trade {
+                    partner: Some(partner),
+                    ..
+                },
+            ) => (*partner < self.num_players).then_some(*partner),
+            (ActionType::GiftResource, ActionPayload::Gift { resource, partner }) => {
+                let partner = (*partner < self.num_players).then_some(*partner)?;
+                Some(*resource as usize * self.num_players + partner)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The only resource in `bundle` and its count, if there is exactly one.
+fn single_resource(bundle: &ResourceBundle) -> Option<(usize, u8)> {
+    let mut held = bundle.iter().filter(|(_, count)| *count > 0);
+    let (resource, count) = held.next()?;
+    held.next().is_none().then_some((resource as usize, count))
+}
+
+fn year_of_plenty_index(bundle: &ResourceBundle) -> Option<usize> {
+    let cards: Vec<usize> = bundle
+        .iter()
+        .flat_map(|(resource, count)| std::iter::repeat_n(resource as usize, count as usize))
+        .collect();
+    match cards.as_slice() {
+        [single] => Some(*single),
+        [a, b] => {
+            // Pairs (a, b) with a <= b that come before (a, _).
+            let before: usize = (0..*a).map(|first| RESOURCES - first).sum();
+            Some(RESOURCES + before + (b - a))
+        }
+        _ => None,
+    }
+}
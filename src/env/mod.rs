@@ -1,9 +1,22 @@
+pub mod action_space;
+pub mod shaping;
+
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::board::{EdgeId, NodeId};
+use crate::coords::CubeCoord;
 use crate::features::{BoardTensor, FeatureCollection, build_board_tensor, collect_features};
-use crate::game::{GameConfig, GameError, GameEvent, GameState, action::GameAction};
+use crate::game::{
+    DiceRoll, GameConfig, GameError, GameEvent, GamePhase, GameState, Structure, VpBreakdown,
+    action::GameAction,
+};
 use crate::types::{ActionPrompt, Color, Resource};
 
+pub use action_space::ActionSpace;
+pub use shaping::RewardShaping;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerObservation {
     pub color: Color,
@@ -13,7 +26,131 @@ pub struct PlayerObservation {
     pub settlements: usize,
     pub cities: usize,
     pub roads: usize,
+    pub knights_played: u8,
+    /// Counted like the award: not through other players' buildings.
+    pub longest_road_length: usize,
+    /// Cards given to the bank per card received, in `Resource::ALL` order.
+    pub trade_rates: [u8; Resource::ALL.len()],
+    /// Public points while the game runs; hidden victory point cards are
+    /// added once it is over.
     pub victory_points: u8,
+    pub vp_breakdown: VpBreakdown,
+    /// Milliseconds left on the player's time bank, when the game is timed.
+    pub time_remaining_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileObservation {
+    pub id: u16,
+    pub coord: CubeCoord,
+    pub resource: Option<Resource>,
+    pub number: Option<u8>,
+    pub has_robber: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeObservation {
+    pub id: NodeId,
+    pub structure: Option<Structure>,
+    /// Chance per roll of each resource paying out to a settlement here,
+    /// ignoring the robber.
+    pub production: BTreeMap<Resource, f32>,
+    /// Id of the port this node trades at, if any.
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoadObservation {
+    pub edge: EdgeId,
+    pub player: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortObservation {
+    pub id: u16,
+    /// `None` for 3:1 ports.
+    pub resource: Option<Resource>,
+    pub nodes: [NodeId; 2],
+}
+
+/// Everything on the table: tiles, buildings, roads and ports. Lists are
+/// sorted by id so consumers can index them directly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardObservation {
+    pub robber_tile: u16,
+    pub tiles: Vec<TileObservation>,
+    pub nodes: Vec<NodeObservation>,
+    pub roads: Vec<RoadObservation>,
+    pub ports: Vec<PortObservation>,
+}
+
+impl BoardObservation {
+    pub fn from_state(state: &GameState) -> Self {
+        let mut tiles: Vec<TileObservation> = state
+            .map
+            .land_tiles
+            .iter()
+            .map(|(coord, tile)| TileObservation {
+                id: tile.id,
+                coord: *coord,
+                resource: tile.resource,
+                number: tile.number,
+                has_robber: tile.id == state.robber_tile,
+            })
+            .collect();
+        tiles.sort_by_key(|tile| tile.id);
+
+        let mut ports: Vec<PortObservation> = state
+            .map
+            .ports_by_id
+            .values()
+            .map(|port| PortObservation {
+                id: port.id,
+                resource: port.resource,
+                nodes: port.land_nodes(),
+            })
+            .collect();
+        ports.sort_by_key(|port| port.id);
+
+        let mut nodes: Vec<NodeObservation> = state
+            .map
+            .land_nodes
+            .iter()
+            .map(|node| NodeObservation {
+                id: *node,
+                structure: state.node_occupancy.get(node).copied(),
+                production: state
+                    .map
+                    .node_production
+                    .get(node)
+                    .cloned()
+                    .unwrap_or_default(),
+                port: ports
+                    .iter()
+                    .find(|port| port.nodes.contains(node))
+                    .map(|port| port.id),
+            })
+            .collect();
+        nodes.sort_by_key(|node| node.id);
+
+        let mut roads: Vec<RoadObservation> = state
+            .road_occupancy
+            .iter()
+            .map(|(edge, player)| RoadObservation {
+                edge: *edge,
+                player: *player,
+            })
+            .collect();
+        roads.sort_by_key(|road| road.edge);
+
+        Self {
+            robber_tile: state.robber_tile,
+            tiles,
+            nodes,
+            roads,
+            ports,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,42 +158,223 @@ pub struct Observation {
     pub current_player: usize,
     pub pending_prompt: ActionPrompt,
     pub turn: u32,
-    pub last_roll: Option<(u8, u8)>,
+    /// Actions executed so far in the game.
+    pub tick: u32,
+    /// Actions executed so far in the current turn.
+    pub turn_tick: u32,
+    pub last_roll: Option<DiceRoll>,
+    pub longest_road_holder: Option<usize>,
+    pub largest_army_holder: Option<usize>,
     pub players: Vec<PlayerObservation>,
+    pub board: BoardObservation,
+    pub obligations: PendingObligations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscardObligation {
+    pub player: usize,
+    /// Cards still to discard.
+    pub cards: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeOfferObservation {
+    pub offerer: usize,
+    pub give: [u8; Resource::ALL.len()],
+    pub receive: [u8; Resource::ALL.len()],
+    /// Players who accepted so far, sorted.
+    pub accepted_by: Vec<usize>,
+    /// Players yet to answer, in the order they will be asked.
+    pub awaiting: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreeRoadsObservation {
+    pub player: usize,
+    pub remaining: u8,
+}
+
+/// Decisions the game is waiting on beyond the current prompt, so agents
+/// don't have to reconstruct them from the event history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingObligations {
+    pub discards: Vec<DiscardObligation>,
+    pub trade_offer: Option<TradeOfferObservation>,
+    pub free_roads: Option<FreeRoadsObservation>,
+}
+
+impl PendingObligations {
+    pub fn from_state(state: &GameState) -> Self {
+        let discards = state
+            .discard_obligations()
+            .into_iter()
+            .map(|(player, cards)| DiscardObligation { player, cards })
+            .collect();
+        let trade_offer = state.trade_state().map(|offer| {
+            let mut accepted_by: Vec<usize> = offer.acceptees.iter().copied().collect();
+            accepted_by.sort_unstable();
+            TradeOfferObservation {
+                offerer: offer.offerer,
+                give: offer.give.counts(),
+                receive: offer.receive.counts(),
+                accepted_by,
+                awaiting: state.trade_responders_pending(),
+            }
+        });
+        let free_roads = state
+            .free_roads()
+            .map(|(player, remaining)| FreeRoadsObservation { player, remaining });
+        Self {
+            discards,
+            trade_offer,
+            free_roads,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepResult {
     pub observation: Observation,
+    /// Including the shaping term, when the env shapes rewards.
     pub rewards: Vec<f32>,
+    /// The shaping term included in `rewards`, by seat; empty without
+    /// shaping.
+    #[serde(default)]
+    pub shaping: Vec<f32>,
     pub done: bool,
+    /// Events of the step and of any forced actions applied after it.
     pub events: Vec<GameEvent>,
+    /// Actions the engine applied on its own after this step because they
+    /// were the only legal move (`GameConfig::auto_forced_actions`).
+    pub forced_actions: Vec<GameAction>,
+    /// Legal actions of the current player in the new state; 0 once the
+    /// game is over.
+    pub num_legal_actions: usize,
+    /// What the new state asks of the current player.
+    pub prompt: ActionPrompt,
+    /// The action stepped with was the only legal one.
+    pub was_forced: bool,
+}
+
+impl StepResult {
+    /// The step's events as `player` may see them.
+    pub fn events_for(&self, player: usize) -> Vec<GameEvent> {
+        self.events
+            .iter()
+            .map(|event| event.visible_to(player))
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct RustEnv {
     state: GameState,
+    action_space: ActionSpace,
+    shaping: Option<RewardShaping>,
+    /// Legal actions in `state` as the last step reported them, so the next
+    /// step knows whether it was forced. `None` after outside changes
+    /// through `game_state_mut`.
+    num_legal_actions: Option<usize>,
 }
 
 impl RustEnv {
     pub fn new(config: GameConfig) -> Self {
-        Self {
-            state: GameState::new(config),
-        }
+        Self::try_new(config).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like `new`, but reports an invalid config, such as a pinned map
+    /// layout that doesn't fit the map type, instead of panicking.
+    pub fn try_new(config: GameConfig) -> Result<Self, GameError> {
+        let state = GameState::try_new(config)?;
+        let action_space = ActionSpace::new(&state);
+        Ok(Self {
+            num_legal_actions: Some(state.legal_actions().len()),
+            state,
+            action_space,
+            shaping: None,
+        })
+    }
+
+    /// Adds potential-based shaping to every step's rewards.
+    pub fn with_reward_shaping(mut self, shaping: RewardShaping) -> Self {
+        self.shaping = Some(shaping);
+        self
     }
 
     pub fn reset(&mut self) -> Observation {
         self.state.reset();
+        self.action_space = ActionSpace::new(&self.state);
+        self.num_legal_actions = Some(self.state.legal_actions().len());
         observation_from_state(&self.state)
     }
 
+    /// The fixed action encoding for this env's map and player count.
+    pub fn action_space(&self) -> &ActionSpace {
+        &self.action_space
+    }
+
+    pub fn action_space_size(&self) -> usize {
+        self.action_space.size()
+    }
+
+    /// Sorted indices of the current player's legal actions.
+    pub fn legal_action_indices(&self) -> Vec<usize> {
+        self.action_space.indices(self.state.legal_actions())
+    }
+
+    /// `action_space_size()` flags, set for the current player's legal actions.
+    pub fn legal_action_mask(&self) -> Vec<bool> {
+        self.action_space.mask(self.state.legal_actions())
+    }
+
+    /// Steps with the legal action encoded as `index`.
+    pub fn step_index(&mut self, index: usize) -> Result<StepResult, GameError> {
+        let action = self
+            .state
+            .legal_actions()
+            .iter()
+            .find(|action| self.action_space.encode(action) == Some(index))
+            .cloned()
+            .ok_or(GameError::IllegalActionIndex(index))?;
+        self.step(action)
+    }
+
     pub fn step(&mut self, action: GameAction) -> Result<StepResult, GameError> {
+        let was_forced = self
+            .num_legal_actions
+            .unwrap_or_else(|| self.state.legal_actions().len())
+            == 1;
+        let before = self
+            .shaping
+            .as_ref()
+            .map(|shaping| shaping.potentials(&self.state));
         let outcome = self.state.step(action)?;
+        let mut rewards = outcome.rewards;
+        let mut shaping = Vec::new();
+        if let (Some(shaper), Some(before)) = (&self.shaping, before) {
+            shaping = shaper.rewards(&before, &self.state);
+            for (reward, term) in rewards.iter_mut().zip(&shaping) {
+                *reward += term;
+            }
+        }
+        let num_legal_actions = self.state.legal_actions().len();
+        self.num_legal_actions = Some(num_legal_actions);
+        let mut events = outcome.events;
+        let mut forced_actions = Vec::with_capacity(outcome.forced.len());
+        for (action, forced_events) in outcome.forced {
+            forced_actions.push(action);
+            events.extend(forced_events);
+        }
         Ok(StepResult {
             observation: observation_from_state(&self.state),
-            rewards: outcome.rewards,
+            rewards,
+            shaping,
             done: outcome.done,
-            events: outcome.events,
+            events,
+            forced_actions,
+            num_legal_actions,
+            prompt: self.state.legal_action_prompt(),
+            was_forced,
         })
     }
 
@@ -68,11 +386,22 @@ impl RustEnv {
         self.state.current_player
     }
 
+    /// Everyone who may submit an action now; several players during a
+    /// simultaneous discard.
+    pub fn players_to_act(&self) -> Vec<usize> {
+        self.state.players_to_act()
+    }
+
+    pub fn legal_actions_for(&self, player_idx: usize) -> Vec<GameAction> {
+        self.state.legal_actions_for(player_idx)
+    }
+
     pub fn game_state(&self) -> &GameState {
         &self.state
     }
 
     pub fn game_state_mut(&mut self) -> &mut GameState {
+        self.num_legal_actions = None;
         &mut self.state
     }
 
@@ -90,24 +419,47 @@ impl RustEnv {
 }
 
 pub fn observation_from_state(state: &GameState) -> Observation {
+    let game_over = matches!(state.phase, GamePhase::Completed { .. });
     Observation {
         current_player: state.current_player,
         pending_prompt: state.legal_action_prompt(),
         turn: state.turn,
+        tick: state.tick,
+        turn_tick: state.ticks_this_turn(),
         last_roll: state.last_roll,
+        longest_road_holder: state.longest_road_holder(),
+        largest_army_holder: state.largest_army_holder(),
         players: state
             .players
             .iter()
-            .map(|player| PlayerObservation {
-                color: player.color,
-                resources: player.resources.counts(),
-                dev_cards: player.dev_cards.len(),
-                fresh_dev_cards: player.fresh_dev_cards.len(),
-                settlements: player.settlements.len(),
-                cities: player.cities.len(),
-                roads: player.roads.len(),
-                victory_points: player.total_points(),
+            .enumerate()
+            .map(|(idx, player)| {
+                let vp_breakdown = if game_over {
+                    player.vp_breakdown()
+                } else {
+                    player.public_vp_breakdown()
+                };
+                PlayerObservation {
+                    color: player.color,
+                    resources: player.resources.counts(),
+                    dev_cards: player.dev_cards.len(),
+                    fresh_dev_cards: player.fresh_dev_cards.len(),
+                    settlements: player.settlements.len(),
+                    cities: player.cities.len(),
+                    roads: player.roads.len(),
+                    knights_played: player.knights_played,
+                    longest_road_length: state.longest_road_length(idx),
+                    trade_rates: state.trade_rates(idx),
+                    victory_points: vp_breakdown.total(),
+                    vp_breakdown,
+                    time_remaining_ms: state
+                        .clock
+                        .as_ref()
+                        .map(|clock| clock.remaining(idx).as_millis() as u64),
+                }
             })
             .collect(),
+        board: BoardObservation::from_state(state),
+        obligations: PendingObligations::from_state(state),
     }
 }
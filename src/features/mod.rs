@@ -1,15 +1,27 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+//! Numeric features and the board tensor agents learn from.
+//!
+//! Both are part of the schema models are trained against: the same state
+//! and perspective give bit-identical values in every process. Hash maps
+//! and sets are never walked in hash order; tiles and ports go by id, nodes
+//! and edges in ascending order, so neither float sums nor tensor cells that
+//! several tiles stamp depend on the hasher. Tensor positions come from the
+//! base map's geometry, and nodes or tiles it doesn't have are left out.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex, Weak};
 
 use once_cell::sync::Lazy;
 
 use crate::{
-    board::{CatanMap, EdgeId, NodeId},
+    board::{CatanMap, EdgeId, NodeId, normalize_edge},
     coords::{CubeCoord, offset_to_cube},
     game::{
+        action::GameAction,
         players::{MAX_CITIES, MAX_ROADS, MAX_SETTLEMENTS, PlayerState},
         state::{GameState, Structure},
     },
-    types::{ActionPrompt, DevelopmentCard, Resource},
+    types::{ActionPrompt, ActionType, DevelopmentCard, Resource, probability::number_probability},
 };
 
 const WIDTH: usize = 21;
@@ -24,10 +36,233 @@ fn is_graph_feature(name: &str) -> bool {
         || name.starts_with("PORT")
 }
 
+/// Feature names for one board shape and player count, sorted the way
+/// consumers index them. Extraction only writes numbers; the names are
+/// formatted once per shape when the layout is first needed.
+#[derive(Debug)]
+pub struct FeatureLayout {
+    names: Vec<String>,
+    /// Sorted position of the n-th value produced during extraction.
+    positions: Vec<usize>,
+    /// Extraction index each `FeatureGroup` starts at.
+    group_starts: [usize; FeatureGroup::ALL.len()],
+    terms: TermIndices,
+}
+
+/// Where the features the value function and `update_from` read sit in
+/// `values`, found once per layout so evaluation never looks names up.
+#[derive(Debug)]
+struct TermIndices {
+    actual_vps: usize,
+    longest_road_length: usize,
+    buildable_nodes: usize,
+    resources_in_hand: usize,
+    devs_in_hand: usize,
+    knights_played: usize,
+    /// By relative seat, then in `Resource::ALL` order.
+    production: Vec<[usize; Resource::ALL.len()]>,
+    /// Roads, settlements and cities left, by relative seat.
+    pieces_left: Vec<[usize; 3]>,
+    /// `TILE{id}_HAS_ROBBER` by tile id.
+    has_robber: HashMap<u16, usize>,
+}
+
+impl TermIndices {
+    fn new(names: &[String], num_players: usize, tiles: &[u16]) -> Self {
+        let index = |name: String| {
+            names
+                .binary_search(&name)
+                .unwrap_or_else(|_| panic!("feature layout lacks {name}"))
+        };
+        Self {
+            actual_vps: index("P0_ACTUAL_VPS".to_string()),
+            longest_road_length: index("P0_LONGEST_ROAD_LENGTH".to_string()),
+            buildable_nodes: index("P0_NUM_BUILDABLE_NODES".to_string()),
+            resources_in_hand: index("P0_NUM_RESOURCES_IN_HAND".to_string()),
+            devs_in_hand: index("P0_NUM_DEVS_IN_HAND".to_string()),
+            knights_played: index("P0_Knight_PLAYED".to_string()),
+            production: (0..num_players)
+                .map(|relative_idx| {
+                    Resource::ALL
+                        .map(|resource| index(format!("P{relative_idx}_{resource:?}_PRODUCTION")))
+                })
+                .collect(),
+            pieces_left: (0..num_players)
+                .map(|relative_idx| {
+                    ["ROADS", "SETTLEMENTS", "CITIES"]
+                        .map(|piece| index(format!("P{relative_idx}_{piece}_LEFT")))
+                })
+                .collect(),
+            has_robber: tiles
+                .iter()
+                .map(|tile_id| (*tile_id, index(format!("TILE{tile_id}_HAS_ROBBER"))))
+                .collect(),
+        }
+    }
+}
+
+impl FeatureLayout {
+    /// The layout for `game`'s map and player count. Each thread remembers
+    /// the last map it was asked about, so extracting features along one
+    /// game neither rebuilds the `LayoutKey` nor takes the shared lock.
+    fn for_game(game: &GameState, perspective: usize) -> Arc<FeatureLayout> {
+        thread_local! {
+            static LAST: RefCell<Option<LastLayout>> = const { RefCell::new(None) };
+        }
+        let num_players = game.players.len();
+        let cached = LAST.with_borrow(|last| {
+            last.as_ref()
+                .filter(|last| {
+                    last.num_players == num_players
+                        && Weak::as_ptr(&last.map) == Arc::as_ptr(&game.map)
+                })
+                .map(|last| last.layout.clone())
+        });
+        if let Some(layout) = cached {
+            return layout;
+        }
+        let layout = Self::shared(game, perspective);
+        LAST.set(Some(LastLayout {
+            map: Arc::downgrade(&game.map),
+            num_players,
+            layout: layout.clone(),
+        }));
+        layout
+    }
+
+    /// The layout every map of `game`'s shape shares, built the first time
+    /// one is seen.
+    fn shared(game: &GameState, perspective: usize) -> Arc<FeatureLayout> {
+        static LAYOUTS: Lazy<Mutex<HashMap<LayoutKey, Arc<FeatureLayout>>>> =
+            Lazy::new(|| Mutex::new(HashMap::new()));
+        let key = LayoutKey::of(game);
+        let mut layouts = LAYOUTS.lock().unwrap_or_else(|err| err.into_inner());
+        layouts
+            .entry(key)
+            .or_insert_with(|| {
+                let order = iter_players(game, perspective);
+                let mut sink = NameSink::default();
+                let mut group_starts = [0; FeatureGroup::ALL.len()];
+                for group in FeatureGroup::ALL {
+                    group_starts[group as usize] = sink.0.len();
+                    gather_group(game, &order, group, &mut sink);
+                }
+                let tiles = sorted(game.map.tiles_by_id.keys());
+                Arc::new(FeatureLayout::from_names(
+                    sink.0,
+                    group_starts,
+                    order.len(),
+                    &tiles,
+                ))
+            })
+            .clone()
+    }
+
+    fn from_names(
+        unsorted: Vec<String>,
+        group_starts: [usize; FeatureGroup::ALL.len()],
+        num_players: usize,
+        tiles: &[u16],
+    ) -> Self {
+        let mut order: Vec<usize> = (0..unsorted.len()).collect();
+        order.sort_by(|a, b| unsorted[*a].cmp(&unsorted[*b]));
+        let mut positions = vec![0; unsorted.len()];
+        for (sorted, original) in order.iter().enumerate() {
+            positions[*original] = sorted;
+        }
+        let mut names = unsorted;
+        let names: Vec<String> = order
+            .iter()
+            .map(|original| std::mem::take(&mut names[*original]))
+            .collect();
+        let terms = TermIndices::new(&names, num_players, tiles);
+        Self {
+            names,
+            positions,
+            group_starts,
+            terms,
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// The layout `FeatureLayout::for_game` last handed out on a thread.
+struct LastLayout {
+    /// Keeps the map's allocation, and so its address, from being reused by
+    /// another map.
+    map: Weak<CatanMap>,
+    num_players: usize,
+    layout: Arc<FeatureLayout>,
+}
+
+/// Everything the feature names depend on: the player count and the ids of
+/// the map's tiles, ports, land nodes and edges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LayoutKey {
+    num_players: usize,
+    tiles: Vec<u16>,
+    ports: Vec<u16>,
+    nodes: Vec<NodeId>,
+    edges: Vec<EdgeId>,
+}
+
+impl LayoutKey {
+    fn of(game: &GameState) -> Self {
+        Self {
+            num_players: game.players.len(),
+            tiles: sorted(game.map.tiles_by_id.keys()),
+            ports: sorted(game.map.ports_by_id.keys()),
+            nodes: sorted(&game.map.land_nodes),
+            edges: all_edges(game),
+        }
+    }
+}
+
+/// Receives features in extraction order. Names are passed lazily so value
+/// extraction never formats them.
+trait FeatureSink {
+    fn put(&mut self, name: impl FnOnce() -> String, value: f64);
+}
+
+#[derive(Default)]
+struct NameSink(Vec<String>);
+
+impl FeatureSink for NameSink {
+    fn put(&mut self, name: impl FnOnce() -> String, _value: f64) {
+        self.0.push(name());
+    }
+}
+
+struct ValueSink<'a> {
+    positions: &'a [usize],
+    values: Vec<f32>,
+    next: usize,
+}
+
+impl FeatureSink for ValueSink<'_> {
+    fn put(&mut self, _name: impl FnOnce() -> String, value: f64) {
+        self.values[self.positions[self.next]] = value as f32;
+        self.next += 1;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FeatureCollection {
-    pub names: Vec<String>,
+    pub layout: Arc<FeatureLayout>,
     pub values: Vec<f32>,
+    /// Seat the features were extracted for; it is always "P0".
+    pub perspective: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -39,33 +274,188 @@ pub struct BoardTensor {
 }
 
 impl FeatureCollection {
+    pub fn names(&self) -> &[String] {
+        self.layout.names()
+    }
+
+    /// The value of the feature called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        let idx = self
+            .names()
+            .binary_search_by(|probe| probe.as_str().cmp(name))
+            .ok()?;
+        Some(self.values[idx])
+    }
+
+    /// `P0_ACTUAL_VPS`, hidden victory point cards included.
+    pub fn actual_vps(&self) -> f32 {
+        self.values[self.layout.terms.actual_vps]
+    }
+
+    /// `P0_LONGEST_ROAD_LENGTH`.
+    pub fn longest_road_length(&self) -> f32 {
+        self.values[self.layout.terms.longest_road_length]
+    }
+
+    /// `P0_NUM_BUILDABLE_NODES`.
+    pub fn buildable_nodes(&self) -> f32 {
+        self.values[self.layout.terms.buildable_nodes]
+    }
+
+    /// `P0_NUM_RESOURCES_IN_HAND`.
+    pub fn resources_in_hand(&self) -> f32 {
+        self.values[self.layout.terms.resources_in_hand]
+    }
+
+    /// `P0_NUM_DEVS_IN_HAND`.
+    pub fn devs_in_hand(&self) -> f32 {
+        self.values[self.layout.terms.devs_in_hand]
+    }
+
+    /// `P0_Knight_PLAYED`.
+    pub fn knights_played(&self) -> f32 {
+        self.values[self.layout.terms.knights_played]
+    }
+
+    /// `P{relative_idx}_{resource}_PRODUCTION`, counting seats from the
+    /// perspective player.
+    pub fn production(&self, relative_idx: usize, resource: Resource) -> f32 {
+        self.values[self.layout.terms.production[relative_idx][resource_index(resource)]]
+    }
+
     pub fn numeric_values(&self) -> Vec<f32> {
-        self.names
+        self.names()
             .iter()
             .zip(self.values.iter())
             .filter(|(name, _)| !is_graph_feature(name))
             .map(|(_, value)| *value)
             .collect()
     }
+
+    /// Features of `state`, reached from the position `parent` describes by
+    /// playing `action` (and any forced actions after it). Only the
+    /// feature groups the move can have changed are extracted again; the
+    /// rest are copied from `parent`. Matches `collect_features` exactly.
+    pub fn update_from(parent: &FeatureCollection, action: &GameAction, state: &GameState) -> Self {
+        let perspective = parent.perspective;
+        if !Arc::ptr_eq(&FeatureLayout::for_game(state, perspective), &parent.layout) {
+            return collect_features(state, perspective);
+        }
+        let order = iter_players(state, perspective);
+        let board_changed = matches!(
+            action.action_type,
+            ActionType::BuildRoad | ActionType::BuildSettlement | ActionType::BuildCity
+        ) || pieces_changed(parent, &order);
+        let robber_moved = action.action_type == ActionType::MoveRobber
+            || parent
+                .layout
+                .terms
+                .has_robber
+                .get(&state.robber_tile)
+                .is_none_or(|idx| parent.values[*idx] != 1.0);
+
+        let mut sink = ValueSink {
+            positions: &parent.layout.positions,
+            values: parent.values.clone(),
+            next: 0,
+        };
+        for group in FeatureGroup::ALL {
+            let stale = match group {
+                FeatureGroup::Players | FeatureGroup::Hands | FeatureGroup::Game => true,
+                FeatureGroup::Network | FeatureGroup::Graph => board_changed,
+                FeatureGroup::Tiles => robber_moved,
+                FeatureGroup::Ports => false,
+            };
+            if stale {
+                sink.next = parent.layout.group_starts[group as usize];
+                gather_group(state, &order, group, &mut sink);
+            }
+        }
+        let values = sink.values;
+        Self {
+            layout: parent.layout.clone(),
+            values,
+            perspective,
+        }
+    }
+}
+
+/// Whether any player's road, settlement or city count differs from what
+/// `parent` recorded, which every change to the board's buildings shows up in.
+fn pieces_changed(parent: &FeatureCollection, order: &[(usize, &PlayerState)]) -> bool {
+    order.iter().enumerate().any(|(relative_idx, (_, player))| {
+        let [roads, settlements, cities] =
+            parent.layout.terms.pieces_left[relative_idx].map(|idx| parent.values[idx]);
+        roads != (MAX_ROADS - player.roads.len()) as f32
+            || settlements != (MAX_SETTLEMENTS - player.settlements.len()) as f32
+            || cities != (MAX_CITIES - player.cities.len()) as f32
+    })
 }
 
 pub fn collect_features(game: &GameState, perspective: usize) -> FeatureCollection {
-    let mut features = BTreeMap::new();
+    let layout = FeatureLayout::for_game(game, perspective);
+    let mut sink = ValueSink {
+        positions: &layout.positions,
+        values: vec![0.0; layout.len()],
+        next: 0,
+    };
     let order = iter_players(game, perspective);
+    for group in FeatureGroup::ALL {
+        gather_group(game, &order, group, &mut sink);
+    }
+    debug_assert_eq!(sink.next, layout.len(), "feature layout out of date");
+    let values = sink.values;
+    FeatureCollection {
+        layout,
+        values,
+        perspective,
+    }
+}
 
-    gather_player_features(game, &order, &mut features);
-    gather_resource_hand_features(&order, &mut features);
-    gather_tile_features(game, &mut features);
-    gather_port_features(game, &mut features);
-    gather_graph_features(game, &order, &mut features);
-    gather_game_features(game, &mut features);
-
-    let (names, values): (Vec<_>, Vec<_>) =
-        features.into_iter().map(|(k, v)| (k, v as f32)).unzip();
+/// Features that change together, in extraction order. `update_from`
+/// re-extracts whole groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeatureGroup {
+    /// Points, awards, pieces left and whether the player rolled.
+    Players,
+    /// What follows from the buildings and roads: longest road,
+    /// production, buildable nodes and trade rates.
+    Network,
+    Hands,
+    Tiles,
+    Ports,
+    Graph,
+    Game,
+}
 
-    FeatureCollection { names, values }
+impl FeatureGroup {
+    const ALL: [FeatureGroup; 7] = [
+        FeatureGroup::Players,
+        FeatureGroup::Network,
+        FeatureGroup::Hands,
+        FeatureGroup::Tiles,
+        FeatureGroup::Ports,
+        FeatureGroup::Graph,
+        FeatureGroup::Game,
+    ];
 }
 
+fn gather_group(
+    game: &GameState,
+    order: &[(usize, &PlayerState)],
+    group: FeatureGroup,
+    sink: &mut impl FeatureSink,
+) {
+    match group {
+        FeatureGroup::Players => gather_player_features(order, sink),
+        FeatureGroup::Network => gather_network_features(game, order, sink),
+        FeatureGroup::Hands => gather_resource_hand_features(order, sink),
+        FeatureGroup::Tiles => gather_tile_features(game, sink),
+        FeatureGroup::Ports => gather_port_features(game, sink),
+        FeatureGroup::Graph => gather_graph_features(game, order, sink),
+        FeatureGroup::Game => gather_game_features(game, sink),
+    }
+}
 pub fn build_board_tensor(game: &GameState, perspective: usize) -> BoardTensor {
     let order = iter_players(game, perspective);
     let num_players = order.len();
@@ -77,47 +467,51 @@ pub fn build_board_tensor(game: &GameState, perspective: usize) -> BoardTensor {
     let tile_map = tile_coordinate_map();
 
     for (relative_idx, (_, player)) in order.iter().enumerate() {
-        for node in &player.settlements {
-            if let Some(&(x, y)) = node_map.get(node) {
+        for node in sorted(&player.settlements) {
+            if let Some(&(x, y)) = node_map.get(&node) {
                 set_value(&mut data, relative_idx * 2, x, y, 1.0);
             }
         }
-        for node in &player.cities {
-            if let Some(&(x, y)) = node_map.get(node) {
+        for node in sorted(&player.cities) {
+            if let Some(&(x, y)) = node_map.get(&node) {
                 set_value(&mut data, relative_idx * 2, x, y, 2.0);
             }
         }
-        for edge in &player.roads {
-            let normalized = normalize_edge(*edge);
-            if let Some(&(x, y)) = edge_map.get(&normalized) {
+        for edge in sorted(&player.roads) {
+            if let Some(&(x, y)) = edge_map.get(&normalize_edge(edge)) {
                 set_value(&mut data, relative_idx * 2 + 1, x, y, 1.0);
             }
         }
     }
 
-    for (coord, tile) in &game.map.land_tiles {
+    // Neighboring tiles share stamped cells; the higher id is stamped last.
+    let mut tiles: Vec<_> = game.map.land_tiles.iter().collect();
+    tiles.sort_unstable_by_key(|(_, tile)| tile.id);
+    for (coord, tile) in tiles {
         if let Some(resource) = tile.resource {
             if let Some(&(x, y)) = tile_map.get(coord) {
-                let proba = tile.number.map(number_probability).unwrap_or(0.0);
+                let proba = tile.number.map(number_probability).unwrap_or(0.0) as f32;
                 let channel = 2 * num_players + resource_index(resource);
                 stamp_tile(&mut data, channel, x, y, proba);
             }
         }
     }
 
-    if let Some(&(x, y)) = tile_map.get(&robber_coordinate(game)) {
+    if let Some(&(x, y)) = tile_map.get(&game.robber_coord()) {
         let channel = 2 * num_players + 5;
         stamp_tile(&mut data, channel, x, y, 1.0);
     }
 
-    for (resource, node_ids) in &game.map.port_nodes {
+    let mut ports: Vec<_> = game.map.port_nodes.iter().collect();
+    ports.sort_unstable_by_key(|(resource, _)| **resource);
+    for (resource, node_ids) in ports {
         let channel_delta = match resource {
             Some(res) => resource_index(*res),
             None => 5,
         };
         let channel = 2 * num_players + 5 + 1 + channel_delta;
-        for node in node_ids {
-            if let Some(&(x, y)) = node_map.get(node) {
+        for node in sorted(node_ids) {
+            if let Some(&(x, y)) = node_map.get(&node) {
                 set_value(&mut data, channel, x, y, 1.0);
             }
         }
@@ -131,55 +525,88 @@ pub fn build_board_tensor(game: &GameState, perspective: usize) -> BoardTensor {
     }
 }
 
-fn gather_player_features(
-    game: &GameState,
-    order: &[(usize, &PlayerState)],
-    features: &mut BTreeMap<String, f64>,
-) {
-    let blocked_nodes = blocked_nodes(game);
-    for (relative_idx, (player_idx, player)) in order.iter().enumerate() {
+fn gather_player_features(order: &[(usize, &PlayerState)], sink: &mut impl FeatureSink) {
+    for (relative_idx, (_, player)) in order.iter().enumerate() {
         if relative_idx == 0 {
-            features.insert("P0_ACTUAL_VPS".to_string(), player.total_points() as f64);
+            sink.put(|| "P0_ACTUAL_VPS".to_string(), player.total_points() as f64);
         }
 
         let public_vps = player.public_points();
-        features.insert(format!("P{relative_idx}_PUBLIC_VPS"), public_vps as f64);
-        features.insert(
-            format!("P{relative_idx}_HAS_ARMY"),
+        sink.put(|| format!("P{relative_idx}_PUBLIC_VPS"), public_vps as f64);
+        sink.put(
+            || format!("P{relative_idx}_HAS_ARMY"),
             bool_to_f32(player.has_largest_army),
         );
-        features.insert(
-            format!("P{relative_idx}_HAS_ROAD"),
+        sink.put(
+            || format!("P{relative_idx}_HAS_ROAD"),
             bool_to_f32(player.has_longest_road),
         );
-        features.insert(
-            format!("P{relative_idx}_ROADS_LEFT"),
+        sink.put(
+            || format!("P{relative_idx}_ROADS_LEFT"),
             (MAX_ROADS - player.roads.len()) as f64,
         );
-        features.insert(
-            format!("P{relative_idx}_SETTLEMENTS_LEFT"),
+        sink.put(
+            || format!("P{relative_idx}_SETTLEMENTS_LEFT"),
             (MAX_SETTLEMENTS - player.settlements.len()) as f64,
         );
-        features.insert(
-            format!("P{relative_idx}_CITIES_LEFT"),
+        sink.put(
+            || format!("P{relative_idx}_CITIES_LEFT"),
             (MAX_CITIES - player.cities.len()) as f64,
         );
-        features.insert(
-            format!("P{relative_idx}_HAS_ROLLED"),
+        sink.put(
+            || format!("P{relative_idx}_HAS_ROLLED"),
             bool_to_f32(player.has_rolled),
         );
+    }
+}
+
+fn gather_network_features(
+    game: &GameState,
+    order: &[(usize, &PlayerState)],
+    sink: &mut impl FeatureSink,
+) {
+    let blocked_nodes = blocked_nodes(game);
+    for (relative_idx, (player_idx, player)) in order.iter().enumerate() {
         let longest = longest_road_length(game, *player_idx, &blocked_nodes);
-        features.insert(
-            format!("P{relative_idx}_LONGEST_ROAD_LENGTH"),
+        sink.put(
+            || format!("P{relative_idx}_LONGEST_ROAD_LENGTH"),
             longest as f64,
         );
+        for resource in Resource::ALL {
+            sink.put(
+                || format!("P{relative_idx}_{resource:?}_PRODUCTION"),
+                player_production(game, player, resource),
+            );
+        }
+        sink.put(
+            || format!("P{relative_idx}_NUM_BUILDABLE_NODES"),
+            game.buildable_settlement_nodes(*player_idx).len() as f64,
+        );
+        let rates = game.trade_rates(*player_idx);
+        for (resource, rate) in Resource::ALL.into_iter().zip(rates) {
+            sink.put(|| format!("P{relative_idx}_RATE_{resource:?}"), rate as f64);
+        }
     }
 }
 
-fn gather_resource_hand_features(
-    order: &[(usize, &PlayerState)],
-    features: &mut BTreeMap<String, f64>,
-) {
+/// Expected cards of `resource` per roll from the player's buildings, with
+/// cities counting twice. The robber is ignored.
+fn player_production(game: &GameState, player: &PlayerState, resource: Resource) -> f64 {
+    let settlements = player.settlements.iter().map(|node| (*node, 1.0));
+    let cities = player.cities.iter().map(|node| (*node, 2.0));
+    // Summed in node order so the total doesn't depend on hash order.
+    let mut buildings: Vec<(NodeId, f64)> = settlements.chain(cities).collect();
+    buildings.sort_unstable_by_key(|(node, _)| *node);
+    buildings
+        .into_iter()
+        .filter_map(|(node, multiplier)| {
+            let proba = game.map.node_production.get(&node)?.get(&resource)?;
+            Some(*proba as f64 * multiplier)
+        })
+        .sum()
+}
+
+fn gather_resource_hand_features(order: &[(usize, &PlayerState)], sink: &mut impl FeatureSink) {
     if order.is_empty() {
         return;
     }
@@ -187,7 +614,7 @@ fn gather_resource_hand_features(
 
     for resource in Resource::ALL {
         let count = perspective.resources.get(resource);
-        features.insert(format!("P0_{:?}_IN_HAND", resource), count as f64);
+        sink.put(|| format!("P0_{:?}_IN_HAND", resource), count as f64);
     }
     for card in DevelopmentCard::ALL {
         let count = perspective
@@ -196,61 +623,69 @@ fn gather_resource_hand_features(
             .chain(perspective.fresh_dev_cards.iter())
             .filter(|&&c| c == card)
             .count();
-        features.insert(format!("P0_{:?}_IN_HAND", card), count as f64);
+        sink.put(|| format!("P0_{:?}_IN_HAND", card), count as f64);
         for (relative_idx, (_, player)) in order.iter().enumerate() {
             if card == DevelopmentCard::VictoryPoint {
                 continue;
             }
             let played = player.played_dev_cards.get(&card).copied().unwrap_or(0);
-            features.insert(format!("P{relative_idx}_{:?}_PLAYED", card), played as f64);
+            sink.put(
+                || format!("P{relative_idx}_{:?}_PLAYED", card),
+                played as f64,
+            );
         }
     }
-    features.insert(
-        "P0_HAS_PLAYED_DEVELOPMENT_CARD_IN_TURN".to_string(),
+    sink.put(
+        || "P0_HAS_PLAYED_DEVELOPMENT_CARD_IN_TURN".to_string(),
         bool_to_f32(perspective.has_played_dev_card_this_turn),
     );
     for (relative_idx, (_, player)) in order.iter().enumerate() {
-        features.insert(
-            format!("P{relative_idx}_NUM_RESOURCES_IN_HAND"),
+        sink.put(
+            || format!("P{relative_idx}_NUM_RESOURCES_IN_HAND"),
             player.resources.total() as f64,
         );
         let dev_total = player.dev_cards.len() + player.fresh_dev_cards.len();
-        features.insert(
-            format!("P{relative_idx}_NUM_DEVS_IN_HAND"),
+        sink.put(
+            || format!("P{relative_idx}_NUM_DEVS_IN_HAND"),
             dev_total as f64,
         );
     }
 }
 
-fn gather_tile_features(game: &GameState, features: &mut BTreeMap<String, f64>) {
-    for (tile_id, tile) in &game.map.tiles_by_id {
+fn gather_tile_features(game: &GameState, sink: &mut impl FeatureSink) {
+    // Sorted so every extraction visits features in the layout's order.
+    let mut tiles: Vec<_> = game.map.tiles_by_id.iter().collect();
+    tiles.sort_unstable_by_key(|(tile_id, _)| **tile_id);
+    for (tile_id, tile) in tiles {
         for resource in Resource::ALL {
             let value = bool_to_f32(tile.resource == Some(resource));
-            features.insert(format!("TILE{tile_id}_IS_{resource:?}"), value);
+            sink.put(|| format!("TILE{tile_id}_IS_{resource:?}"), value);
         }
-        features.insert(
-            format!("TILE{tile_id}_IS_DESERT"),
+        sink.put(
+            || format!("TILE{tile_id}_IS_DESERT"),
             bool_to_f32(tile.resource.is_none()),
         );
-        let proba = tile.number.map(number_probability).unwrap_or(0.0) as f64;
-        features.insert(format!("TILE{tile_id}_PROBA"), proba);
-        features.insert(
-            format!("TILE{tile_id}_HAS_ROBBER"),
+        let proba = tile.number.map(number_probability).unwrap_or(0.0);
+        sink.put(|| format!("TILE{tile_id}_PROBA"), proba);
+        sink.put(
+            || format!("TILE{tile_id}_HAS_ROBBER"),
             bool_to_f32(tile.id == game.robber_tile),
         );
     }
 }
 
-fn gather_port_features(game: &GameState, features: &mut BTreeMap<String, f64>) {
-    for (port_id, port) in &game.map.ports_by_id {
+fn gather_port_features(game: &GameState, sink: &mut impl FeatureSink) {
+    let mut ports: Vec<_> = game.map.ports_by_id.iter().collect();
+    ports.sort_unstable_by_key(|(port_id, _)| **port_id);
+    for (port_id, port) in ports {
         for resource in Resource::ALL {
-            features.insert(
-                format!("PORT{port_id}_IS_{resource:?}"),
+            sink.put(
+                || format!("PORT{port_id}_IS_{resource:?}"),
                 bool_to_f32(port.resource == Some(resource)),
             );
         }
-        features.insert(
-            format!("PORT{port_id}_IS_THREE_TO_ONE"),
+        sink.put(
+            || format!("PORT{port_id}_IS_THREE_TO_ONE"),
             bool_to_f32(port.resource.is_none()),
         );
     }
@@ -259,48 +694,49 @@ fn gather_port_features(game: &GameState, features: &mut BTreeMap<String, f64>)
 fn gather_graph_features(
     game: &GameState,
     order: &[(usize, &PlayerState)],
-    features: &mut BTreeMap<String, f64>,
+    sink: &mut impl FeatureSink,
 ) {
     let nodes: BTreeSet<_> = game.map.land_nodes.iter().copied().collect();
+    let edges = all_edges(game);
     for (relative_idx, (_player_idx, player)) in order.iter().enumerate() {
         for node in &nodes {
             let settlement = player.settlements.contains(node);
             let city = player.cities.contains(node);
-            features.insert(
-                format!("NODE{node}_P{relative_idx}_SETTLEMENT"),
+            sink.put(
+                || format!("NODE{node}_P{relative_idx}_SETTLEMENT"),
                 bool_to_f32(settlement),
             );
-            features.insert(
-                format!("NODE{node}_P{relative_idx}_CITY"),
+            sink.put(
+                || format!("NODE{node}_P{relative_idx}_CITY"),
                 bool_to_f32(city),
             );
         }
 
-        for edge in all_edges(game) {
+        for &edge in &edges {
             let owned = player.roads.contains(&edge) || player.roads.contains(&(edge.1, edge.0));
-            features.insert(
-                format!("EDGE({},{})_P{relative_idx}_ROAD", edge.0, edge.1),
+            sink.put(
+                || format!("EDGE({},{})_P{relative_idx}_ROAD", edge.0, edge.1),
                 bool_to_f32(owned),
             );
         }
     }
 }
 
-fn gather_game_features(game: &GameState, features: &mut BTreeMap<String, f64>) {
-    features.insert(
-        "BANK_DEV_CARDS".to_string(),
+fn gather_game_features(game: &GameState, sink: &mut impl FeatureSink) {
+    sink.put(
+        || "BANK_DEV_CARDS".to_string(),
         game.bank.development_deck_len() as f64,
     );
-    features.insert(
-        "IS_MOVING_ROBBER".to_string(),
+    sink.put(
+        || "IS_MOVING_ROBBER".to_string(),
         bool_to_f32(matches!(game.pending_prompt, ActionPrompt::MoveRobber)),
     );
-    features.insert(
-        "IS_DISCARDING".to_string(),
+    sink.put(
+        || "IS_DISCARDING".to_string(),
         bool_to_f32(matches!(game.pending_prompt, ActionPrompt::Discard)),
     );
     for (resource, count) in game.bank.resources().iter() {
-        features.insert(format!("BANK_{resource:?}"), count as f64);
+        sink.put(|| format!("BANK_{resource:?}"), count as f64);
     }
 }
 
@@ -313,6 +749,13 @@ fn iter_players<'a>(game: &'a GameState, perspective: usize) -> Vec<(usize, &'a
     result
 }
 
+/// The items of a hash set, in ascending order.
+fn sorted<'a, T: Ord + Copy + 'a>(items: impl IntoIterator<Item = &'a T>) -> Vec<T> {
+    let mut items: Vec<T> = items.into_iter().copied().collect();
+    items.sort_unstable();
+    items
+}
+
 fn bool_to_f32(value: bool) -> f64 {
     if value { 1.0 } else { 0.0 }
 }
@@ -327,14 +770,6 @@ fn all_edges(game: &GameState) -> Vec<EdgeId> {
     edges.into_iter().collect()
 }
 
-fn normalize_edge(edge: EdgeId) -> EdgeId {
-    if edge.0 <= edge.1 {
-        edge
-    } else {
-        (edge.1, edge.0)
-    }
-}
-
 fn longest_road_length(
     game: &GameState,
     player_idx: usize,
@@ -418,12 +853,12 @@ type BoardMaps = (
 
 fn board_maps() -> &'static BoardMaps {
     static MAPS: Lazy<BoardMaps> = Lazy::new(|| {
-        let graph = base_graph();
+        let base = CatanMap::build(crate::board::MapType::Base);
         let mut node_map: HashMap<NodeId, (usize, usize)> = HashMap::new();
         let mut edge_map: HashMap<EdgeId, (usize, usize)> = HashMap::new();
         let mut paths = Vec::new();
         for &(start, end) in PAIRS {
-            let path = shortest_path(&graph, start, end).expect("path exists");
+            let path = base.node_path(start, end).expect("path exists");
             paths.push(path);
         }
         for (i, path) in paths.iter().enumerate() {
@@ -479,72 +914,6 @@ fn tile_coordinate_map() -> &'static HashMap<CubeCoord, (usize, usize)> {
     &MAP
 }
 
-fn robber_coordinate(game: &GameState) -> CubeCoord {
-    for (coord, tile) in &game.map.land_tiles {
-        if tile.id == game.robber_tile {
-            return *coord;
-        }
-    }
-    CubeCoord::default()
-}
-
-fn base_graph() -> &'static HashMap<NodeId, Vec<NodeId>> {
-    static GRAPH: Lazy<HashMap<NodeId, Vec<NodeId>>> = Lazy::new(|| {
-        let base = CatanMap::build(crate::board::MapType::Base);
-        let mut graph: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
-        for tile in base.tiles.values() {
-            let edges = match tile {
-                crate::board::Tile::Land(t) => &t.edges,
-                crate::board::Tile::Port(t) => &t.edges,
-                crate::board::Tile::Water(t) => &t.edges,
-            };
-            for &(a, b) in edges.values() {
-                graph.entry(a).or_default().insert(b);
-                graph.entry(b).or_default().insert(a);
-            }
-        }
-        graph
-            .into_iter()
-            .map(|(k, v)| (k, v.into_iter().collect()))
-            .collect()
-    });
-    &GRAPH
-}
-
-fn shortest_path(
-    graph: &HashMap<NodeId, Vec<NodeId>>,
-    start: NodeId,
-    end: NodeId,
-) -> Option<Vec<NodeId>> {
-    let mut queue = VecDeque::new();
-    let mut parents: HashMap<NodeId, NodeId> = HashMap::new();
-    let mut visited = HashSet::new();
-    queue.push_back(start);
-    visited.insert(start);
-
-    while let Some(node) = queue.pop_front() {
-        if node == end {
-            let mut path = vec![node];
-            let mut current = node;
-            while let Some(&parent) = parents.get(&current) {
-                path.push(parent);
-                current = parent;
-            }
-            path.reverse();
-            return Some(path);
-        }
-        if let Some(neighbors) = graph.get(&node) {
-            for &neighbor in neighbors {
-                if visited.insert(neighbor) {
-                    parents.insert(neighbor, node);
-                    queue.push_back(neighbor);
-                }
-            }
-        }
-    }
-    None
-}
-
 fn set_value(data: &mut [f32], channel: usize, x: usize, y: usize, value: f32) {
     if x >= WIDTH || y >= HEIGHT {
         return;
@@ -571,18 +940,6 @@ fn stamp_tile(data: &mut [f32], channel: usize, x: usize, y: usize, value: f32)
     }
 }
 
-fn number_probability(number: u8) -> f32 {
-    match number {
-        2 | 12 => 1.0 / 36.0,
-        3 | 11 => 2.0 / 36.0,
-        4 | 10 => 3.0 / 36.0,
-        5 | 9 => 4.0 / 36.0,
-        6 | 8 => 5.0 / 36.0,
-        7 => 6.0 / 36.0,
-        _ => 0.0,
-    }
-}
-
 fn resource_index(resource: Resource) -> usize {
     match resource {
         Resource::Wood => 0,
@@ -592,3 +949,68 @@ fn resource_index(resource: Resource) -> usize {
         Resource::Ore => 4,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    use super::*;
+    use crate::game::state::{GameConfig, GamePhase};
+
+    /// Updating the parent's features with each move of a random playout
+    /// gives exactly what extracting the child from scratch does.
+    #[test]
+    fn update_from_matches_collect_features() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for seed in 0..2 {
+            let mut state = GameState::new(GameConfig {
+                seed,
+                num_players: 3 + seed as usize % 2,
+                ..GameConfig::default()
+            });
+            let mut features: Vec<FeatureCollection> = (0..state.players.len())
+                .map(|seat| collect_features(&state, seat))
+                .collect();
+            let mut step = 0;
+            while !matches!(state.phase, GamePhase::Completed { .. }) && step < 400 {
+                let action = state.legal_actions().choose(&mut rng).cloned().unwrap();
+                state.step(action.clone()).unwrap();
+                for (seat, parent) in features.iter_mut().enumerate() {
+                    let updated = FeatureCollection::update_from(parent, &action, &state);
+                    let fresh = collect_features(&state, seat);
+                    assert_eq!(
+                        updated.values, fresh.values,
+                        "seed {seed}, step {step}, seat {seat} after {action:?}"
+                    );
+                    *parent = updated;
+                }
+                step += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn maps_of_one_shape_share_a_layout() {
+        let game = |seed, num_players| {
+            GameState::new(GameConfig {
+                seed,
+                num_players,
+                ..GameConfig::default()
+            })
+        };
+        let first = game(1, 4);
+        let layout = collect_features(&first, 0).layout;
+        assert!(Arc::ptr_eq(&collect_features(&first, 2).layout, &layout));
+        let other_map = game(2, 4);
+        assert!(!Arc::ptr_eq(&first.map, &other_map.map));
+        assert!(Arc::ptr_eq(
+            &collect_features(&other_map, 0).layout,
+            &layout
+        ));
+        let fewer_players = collect_features(&game(1, 3), 0).layout;
+        assert!(!Arc::ptr_eq(&fewer_players, &layout));
+        assert!(Arc::ptr_eq(&collect_features(&first, 1).layout, &layout));
+    }
+}
@@ -1,10 +1,15 @@
+use std::hash::{Hash, Hasher};
+
 use serde::{Deserialize, Serialize};
 
-use crate::board::{EdgeId, NodeId};
+use crate::board::{EdgeId, NodeId, normalize_edge};
+use crate::game::dice::DiceRoll;
 use crate::game::resources::ResourceBundle;
 use crate::types::{ActionType, DevelopmentCard, Resource};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+/// Equality and hashing go through the canonical payload, so the same move
+/// spelled two ways (e.g. an edge with its nodes swapped) is one action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameAction {
     pub player_index: usize,
     pub action_type: ActionType,
@@ -24,6 +29,275 @@ impl GameAction {
         self.payload = payload;
         self
     }
+
+    /// Rewrites the payload into its canonical form in place.
+    pub fn canonicalize(&mut self) {
+        self.payload = self.payload.canonical();
+    }
+}
+
+/// One constructor per action type, each taking exactly what that action
+/// needs. Prefer these over `new(..).with_payload(..)`, which accepts any
+/// payload and leaves mistakes for `GameState::step` to reject.
+impl GameAction {
+    pub fn roll(player: usize) -> Self {
+        Self::new(player, ActionType::Roll)
+    }
+
+    pub fn end_turn(player: usize) -> Self {
+        Self::new(player, ActionType::EndTurn)
+    }
+
+    pub fn resign(player: usize) -> Self {
+        Self::new(player, ActionType::Resign)
+    }
+
+    pub fn forfeit(player: usize) -> Self {
+        Self::new(player, ActionType::Forfeit)
+    }
+
+    pub fn build_road(player: usize, edge: EdgeId) -> Self {
+        Self::new(player, ActionType::BuildRoad)
+            .with_payload(ActionPayload::Edge(normalize_edge(edge)))
+    }
+
+    pub fn build_settlement(player: usize, node: NodeId) -> Self {
+        Self::new(player, ActionType::BuildSettlement).with_payload(ActionPayload::Node(node))
+    }
+
+    pub fn build_city(player: usize, node: NodeId) -> Self {
+        Self::new(player, ActionType::BuildCity).with_payload(ActionPayload::Node(node))
+    }
+
+    pub fn buy_development_card(player: usize) -> Self {
+        Self::new(player, ActionType::BuyDevelopmentCard)
+    }
+
+    pub fn discard(player: usize, resource: Resource) -> Self {
+        Self::new(player, ActionType::Discard).with_payload(ActionPayload::Resource(resource))
+    }
+
+    /// Moves the robber to `tile_id`, stealing a random card from `victim`
+    /// if there is one.
+    pub fn move_robber(player: usize, tile_id: u16, victim: Option<usize>) -> Self {
+        Self::new(player, ActionType::MoveRobber).with_payload(ActionPayload::Robber {
+            tile_id,
+            victim,
+            resource: None,
+        })
+    }
+
+    pub fn play_knight(player: usize) -> Self {
+        Self::new(player, ActionType::PlayKnightCard)
+    }
+
+    pub fn play_road_building(player: usize) -> Self {
+        Self::new(player, ActionType::PlayRoadBuilding)
+    }
+
+    pub fn play_monopoly(player: usize, resource: Resource) -> Self {
+        Self::new(player, ActionType::PlayMonopoly).with_payload(ActionPayload::Resource(resource))
+    }
+
+    /// Takes `first` and, if given, `second` (which may be the same
+    /// resource again) from the bank.
+    pub fn play_year_of_plenty(player: usize, first: Resource, second: Option<Resource>) -> Self {
+        let mut bundle = ResourceBundle::zero();
+        bundle.add(first, 1);
+        if let Some(second) = second {
+            bundle.add(second, 1);
+        }
+        Self::new(player, ActionType::PlayYearOfPlenty)
+            .with_payload(ActionPayload::Resources(bundle))
+    }
+
+    /// Gives `rate` of `give` to the bank for one `receive`.
+    pub fn maritime_trade(
+        player: usize,
+        give: Resource,
+        rate: u8,
+        receive: Resource,
+    ) -> Result<Self, ActionError> {
+        if !(2..=4).contains(&rate) {
+            return Err(ActionError::InvalidRate(rate));
+        }
+        if give == receive {
+            return Err(ActionError::SameResource(give));
+        }
+        let mut bundle = ResourceBundle::zero();
+        bundle.add(give, rate);
+        Ok(Self::new(player, ActionType::MaritimeTrade).with_payload(
+            ActionPayload::MaritimeTrade {
+                give: bundle,
+                receive,
+            },
+        ))
+    }
+
+    /// Offers the other players `give` in exchange for `receive`.
+    pub fn offer_trade(
+        player: usize,
+        give: ResourceBundle,
+        receive: ResourceBundle,
+    ) -> Result<Self, ActionError> {
+        check_trade(&give, &receive)?;
+        Ok(
+            Self::new(player, ActionType::OfferTrade).with_payload(ActionPayload::Trade {
+                give,
+                receive,
+                partner: None,
+            }),
+        )
+    }
+
+    pub fn accept_trade(player: usize) -> Self {
+        Self::new(player, ActionType::AcceptTrade)
+    }
+
+    pub fn reject_trade(player: usize) -> Self {
+        Self::new(player, ActionType::RejectTrade)
+    }
+
+    pub fn cancel_trade(player: usize) -> Self {
+        Self::new(player, ActionType::CancelTrade)
+    }
+
+    /// Completes the offer of `give` for `receive` with `partner`, one of
+    /// the players who accepted it.
+    pub fn confirm_trade(
+        player: usize,
+        give: ResourceBundle,
+        receive: ResourceBundle,
+        partner: usize,
+    ) -> Result<Self, ActionError> {
+        check_trade(&give, &receive)?;
+        if partner == player {
+            return Err(ActionError::SelfTrade(player));
+        }
+        Ok(
+            Self::new(player, ActionType::ConfirmTrade).with_payload(ActionPayload::Trade {
+                give,
+                receive,
+                partner: Some(partner),
+            }),
+        )
+    }
+
+    pub fn gift(player: usize, resource: Resource, partner: usize) -> Result<Self, ActionError> {
+        if partner == player {
+            return Err(ActionError::SelfTrade(player));
+        }
+        Ok(Self::new(player, ActionType::GiftResource)
+            .with_payload(ActionPayload::Gift { resource, partner }))
+    }
+
+    /// Whether the payload has the shape the action type calls for, for
+    /// actions built by hand or read from elsewhere. Says nothing about
+    /// whether the action is legal in any particular game.
+    pub fn check_payload(&self) -> Result<(), ActionError> {
+        let fits = match self.action_type {
+            ActionType::Roll => {
+                matches!(self.payload, ActionPayload::None | ActionPayload::Dice(_))
+            }
+            ActionType::BuyDevelopmentCard => matches!(
+                self.payload,
+                ActionPayload::None | ActionPayload::DevelopmentCard(_)
+            ),
+            ActionType::EndTurn
+            | ActionType::Resign
+            | ActionType::Forfeit
+            | ActionType::PlayKnightCard
+            | ActionType::PlayRoadBuilding
+            | ActionType::AcceptTrade
+            | ActionType::RejectTrade
+            | ActionType::CancelTrade => matches!(self.payload, ActionPayload::None),
+            ActionType::BuildRoad => matches!(self.payload, ActionPayload::Edge(_)),
+            ActionType::BuildSettlement | ActionType::BuildCity => {
+                matches!(self.payload, ActionPayload::Node(_))
+            }
+            // Discards are logged with the card that went, as a bundle.
+            ActionType::Discard => matches!(
+                self.payload,
+                ActionPayload::Resource(_) | ActionPayload::Resources(_)
+            ),
+            ActionType::MoveRobber => matches!(self.payload, ActionPayload::Robber { .. }),
+            ActionType::PlayMonopoly => matches!(self.payload, ActionPayload::Resource(_)),
+            ActionType::PlayYearOfPlenty => matches!(self.payload, ActionPayload::Resources(_)),
+            ActionType::MaritimeTrade => {
+                matches!(self.payload, ActionPayload::MaritimeTrade { .. })
+            }
+            ActionType::OfferTrade => {
+                matches!(self.payload, ActionPayload::Trade { partner: None, .. })
+            }
+            ActionType::ConfirmTrade => matches!(
+                self.payload,
+                ActionPayload::Trade {
+                    partner: Some(_),
+                    ..
+                }
+            ),
+            ActionType::GiftResource => matches!(self.payload, ActionPayload::Gift { .. }),
+        };
+        if fits {
+            Ok(())
+        } else {
+            Err(ActionError::PayloadMismatch {
+                action: self.action_type,
+                payload: self.payload.clone(),
+            })
+        }
+    }
+}
+
+/// An action that can't be built, whatever the state of the game.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ActionError {
+    #[error("{action:?} does not take payload {payload:?}")]
+    PayloadMismatch {
+        action: ActionType,
+        payload: ActionPayload,
+    },
+    #[error("maritime trades give 2, 3 or 4 cards, not {0}")]
+    InvalidRate(u8),
+    #[error("cannot trade {0:?} for itself")]
+    SameResource(Resource),
+    #[error("a trade has to give and receive something")]
+    EmptyTrade,
+    #[error("cannot both give and receive {0:?}")]
+    OverlappingTrade(Resource),
+    #[error("player {0} cannot trade with themselves")]
+    SelfTrade(usize),
+}
+
+fn check_trade(give: &ResourceBundle, receive: &ResourceBundle) -> Result<(), ActionError> {
+    if give.is_empty() || receive.is_empty() {
+        return Err(ActionError::EmptyTrade);
+    }
+    match Resource::ALL
+        .into_iter()
+        .find(|resource| give.get(*resource) > 0 && receive.get(*resource) > 0)
+    {
+        Some(resource) => Err(ActionError::OverlappingTrade(resource)),
+        None => Ok(()),
+    }
+}
+
+impl PartialEq for GameAction {
+    fn eq(&self, other: &Self) -> bool {
+        self.player_index == other.player_index
+            && self.action_type == other.action_type
+            && self.payload.canonical() == other.payload.canonical()
+    }
+}
+
+impl Eq for GameAction {}
+
+impl Hash for GameAction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.player_index.hash(state);
+        self.action_type.hash(state);
+        self.payload.canonical().hash(state);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -31,7 +305,7 @@ pub enum ActionPayload {
     None,
     Node(NodeId),
     Edge(EdgeId),
-    Dice(u8, u8),
+    Dice(DiceRoll),
     Resources(ResourceBundle),
     Resource(Resource),
     Trade {
@@ -44,6 +318,10 @@ pub enum ActionPayload {
         receive: Resource,
     },
     DevelopmentCard(DevelopmentCard),
+    Gift {
+        resource: Resource,
+        partner: usize,
+    },
     Robber {
         tile_id: u16,
         victim: Option<usize>,
@@ -51,6 +329,17 @@ pub enum ActionPayload {
     },
 }
 
+impl ActionPayload {
+    /// One spelling per distinct payload. Edges list their lower node first;
+    /// bundles are stored as per-resource counts and so are canonical already.
+    pub fn canonical(&self) -> ActionPayload {
+        match self {
+            ActionPayload::Edge(edge) => ActionPayload::Edge(normalize_edge(*edge)),
+            other => other.clone(),
+        }
+    }
+}
+
 impl Default for ActionPayload {
     fn default() -> Self {
         ActionPayload::None
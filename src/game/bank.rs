@@ -1,21 +1,68 @@
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 
 use crate::game::resources::{COST_DEVELOPMENT, ResourceBundle, ResourceError};
 use crate::types::{DevelopmentCard, Resource};
 
-#[derive(Debug, Clone)]
+/// Cards of each resource in the game.
+pub const BANK_RESOURCE_COUNT: u8 = 19;
+
+/// The standard development deck.
+pub const DEVELOPMENT_DECK: [(DevelopmentCard, usize); 5] = [
+    (DevelopmentCard::Knight, 14),
+    (DevelopmentCard::VictoryPoint, 5),
+    (DevelopmentCard::RoadBuilding, 2),
+    (DevelopmentCard::YearOfPlenty, 2),
+    (DevelopmentCard::Monopoly, 2),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Bank {
     resources: ResourceBundle,
     development_deck: Vec<DevelopmentCard>,
+    /// Everything the bank started with. Cards only ever move between the
+    /// bank and the players, so this is also what the whole game holds.
+    resource_supply: ResourceBundle,
+    development_supply: Vec<(DevelopmentCard, usize)>,
 }
 
 impl Bank {
+    /// 19 of each resource and the standard 25 card deck, shuffled.
     pub fn standard(rng: &mut impl rand::Rng) -> Self {
-        let mut deck = build_development_deck();
-        deck.shuffle(rng);
+        Self::with_counts(
+            ResourceBundle::from_counts([BANK_RESOURCE_COUNT; Resource::ALL.len()]),
+            &DEVELOPMENT_DECK,
+            rng,
+        )
+    }
+
+    /// A bank holding `resources` and a shuffled deck with `deck` copies of
+    /// each card, for extended player counts and scenarios.
+    pub fn with_counts(
+        resources: ResourceBundle,
+        deck: &[(DevelopmentCard, usize)],
+        rng: &mut impl rand::Rng,
+    ) -> Self {
+        let mut development_deck = build_development_deck(deck);
+        development_deck.shuffle(rng);
+        Self::from_parts(resources, development_deck)
+    }
+
+    /// A bank holding `resources` and exactly `development_deck`, with no
+    /// shuffling. Cards are drawn from the end of the deck.
+    pub fn from_parts(resources: ResourceBundle, development_deck: Vec<DevelopmentCard>) -> Self {
+        let development_supply = DevelopmentCard::ALL
+            .iter()
+            .map(|card| {
+                let count = development_deck.iter().filter(|c| *c == card).count();
+                (*card, count)
+            })
+            .collect();
         Self {
-            resources: ResourceBundle::from_counts([19, 19, 19, 19, 19]),
-            development_deck: deck,
+            resources,
+            development_deck,
+            resource_supply: resources,
+            development_supply,
         }
     }
 
@@ -23,6 +70,24 @@ impl Bank {
         &self.resources
     }
 
+    /// The remaining development cards in order; the last one is drawn next.
+    pub fn development_deck(&self) -> &[DevelopmentCard] {
+        &self.development_deck
+    }
+
+    /// Resource cards in the whole game, wherever they are now.
+    pub fn resource_supply(&self) -> &ResourceBundle {
+        &self.resource_supply
+    }
+
+    /// Copies of `card` in the whole game, wherever they are now.
+    pub fn development_supply(&self, card: DevelopmentCard) -> usize {
+        self.development_supply
+            .iter()
+            .find(|(c, _)| *c == card)
+            .map_or(0, |(_, count)| *count)
+    }
+
     pub fn receive(&mut self, bundle: &ResourceBundle) {
         let mut updated = self.resources;
         updated.add_bundle(bundle);
@@ -66,20 +131,32 @@ impl Bank {
     pub fn development_deck_len(&self) -> usize {
         self.development_deck.len()
     }
+
+    /// How many copies of `card` are left in the deck.
+    pub fn development_card_count(&self, card: DevelopmentCard) -> usize {
+        self.development_deck.iter().filter(|c| **c == card).count()
+    }
+
+    /// Like `buy_development_card`, but takes a copy of `card` instead of a
+    /// random one. Used to replay logged purchases and to expand chance
+    /// nodes. Returns `Ok(None)` without charging anything if no copy is left.
+    pub fn buy_chosen_development_card(
+        &mut self,
+        card: DevelopmentCard,
+        player_resources: &mut ResourceBundle,
+    ) -> Result<Option<DevelopmentCard>, ResourceError> {
+        let Some(pos) = self.development_deck.iter().position(|c| *c == card) else {
+            return Ok(None);
+        };
+        player_resources.subtract_bundle(&COST_DEVELOPMENT)?;
+        self.resources.add_bundle(&COST_DEVELOPMENT);
+        Ok(Some(self.development_deck.swap_remove(pos)))
+    }
 }
 
-fn build_development_deck() -> Vec<DevelopmentCard> {
-    use DevelopmentCard::*;
-    const DISTRIBUTION: &[(DevelopmentCard, usize)] = &[
-        (Knight, 14),
-        (VictoryPoint, 5),
-        (RoadBuilding, 2),
-        (YearOfPlenty, 2),
-        (Monopoly, 2),
-    ];
-
-    let mut deck = Vec::with_capacity(25);
-    for (card, count) in DISTRIBUTION {
+fn build_development_deck(counts: &[(DevelopmentCard, usize)]) -> Vec<DevelopmentCard> {
+    let mut deck = Vec::with_capacity(counts.iter().map(|(_, count)| count).sum());
+    for (card, count) in counts {
         for _ in 0..*count {
             deck.push(*card);
         }
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a player whose time bank runs out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeoutPolicy {
+    /// The late player loses and the best placed opponent wins.
+    #[default]
+    Forfeit,
+    /// The late decision is thrown away and a random legal action is played
+    /// instead. The player keeps going on the increment alone.
+    RandomAction,
+    /// The late player resigns and the others play on without them.
+    Resign,
+}
+
+/// Chess-style time control: every player starts with `initial` and gets
+/// `increment` back after each decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+    pub on_timeout: TimeoutPolicy,
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let policy = match self.on_timeout {
+            TimeoutPolicy::Forfeit => "FORFEIT",
+            TimeoutPolicy::RandomAction => "RANDOM",
+            TimeoutPolicy::Resign => "RESIGN",
+        };
+        write!(
+            f,
+            "{}+{}:{}",
+            self.initial.as_secs_f64(),
+            self.increment.as_secs_f64(),
+            policy
+        )
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    /// Accepts `SECONDS[+INCREMENT][:FORFEIT|RANDOM|RESIGN]`, e.g.
+    /// `60+0.5:random`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid time control '{s}', expected SECONDS[+INCREMENT][:FORFEIT|RANDOM|RESIGN]"
+            )
+        };
+        let (times, policy) = match s.split_once(':') {
+            Some((times, policy)) => (times, Some(policy)),
+            None => (s, None),
+        };
+        let on_timeout = match policy.map(|policy| policy.to_ascii_lowercase()) {
+            None => TimeoutPolicy::Forfeit,
+            Some(policy) if policy == "forfeit" => TimeoutPolicy::Forfeit,
+            Some(policy) if policy == "random" => TimeoutPolicy::RandomAction,
+            Some(policy) if policy == "resign" => TimeoutPolicy::Resign,
+            Some(_) => return Err(invalid()),
+        };
+        let (initial, increment) = match times.split_once('+') {
+            Some((initial, increment)) => (initial, Some(increment)),
+            None => (times, None),
+        };
+        let seconds = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(invalid)
+        };
+        Ok(TimeControl {
+            initial: seconds(initial)?,
+            increment: increment.map(seconds).transpose()?.unwrap_or_default(),
+            on_timeout,
+        })
+    }
+}
+
+/// Every player's remaining thinking time under a `TimeControl`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameClock {
+    control: TimeControl,
+    remaining: Vec<Duration>,
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl, num_players: usize) -> Self {
+        Self {
+            remaining: vec![control.initial; num_players],
+            control,
+        }
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn remaining(&self, player_idx: usize) -> Duration {
+        self.remaining[player_idx]
+    }
+
+    /// Charges a decision that took `elapsed` to the player's bank and adds
+    /// the increment. Returns true when the decision overran the bank.
+    pub fn charge(&mut self, player_idx: usize, elapsed: Duration) -> bool {
+        let bank = &mut self.remaining[player_idx];
+        let flagged = elapsed > *bank;
+        *bank = bank.saturating_sub(elapsed) + self.control.increment;
+        flagged
+    }
+}
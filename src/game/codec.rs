@@ -0,0 +1,811 @@
+//! Compact binary encoding of actions and events for replays and network
+//! play. Streams start with `MAGIC` and a format version so readers can
+//! refuse data they don't understand; single records can also be written and
+//! read on their own for callers that do their own framing.
+
+use crate::board::EdgeId;
+use crate::types::{ActionType, DevelopmentCard, Resource};
+
+use super::action::{ActionPayload, GameAction};
+use super::dice::DiceRoll;
+use super::resources::ResourceBundle;
+use super::state::GameEvent;
+
+pub const MAGIC: [u8; 4] = *b"CTNR";
+pub const CODEC_VERSION: u8 = 1;
+
+/// Stands in for `None` in optional player and resource fields.
+const NONE_BYTE: u8 = u8::MAX;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CodecError {
+    #[error("not an encoded stream (bad magic)")]
+    BadMagic,
+    #[error("unsupported codec version {0}, expected {CODEC_VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("unexpected end of data")]
+    UnexpectedEof,
+    #[error("invalid {what} tag {tag}")]
+    InvalidTag { what: &'static str, tag: u8 },
+    #[error("{0} trailing bytes after the last record")]
+    TrailingBytes(usize),
+    #[error("invalid dice roll {0:?}")]
+    InvalidRoll(Vec<u8>),
+}
+
+/// Encodes a whole action log, header included.
+pub fn encode_actions(actions: &[GameAction]) -> Vec<u8> {
+    let mut buf = header(actions.len());
+    for action in actions {
+        write_action(&mut buf, action);
+    }
+    buf
+}
+
+pub fn decode_actions(bytes: &[u8]) -> Result<Vec<GameAction>, CodecError> {
+    let (count, mut input) = read_header(bytes)?;
+    let actions = (0..count)
+        .map(|_| read_action(&mut input))
+        .collect::<Result<Vec<_>, _>>()?;
+    finish(input, actions)
+}
+
+/// Encodes a sequence of events, header included.
+pub fn encode_events(events: &[GameEvent]) -> Vec<u8> {
+    let mut buf = header(events.len());
+    for event in events {
+        write_event(&mut buf, event);
+    }
+    buf
+}
+
+pub fn decode_events(bytes: &[u8]) -> Result<Vec<GameEvent>, CodecError> {
+    let (count, mut input) = read_header(bytes)?;
+    let events = (0..count)
+        .map(|_| read_event(&mut input))
+        .collect::<Result<Vec<_>, _>>()?;
+    finish(input, events)
+}
+
+/// Appends one action without a header.
+pub fn write_action(buf: &mut Vec<u8>, action: &GameAction) {
+    buf.push(action.player_index as u8);
+    buf.push(action.action_type as u8);
+    match &action.payload {
+        ActionPayload::None => buf.push(0),
+        ActionPayload::Node(node) => {
+            buf.push(1);
+            buf.extend_from_slice(&node.to_le_bytes());
+        }
+        ActionPayload::Edge(edge) => {
+            buf.push(2);
+            write_edge(buf, *edge);
+        }
+        // Plain rolls keep their original three-byte form.
+        ActionPayload::Dice(roll) if roll.extra_dice().is_empty() => {
+            buf.extend_from_slice(&[3, roll.d1, roll.d2]);
+        }
+        ActionPayload::Dice(roll) => {
+            buf.push(11);
+            write_roll(buf, roll);
+        }
+        ActionPayload::Resources(bundle) => {
+            buf.push(4);
+            buf.extend_from_slice(&bundle.counts());
+        }
+        ActionPayload::Resource(resource) => buf.extend_from_slice(&[5, *resource as u8]),
+        ActionPayload::Trade {
+            give,
+            receive,
+            partner,
+        } => {
+            buf.push(6);
+            buf.extend_from_slice(&give.counts());
+            buf.extend_from_slice(&receive.counts());
+            buf.push(partner.map_or(NONE_BYTE, |partner| partner as u8));
+        }
+        ActionPayload::MaritimeTrade { give, receive } => {
+            buf.push(7);
+            buf.extend_from_slice(&give.counts());
+            buf.push(*receive as u8);
+        }
+        ActionPayload::DevelopmentCard(card) => buf.extend_from_slice(&[8, *card as u8]),
+        ActionPayload::Gift { resource, partner } => {
+            buf.extend_from_slice(&[9, *resource as u8, *partner as u8]);
+        }
+        ActionPayload::Robber {
+            tile_id,
+            victim,
+            resource,
+        } => {
+            buf.push(10);
+            buf.extend_from_slice(&tile_id.to_le_bytes());
+            buf.push(victim.map_or(NONE_BYTE, |victim| victim as u8));
+            buf.push(resource.map_or(NONE_BYTE, |resource| resource as u8));
+        }
+    }
+}
+
+/// Reads one action written by `write_action`, advancing `input` past it.
+pub fn read_action(input: &mut &[u8]) -> Result<GameAction, CodecError> {
+    let player_index = read_u8(input)? as usize;
+    let action_type = read_action_type(input)?;
+    let payload = match read_u8(input)? {
+        0 => ActionPayload::None,
+        1 => ActionPayload::Node(read_u16(input)?),
+        2 => ActionPayload::Edge(read_edge(input)?),
+        3 => ActionPayload::Dice(DiceRoll::new(read_u8(input)?, read_u8(input)?)),
+        4 => ActionPayload::Resources(read_bundle(input)?),
+        5 => ActionPayload::Resource(read_resource(input)?),
+        6 => ActionPayload::Trade {
+            give: read_bundle(input)?,
+            receive: read_bundle(input)?,
+            partner: read_optional(input)?.map(usize::from),
+        },
+        7 => ActionPayload::MaritimeTrade {
+            give: read_bundle(input)?,
+            receive: read_resource(input)?,
+        },
+        8 => ActionPayload::DevelopmentCard(read_dev_card(input)?),
+        9 => ActionPayload::Gift {
+            resource: read_resource(input)?,
+            partner: read_u8(input)? as usize,
+        },
+        10 => ActionPayload::Robber {
+            tile_id: read_u16(input)?,
+            victim: read_optional(input)?.map(usize::from),
+            resource: read_optional(input)?.map(resource_from_tag).transpose()?,
+        },
+        11 => ActionPayload::Dice(read_roll(input)?),
+        tag => {
+            return Err(CodecError::InvalidTag {
+                what: "payload",
+                tag,
+            });
+        }
+    };
+    Ok(GameAction {
+        player_index,
+        action_type,
+        payload,
+    })
+}
+
+/// Appends one event without a header.
+pub fn write_event(buf: &mut Vec<u8>, event: &GameEvent) {
+    match event {
+        GameEvent::DiceRolled { player, dice, sum } if dice.extra_dice().is_empty() => {
+            buf.extend_from_slice(&[0, *player as u8, dice.d1, dice.d2, *sum]);
+        }
+        GameEvent::DiceRolled { player, dice, sum } => {
+            buf.extend_from_slice(&[21, *player as u8]);
+            write_roll(buf, dice);
+            buf.push(*sum);
+        }
+        GameEvent::ResourcesDistributed {
+            player,
+            bundle,
+            tiles,
+        } if tiles.is_empty() => {
+            buf.extend_from_slice(&[1, *player as u8]);
+            buf.extend_from_slice(&bundle.counts());
+        }
+        GameEvent::ResourcesDistributed {
+            player,
+            bundle,
+            tiles,
+        } => {
+            buf.extend_from_slice(&[22, *player as u8]);
+            buf.extend_from_slice(&bundle.counts());
+            buf.push(tiles.len() as u8);
+            for (tile_id, paid) in tiles {
+                buf.extend_from_slice(&tile_id.to_le_bytes());
+                buf.extend_from_slice(&paid.counts());
+            }
+        }
+        GameEvent::BuiltRoad { player, edge } => {
+            buf.extend_from_slice(&[2, *player as u8]);
+            write_edge(buf, *edge);
+        }
+        GameEvent::BuiltSettlement { player, node } => {
+            buf.extend_from_slice(&[3, *player as u8]);
+            buf.extend_from_slice(&node.to_le_bytes());
+        }
+        GameEvent::BuiltCity { player, node } => {
+            buf.extend_from_slice(&[4, *player as u8]);
+            buf.extend_from_slice(&node.to_le_bytes());
+        }
+        GameEvent::TurnAdvanced { next_player } => buf.extend_from_slice(&[5, *next_player as u8]),
+        GameEvent::GameWon { winner } => buf.extend_from_slice(&[6, *winner as u8]),
+        GameEvent::PlayerResigned { player } => buf.extend_from_slice(&[23, *player as u8]),
+        GameEvent::PlayerForfeited { player } => buf.extend_from_slice(&[24, *player as u8]),
+        GameEvent::VictoryPointsRevealed { player, cards } => {
+            buf.extend_from_slice(&[7, *player as u8, *cards]);
+        }
+        GameEvent::DevelopmentCardBought { player, card } => {
+            buf.extend_from_slice(&[8, *player as u8, card.map_or(NONE_BYTE, |card| card as u8)]);
+        }
+        GameEvent::RobberMoved {
+            player,
+            tile,
+            victim,
+        } => {
+            buf.extend_from_slice(&[9, *player as u8]);
+            buf.extend_from_slice(&tile.to_le_bytes());
+            buf.push(victim.map_or(NONE_BYTE, |victim| victim as u8));
+        }
+        GameEvent::ResourceStolen {
+            thief,
+            victim,
+            amount,
+            resource,
+        } => {
+            buf.extend_from_slice(&[
+                10,
+                *thief as u8,
+                *victim as u8,
+                *amount,
+                resource.map_or(NONE_BYTE, |resource| resource as u8),
+            ]);
+        }
+        GameEvent::MonopolyPlayed {
+            player,
+            resource,
+            taken,
+        } => {
+            buf.extend_from_slice(&[11, *player as u8, *resource as u8, taken.len() as u8]);
+            for (victim, amount) in taken {
+                buf.extend_from_slice(&[*victim as u8, *amount]);
+            }
+        }
+        GameEvent::YearOfPlentyPlayed { player, resources } => {
+            buf.extend_from_slice(&[12, *player as u8]);
+            buf.extend_from_slice(&resources.counts());
+        }
+        GameEvent::RoadBuildingPlayed { player } => buf.extend_from_slice(&[13, *player as u8]),
+        GameEvent::TradeOffered {
+            player,
+            give,
+            receive,
+        } => {
+            buf.extend_from_slice(&[14, *player as u8]);
+            buf.extend_from_slice(&give.counts());
+            buf.extend_from_slice(&receive.counts());
+        }
+        GameEvent::TradeAccepted { player, offerer } => {
+            buf.extend_from_slice(&[15, *player as u8, *offerer as u8]);
+        }
+        GameEvent::TradeRejected { player, offerer } => {
+            buf.extend_from_slice(&[16, *player as u8, *offerer as u8]);
+        }
+        GameEvent::TradeCancelled { player } => buf.extend_from_slice(&[17, *player as u8]),
+        GameEvent::TradeExecuted {
+            player,
+            partner,
+            give,
+            receive,
+        } => {
+            buf.extend_from_slice(&[
+                18,
+                *player as u8,
+                partner.map_or(NONE_BYTE, |partner| partner as u8),
+            ]);
+            buf.extend_from_slice(&give.counts());
+            buf.extend_from_slice(&receive.counts());
+        }
+        GameEvent::ResourceGifted {
+            player,
+            partner,
+            resource,
+        } => buf.extend_from_slice(&[25, *player as u8, *partner as u8, *resource as u8]),
+        GameEvent::LongestRoadChanged {
+            previous,
+            holder,
+            length,
+        } => buf.extend_from_slice(&[
+            19,
+            previous.map_or(NONE_BYTE, |seat| seat as u8),
+            holder.map_or(NONE_BYTE, |seat| seat as u8),
+            *length,
+        ]),
+        GameEvent::LargestArmyChanged {
+            previous,
+            holder,
+            knights,
+        } => buf.extend_from_slice(&[
+            20,
+            previous.map_or(NONE_BYTE, |seat| seat as u8),
+            holder.map_or(NONE_BYTE, |seat| seat as u8),
+            *knights,
+        ]),
+    }
+}
+
+/// Reads one event written by `write_event`, advancing `input` past it.
+pub fn read_event(input: &mut &[u8]) -> Result<GameEvent, CodecError> {
+    let event = match read_u8(input)? {
+        0 => GameEvent::DiceRolled {
+            player: read_u8(input)? as usize,
+            dice: DiceRoll::new(read_u8(input)?, read_u8(input)?),
+            sum: read_u8(input)?,
+        },
+        1 => GameEvent::ResourcesDistributed {
+            player: read_u8(input)? as usize,
+            bundle: read_bundle(input)?,
+            tiles: Vec::new(),
+        },
+        2 => GameEvent::BuiltRoad {
+            player: read_u8(input)? as usize,
+            edge: read_edge(input)?,
+        },
+        3 => GameEvent::BuiltSettlement {
+            player: read_u8(input)? as usize,
+            node: read_u16(input)?,
+        },
+        4 => GameEvent::BuiltCity {
+            player: read_u8(input)? as usize,
+            node: read_u16(input)?,
+        },
+        5 => GameEvent::TurnAdvanced {
+            next_player: read_u8(input)? as usize,
+        },
+        6 => GameEvent::GameWon {
+            winner: read_u8(input)? as usize,
+        },
+        7 => GameEvent::VictoryPointsRevealed {
+            player: read_u8(input)? as usize,
+            cards: read_u8(input)?,
+        },
+        8 => GameEvent::DevelopmentCardBought {
+            player: read_u8(input)? as usize,
+            card: read_optional(input)?.map(dev_card_from_tag).transpose()?,
+        },
+        9 => GameEvent::RobberMoved {
+            player: read_u8(input)? as usize,
+            tile: read_u16(input)?,
+            victim: read_optional(input)?.map(usize::from),
+        },
+        10 => GameEvent::ResourceStolen {
+            thief: read_u8(input)? as usize,
+            victim: read_u8(input)? as usize,
+            amount: read_u8(input)?,
+            resource: read_optional(input)?.map(resource_from_tag).transpose()?,
+        },
+        11 => {
+            let player = read_u8(input)? as usize;
+            let resource = read_resource(input)?;
+            let count = read_u8(input)?;
+            let taken = (0..count)
+                .map(|_| Ok((read_u8(input)? as usize, read_u8(input)?)))
+                .collect::<Result<Vec<_>, CodecError>>()?;
+            GameEvent::MonopolyPlayed {
+                player,
+                resource,
+                taken,
+            }
+        }
+        12 => GameEvent::YearOfPlentyPlayed {
+            player: read_u8(input)? as usize,
+            resources: read_bundle(input)?,
+        },
+        13 => GameEvent::RoadBuildingPlayed {
+            player: read_u8(input)? as usize,
+        },
+        14 => GameEvent::TradeOffered {
+            player: read_u8(input)? as usize,
+            give: read_bundle(input)?,
+            receive: read_bundle(input)?,
+        },
+        15 => GameEvent::TradeAccepted {
+            player: read_u8(input)? as usize,
+            offerer: read_u8(input)? as usize,
+        },
+        16 => GameEvent::TradeRejected {
+            player: read_u8(input)? as usize,
+            offerer: read_u8(input)? as usize,
+        },
+        17 => GameEvent::TradeCancelled {
+            player: read_u8(input)? as usize,
+        },
+        18 => GameEvent::TradeExecuted {
+            player: read_u8(input)? as usize,
+            partner: read_optional(input)?.map(usize::from),
+            give: read_bundle(input)?,
+            receive: read_bundle(input)?,
+        },
+        19 => GameEvent::LongestRoadChanged {
+            previous: read_optional(input)?.map(usize::from),
+            holder: read_optional(input)?.map(usize::from),
+            length: read_u8(input)?,
+        },
+        20 => GameEvent::LargestArmyChanged {
+            previous: read_optional(input)?.map(usize::from),
+            holder: read_optional(input)?.map(usize::from),
+            knights: read_u8(input)?,
+        },
+        21 => GameEvent::DiceRolled {
+            player: read_u8(input)? as usize,
+            dice: read_roll(input)?,
+            sum: read_u8(input)?,
+        },
+        22 => {
+            let player = read_u8(input)? as usize;
+            let bundle = read_bundle(input)?;
+            let count = read_u8(input)?;
+            let tiles = (0..count)
+                .map(|_| Ok((read_u16(input)?, read_bundle(input)?)))
+                .collect::<Result<_, CodecError>>()?;
+            GameEvent::ResourcesDistributed {
+                player,
+                bundle,
+                tiles,
+            }
+        }
+        23 => GameEvent::PlayerResigned {
+            player: read_u8(input)? as usize,
+        },
+        24 => GameEvent::PlayerForfeited {
+            player: read_u8(input)? as usize,
+        },
+        25 => GameEvent::ResourceGifted {
+            player: read_u8(input)? as usize,
+            partner: read_u8(input)? as usize,
+            resource: read_resource(input)?,
+        },
+        tag => return Err(CodecError::InvalidTag { what: "event", tag }),
+    };
+    Ok(event)
+}
+
+fn header(count: usize) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(9 + count * 4);
+    buf.extend_from_slice(&MAGIC);
+    buf.push(CODEC_VERSION);
+    buf.extend_from_slice(&(count as u32).to_le_bytes());
+    buf
+}
+
+fn read_header(bytes: &[u8]) -> Result<(u32, &[u8]), CodecError> {
+    let mut input = bytes;
+    if take(&mut input, MAGIC.len())? != MAGIC {
+        return Err(CodecError::BadMagic);
+    }
+    let version = read_u8(&mut input)?;
+    if version != CODEC_VERSION {
+        return Err(CodecError::UnsupportedVersion(version));
+    }
+    let count = u32::from_le_bytes(take(&mut input, 4)?.try_into().unwrap());
+    Ok((count, input))
+}
+
+fn finish<T>(input: &[u8], records: Vec<T>) -> Result<Vec<T>, CodecError> {
+    if input.is_empty() {
+        Ok(records)
+    } else {
+        Err(CodecError::TrailingBytes(input.len()))
+    }
+}
+
+/// The production pair, then the number of extra dice and the dice.
+fn write_roll(buf: &mut Vec<u8>, roll: &DiceRoll) {
+    let extra = roll.extra_dice();
+    buf.extend_from_slice(&[roll.d1, roll.d2, extra.len() as u8]);
+    buf.extend_from_slice(extra);
+}
+
+fn write_edge(buf: &mut Vec<u8>, edge: EdgeId) {
+    buf.extend_from_slice(&edge.0.to_le_bytes());
+    buf.extend_from_slice(&edge.1.to_le_bytes());
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], CodecError> {
+    if input.len() < len {
+        return Err(CodecError::UnexpectedEof);
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head)
+}
+
+fn read_u8(input: &mut &[u8]) -> Result<u8, CodecError> {
+    Ok(take(input, 1)?[0])
+}
+
+fn read_u16(input: &mut &[u8]) -> Result<u16, CodecError> {
+    Ok(u16::from_le_bytes(take(input, 2)?.try_into().unwrap()))
+}
+
+fn read_roll(input: &mut &[u8]) -> Result<DiceRoll, CodecError> {
+    let pair = [read_u8(input)?, read_u8(input)?];
+    let count = read_u8(input)? as usize;
+    let dice = [&pair[..], take(input, count)?].concat();
+    DiceRoll::from_dice(&dice).map_err(|_| CodecError::InvalidRoll(dice))
+}
+
+fn read_edge(input: &mut &[u8]) -> Result<EdgeId, CodecError> {
+    Ok((read_u16(input)?, read_u16(input)?))
+}
+
+fn read_optional(input: &mut &[u8]) -> Result<Option<u8>, CodecError> {
+    let value = read_u8(input)?;
+    Ok((value != NONE_BYTE).then_some(value))
+}
+
+fn read_bundle(input: &mut &[u8]) -> Result<ResourceBundle, CodecError> {
+    let counts = take(input, Resource::ALL.len())?.try_into().unwrap();
+    Ok(ResourceBundle::from_counts(counts))
+}
+
+fn read_resource(input: &mut &[u8]) -> Result<Resource, CodecError> {
+    resource_from_tag(read_u8(input)?)
+}
+
+fn resource_from_tag(tag: u8) -> Result<Resource, CodecError> {
+    Resource::ALL
+        .get(tag as usize)
+        .copied()
+        .ok_or(CodecError::InvalidTag {
+            what: "resource",
+            tag,
+        })
+}
+
+fn read_dev_card(input: &mut &[u8]) -> Result<DevelopmentCard, CodecError> {
+    dev_card_from_tag(read_u8(input)?)
+}
+
+fn dev_card_from_tag(tag: u8) -> Result<DevelopmentCard, CodecError> {
+    DevelopmentCard::ALL
+        .get(tag as usize)
+        .copied()
+        .ok_or(CodecError::InvalidTag {
+            what: "development card",
+            tag,
+        })
+}
+
+fn read_action_type(input: &mut &[u8]) -> Result<ActionType, CodecError> {
+    let tag = read_u8(input)?;
+    ActionType::ALL
+        .get(tag as usize)
+        .copied()
+        .ok_or(CodecError::InvalidTag {
+            what: "action type",
+            tag,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(counts: [u8; 5]) -> ResourceBundle {
+        ResourceBundle::from_counts(counts)
+    }
+
+    fn extra_roll() -> DiceRoll {
+        DiceRoll::from_dice(&[2, 5, 6]).unwrap()
+    }
+
+    /// One action per payload tag, in tag order.
+    fn actions() -> Vec<GameAction> {
+        let action = |action_type, payload| GameAction::new(1, action_type).with_payload(payload);
+        vec![
+            action(ActionType::EndTurn, ActionPayload::None),
+            action(ActionType::BuildCity, ActionPayload::Node(300)),
+            action(ActionType::BuildRoad, ActionPayload::Edge((12, 513))),
+            action(ActionType::Roll, ActionPayload::Dice(DiceRoll::new(3, 4))),
+            action(
+                ActionType::PlayYearOfPlenty,
+                ActionPayload::Resources(bundle([1, 0, 0, 1, 0])),
+            ),
+            action(
+                ActionType::PlayMonopoly,
+                ActionPayload::Resource(Resource::Ore),
+            ),
+            action(
+                ActionType::ConfirmTrade,
+                ActionPayload::Trade {
+                    give: bundle([2, 0, 0, 0, 0]),
+                    receive: bundle([0, 0, 1, 0, 0]),
+                    partner: Some(3),
+                },
+            ),
+            action(
+                ActionType::MaritimeTrade,
+                ActionPayload::MaritimeTrade {
+                    give: bundle([0, 4, 0, 0, 0]),
+                    receive: Resource::Wheat,
+                },
+            ),
+            action(
+                ActionType::BuyDevelopmentCard,
+                ActionPayload::DevelopmentCard(DevelopmentCard::VictoryPoint),
+            ),
+            action(
+                ActionType::GiftResource,
+                ActionPayload::Gift {
+                    resource: Resource::Sheep,
+                    partner: 3,
+                },
+            ),
+            action(
+                ActionType::MoveRobber,
+                ActionPayload::Robber {
+                    tile_id: 17,
+                    victim: Some(2),
+                    resource: None,
+                },
+            ),
+            action(ActionType::Roll, ActionPayload::Dice(extra_roll())),
+        ]
+    }
+
+    /// One event per event tag, in tag order.
+    fn events() -> Vec<GameEvent> {
+        vec![
+            GameEvent::DiceRolled {
+                player: 0,
+                dice: DiceRoll::new(6, 6),
+                sum: 12,
+            },
+            GameEvent::ResourcesDistributed {
+                player: 1,
+                bundle: bundle([0, 1, 0, 2, 0]),
+                tiles: Vec::new(),
+            },
+            GameEvent::BuiltRoad {
+                player: 2,
+                edge: (7, 300),
+            },
+            GameEvent::BuiltSettlement {
+                player: 3,
+                node: 299,
+            },
+            GameEvent::BuiltCity { player: 0, node: 8 },
+            GameEvent::TurnAdvanced { next_player: 1 },
+            GameEvent::GameWon { winner: 2 },
+            GameEvent::VictoryPointsRevealed {
+                player: 2,
+                cards: 3,
+            },
+            GameEvent::DevelopmentCardBought {
+                player: 1,
+                card: None,
+            },
+            GameEvent::RobberMoved {
+                player: 0,
+                tile: 400,
+                victim: None,
+            },
+            GameEvent::ResourceStolen {
+                thief: 0,
+                victim: 1,
+                amount: 1,
+                resource: Some(Resource::Brick),
+            },
+            GameEvent::MonopolyPlayed {
+                player: 3,
+                resource: Resource::Wood,
+                taken: vec![(0, 2), (2, 1)],
+            },
+            GameEvent::YearOfPlentyPlayed {
+                player: 2,
+                resources: bundle([0, 0, 2, 0, 0]),
+            },
+            GameEvent::RoadBuildingPlayed { player: 1 },
+            GameEvent::TradeOffered {
+                player: 0,
+                give: bundle([1, 0, 0, 0, 0]),
+                receive: bundle([0, 0, 0, 0, 1]),
+            },
+            GameEvent::TradeAccepted {
+                player: 2,
+                offerer: 0,
+            },
+            GameEvent::TradeRejected {
+                player: 3,
+                offerer: 0,
+            },
+            GameEvent::TradeCancelled { player: 0 },
+            GameEvent::TradeExecuted {
+                player: 1,
+                partner: None,
+                give: bundle([0, 0, 3, 0, 0]),
+                receive: bundle([1, 0, 0, 0, 0]),
+            },
+            GameEvent::LongestRoadChanged {
+                previous: None,
+                holder: Some(2),
+                length: 5,
+            },
+            GameEvent::LargestArmyChanged {
+                previous: Some(1),
+                holder: None,
+                knights: 0,
+            },
+            GameEvent::DiceRolled {
+                player: 3,
+                dice: extra_roll(),
+                sum: 7,
+            },
+            GameEvent::ResourcesDistributed {
+                player: 0,
+                bundle: bundle([2, 0, 0, 0, 1]),
+                tiles: vec![(4, bundle([2, 0, 0, 0, 0])), (9, bundle([0, 0, 0, 0, 1]))],
+            },
+            GameEvent::PlayerResigned { player: 1 },
+            GameEvent::PlayerForfeited { player: 2 },
+            GameEvent::ResourceGifted {
+                player: 0,
+                partner: 2,
+                resource: Resource::Ore,
+            },
+        ]
+    }
+
+    #[test]
+    fn every_payload_round_trips() {
+        let actions = actions();
+        for (tag, action) in actions.iter().enumerate() {
+            let mut buf = Vec::new();
+            write_action(&mut buf, action);
+            assert_eq!(buf[2], tag as u8, "{action:?}");
+        }
+        let decoded = decode_actions(&encode_actions(&actions)).unwrap();
+        assert_eq!(format!("{decoded:?}"), format!("{actions:?}"));
+    }
+
+    #[test]
+    fn every_event_round_trips() {
+        let events = events();
+        for (tag, event) in events.iter().enumerate() {
+            let mut buf = Vec::new();
+            write_event(&mut buf, event);
+            assert_eq!(buf[0], tag as u8, "{event:?}");
+        }
+        let decoded = decode_events(&encode_events(&events)).unwrap();
+        assert_eq!(format!("{decoded:?}"), format!("{events:?}"));
+    }
+
+    #[test]
+    fn refuses_streams_it_does_not_understand() {
+        let bytes = encode_actions(&actions());
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            decode_actions(&bad_magic).unwrap_err(),
+            CodecError::BadMagic
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[MAGIC.len()] = CODEC_VERSION + 1;
+        assert_eq!(
+            decode_actions(&bad_version).unwrap_err(),
+            CodecError::UnsupportedVersion(CODEC_VERSION + 1)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            decode_actions(&trailing).unwrap_err(),
+            CodecError::TrailingBytes(2)
+        );
+
+        assert_eq!(
+            decode_actions(&bytes[..bytes.len() - 1]).unwrap_err(),
+            CodecError::UnexpectedEof
+        );
+        let mut unknown = encode_events(&[]);
+        unknown[MAGIC.len() + 1] = 1;
+        unknown.push(200);
+        assert_eq!(
+            decode_events(&unknown).unwrap_err(),
+            CodecError::InvalidTag {
+                what: "event",
+                tag: 200
+            }
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Most extra dice a `DiceRoll` can carry besides the production pair.
+pub const MAX_EXTRA_DICE: usize = 2;
+
+pub(crate) const EMPTY_SCRIPT: &str = "scripted dice need at least one roll";
+
+/// The result of one roll: the two dice whose sum drives production and the
+/// robber, plus any extra dice a variant rolls with them (an event die, a
+/// third die). The base rules ignore the extra dice.
+///
+/// Serializes as the flat list of dice, `[d1, d2, extra...]`, so plain
+/// rolls look like the `(d1, d2)` pairs they replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiceRoll {
+    pub d1: u8,
+    pub d2: u8,
+    /// Extra dice in roll order, zero-padded (die faces start at 1).
+    extra: [u8; MAX_EXTRA_DICE],
+}
+
+impl DiceRoll {
+    pub fn new(d1: u8, d2: u8) -> Self {
+        Self {
+            d1,
+            d2,
+            extra: [0; MAX_EXTRA_DICE],
+        }
+    }
+
+    /// Adds an extra die after any already attached. Panics when `die` is 0
+    /// or the roll already has `MAX_EXTRA_DICE` extras.
+    pub fn with_extra(mut self, die: u8) -> Self {
+        assert!(die > 0, "die faces start at 1");
+        let slot = self.extra_dice().len();
+        assert!(slot < MAX_EXTRA_DICE, "too many extra dice");
+        self.extra[slot] = die;
+        self
+    }
+
+    /// The production dice sum.
+    pub fn sum(&self) -> u8 {
+        self.d1 + self.d2
+    }
+
+    pub fn extra_dice(&self) -> &[u8] {
+        let len = self.extra.iter().take_while(|die| **die > 0).count();
+        &self.extra[..len]
+    }
+
+    /// Every die in roll order, production pair first.
+    pub fn dice(&self) -> impl Iterator<Item = u8> + '_ {
+        [self.d1, self.d2]
+            .into_iter()
+            .chain(self.extra_dice().iter().copied())
+    }
+
+    /// Rebuilds a roll from the `dice()` list.
+    pub fn from_dice(dice: &[u8]) -> Result<Self, String> {
+        let [d1, d2, extra @ ..] = dice else {
+            return Err(format!("a roll needs two dice, got {}", dice.len()));
+        };
+        if extra.len() > MAX_EXTRA_DICE || extra.contains(&0) {
+            return Err(format!("invalid extra dice {extra:?}"));
+        }
+        Ok(extra
+            .iter()
+            .fold(Self::new(*d1, *d2), |roll, die| roll.with_extra(*die)))
+    }
+}
+
+impl From<(u8, u8)> for DiceRoll {
+    fn from((d1, d2): (u8, u8)) -> Self {
+        Self::new(d1, d2)
+    }
+}
+
+impl fmt::Display for DiceRoll {
+    /// `3-4`, with extra dice appended as `3-4-6`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dice: Vec<String> = self.dice().map(|die| die.to_string()).collect();
+        write!(f, "{}", dice.join("-"))
+    }
+}
+
+impl Serialize for DiceRoll {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.dice())
+    }
+}
+
+impl<'de> Deserialize<'de> for DiceRoll {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let dice = Vec::<u8>::deserialize(deserializer)?;
+        DiceRoll::from_dice(&dice).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Source of dice rolls for a game. Models draw any randomness from the
+/// game's own RNG so that seeded games stay reproducible.
+pub trait DiceModel: fmt::Debug + Send + Sync {
+    fn roll(&mut self, rng: &mut StdRng) -> DiceRoll;
+
+    /// Every roll the next call to `roll` can produce, with its probability.
+    /// Search uses this to expand dice chance nodes.
+    fn outcomes(&self) -> Vec<(DiceRoll, f64)>;
+
+    fn box_clone(&self) -> Box<dyn DiceModel>;
+}
+
+impl Clone for Box<dyn DiceModel> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Two independent fair six-sided dice.
+#[derive(Debug, Clone, Default)]
+pub struct FairDice;
+
+impl DiceModel for FairDice {
+    fn roll(&mut self, rng: &mut StdRng) -> DiceRoll {
+        DiceRoll::new(rng.gen_range(1..=6), rng.gen_range(1..=6))
+    }
+
+    fn outcomes(&self) -> Vec<(DiceRoll, f64)> {
+        fair_outcomes()
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceModel> {
+        Box::new(self.clone())
+    }
+}
+
+/// "Balanced dice": a 36-card deck holding every (d1, d2) combination once.
+/// Cards are drawn without replacement and the deck is reshuffled once only
+/// `reshuffle_at` cards remain.
+#[derive(Debug, Clone)]
+pub struct BalancedDice {
+    deck: Vec<DiceRoll>,
+    reshuffle_at: usize,
+}
+
+impl BalancedDice {
+    pub const DEFAULT_RESHUFFLE_AT: usize = 5;
+
+    pub fn new(reshuffle_at: usize) -> Self {
+        Self {
+            deck: Vec::new(),
+            reshuffle_at: reshuffle_at.min(35),
+        }
+    }
+
+    pub fn remaining(&self) -> &[DiceRoll] {
+        &self.deck
+    }
+}
+
+impl Default for BalancedDice {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_RESHUFFLE_AT)
+    }
+}
+
+impl DiceModel for BalancedDice {
+    fn roll(&mut self, rng: &mut StdRng) -> DiceRoll {
+        if self.deck.len() <= self.reshuffle_at {
+            self.deck = all_rolls().collect();
+            self.deck.shuffle(rng);
+        }
+        self.deck.pop().expect("deck refilled above")
+    }
+
+    fn outcomes(&self) -> Vec<(DiceRoll, f64)> {
+        if self.deck.len() <= self.reshuffle_at {
+            return fair_outcomes();
+        }
+        let p = 1.0 / self.deck.len() as f64;
+        let mut outcomes: Vec<(DiceRoll, f64)> = Vec::new();
+        for roll in &self.deck {
+            match outcomes.iter_mut().find(|(seen, _)| seen == roll) {
+                Some((_, total)) => *total += p,
+                None => outcomes.push((*roll, p)),
+            }
+        }
+        outcomes
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceModel> {
+        Box::new(self.clone())
+    }
+}
+
+/// Replays a fixed sequence of rolls, cycling once exhausted. Mostly useful
+/// for tests and reproducing specific game situations.
+#[derive(Debug, Clone)]
+pub struct ScriptedDice {
+    rolls: Vec<DiceRoll>,
+    cursor: usize,
+}
+
+impl ScriptedDice {
+    /// Panics on an empty list; `try_from` reports it instead.
+    pub fn new(rolls: Vec<DiceRoll>) -> Self {
+        Self::try_from(rolls).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryFrom<Vec<DiceRoll>> for ScriptedDice {
+    type Error = String;
+
+    fn try_from(rolls: Vec<DiceRoll>) -> Result<Self, Self::Error> {
+        if rolls.is_empty() {
+            return Err(EMPTY_SCRIPT.to_string());
+        }
+        Ok(Self { rolls, cursor: 0 })
+    }
+}
+
+impl DiceModel for ScriptedDice {
+    fn roll(&mut self, _rng: &mut StdRng) -> DiceRoll {
+        let roll = self.rolls[self.cursor % self.rolls.len()];
+        self.cursor += 1;
+        roll
+    }
+
+    fn outcomes(&self) -> Vec<(DiceRoll, f64)> {
+        vec![(self.rolls[self.cursor % self.rolls.len()], 1.0)]
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceModel> {
+        Box::new(self.clone())
+    }
+}
+
+fn all_rolls() -> impl Iterator<Item = DiceRoll> {
+    (1..=6).flat_map(|d1| (1..=6).map(move |d2| DiceRoll::new(d1, d2)))
+}
+
+fn fair_outcomes() -> Vec<(DiceRoll, f64)> {
+    all_rolls().map(|roll| (roll, 1.0 / 36.0)).collect()
+}
+
+/// Serializable selection of a built-in `DiceModel`, carried by `GameConfig`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiceConfig {
+    #[default]
+    Fair,
+    Balanced {
+        reshuffle_at: usize,
+    },
+    Scripted(#[serde(deserialize_with = "scripted_rolls")] Vec<DiceRoll>),
+}
+
+/// Reads the rolls of a `Scripted` config, refusing an empty script.
+fn scripted_rolls<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<DiceRoll>, D::Error> {
+    let rolls = Vec::<DiceRoll>::deserialize(deserializer)?;
+    if rolls.is_empty() {
+        return Err(serde::de::Error::custom(EMPTY_SCRIPT));
+    }
+    Ok(rolls)
+}
+
+impl DiceConfig {
+    pub fn build(&self) -> Box<dyn DiceModel> {
+        match self {
+            DiceConfig::Fair => Box::new(FairDice),
+            DiceConfig::Balanced { reshuffle_at } => Box::new(BalancedDice::new(*reshuffle_at)),
+            DiceConfig::Scripted(rolls) => Box::new(ScriptedDice::new(rolls.clone())),
+        }
+    }
+}
+
+impl fmt::Display for DiceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiceConfig::Fair => write!(f, "FAIR"),
+            DiceConfig::Balanced { reshuffle_at } => write!(f, "BALANCED:{reshuffle_at}"),
+            DiceConfig::Scripted(rolls) => {
+                let rolls: Vec<String> = rolls.iter().map(DiceRoll::to_string).collect();
+                write!(f, "SCRIPTED:{}", rolls.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for DiceConfig {
+    type Err = String;
+
+    /// Accepts `fair`, `balanced[:RESHUFFLE_AT]` and `scripted:1-6,3-4,...`,
+    /// where a roll may list extra dice after the pair (`3-4-6`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, rest) = s.split_once(':').unwrap_or((s, ""));
+        match kind.to_ascii_lowercase().as_str() {
+            "fair" => Ok(DiceConfig::Fair),
+            "balanced" if rest.is_empty() => Ok(DiceConfig::Balanced {
+                reshuffle_at: BalancedDice::DEFAULT_RESHUFFLE_AT,
+            }),
+            "balanced" => rest
+                .trim()
+                .parse()
+                .map(|reshuffle_at| DiceConfig::Balanced { reshuffle_at })
+                .map_err(|_| format!("invalid reshuffle point '{rest}'")),
+            "scripted" => {
+                let rolls = rest
+                    .split(',')
+                    .filter(|part| !part.is_empty())
+                    .map(parse_roll)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if rolls.is_empty() {
+                    return Err(EMPTY_SCRIPT.to_string());
+                }
+                Ok(DiceConfig::Scripted(rolls))
+            }
+            _ => Err(format!("unknown dice model: {s}")),
+        }
+    }
+}
+
+fn parse_roll(part: &str) -> Result<DiceRoll, String> {
+    let dice = part
+        .split('-')
+        .map(|value| match value.trim().parse::<u8>() {
+            Ok(die @ 1..=6) => Ok(die),
+            _ => Err(format!("invalid die value '{value}'")),
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    DiceRoll::from_dice(&dice).map_err(|err| format!("invalid roll '{part}': {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_round_trip_through_display() {
+        let configs = [
+            DiceConfig::Fair,
+            DiceConfig::Balanced { reshuffle_at: 12 },
+            DiceConfig::Scripted(vec![DiceRoll::new(3, 4), DiceRoll::new(1, 6).with_extra(2)]),
+        ];
+        for config in configs {
+            assert_eq!(config.to_string().parse::<DiceConfig>(), Ok(config));
+        }
+    }
+
+    #[test]
+    fn empty_scripts_are_refused() {
+        assert!(ScriptedDice::try_from(Vec::new()).is_err());
+        assert!("scripted:".parse::<DiceConfig>().is_err());
+        assert!(serde_json::from_str::<DiceConfig>(r#"{"Scripted":[]}"#).is_err());
+        let rolls: DiceConfig = serde_json::from_str(r#"{"Scripted":[[3,4]]}"#).unwrap();
+        assert_eq!(rolls, DiceConfig::Scripted(vec![DiceRoll::new(3, 4)]));
+    }
+}
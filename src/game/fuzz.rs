@@ -0,0 +1,175 @@
+//! Random-play fuzzing of the rules engine. Games are driven purely by the
+//! engine's own `legal_actions`, so any rejected step, broken invariant or
+//! game that never ends is an engine bug.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::board::{HarborLayout, MapType};
+
+use super::action::GameAction;
+use super::game::TURNS_LIMIT;
+use super::house_rules::HouseRules;
+use super::invariants::InvariantViolation;
+use super::setup::SetupMode;
+use super::state::{GameConfig, GamePhase, GameState};
+
+/// Bounds for `fuzz_game`.
+#[derive(Debug, Clone)]
+pub struct FuzzOptions {
+    /// Steps after which a game that hasn't finished or reached
+    /// `TURNS_LIMIT` counts as stuck.
+    pub max_steps: usize,
+    /// Check `validate_invariants` after every step, even in release builds.
+    pub check_invariants: bool,
+}
+
+impl Default for FuzzOptions {
+    fn default() -> Self {
+        Self {
+            max_steps: 50_000,
+            check_invariants: true,
+        }
+    }
+}
+
+/// How a fuzzed game ended when nothing went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzReport {
+    pub steps: usize,
+    pub turns: u32,
+    pub winner: Option<usize>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FuzzFailure {
+    #[error("step {step}: listed action {action:?} was rejected: {error}")]
+    LegalActionRejected {
+        step: usize,
+        action: GameAction,
+        error: String,
+    },
+    #[error("step {step}: {violation} (after {action:?})")]
+    InvariantBroken {
+        step: usize,
+        action: GameAction,
+        violation: InvariantViolation,
+    },
+    #[error("step {step}: no legal actions in an unfinished game")]
+    NoLegalActions { step: usize },
+    #[error("game still running after {steps} steps")]
+    DidNotTerminate { steps: usize },
+}
+
+/// Plays one game from `config` choosing uniformly among the legal actions
+/// of a random player who may act, and reports the first thing that breaks.
+pub fn fuzz_game(
+    config: GameConfig,
+    options: &FuzzOptions,
+    rng: &mut impl Rng,
+) -> Result<FuzzReport, FuzzFailure> {
+    let mut state = GameState::new(config);
+    for step in 0..options.max_steps {
+        if let GamePhase::Completed { winner } = state.phase {
+            return Ok(FuzzReport {
+                steps: step,
+                turns: state.turn,
+                winner,
+            });
+        }
+        if state.turn >= TURNS_LIMIT {
+            return Ok(FuzzReport {
+                steps: step,
+                turns: state.turn,
+                winner: None,
+            });
+        }
+
+        let players = state.players_to_act();
+        let player = *players.choose(rng).expect("someone is always to act");
+        let actions = state.legal_actions_for(player);
+        let Some(action) = actions.choose(rng).cloned() else {
+            return Err(FuzzFailure::NoLegalActions { step });
+        };
+        if let Err(error) = state.step(action.clone()) {
+            return Err(FuzzFailure::LegalActionRejected {
+                step,
+                action,
+                error: error.to_string(),
+            });
+        }
+        if options.check_invariants
+            && let Err(violation) = state.validate_invariants()
+        {
+            return Err(FuzzFailure::InvariantBroken {
+                step,
+                action,
+                violation,
+            });
+        }
+    }
+    Err(FuzzFailure::DidNotTerminate {
+        steps: options.max_steps,
+    })
+}
+
+/// A random but valid configuration covering the optional rules: player
+/// count, map, domestic trades, teams, simultaneous discards, setup order,
+/// automatic forced moves and house rules.
+pub fn random_config(rng: &mut impl Rng) -> GameConfig {
+    let num_players = rng.gen_range(2..=4);
+    let map_type = match rng.gen_range(0..4) {
+        0 => MapType::Base,
+        1 => MapType::Tournament,
+        2 => MapType::Mini,
+        _ => MapType::Custom(format!("hex{}", rng.gen_range(1..=3))),
+    };
+    let teams = (num_players == 4 && rng.gen_bool(0.25)).then(|| vec![0, 1, 0, 1]);
+    GameConfig {
+        num_players,
+        map_type,
+        harbor_layout: if rng.gen_bool(0.5) {
+            HarborLayout::Official
+        } else {
+            HarborLayout::Shuffled
+        },
+        vps_to_win: rng.gen_range(3..=10),
+        seed: rng.r#gen(),
+        domestic_trades: rng.gen_bool(0.5),
+        team_gifts: teams.is_some() && rng.gen_bool(0.5),
+        teams,
+        simultaneous_discard: rng.gen_bool(0.5),
+        setup: match rng.gen_range(0..3) {
+            0 => SetupMode::Snake,
+            1 => SetupMode::Fixed,
+            _ => SetupMode::Random,
+        },
+        auto_forced_actions: rng.gen_bool(0.5),
+        house_rules: HouseRules {
+            friendly_robber: rng.gen_bool(0.5),
+            robber_avoids_desert: rng.gen_bool(0.5),
+        },
+        ..GameConfig::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    /// Every random configuration plays to the end without a rejected
+    /// legal action or a broken invariant.
+    #[test]
+    fn random_games_follow_the_rules() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for case in 0..64 {
+            let config = random_config(&mut rng);
+            if let Err(failure) = fuzz_game(config.clone(), &FuzzOptions::default(), &mut rng) {
+                panic!("case {case} failed: {failure}\nconfig: {config:?}");
+            }
+        }
+    }
+}
@@ -1,37 +1,188 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::game::action::GameAction;
-use crate::game::{GameConfig, GamePhase, GameState};
-use crate::players::BasePlayer;
-use crate::types::Color;
+use crate::game::state::is_chance_outcome;
+use crate::game::{
+    GameConfig, GameError, GameEvent, GamePhase, GameState, StepOutcome, TimeoutPolicy,
+};
+use crate::players::{AsyncPlayer, BasePlayer};
+use crate::types::{Color, Resource};
+
+/// Games still running after this many turns are abandoned without a winner.
+pub const TURNS_LIMIT: u32 = 1000;
+
+/// How a game went, as returned by `Game::play`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub winner: Option<Color>,
+    pub winner_seat: Option<usize>,
+    pub turns: u32,
+    pub ticks: u32,
+    /// Actions executed, including the ones the engine applied by itself.
+    pub actions: usize,
+    /// Time spent playing, decisions included.
+    pub duration: Duration,
+    /// By seat.
+    pub players: Vec<PlayerResult>,
+}
+
+impl GameResult {
+    /// Final VPs by seat, hidden ones included.
+    pub fn victory_points(&self) -> Vec<u8> {
+        self.players
+            .iter()
+            .map(|player| player.victory_points)
+            .collect()
+    }
+}
+
+/// Where one seat ended up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerResult {
+    pub color: Color,
+    pub victory_points: u8,
+    pub settlements: usize,
+    pub cities: usize,
+    pub roads: usize,
+    pub knights_played: u8,
+    pub dev_cards_played: u32,
+    pub has_longest_road: bool,
+    pub has_largest_army: bool,
+    /// Cards collected from dice rolls, by resource.
+    pub dice_income: [u32; Resource::ALL.len()],
+}
+
+/// An event and the index in `GameState::action_log` of the action that
+/// caused it.
+#[derive(Debug, Clone)]
+pub struct LoggedEvent {
+    pub action_index: usize,
+    pub event: GameEvent,
+}
+
+/// A game as it was at some point, to go back to with `Game::restore`.
+#[derive(Debug, Clone)]
+pub struct GameSnapshot {
+    state: GameState,
+    events: usize,
+}
 
-const TURNS_LIMIT: u32 = 1000;
+impl GameSnapshot {
+    /// Actions that had been played when the snapshot was taken.
+    pub fn num_actions(&self) -> usize {
+        self.state.action_log().len()
+    }
+}
 
 pub struct Game {
     pub seed: u64,
     pub id: Uuid,
     pub vps_to_win: u8,
     pub state: GameState,
+    event_log: Option<Vec<LoggedEvent>>,
 }
 
 impl Game {
+    /// Panics on an invalid config, e.g. a map layout that doesn't fit the
+    /// map type; see `try_new`.
     pub fn new(config: GameConfig) -> Self {
-        Self {
+        Self::try_new(config).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// A new game, or why `config` can't make one. Use this for configs
+    /// from outside the engine, such as pinned map layouts.
+    pub fn try_new(config: GameConfig) -> Result<Self, GameError> {
+        Ok(Self {
             seed: config.seed,
             id: Uuid::new_v4(),
             vps_to_win: config.vps_to_win,
-            state: GameState::new(config),
-        }
+            state: GameState::try_new(config)?,
+            event_log: None,
+        })
+    }
+
+    /// Keeps the events of every executed action, for UIs that show what
+    /// happened rather than just what was played. Off by default so search
+    /// copies stay cheap.
+    pub fn with_event_log(mut self) -> Self {
+        self.event_log = Some(Vec::new());
+        self
+    }
+
+    /// Events recorded since `with_event_log`; empty if it wasn't called.
+    pub fn events(&self) -> &[LoggedEvent] {
+        self.event_log.as_deref().unwrap_or_default()
+    }
+
+    pub fn play<P: BasePlayer>(&mut self, players: &mut [P]) -> GameResult {
+        self.play_with(players, |_| {})
     }
 
-    pub fn play<P: BasePlayer>(&mut self, players: &[P]) -> Option<Color> {
-        while self.winning_color().is_none() && self.state.turn < TURNS_LIMIT {
+    /// Same as `play`, but calls `on_tick` with the game after every tick.
+    pub fn play_with<P: BasePlayer, F: FnMut(&Game)>(
+        &mut self,
+        players: &mut [P],
+        mut on_tick: F,
+    ) -> GameResult {
+        let started = Instant::now();
+        self.seed_players(players);
+        while !self.is_over() && self.state.turn < TURNS_LIMIT {
             self.play_tick(players);
+            on_tick(self);
+        }
+        self.result(started.elapsed())
+    }
+
+    /// The game as it stands, for callers that drive it with `play_tick`
+    /// and timed it themselves.
+    pub fn result(&self, duration: Duration) -> GameResult {
+        let winner = self.winning_color();
+        GameResult {
+            winner,
+            winner_seat: winner.and_then(|color| {
+                self.state
+                    .players
+                    .iter()
+                    .position(|player| player.color == color)
+            }),
+            turns: self.state.turn,
+            ticks: self.state.tick,
+            actions: self.state.action_log().len(),
+            duration,
+            players: self
+                .state
+                .players
+                .iter()
+                .map(|player| PlayerResult {
+                    color: player.color,
+                    victory_points: player.total_points(),
+                    settlements: player.settlements.len(),
+                    cities: player.cities.len(),
+                    roads: player.roads.len(),
+                    knights_played: player.knights_played,
+                    dev_cards_played: player.played_dev_cards.values().sum(),
+                    has_longest_road: player.has_longest_road,
+                    has_largest_army: player.has_largest_army,
+                    dice_income: player.dice_income,
+                })
+                .collect(),
+        }
+    }
+
+    /// Hands each player a random generator derived from this game's seed,
+    /// stream and the player's seat. `play` does this itself; callers that
+    /// drive the game with `play_tick` should call it once before starting.
+    pub fn seed_players<P: BasePlayer>(&self, players: &mut [P]) {
+        let stream = self.state.rng_stream();
+        for (seat, player) in players.iter_mut().enumerate() {
+            player.reseed(stream.player_rng(seat));
         }
-        self.winning_color()
     }
 
-    pub fn play_tick<P: BasePlayer>(&mut self, players: &[P]) -> Option<GameAction> {
+    pub fn play_tick<P: BasePlayer>(&mut self, players: &mut [P]) -> Option<GameAction> {
         let current_idx = self.state.current_player;
         if current_idx >= players.len() {
             return None;
@@ -42,19 +193,131 @@ impl Game {
             return None;
         }
 
-        let player = &players[current_idx];
-        let action = player.decide(self, legal_actions);
+        let player = &mut players[current_idx];
+        let started = Instant::now();
+        let action = BasePlayer::decide(player, self, legal_actions);
+        self.settle_decision(current_idx, started, action)
+    }
+
+    /// Same as `play`, but awaits each decision, so players can answer
+    /// from elsewhere without holding up a thread while they think. A player
+    /// who answers `None` or with an action the game refuses is settled like
+    /// one out of time, so a vanished or broken player can't stall the game.
+    pub async fn play_async<P: AsyncPlayer>(&mut self, players: &mut [P]) -> GameResult {
+        let started = Instant::now();
+        let stream = self.state.rng_stream();
+        for (seat, player) in players.iter_mut().enumerate() {
+            AsyncPlayer::reseed(player, stream.player_rng(seat));
+        }
+        while !self.is_over() && self.state.turn < TURNS_LIMIT {
+            self.play_tick_async(players).await;
+        }
+        self.result(started.elapsed())
+    }
+
+    /// Same as `play_tick`, awaiting the current player's decision. When
+    /// nothing gets played, the clock's timeout policy decides instead, and
+    /// an untimed game is forfeited.
+    pub async fn play_tick_async<P: AsyncPlayer>(
+        &mut self,
+        players: &mut [P],
+    ) -> Option<GameAction> {
+        let current_idx = self.state.current_player;
+        if current_idx >= players.len() {
+            return None;
+        }
+
+        let legal_actions = self.state.legal_actions();
+        if legal_actions.is_empty() {
+            return None;
+        }
 
-        if let Some(action) = action {
-            self.execute(action.clone());
-            Some(action)
-        } else {
-            None
+        let player = &mut players[current_idx];
+        let started = Instant::now();
+        let action = AsyncPlayer::decide(player, self, legal_actions).await;
+        let tick = self.state.tick;
+        let played = self.settle_decision(current_idx, started, action);
+        if self.state.tick != tick {
+            return played;
         }
+        let policy = self
+            .state
+            .clock
+            .as_ref()
+            .map_or(TimeoutPolicy::Forfeit, |clock| clock.control().on_timeout);
+        self.settle_timeout(current_idx, policy)
+    }
+
+    /// Charges the decision's time to the player's clock, applies the
+    /// timeout policy if it ran out and executes what was decided.
+    fn settle_decision(
+        &mut self,
+        current_idx: usize,
+        started: Instant,
+        action: Option<GameAction>,
+    ) -> Option<GameAction> {
+        if let Some(clock) = self.state.clock.as_mut()
+            && clock.charge(current_idx, started.elapsed())
+        {
+            let policy = clock.control().on_timeout;
+            return self.settle_timeout(current_idx, policy);
+        }
+
+        let action = action?;
+        self.execute(action.clone());
+        Some(action)
+    }
+
+    /// Plays what `policy` calls for in place of `current_idx`'s decision.
+    fn settle_timeout(&mut self, current_idx: usize, policy: TimeoutPolicy) -> Option<GameAction> {
+        let action = match policy {
+            TimeoutPolicy::Forfeit => {
+                if let Ok(outcome) = self.state.apply_timeout_forfeit(current_idx) {
+                    self.log_outcome(outcome);
+                }
+                return Some(GameAction::forfeit(current_idx));
+            }
+            TimeoutPolicy::RandomAction => self.state.random_legal_action(),
+            // Nobody can resign during initial placement, so a late
+            // placement is made at random instead.
+            TimeoutPolicy::Resign if matches!(self.state.phase, GamePhase::Setup(_)) => {
+                self.state.random_legal_action()
+            }
+            TimeoutPolicy::Resign => Some(GameAction::resign(current_idx)),
+        }?;
+        self.execute(action.clone());
+        Some(action)
     }
 
     pub fn execute(&mut self, action: GameAction) {
-        let _ = self.state.step(action);
+        let _ = self.try_execute(action);
+    }
+
+    /// Same as `execute`, but reports actions the state refused.
+    pub fn try_execute(&mut self, action: GameAction) -> Result<(), GameError> {
+        let outcome = self.state.step(action)?;
+        self.log_outcome(outcome);
+        Ok(())
+    }
+
+    /// Records the events of an applied action and its forced follow-ups.
+    fn log_outcome(&mut self, outcome: StepOutcome) {
+        if let Some(log) = self.event_log.as_mut() {
+            // Forced follow-ups were logged after the action itself.
+            let first_index = self.state.action_log().len() - 1 - outcome.forced.len();
+            let forced = outcome.forced.into_iter().map(|(_, events)| events);
+            for (offset, events) in std::iter::once(outcome.events).chain(forced).enumerate() {
+                log.extend(events.into_iter().map(|event| LoggedEvent {
+                    action_index: first_index + offset,
+                    event,
+                }));
+            }
+        }
+    }
+
+    /// True once someone has won or the game ended in a draw.
+    pub fn is_over(&self) -> bool {
+        matches!(self.state.phase, GamePhase::Completed { .. }) || self.winning_color().is_some()
     }
 
     pub fn winning_color(&self) -> Option<Color> {
@@ -82,12 +345,155 @@ impl Game {
         }
     }
 
+    /// Saves the current position, clocks included, so the game can be
+    /// taken back to it.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            state: self.state.clone(),
+            events: self.events().len(),
+        }
+    }
+
+    /// Undoes everything played since `snapshot` was taken from this game,
+    /// dropping the events it logged. Refuses to go back past a roll, card
+    /// draw or steal: its result has been seen, and playing it again would
+    /// either repeat it or draw a new one.
+    pub fn restore(&mut self, snapshot: GameSnapshot) -> Result<(), GameError> {
+        if let Some(chance) = self.chance_since(&snapshot) {
+            return Err(GameError::TakebackPastChance(chance.action_type));
+        }
+        self.state = snapshot.state;
+        if let Some(log) = self.event_log.as_mut() {
+            log.truncate(snapshot.events);
+        }
+        Ok(())
+    }
+
+    /// Whether `restore` would take the game back to `snapshot`.
+    pub fn can_restore(&self, snapshot: &GameSnapshot) -> bool {
+        self.chance_since(snapshot).is_none()
+    }
+
+    /// The first roll, card draw or steal played since `snapshot`.
+    fn chance_since(&self, snapshot: &GameSnapshot) -> Option<&GameAction> {
+        let since = self.state.action_log().get(snapshot.num_actions()..);
+        since
+            .unwrap_or_default()
+            .iter()
+            .find(|action| is_chance_outcome(action))
+    }
+
     pub fn copy(&self) -> Self {
         Self {
             seed: self.seed,
             id: self.id,
             vps_to_win: self.vps_to_win,
             state: self.state.clone(),
+            event_log: self.event_log.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::game::{DiceConfig, DiceRoll, SetupMode};
+    use crate::players::RandomPlayer;
+    use crate::types::ActionType;
+
+    #[test]
+    fn takebacks_stop_at_chance_events() {
+        let mut game = Game::new(GameConfig {
+            num_players: 2,
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(vec![DiceRoll::new(2, 3)]),
+            ..GameConfig::default()
+        })
+        .with_event_log();
+        let player = game.state.current_player;
+        let before_roll = game.snapshot();
+        game.try_execute(GameAction::roll(player)).unwrap();
+        assert!(!game.can_restore(&before_roll));
+        assert!(matches!(
+            game.restore(before_roll),
+            Err(GameError::TakebackPastChance(ActionType::Roll))
+        ));
+        let played = game.state.action_log().len();
+
+        let after_roll = game.snapshot();
+        let events = game.events().len();
+        game.try_execute(GameAction::end_turn(player)).unwrap();
+        assert_ne!(game.state.current_player, player);
+        game.restore(after_roll).unwrap();
+        assert_eq!(game.state.action_log().len(), played);
+        assert_eq!(game.state.current_player, player);
+        assert_eq!(game.events().len(), events);
+    }
+
+    #[test]
+    fn late_placements_are_random_when_timeouts_resign() {
+        let mut game = Game::new(GameConfig {
+            seed: 5,
+            time_control: Some("0:resign".parse().unwrap()),
+            ..GameConfig::default()
+        });
+        let mut players: Vec<RandomPlayer> = (0..4).map(RandomPlayer::new).collect();
+        let result = game.play(&mut players);
+        assert!(game.is_over());
+        assert!(result.winner.is_some());
+        assert_eq!(game.state.resigned().len(), 3);
+    }
+
+    /// Answers `None`, or an end of turn whether or not it is allowed.
+    struct Unhelpful {
+        answers: bool,
+    }
+
+    impl AsyncPlayer for Unhelpful {
+        fn decide(
+            &mut self,
+            game: &Game,
+            _actions: &[GameAction],
+        ) -> impl Future<Output = Option<GameAction>> + Send {
+            let answer = self
+                .answers
+                .then(|| GameAction::end_turn(game.state.current_player));
+            std::future::ready(answer)
+        }
+    }
+
+    /// Polls `future` once; everything it awaits here is ready at once.
+    fn poll_ready<F: Future>(future: F) -> F::Output {
+        let mut context = Context::from_waker(Waker::noop());
+        match std::pin::pin!(future).poll(&mut context) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("nothing here should wait"),
+        }
+    }
+
+    #[test]
+    fn async_players_who_give_nothing_are_settled_by_the_timeout_policy() {
+        let config = |time_control: Option<&str>| GameConfig {
+            num_players: 2,
+            time_control: time_control.map(|control| control.parse().unwrap()),
+            ..GameConfig::default()
+        };
+        let seats = |answers| vec![Unhelpful { answers }, Unhelpful { answers }];
+
+        // Untimed, the first player to stall forfeits.
+        let mut game = Game::new(config(None));
+        let result = poll_ready(game.play_async(&mut seats(false)));
+        assert_eq!(result.winner_seat, Some(1));
+        assert_eq!(game.state.action_log().len(), 1);
+
+        // Refused actions during setup are placed at random, then the
+        // first player to roll resigns instead.
+        let mut game = Game::new(config(Some("60:resign")));
+        let result = poll_ready(game.play_async(&mut seats(true)));
+        assert!(game.is_over());
+        assert_eq!(game.state.resigned().len(), 1);
+        assert_eq!(Some(1 - game.state.resigned()[0]), result.winner_seat);
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Public VPs at or below which `HouseRules::friendly_robber` protects a player.
+pub const FRIENDLY_ROBBER_MAX_POINTS: u8 = 2;
+
+/// Common house rules on top of the standard game, all off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HouseRules {
+    /// Players with `FRIENDLY_ROBBER_MAX_POINTS` public VPs or fewer can't
+    /// be stolen from. The robber may still block their tiles.
+    pub friendly_robber: bool,
+    /// Once moved off, the robber may never go back onto a desert.
+    pub robber_avoids_desert: bool,
+}
@@ -0,0 +1,167 @@
+use crate::board::{EdgeId, NodeId};
+use crate::types::{DevelopmentCard, Resource};
+
+use super::players::{MAX_CITIES, MAX_ROADS, MAX_SETTLEMENTS};
+use super::state::{GamePhase, GameState, Structure};
+
+/// A broken bookkeeping rule found by `GameState::validate_invariants`.
+/// Any of these means an engine bug, never a bad action.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvariantViolation {
+    #[error("{resource:?} cards add up to {total}, expected {expected}")]
+    ResourceNotConserved {
+        resource: Resource,
+        total: u32,
+        expected: u32,
+    },
+    #[error("{card:?} cards add up to {total}, expected {expected}")]
+    DevelopmentCardNotConserved {
+        card: DevelopmentCard,
+        total: usize,
+        expected: usize,
+    },
+    #[error("building at node {0} disagrees between the board and its owner")]
+    StructureMismatch(NodeId),
+    #[error("road on edge {0:?} disagrees between the board and its owner")]
+    RoadMismatch(EdgeId),
+    #[error("player {player} has more {piece} than the game provides")]
+    PieceLimitExceeded { player: usize, piece: &'static str },
+    #[error("more than one player holds {0}")]
+    DuplicateAward(&'static str),
+    #[error("player {player} counts {counted} victory point cards but holds {held}")]
+    VictoryPointCardMismatch {
+        player: usize,
+        counted: u8,
+        held: usize,
+    },
+    #[error("player {0} resigned but still holds cards or has the move")]
+    ResignedPlayerActive(usize),
+}
+
+impl GameState {
+    /// Checks that the state's redundant bookkeeping agrees with itself:
+    /// every resource and development card is somewhere, the board and the
+    /// players list the same buildings and roads, nobody has more pieces than
+    /// the box holds, each award has at most one holder and players who
+    /// resigned are out of play.
+    ///
+    /// `step` runs this after every action in debug builds, or always with
+    /// `GameConfig::check_invariants`.
+    pub fn validate_invariants(&self) -> Result<(), InvariantViolation> {
+        for resource in Resource::ALL {
+            let total = self.bank.available(resource) as u32
+                + self
+                    .players
+                    .iter()
+                    .map(|player| player.resources.get(resource) as u32)
+                    .sum::<u32>();
+            let expected = self.bank.resource_supply().get(resource) as u32;
+            if total != expected {
+                return Err(InvariantViolation::ResourceNotConserved {
+                    resource,
+                    total,
+                    expected,
+                });
+            }
+        }
+
+        for card in DevelopmentCard::ALL {
+            let expected = self.bank.development_supply(card);
+            let total = self.bank.development_card_count(card)
+                + self
+                    .players
+                    .iter()
+                    .map(|player| {
+                        player.matured_dev_card_count(card)
+                            + player.fresh_dev_card_count(card)
+                            + player.played_dev_cards.get(&card).copied().unwrap_or(0) as usize
+                    })
+                    .sum::<usize>();
+            if total != expected {
+                return Err(InvariantViolation::DevelopmentCardNotConserved {
+                    card,
+                    total,
+                    expected,
+                });
+            }
+        }
+
+        for (node, structure) in &self.node_occupancy {
+            let listed = match *structure {
+                Structure::Settlement { player } => self
+                    .players
+                    .get(player)
+                    .is_some_and(|owner| owner.settlements.contains(node)),
+                Structure::City { player } => self
+                    .players
+                    .get(player)
+                    .is_some_and(|owner| owner.cities.contains(node)),
+            };
+            if !listed {
+                return Err(InvariantViolation::StructureMismatch(*node));
+            }
+        }
+        for (edge, owner) in &self.road_occupancy {
+            if !self
+                .players
+                .get(*owner)
+                .is_some_and(|player| player.roads.contains(edge))
+            {
+                return Err(InvariantViolation::RoadMismatch(*edge));
+            }
+        }
+
+        for (idx, player) in self.players.iter().enumerate() {
+            for node in &player.settlements {
+                if self.node_occupancy.get(node) != Some(&Structure::Settlement { player: idx }) {
+                    return Err(InvariantViolation::StructureMismatch(*node));
+                }
+            }
+            for node in &player.cities {
+                if self.node_occupancy.get(node) != Some(&Structure::City { player: idx }) {
+                    return Err(InvariantViolation::StructureMismatch(*node));
+                }
+            }
+            for edge in &player.roads {
+                if self.road_occupancy.get(edge) != Some(&idx) {
+                    return Err(InvariantViolation::RoadMismatch(*edge));
+                }
+            }
+
+            let limits = [
+                ("settlements", player.settlements.len(), MAX_SETTLEMENTS),
+                ("cities", player.cities.len(), MAX_CITIES),
+                ("roads", player.roads.len(), MAX_ROADS),
+            ];
+            if let Some((piece, _, _)) = limits.iter().find(|(_, used, max)| used > max) {
+                return Err(InvariantViolation::PieceLimitExceeded { player: idx, piece });
+            }
+
+            let held = player.matured_dev_card_count(DevelopmentCard::VictoryPoint)
+                + player.fresh_dev_card_count(DevelopmentCard::VictoryPoint);
+            if held != player.victory_points as usize {
+                return Err(InvariantViolation::VictoryPointCardMismatch {
+                    player: idx,
+                    counted: player.victory_points,
+                    held,
+                });
+            }
+        }
+
+        if self.players.iter().filter(|p| p.has_longest_road).count() > 1 {
+            return Err(InvariantViolation::DuplicateAward("longest road"));
+        }
+        if self.players.iter().filter(|p| p.has_largest_army).count() > 1 {
+            return Err(InvariantViolation::DuplicateAward("largest army"));
+        }
+
+        let in_progress = !matches!(self.phase, GamePhase::Completed { .. });
+        if let Some(seat) = self.resigned().iter().copied().find(|seat| {
+            !self.players[*seat].resources.is_empty()
+                || (in_progress && self.players_to_act().contains(seat))
+        }) {
+            return Err(InvariantViolation::ResignedPlayerActive(seat));
+        }
+        Ok(())
+    }
+}
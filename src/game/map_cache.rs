@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rand::rngs::StdRng;
+
+use crate::board::{CatanMap, MapLayout, MapType};
+use crate::game::state::RngStream;
+
+type MapKey = (RngStream, MapType, Option<MapLayout>);
+
+/// Maps built so far, with the game generator as it was right after
+/// building each, keyed by what decides the map. Games created through
+/// `GameState::with_map_cache` with the same stream and map settings share
+/// one `CatanMap`. Holds at most `capacity` maps, dropping the least
+/// recently used one to make room.
+#[derive(Debug)]
+pub struct MapCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    maps: HashMap<MapKey, CachedMap>,
+    /// Counts lookups; each map remembers the last one that used it.
+    uses: u64,
+}
+
+#[derive(Debug)]
+struct CachedMap {
+    map: Arc<CatanMap>,
+    rng_after: StdRng,
+    last_used: u64,
+}
+
+impl MapCache {
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().maps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The map for `key`, built by `build` from `rng` if no game has asked
+    /// for it yet. Either way `rng` ends up where building the map would
+    /// have left it, so the rest of setup is unchanged. The lock isn't held
+    /// while building.
+    pub(crate) fn get_or_build<E>(
+        &self,
+        key: MapKey,
+        rng: &mut StdRng,
+        build: impl FnOnce(&mut StdRng) -> Result<CatanMap, E>,
+    ) -> Result<Arc<CatanMap>, E> {
+        {
+            let mut entries = self.lock();
+            entries.uses += 1;
+            let now = entries.uses;
+            if let Some(cached) = entries.maps.get_mut(&key) {
+                cached.last_used = now;
+                *rng = cached.rng_after.clone();
+                return Ok(Arc::clone(&cached.map));
+            }
+        }
+
+        let map = Arc::new(build(rng)?);
+        let mut entries = self.lock();
+        if entries.maps.len() >= self.capacity
+            && let Some(oldest) = entries
+                .maps
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone())
+        {
+            entries.maps.remove(&oldest);
+        }
+        entries.uses += 1;
+        let last_used = entries.uses;
+        entries.maps.insert(
+            key,
+            CachedMap {
+                map: Arc::clone(&map),
+                rng_after: rng.clone(),
+                last_used,
+            },
+        );
+        Ok(map)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Default for MapCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameConfig, GameState};
+
+    fn config(stream: u64) -> GameConfig {
+        GameConfig {
+            seed: 3,
+            stream,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn shares_maps_and_drops_the_least_recently_used() {
+        let cache = MapCache::new(2);
+        let first = GameState::with_map_cache(config(0), &cache).unwrap();
+        GameState::with_map_cache(config(1), &cache).unwrap();
+        let again = GameState::with_map_cache(config(0), &cache).unwrap();
+        assert!(Arc::ptr_eq(&first.map, &again.map));
+        // Same game as without the cache.
+        let uncached = GameState::try_new(config(0)).unwrap();
+        assert_eq!(again.zobrist_hash(), uncached.zobrist_hash());
+
+        // Stream 1 is the least recently used, so it makes room for 2.
+        GameState::with_map_cache(config(2), &cache).unwrap();
+        assert_eq!(cache.len(), 2);
+        let kept = GameState::with_map_cache(config(0), &cache).unwrap();
+        assert!(Arc::ptr_eq(&first.map, &kept.map));
+    }
+}
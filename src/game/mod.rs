@@ -1,15 +1,41 @@
 pub mod action;
 pub mod bank;
+pub mod clock;
+pub mod codec;
+pub mod dice;
+pub mod fuzz;
 pub mod game;
+pub mod house_rules;
+pub mod invariants;
+pub mod map_cache;
 pub mod players;
+pub mod position;
 pub mod resources;
+pub mod rewards;
+pub mod setup;
 pub mod state;
+pub mod victory;
 
 pub use action::{ActionPayload, GameAction};
 pub use bank::Bank;
-pub use game::Game;
-pub use players::PlayerState;
+pub use clock::{GameClock, TimeControl, TimeoutPolicy};
+pub use codec::CodecError;
+pub use dice::{
+    BalancedDice, DiceConfig, DiceModel, DiceRoll, FairDice, MAX_EXTRA_DICE, ScriptedDice,
+};
+pub use game::{Game, GameResult, GameSnapshot, LoggedEvent, PlayerResult, TURNS_LIMIT};
+pub use house_rules::{FRIENDLY_ROBBER_MAX_POINTS, HouseRules};
+pub use invariants::InvariantViolation;
+pub use map_cache::MapCache;
+pub use players::{PlayerState, VpBreakdown};
+pub use position::{PositionCounter, PositionKey, dedup_positions};
 pub use resources::{
     COST_CITY, COST_DEVELOPMENT, COST_ROAD, COST_SETTLEMENT, ResourceBundle, ResourceError,
 };
-pub use state::{GameConfig, GameError, GameEvent, GamePhase, GameState, StepOutcome, Structure};
+pub use rewards::RewardConfig;
+pub use setup::SetupMode;
+pub use state::{
+    GameConfig, GameError, GameEvent, GamePhase, GameState, MAX_TRADE_OFFERS_PER_TURN, RngStream,
+    StepOutcome, Structure, TradeState,
+};
+pub use victory::VictoryCondition;
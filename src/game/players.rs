@@ -4,12 +4,35 @@ use serde::{Deserialize, Serialize};
 
 use crate::board::{EdgeId, NodeId};
 use crate::game::resources::{ResourceBundle, ResourceError};
-use crate::types::{Color, DevelopmentCard};
+use crate::types::{Color, DevelopmentCard, Resource};
 
 pub const MAX_ROADS: usize = 15;
 pub const MAX_SETTLEMENTS: usize = 5;
 pub const MAX_CITIES: usize = 4;
 
+/// Where a player's victory points come from. Victory point cards stay in
+/// hand until the game ends, so they only count towards `total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VpBreakdown {
+    pub settlements: u8,
+    pub cities: u8,
+    pub victory_point_cards: u8,
+    pub longest_road: u8,
+    pub largest_army: u8,
+}
+
+impl VpBreakdown {
+    /// Points every player at the table can see.
+    pub fn public(&self) -> u8 {
+        self.settlements + self.cities + self.longest_road + self.largest_army
+    }
+
+    /// Points actually held, hidden victory point cards included.
+    pub fn total(&self) -> u8 {
+        self.public() + self.victory_point_cards
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerState {
     pub color: Color,
@@ -26,6 +49,7 @@ pub struct PlayerState {
     pub has_rolled: bool,
     pub has_played_dev_card_this_turn: bool,
     pub played_dev_cards: HashMap<DevelopmentCard, u32>,
+    pub dice_income: [u32; Resource::ALL.len()],
 }
 
 impl PlayerState {
@@ -45,6 +69,7 @@ impl PlayerState {
             has_rolled: false,
             has_played_dev_card_this_turn: false,
             played_dev_cards: HashMap::new(),
+            dice_income: [0; Resource::ALL.len()],
         }
     }
 
@@ -69,6 +94,12 @@ impl PlayerState {
         }
     }
 
+    pub fn record_dice_income(&mut self, bundle: &ResourceBundle) {
+        for (total, amount) in self.dice_income.iter_mut().zip(bundle.counts()) {
+            *total += amount as u32;
+        }
+    }
+
     pub fn record_dev_card_play(&mut self, card: DevelopmentCard) {
         *self.played_dev_cards.entry(card).or_insert(0) += 1;
         if matches!(card, DevelopmentCard::Knight) {
@@ -118,25 +149,34 @@ impl PlayerState {
     }
 
     pub fn total_points(&self) -> u8 {
-        let settlement_points = self.settlements.len() as u8;
-        let city_points = (self.cities.len() as u8) * 2;
-        settlement_points + city_points + self.victory_points + self.bonus_points()
+        self.vp_breakdown().total()
     }
 
     pub fn public_points(&self) -> u8 {
-        let settlement_points = self.settlements.len() as u8;
-        let city_points = (self.cities.len() as u8) * 2;
-        settlement_points + city_points + self.bonus_points()
+        self.vp_breakdown().public()
     }
 
     pub fn bonus_points(&self) -> u8 {
-        let mut bonus = 0;
-        if self.has_longest_road {
-            bonus += 2;
+        let breakdown = self.vp_breakdown();
+        breakdown.longest_road + breakdown.largest_army
+    }
+
+    /// The breakdown as other players see it: victory point cards stay
+    /// hidden until the game is over.
+    pub fn public_vp_breakdown(&self) -> VpBreakdown {
+        VpBreakdown {
+            victory_point_cards: 0,
+            ..self.vp_breakdown()
         }
-        if self.has_largest_army {
-            bonus += 2;
+    }
+
+    pub fn vp_breakdown(&self) -> VpBreakdown {
+        VpBreakdown {
+            settlements: self.settlements.len() as u8,
+            cities: (self.cities.len() as u8) * 2,
+            victory_point_cards: self.victory_points,
+            longest_road: if self.has_longest_road { 2 } else { 0 },
+            largest_army: if self.has_largest_army { 2 } else { 0 },
         }
-        bonus
     }
 }
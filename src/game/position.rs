@@ -0,0 +1,104 @@
+//! Position fingerprints for deduplicating and counting self-play positions,
+//! e.g. for replay buffers and opening books.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::ActionPrompt;
+
+/// Identifies a position: the state's Zobrist hash plus whose decision it is
+/// and when. Built by `GameState::position_key`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PositionKey {
+    pub hash: u64,
+    pub turn: u32,
+    pub current_player: usize,
+    pub prompt: ActionPrompt,
+}
+
+/// How often each position has been seen.
+#[derive(Debug, Clone, Default)]
+pub struct PositionCounter {
+    counts: HashMap<PositionKey, u32>,
+}
+
+impl PositionCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one more sighting of `key`. Returns true the first time.
+    pub fn record(&mut self, key: PositionKey) -> bool {
+        match self.counts.entry(key) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += 1;
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(1);
+                true
+            }
+        }
+    }
+
+    pub fn count(&self, key: &PositionKey) -> u32 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct positions seen.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The `n` most frequent positions, most frequent first.
+    pub fn most_common(&self, n: usize) -> Vec<(PositionKey, u32)> {
+        let mut entries: Vec<(PositionKey, u32)> = self
+            .counts
+            .iter()
+            .map(|(key, count)| (*key, *count))
+            .collect();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.hash.cmp(&b.0.hash)));
+        entries.truncate(n);
+        entries
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&PositionKey, &u32)> {
+        self.counts.iter()
+    }
+}
+
+/// Keeps the first item of every position, in order.
+pub fn dedup_positions<T>(
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> PositionKey,
+) -> Vec<T> {
+    let mut seen = PositionCounter::new();
+    items
+        .into_iter()
+        .filter(|item| seen.record(key(item)))
+        .collect()
+}
+
+/// Pseudo-random key for one (feature kind, packed value) pair; XORing the
+/// keys of a position's features gives its Zobrist hash. Keys are derived
+/// with splitmix64 rather than stored in tables, so they are identical in
+/// every process.
+pub(crate) fn zobrist_key(kind: u8, fields: &[u64]) -> u64 {
+    let packed = fields
+        .iter()
+        .fold(kind as u64, |acc, field| acc.rotate_left(13) ^ field);
+    splitmix64(packed ^ ((kind as u64) << 56))
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
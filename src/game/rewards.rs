@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use super::state::GameState;
+
+/// How rewards are handed out when a game ends, and to players who resign
+/// before that.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum RewardConfig {
+    /// 1 for every seat of the winning team and -1 for everyone else.
+    /// A draw pays nothing.
+    #[default]
+    WinnerTakesAll,
+    /// One reward per finishing position, first place first, with one entry
+    /// per seat. The winning team places first and everyone else follows by
+    /// points, pooled per team like `VictoryCondition` does; seats on equal
+    /// points share the mean of the places they span. Resigned players
+    /// place last, the earliest to resign lowest.
+    Ranked(Vec<f32>),
+}
+
+impl RewardConfig {
+    /// Ranked rewards spread evenly from 1 for first place to -1 for last,
+    /// so 1, 0.33, -0.33, -1 with four players.
+    pub fn ranked(num_players: usize) -> Self {
+        let last_place = num_players.saturating_sub(1).max(1) as f32;
+        Self::Ranked(
+            (0..num_players)
+                .map(|place| (last_place - 2.0 * place as f32) / last_place)
+                .collect(),
+        )
+    }
+
+    /// What a player resigning now gets, after `resigned_before` others.
+    pub(crate) fn resignation(&self, num_players: usize, resigned_before: usize) -> f32 {
+        match self {
+            RewardConfig::WinnerTakesAll => -1.0,
+            RewardConfig::Ranked(places) => places[num_players - 1 - resigned_before],
+        }
+    }
+
+    /// Writes the final rewards of the seats still in the game; resigned
+    /// seats were paid when they left.
+    pub(crate) fn settle(&self, state: &GameState, winner: Option<usize>, rewards: &mut [f32]) {
+        match self {
+            RewardConfig::WinnerTakesAll => {
+                let Some(winner) = winner else {
+                    return;
+                };
+                let winning_team = state.team_of(winner);
+                for (idx, reward) in rewards.iter_mut().enumerate() {
+                    if state.team_of(idx) == winning_team {
+                        *reward = 1.0;
+                    } else if state.is_active(idx) {
+                        *reward = -1.0;
+                    }
+                }
+            }
+            RewardConfig::Ranked(places) => {
+                let winning_team = winner.map(|winner| state.team_of(winner));
+                let standing = |idx: usize| {
+                    let team = state.team_of(idx);
+                    (Some(team) == winning_team, state.team_points(team))
+                };
+                let mut order: Vec<usize> = (0..rewards.len())
+                    .filter(|idx| state.is_active(*idx))
+                    .collect();
+                order.sort_by_key(|idx| std::cmp::Reverse(standing(*idx)));
+                let mut place = 0;
+                for tied in order.chunk_by(|a, b| standing(*a) == standing(*b)) {
+                    let spanned = &places[place..place + tied.len()];
+                    let shared = spanned.iter().sum::<f32>() / tied.len() as f32;
+                    for idx in tied {
+                        rewards[*idx] = shared;
+                    }
+                    place += tied.len();
+                }
+            }
+        }
+    }
+}
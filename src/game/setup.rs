@@ -0,0 +1,45 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// How the initial settlements and roads are placed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SetupMode {
+    /// The standard rules: seats place in order, then again in reverse, and
+    /// the second settlement collects its adjacent resources.
+    #[default]
+    Snake,
+    /// Seats place in order both rounds, so the first seat also places first
+    /// in the second round.
+    Fixed,
+    /// The engine places every initial settlement and road at random (in
+    /// snake order, from the game's own random stream) while creating the
+    /// state, so the game starts straight at the first roll. Handy for
+    /// training agents that shouldn't spend effort on the opening.
+    Random,
+}
+
+impl fmt::Display for SetupMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupMode::Snake => write!(f, "SNAKE"),
+            SetupMode::Fixed => write!(f, "FIXED"),
+            SetupMode::Random => write!(f, "RANDOM"),
+        }
+    }
+}
+
+impl FromStr for SetupMode {
+    type Err = String;
+
+    /// Accepts `snake`, `fixed` and `random`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "snake" => Ok(SetupMode::Snake),
+            "fixed" => Ok(SetupMode::Fixed),
+            "random" => Ok(SetupMode::Random),
+            _ => Err(format!("unknown setup mode: {s}")),
+        }
+    }
+}
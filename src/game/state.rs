@@ -1,18 +1,31 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
-use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
 use serde::{Deserialize, Serialize};
+use smallvec::{SmallVec, smallvec};
 
 use crate::{
-    board::{CatanMap, EdgeId, MapType, NodeId},
+    board::{
+        CatanMap, EdgeId, HarborLayout, MapLayout, MapType, NodeId, Production, normalize_edge,
+    },
+    coords::CubeCoord,
     types::{ActionPrompt, ActionType, Color, DevelopmentCard, Resource},
 };
 
 use super::{
-    action::{ActionPayload, GameAction},
+    action::{ActionError, ActionPayload, GameAction},
     bank::Bank,
+    clock::{GameClock, TimeControl},
+    dice::{DiceConfig, DiceModel, DiceRoll, EMPTY_SCRIPT},
+    house_rules::{FRIENDLY_ROBBER_MAX_POINTS, HouseRules},
+    map_cache::MapCache,
     players::PlayerState,
+    position::{PositionKey, zobrist_key},
     resources::{COST_CITY, COST_DEVELOPMENT, COST_ROAD, COST_SETTLEMENT, ResourceBundle},
+    rewards::RewardConfig,
+    setup::SetupMode,
+    victory::VictoryCondition,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +34,44 @@ pub struct GameConfig {
     pub map_type: MapType,
     pub vps_to_win: u8,
     pub seed: u64,
+    pub stream: u64,
+    pub map_layout: Option<MapLayout>,
+    /// Shuffled or official harbors, for ports `map_layout` doesn't pin.
+    /// The tournament map always uses the official ones.
+    pub harbor_layout: HarborLayout,
+    pub dice: DiceConfig,
+    /// Offer bounded 1-for-1 and 2-for-1 domestic trades as regular
+    /// `OfferTrade` actions. Off by default since it widens the action space.
+    pub domestic_trades: bool,
+    pub victory: VictoryCondition,
+    /// Team id of each seat. Teammates pool their VPs towards `vps_to_win`,
+    /// share the win and can't rob each other.
+    pub teams: Option<Vec<usize>>,
+    /// With teams on, let players hand single cards to a teammate on their turn.
+    pub team_gifts: bool,
+    /// Per-player time banks enforced by `Game::play`.
+    pub time_control: Option<TimeControl>,
+    /// After a 7, prompt every over-limit player at once and take their
+    /// discards in any order, instead of one player after another.
+    pub simultaneous_discard: bool,
+    /// Run `GameState::validate_invariants` after every step and panic on a
+    /// violation. Debug builds always do.
+    pub check_invariants: bool,
+    /// Order of initial placement, or whether the engine does it itself.
+    pub setup: SetupMode,
+    /// Whenever a single legal action is left (a lone roll, `EndTurn`, the
+    /// only resource left to discard), apply it inside `step` instead of
+    /// prompting for it. Shortens self-play episodes considerably.
+    pub auto_forced_actions: bool,
+    /// Take a resigning player's buildings and roads off the board instead
+    /// of leaving them in the way.
+    pub resign_removes_structures: bool,
+    /// Break every `ResourcesDistributed` event down by the tiles that paid
+    /// it. Off by default to keep event streams compact.
+    pub payout_detail: bool,
+    /// Winner-takes-all or placement-based rewards in `StepOutcome`.
+    pub rewards: RewardConfig,
+    pub house_rules: HouseRules,
 }
 
 impl Default for GameConfig {
@@ -30,10 +81,77 @@ impl Default for GameConfig {
             map_type: MapType::Base,
             vps_to_win: 10,
             seed: 42,
+            stream: 0,
+            map_layout: None,
+            harbor_layout: HarborLayout::Shuffled,
+            dice: DiceConfig::Fair,
+            domestic_trades: false,
+            victory: VictoryCondition::Points,
+            teams: None,
+            team_gifts: false,
+            time_control: None,
+            simultaneous_discard: false,
+            check_invariants: false,
+            setup: SetupMode::Snake,
+            auto_forced_actions: false,
+            resign_removes_structures: false,
+            payout_detail: false,
+            rewards: RewardConfig::WinnerTakesAll,
+            house_rules: HouseRules::default(),
         }
     }
 }
 
+impl GameConfig {
+    pub fn rng_stream(&self) -> RngStream {
+        RngStream::new(self.seed, self.stream)
+    }
+}
+
+/// Identifies the single random stream a game draws from. Games sharing a
+/// seed but using different stream ids get independent sequences, so a batch
+/// of self-play games can be spread over workers in any order and still be
+/// reproduced exactly from `(seed, stream)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RngStream {
+    pub seed: u64,
+    pub stream: u64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64, stream: u64) -> Self {
+        Self { seed, stream }
+    }
+
+    pub fn to_rng(self) -> StdRng {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&self.seed.to_le_bytes());
+        key[8..16].copy_from_slice(&self.stream.to_le_bytes());
+        StdRng::from_seed(key)
+    }
+
+    /// A generator for the bot in `seat`, independent of the game's own
+    /// stream and of every other seat's, so bot randomness is reproducible
+    /// from the game seed too.
+    pub fn player_rng(self, seat: usize) -> StdRng {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&self.seed.to_le_bytes());
+        key[8..16].copy_from_slice(&self.stream.to_le_bytes());
+        key[16..24].copy_from_slice(&(seat as u64 + 1).to_le_bytes());
+        StdRng::from_seed(key)
+    }
+
+    /// The generator `GameState::random_legal_action` draws from,
+    /// independent of the game's stream and of every seat's.
+    pub fn timeout_rng(self) -> StdRng {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&self.seed.to_le_bytes());
+        key[8..16].copy_from_slice(&self.stream.to_le_bytes());
+        key[24] = 1;
+        StdRng::from_seed(key)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum GamePhase {
     Setup(SetupState),
@@ -44,7 +162,9 @@ pub enum GamePhase {
 #[derive(Debug, Clone)]
 pub struct GameState {
     pub config: GameConfig,
-    pub map: CatanMap,
+    /// Never changes during a game; shared by clones and by every game
+    /// built from the same seed and map settings.
+    pub map: Arc<CatanMap>,
     pub players: Vec<PlayerState>,
     pub bank: Bank,
     pub phase: GamePhase,
@@ -52,11 +172,17 @@ pub struct GameState {
     pub current_player: usize,
     turn_owner: usize,
     pub turn: u32,
+    /// Actions executed so far. Unlike `turn` this moves on every step, so
+    /// it tells apart the several actions taken within one turn.
+    pub tick: u32,
+    /// `tick` when the current turn started.
+    turn_start_tick: u32,
     pub robber_tile: u16,
-    pub last_roll: Option<(u8, u8)>,
+    pub last_roll: Option<DiceRoll>,
     pub node_occupancy: HashMap<NodeId, Structure>,
     pub road_occupancy: HashMap<EdgeId, usize>,
     pub actions: Vec<GameAction>,
+    pub clock: Option<GameClock>,
     all_edges: Vec<EdgeId>,
     available_actions: Vec<GameAction>,
     awaiting_roll: bool,
@@ -66,11 +192,19 @@ pub struct GameState {
     road_building_free_roads: u8,
     trade_state: Option<TradeState>,
     trade_queue: VecDeque<usize>,
+    trade_offers_this_turn: u8,
+    /// Seats that resigned, in the order they did.
+    resigned: Vec<usize>,
     setup_pending_roads: HashMap<usize, NodeId>,
+    dice: Box<dyn DiceModel>,
     rng: StdRng,
+    rng_stream: RngStream,
+    /// Picks actions for players who ran out of time, apart from `rng` so
+    /// wall-clock timing can't change later dice.
+    timeout_rng: StdRng,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Structure {
     Settlement { player: usize },
     City { player: usize },
@@ -81,6 +215,10 @@ pub struct StepOutcome {
     pub events: Vec<GameEvent>,
     pub rewards: Vec<f32>,
     pub done: bool,
+    /// Actions applied automatically after this one under
+    /// `GameConfig::auto_forced_actions`, as logged, with their events.
+    /// `rewards` and `done` already account for them.
+    pub forced: Vec<(GameAction, Vec<GameEvent>)>,
 }
 
 impl StepOutcome {
@@ -89,28 +227,39 @@ impl StepOutcome {
             events: Vec::new(),
             rewards: vec![0.0; num_players],
             done: false,
+            forced: Vec::new(),
         }
     }
 }
 
+/// How many domestic trades a player may offer per turn when
+/// `GameConfig::domestic_trades` is on, so bots can't stall a turn forever.
+pub const MAX_TRADE_OFFERS_PER_TURN: u8 = 2;
+
+/// The domestic trade currently on the table.
 #[derive(Debug, Clone)]
-struct TradeState {
-    offerer: usize,
-    give: ResourceBundle,
-    receive: ResourceBundle,
-    acceptees: HashSet<usize>,
+pub struct TradeState {
+    pub offerer: usize,
+    pub give: ResourceBundle,
+    pub receive: ResourceBundle,
+    pub acceptees: HashSet<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameEvent {
     DiceRolled {
         player: usize,
-        dice: (u8, u8),
+        dice: DiceRoll,
         sum: u8,
     },
+    /// Everything `player` collected from one roll, or from their second
+    /// settlement. `tiles` splits `bundle` by tile id when
+    /// `GameConfig::payout_detail` is on and is empty otherwise.
     ResourcesDistributed {
         player: usize,
         bundle: ResourceBundle,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tiles: Vec<(u16, ResourceBundle)>,
     },
     BuiltRoad {
         player: usize,
@@ -127,9 +276,129 @@ pub enum GameEvent {
     TurnAdvanced {
         next_player: usize,
     },
+    /// `card` is only known to the buyer; `visible_to` blanks it for
+    /// everyone else.
+    DevelopmentCardBought {
+        player: usize,
+        card: Option<DevelopmentCard>,
+    },
+    /// The robber moved to `tile`, by a seven or a knight.
+    RobberMoved {
+        player: usize,
+        tile: u16,
+        victim: Option<usize>,
+    },
+    /// Everyone sees how many cards changed hands; `resource` is only known
+    /// to the thief and the victim.
+    ResourceStolen {
+        thief: usize,
+        victim: usize,
+        amount: u8,
+        resource: Option<Resource>,
+    },
+    /// `taken` lists every opponent who lost cards and how many.
+    MonopolyPlayed {
+        player: usize,
+        resource: Resource,
+        taken: Vec<(usize, u8)>,
+    },
+    YearOfPlentyPlayed {
+        player: usize,
+        resources: ResourceBundle,
+    },
+    RoadBuildingPlayed {
+        player: usize,
+    },
+    /// A domestic offer: `player` gives `give` in exchange for `receive`.
+    TradeOffered {
+        player: usize,
+        give: ResourceBundle,
+        receive: ResourceBundle,
+    },
+    TradeAccepted {
+        player: usize,
+        offerer: usize,
+    },
+    TradeRejected {
+        player: usize,
+        offerer: usize,
+    },
+    TradeCancelled {
+        player: usize,
+    },
+    /// Cards actually changed hands. `give` and `receive` are from
+    /// `player`'s side; `partner` is `None` for a maritime trade with the
+    /// bank.
+    TradeExecuted {
+        player: usize,
+        partner: Option<usize>,
+        give: ResourceBundle,
+        receive: ResourceBundle,
+    },
+    /// `player` handed one `resource` card to their teammate `partner`.
+    ResourceGifted {
+        player: usize,
+        partner: usize,
+        resource: Resource,
+    },
+    /// The Longest Road card changed hands. `holder` is `None` once nobody
+    /// qualifies alone any more; `length` is the holder's road, else 0.
+    LongestRoadChanged {
+        previous: Option<usize>,
+        holder: Option<usize>,
+        length: u8,
+    },
+    /// The Largest Army card changed hands; `knights` is what the holder
+    /// has played, else 0.
+    LargestArmyChanged {
+        previous: Option<usize>,
+        holder: Option<usize>,
+        knights: u8,
+    },
+    /// Hidden victory point cards turned face up when the game ends, one
+    /// event per player holding any.
+    VictoryPointsRevealed {
+        player: usize,
+        cards: u8,
+    },
     GameWon {
         winner: usize,
     },
+    /// `player` left the game; their cards went back to the bank.
+    PlayerResigned {
+        player: usize,
+    },
+    /// `player` conceded and the game ended.
+    PlayerForfeited {
+        player: usize,
+    },
+}
+
+impl GameEvent {
+    /// The event as `viewer` is allowed to see it, with other players'
+    /// hidden information removed.
+    pub fn visible_to(&self, viewer: usize) -> GameEvent {
+        match self {
+            GameEvent::DevelopmentCardBought { player, .. } if *player != viewer => {
+                GameEvent::DevelopmentCardBought {
+                    player: *player,
+                    card: None,
+                }
+            }
+            GameEvent::ResourceStolen {
+                thief,
+                victim,
+                amount,
+                ..
+            } if viewer != *thief && viewer != *victim => GameEvent::ResourceStolen {
+                thief: *thief,
+                victim: *victim,
+                amount: *amount,
+                resource: None,
+            },
+            event => event.clone(),
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -161,19 +430,165 @@ pub enum GameError {
     InsufficientResources,
     #[error("bank resources unavailable")]
     BankOutOfResources,
-    #[error("action not allowed at this stage")]
-    IllegalAction,
+    #[error("dice already rolled this turn")]
+    AlreadyRolled,
+    #[error("dice must be rolled first")]
+    MustRollFirst,
+    #[error("setup road must touch the settlement at node {0}")]
+    SetupRoadDisconnected(NodeId),
+    #[error("no settlements left to build")]
+    SettlementLimitReached,
+    #[error("no cities left to build")]
+    CityLimitReached,
+    #[error("no roads left to build")]
+    RoadLimitReached,
+    #[error("no settlement of this player at node {0} to upgrade")]
+    NoSettlementToUpgrade(NodeId),
+    #[error("player {0} has nothing to discard")]
+    NothingToDiscard(usize),
+    #[error("tile {0} not found on map")]
+    TileNotFound(u16),
+    #[error("robber is already on tile {0}")]
+    RobberNotMoved(u16),
+    #[error("cannot rob teammate {0}")]
+    CannotRobTeammate(usize),
+    #[error("development card deck is empty")]
+    DevelopmentDeckEmpty,
+    #[error("{0:?} cannot be played now")]
+    DevCardNotPlayable(DevelopmentCard),
+    #[error("cannot gift cards to player {0}")]
+    GiftNotAllowed(usize),
+    #[error("maritime trade must give a single resource other than the one received")]
+    InvalidMaritimeTrade,
+    #[error("maritime trade of {resource:?} is {rate}:1, not {offered}:1")]
+    WrongMaritimeRate {
+        resource: Resource,
+        rate: u8,
+        offered: u8,
+    },
+    #[error("trade must give and receive resources")]
+    EmptyTrade,
+    #[error("a trade is already in progress")]
+    TradeInProgress,
+    #[error("domestic trades are off in this game")]
+    DomesticTradesDisabled,
+    #[error("only {MAX_TRADE_OFFERS_PER_TURN} trade offers are allowed per turn")]
+    TooManyTradeOffers,
+    #[error("no trade in progress")]
+    NoActiveTrade,
+    #[error("player {0} cannot respond to this trade")]
+    NotTradeParticipant(usize),
+    #[error("player {0} has not accepted the trade")]
+    PartnerNotAccepted(usize),
+    #[error("no legal action has index {0}")]
+    IllegalActionIndex(usize),
+    #[error("house rules keep the robber off the desert (tile {0})")]
+    RobberOnDesert(u16),
+    #[error("player {0} has too few points to be robbed")]
+    VictimProtected(usize),
+    #[error("road cannot continue through another player's building at node {0}")]
+    RoadBlocked(NodeId),
+    #[error("cannot resign during initial placement")]
+    ResignDuringSetup,
+    #[error("player {0} has resigned")]
+    Resigned(usize),
+    #[error("the game draws the outcome of {0:?}; it can't be chosen")]
+    ChosenOutcome(ActionType),
+    #[error("{0:?} cannot be stepped; only the game clock applies it")]
+    InvalidAction(ActionType),
+    #[error("invalid game config: {0}")]
+    InvalidConfig(&'static str),
+    #[error("invalid map layout: {0}")]
+    InvalidMapLayout(String),
+    #[error("cannot take back past {0:?}; its outcome has been seen")]
+    TakebackPastChance(ActionType),
+    #[error(transparent)]
+    MalformedAction(#[from] ActionError),
+}
+
+/// The map for a new game, built from `rng` for `map_type`, or from
+/// `layout` when one is pinned.
+fn build_map(
+    map_type: &MapType,
+    layout: Option<&MapLayout>,
+    rng: &mut StdRng,
+) -> Result<CatanMap, GameError> {
+    match layout {
+        Some(layout) => {
+            layout
+                .validate(map_type.template())
+                .map_err(GameError::InvalidMapLayout)?;
+            Ok(CatanMap::build_with_layout(map_type.clone(), layout, rng))
+        }
+        None => Ok(CatanMap::build_with_rng(map_type.clone(), rng)),
+    }
 }
 
 impl GameState {
+    /// Panics on an invalid config; see `try_new`.
     pub fn new(config: GameConfig) -> Self {
-        assert!(
-            (2..=4).contains(&config.num_players),
-            "Catan supports between 2 and 4 players"
-        );
+        Self::try_new(config).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        let mut rng = StdRng::seed_from_u64(config.seed);
-        let map = CatanMap::build_with_rng(config.map_type, &mut rng);
+    /// A new game, or why `config` can't make one.
+    pub fn try_new(config: GameConfig) -> Result<Self, GameError> {
+        Self::build(config, None)
+    }
+
+    /// Like `try_new`, but takes the map from `cache` when an earlier game
+    /// with the same stream and map settings already built it.
+    pub fn with_map_cache(config: GameConfig, cache: &MapCache) -> Result<Self, GameError> {
+        Self::build(config, Some(cache))
+    }
+
+    fn build(config: GameConfig, cache: Option<&MapCache>) -> Result<Self, GameError> {
+        if !(2..=4).contains(&config.num_players) {
+            return Err(GameError::InvalidConfig(
+                "Catan supports between 2 and 4 players",
+            ));
+        }
+        if let Some(teams) = &config.teams
+            && (teams.len() != config.num_players
+                || teams.iter().any(|team| *team >= config.num_players))
+        {
+            return Err(GameError::InvalidConfig(
+                "team assignment must give every seat a team id below num_players",
+            ));
+        }
+        if let DiceConfig::Scripted(rolls) = &config.dice
+            && rolls.is_empty()
+        {
+            return Err(GameError::InvalidConfig(EMPTY_SCRIPT));
+        }
+        if let RewardConfig::Ranked(places) = &config.rewards
+            && places.len() != config.num_players
+        {
+            return Err(GameError::InvalidConfig(
+                "ranked rewards need one reward per seat",
+            ));
+        }
+
+        let rng_stream = config.rng_stream();
+        let mut rng = rng_stream.to_rng();
+        let map_layout = match config.harbor_layout {
+            HarborLayout::Official if config.map_type != MapType::Tournament => Some(
+                config
+                    .map_layout
+                    .clone()
+                    .unwrap_or_default()
+                    .with_official_harbors(config.map_type.template()),
+            ),
+            _ => config.map_layout.clone(),
+        };
+        let map = match cache {
+            Some(cache) => {
+                let key = (rng_stream, config.map_type.clone(), map_layout.clone());
+                cache.get_or_build(key, &mut rng, |rng| {
+                    build_map(&config.map_type, map_layout.as_ref(), rng)
+                })?
+            }
+            None => Arc::new(build_map(&config.map_type, map_layout.as_ref(), &mut rng)?),
+        };
         let all_edges = collect_all_edges(&map);
         let robber_tile = map
             .tiles_by_id
@@ -188,11 +603,16 @@ impl GameState {
             .collect::<Vec<_>>();
 
         let bank = Bank::standard(&mut rng);
-        let setup_state = SetupState::new(config.num_players);
+        let setup_state = SetupState::new(config.num_players, config.setup);
         let pending_prompt = setup_state
             .current_prompt()
             .unwrap_or(ActionPrompt::PlayTurn);
         let current_player = setup_state.current_player().unwrap_or(0);
+        let dice = config.dice.build();
+        let clock = config
+            .time_control
+            .clone()
+            .map(|control| GameClock::new(control, config.num_players));
 
         let mut state = Self {
             config,
@@ -204,11 +624,14 @@ impl GameState {
             current_player,
             turn_owner: current_player,
             turn: 0,
+            tick: 0,
+            turn_start_tick: 0,
             robber_tile,
             last_roll: None,
             node_occupancy: HashMap::new(),
             road_occupancy: HashMap::new(),
             actions: Vec::new(),
+            clock,
             all_edges,
             available_actions: Vec::new(),
             awaiting_roll: false,
@@ -218,48 +641,439 @@ impl GameState {
             road_building_free_roads: 0,
             trade_state: None,
             trade_queue: VecDeque::new(),
+            trade_offers_this_turn: 0,
+            resigned: Vec::new(),
             setup_pending_roads: HashMap::new(),
+            dice,
             rng,
+            rng_stream,
+            timeout_rng: rng_stream.timeout_rng(),
         };
         state.refresh_available_actions();
-        state
+        if state.config.setup == SetupMode::Random {
+            state.place_setup_randomly();
+        }
+        if state.config.auto_forced_actions {
+            state.apply_forced_actions();
+        }
+        Ok(state)
+    }
+
+    /// Plays every setup step with a uniformly random legal placement. On a
+    /// board too crowded to finish setup it stops where a player would be
+    /// stuck too.
+    fn place_setup_randomly(&mut self) {
+        while matches!(self.phase, GamePhase::Setup(_)) {
+            let Some(action) = self.available_actions.choose(&mut self.rng).cloned() else {
+                break;
+            };
+            self.step(action)
+                .expect("legal setup placements always apply");
+        }
     }
 
     pub fn reset(&mut self) {
         *self = GameState::new(self.config.clone());
     }
 
-    pub fn step(&mut self, mut action: GameAction) -> Result<StepOutcome, GameError> {
+    pub fn step(&mut self, action: GameAction) -> Result<StepOutcome, GameError> {
+        if is_chance_outcome(&action) {
+            return Err(GameError::ChosenOutcome(action.action_type));
+        }
+        if action.action_type == ActionType::Forfeit {
+            return Err(GameError::InvalidAction(ActionType::Forfeit));
+        }
+        self.step_resolved(action)
+    }
+
+    /// Plays one of `chance_outcomes` exactly as given: the rolled dice,
+    /// drawn development card or stolen resource is the one named in the
+    /// payload. For search and replays; `step` draws these itself. Anything
+    /// else goes through `step` as usual.
+    pub fn apply_chance_outcome(&mut self, outcome: GameAction) -> Result<StepOutcome, GameError> {
+        if is_chance_outcome(&outcome) {
+            self.step_resolved(outcome)
+        } else {
+            self.step(outcome)
+        }
+    }
+
+    /// Ends the game with `player_idx` out of time under
+    /// `TimeoutPolicy::Forfeit`. Only the game clock forfeits a seat, so
+    /// `step` refuses the action.
+    pub(crate) fn apply_timeout_forfeit(
+        &mut self,
+        player_idx: usize,
+    ) -> Result<StepOutcome, GameError> {
+        self.apply_action(GameAction::forfeit(player_idx))
+    }
+
+    fn step_resolved(&mut self, action: GameAction) -> Result<StepOutcome, GameError> {
+        let mut outcome = self.apply_action(action)?;
+        if self.config.auto_forced_actions {
+            for (action, forced) in self.apply_forced_actions() {
+                for (total, reward) in outcome.rewards.iter_mut().zip(forced.rewards) {
+                    *total += reward;
+                }
+                outcome.done |= forced.done;
+                outcome.forced.push((action, forced.events));
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Applies the only legal action for as long as there is exactly one.
+    fn apply_forced_actions(&mut self) -> Vec<(GameAction, StepOutcome)> {
+        let mut applied = Vec::new();
+        while !matches!(self.phase, GamePhase::Completed { .. })
+            && let [action] = self.available_actions.as_slice()
+        {
+            let outcome = self
+                .apply_action(action.clone())
+                .expect("the only legal action always applies");
+            let logged = self.actions.last().cloned().expect("just logged");
+            applied.push((logged, outcome));
+        }
+        applied
+    }
+
+    fn apply_action(&mut self, mut action: GameAction) -> Result<StepOutcome, GameError> {
         if matches!(self.phase, GamePhase::Completed { .. }) {
             return Err(GameError::GameFinished);
         }
+        action.check_payload()?;
+        action.canonicalize();
         if action.player_index >= self.players.len() {
             return Err(GameError::InvalidPlayer(action.player_index));
         }
         let mut outcome = StepOutcome::empty(self.players.len());
-        if matches!(&self.phase, GamePhase::Setup(_)) {
+        let turn = self.turn;
+        let in_setup = matches!(&self.phase, GamePhase::Setup(_));
+        if in_setup && action.action_type == ActionType::Resign {
+            return Err(GameError::ResignDuringSetup);
+        }
+        let awards = (self.longest_road_holder(), self.largest_army_holder());
+        if action.action_type == ActionType::Forfeit {
+            self.forfeit(action.player_index, &mut outcome)?
+        } else if in_setup {
             self.handle_setup_action(&mut action, &mut outcome)?
         } else {
             self.handle_play_action(&mut action, &mut outcome)?
         }
+        self.push_award_changes(awards, &mut outcome);
         self.actions.push(action);
+        self.tick += 1;
+        if self.turn != turn || in_setup != matches!(&self.phase, GamePhase::Setup(_)) {
+            self.turn_start_tick = self.tick;
+        }
         self.refresh_available_actions();
+        if (cfg!(debug_assertions) || self.config.check_invariants)
+            && let Err(violation) = self.validate_invariants()
+        {
+            panic!(
+                "invariant violated after {:?}: {violation}",
+                self.actions.last()
+            );
+        }
         if let GamePhase::Completed { winner } = self.phase {
             outcome.done = true;
+            for (player, state) in self.players.iter().enumerate() {
+                if state.victory_points > 0 {
+                    outcome.events.push(GameEvent::VictoryPointsRevealed {
+                        player,
+                        cards: state.victory_points,
+                    });
+                }
+            }
             if let Some(winner_idx) = winner {
                 outcome
                     .events
                     .push(GameEvent::GameWon { winner: winner_idx });
-                for (idx, reward) in outcome.rewards.iter_mut().enumerate() {
-                    if idx == winner_idx {
-                        *reward = 1.0;
-                    } else {
-                        *reward = -1.0;
+            }
+            self.config
+                .rewards
+                .settle(self, winner, &mut outcome.rewards);
+        }
+        Ok(outcome)
+    }
+
+    /// Like `step`, but dice, steals and development card draws come from
+    /// `rng` instead of the game's own generator, which is left exactly where
+    /// it was. Search uses this to sample chance outcomes on copies without
+    /// every copy sharing the real game's future.
+    pub fn step_with_rng(
+        &mut self,
+        action: GameAction,
+        rng: &mut impl Rng,
+    ) -> Result<StepOutcome, GameError> {
+        let borrowed = StdRng::from_rng(rng).expect("seeding from an rng does not fail");
+        let own = std::mem::replace(&mut self.rng, borrowed);
+        let result = self.step(action);
+        self.rng = own;
+        result
+    }
+
+    /// The ways `action` can resolve, each as a fully specified action with
+    /// its probability. Rolls expand into one outcome per dice sum under the
+    /// game's dice model, development card purchases into the cards left in
+    /// the deck, and robber steals into the victim's cards. Every other
+    /// action has the single outcome `(action, 1.0)`. Feeding an outcome to
+    /// `apply_chance_outcome` plays exactly that resolution. The engine sees every hand and
+    /// the deck, so these are the true odds rather than a player's estimate.
+    pub fn chance_outcomes(&self, action: &GameAction) -> Vec<(GameAction, f64)> {
+        let with_payload = |payload: ActionPayload| GameAction {
+            payload,
+            ..action.clone()
+        };
+        match (action.action_type, &action.payload) {
+            (ActionType::Roll, ActionPayload::None) => {
+                // Outcomes with the same sum (and extra dice) lead to the
+                // same position.
+                let mut by_sum: Vec<(DiceRoll, f64)> = Vec::new();
+                for (roll, p) in self.dice.outcomes() {
+                    match by_sum.iter_mut().find(|(seen, _)| {
+                        seen.sum() == roll.sum() && seen.extra_dice() == roll.extra_dice()
+                    }) {
+                        Some((_, total)) => *total += p,
+                        None => by_sum.push((roll, p)),
                     }
                 }
+                by_sum
+                    .into_iter()
+                    .map(|(roll, p)| (with_payload(ActionPayload::Dice(roll)), p))
+                    .collect()
             }
+            (ActionType::BuyDevelopmentCard, ActionPayload::None)
+                if self.bank.development_deck_len() > 0 =>
+            {
+                let deck = self.bank.development_deck_len() as f64;
+                DevelopmentCard::ALL
+                    .into_iter()
+                    .map(|card| (card, self.bank.development_card_count(card)))
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(card, count)| {
+                        (
+                            with_payload(ActionPayload::DevelopmentCard(card)),
+                            count as f64 / deck,
+                        )
+                    })
+                    .collect()
+            }
+            (
+                ActionType::MoveRobber,
+                ActionPayload::Robber {
+                    tile_id,
+                    victim: Some(victim),
+                    resource: None,
+                },
+            ) if self
+                .players
+                .get(*victim)
+                .is_some_and(|player| player.resources.total() > 0) =>
+            {
+                let hand = &self.players[*victim].resources;
+                let total = hand.total() as f64;
+                hand.iter()
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(resource, count)| {
+                        let payload = ActionPayload::Robber {
+                            tile_id: *tile_id,
+                            victim: Some(*victim),
+                            resource: Some(resource),
+                        };
+                        (with_payload(payload), count as f64 / total)
+                    })
+                    .collect()
+            }
+            _ => vec![(action.clone(), 1.0)],
+        }
+    }
+
+    /// Runs the same validation as `step` without changing `self`: `Ok(())`
+    /// when `action` would be accepted right now. Actions among
+    /// `legal_actions_for` pass straight away. Anything else, such as a
+    /// domestic offer the generator doesn't list or an action `step`
+    /// refuses, is tried on a scratch copy so the error is the one `step`
+    /// would give.
+    pub fn check_action(&self, action: &GameAction) -> Result<(), GameError> {
+        action.check_payload()?;
+        let mut canonical = action.clone();
+        canonical.canonicalize();
+        let listed = if canonical.player_index == self.current_player {
+            self.available_actions.contains(&canonical)
+        } else {
+            self.legal_actions_for(canonical.player_index)
+                .contains(&canonical)
+        };
+        if listed {
+            return Ok(());
         }
-        Ok(outcome)
+        self.clone().step(canonical).map(|_| ())
+    }
+
+    /// Replaces the dice model configured through `GameConfig::dice`, e.g. to
+    /// plug in a custom distribution for experiments.
+    pub fn set_dice_model(&mut self, dice: Box<dyn DiceModel>) {
+        self.dice = dice;
+    }
+
+    /// Expected cards per roll from the player's settlements and cities,
+    /// leaving out whatever tile is currently blocked by the robber.
+    pub fn expected_income(&self, player_idx: usize) -> Production {
+        let mut income = Production::default();
+        let Some(player) = self.players.get(player_idx) else {
+            return income;
+        };
+        let robber_tile = Some(self.robber_tile);
+        for node in &player.settlements {
+            income.add(&self.map.expected_production_with_robber(*node, robber_tile));
+        }
+        for node in &player.cities {
+            income.add(
+                &self
+                    .map
+                    .expected_production_with_robber(*node, robber_tile)
+                    .scaled(2),
+            );
+        }
+        income
+    }
+
+    /// Team id of a seat; without teams every seat is its own team.
+    pub fn team_of(&self, player_idx: usize) -> usize {
+        match &self.config.teams {
+            Some(teams) => teams[player_idx],
+            None => player_idx,
+        }
+    }
+
+    /// False for desert tiles under `HouseRules::robber_avoids_desert`.
+    pub fn robber_may_enter(&self, tile_id: u16) -> bool {
+        !self.config.house_rules.robber_avoids_desert
+            || self
+                .map
+                .tiles_by_id
+                .get(&tile_id)
+                .is_none_or(|tile| tile.resource.is_some())
+    }
+
+    /// Where the robber stands.
+    pub fn robber_coord(&self) -> CubeCoord {
+        self.map
+            .tile_coords
+            .get(&self.robber_tile)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Who the current player could steal from after moving the robber to
+    /// `tile_id`: players with a building on the tile and cards in hand,
+    /// other than the current player, their teammates and anyone the
+    /// friendly robber protects. In seat order.
+    pub fn robber_victims(&self, tile_id: u16) -> SmallVec<[usize; 4]> {
+        let Some(tile) = self.map.tiles_by_id.get(&tile_id) else {
+            return SmallVec::new();
+        };
+        let mut victims: SmallVec<[usize; 4]> = tile
+            .nodes
+            .values()
+            .filter_map(|node| match self.node_occupancy.get(node)? {
+                Structure::Settlement { player } | Structure::City { player } => Some(*player),
+            })
+            .filter(|owner| {
+                *owner != self.current_player
+                    && !self.are_teammates(*owner, self.current_player)
+                    && !self.players[*owner].resources.is_empty()
+                    && !self.is_protected_from_robber(*owner)
+            })
+            .collect();
+        victims.sort_unstable();
+        victims.dedup();
+        victims
+    }
+
+    /// True when `HouseRules::friendly_robber` shields `player` from steals.
+    pub fn is_protected_from_robber(&self, player: usize) -> bool {
+        self.config.house_rules.friendly_robber
+            && self.players[player].public_points() <= FRIENDLY_ROBBER_MAX_POINTS
+    }
+
+    pub fn are_teammates(&self, a: usize, b: usize) -> bool {
+        a != b && self.config.teams.is_some() && self.team_of(a) == self.team_of(b)
+    }
+
+    /// Combined VPs of everyone on `team`.
+    pub fn team_points(&self, team: usize) -> u32 {
+        (0..self.players.len())
+            .filter(|idx| self.team_of(*idx) == team)
+            .map(|idx| self.players[idx].total_points() as u32)
+            .sum()
+    }
+
+    /// Ends the game with `player_idx` conceding; the seat with the most
+    /// team points among the other teams wins. The player is paid and
+    /// ranked like one resigning, and their hand goes back to the bank.
+    fn forfeit(&mut self, player_idx: usize, outcome: &mut StepOutcome) -> Result<(), GameError> {
+        if !self.is_active(player_idx) {
+            return Err(GameError::Resigned(player_idx));
+        }
+        outcome.rewards[player_idx] = self
+            .config
+            .rewards
+            .resignation(self.players.len(), self.resigned.len());
+        self.resigned.push(player_idx);
+        outcome
+            .events
+            .push(GameEvent::PlayerForfeited { player: player_idx });
+        let hand = std::mem::take(&mut self.players[player_idx].resources);
+        self.bank.receive(&hand);
+        let loser_team = self.team_of(player_idx);
+        let winner = (0..self.players.len())
+            .filter(|idx| self.team_of(*idx) != loser_team && self.is_active(*idx))
+            .max_by_key(|idx| {
+                (
+                    self.team_points(self.team_of(*idx)),
+                    std::cmp::Reverse(*idx),
+                )
+            });
+        self.phase = GamePhase::Completed { winner };
+        Ok(())
+    }
+
+    /// Picks one of the current legal actions for a player out of time,
+    /// from a stream of its own so the game's dice and draws stay as the
+    /// seed has them.
+    pub fn random_legal_action(&mut self) -> Option<GameAction> {
+        if self.available_actions.is_empty() {
+            return None;
+        }
+        let idx = self.timeout_rng.gen_range(0..self.available_actions.len());
+        Some(self.available_actions[idx].clone())
+    }
+
+    pub fn trade_state(&self) -> Option<&TradeState> {
+        self.trade_state.as_ref()
+    }
+
+    /// Switches the game onto another random stream from here on. Rollouts
+    /// branched off one position use this so they don't all share the same
+    /// future dice.
+    pub fn reseed(&mut self, stream: RngStream) {
+        self.rng = stream.to_rng();
+        self.timeout_rng = stream.timeout_rng();
+        self.rng_stream = stream;
+    }
+
+    /// Actions taken so far in the current turn, including rolls, discards
+    /// and trade responses by other players. Initial placement counts as a
+    /// turn of its own, separate from the first player's first turn.
+    pub fn ticks_this_turn(&self) -> u32 {
+        self.tick - self.turn_start_tick
+    }
+
+    /// The stream the game was created with or last reseeded to.
+    pub fn rng_stream(&self) -> RngStream {
+        self.rng_stream
     }
 
     pub fn legal_action_prompt(&self) -> ActionPrompt {
@@ -273,11 +1087,16 @@ impl GameState {
     ) -> Result<(), GameError> {
         let (current_player, prompt, is_second_settlement) = match &self.phase {
             GamePhase::Setup(state) => (
-                state.current_player().ok_or(GameError::IllegalAction)?,
+                state.current_player().ok_or(GameError::GameFinished)?,
                 state.current_prompt().unwrap_or(ActionPrompt::PlayTurn),
                 state.is_second_settlement(),
             ),
-            _ => return Err(GameError::IllegalAction),
+            _ => {
+                return Err(GameError::InvalidPrompt {
+                    prompt: self.pending_prompt,
+                    action: action.action_type,
+                });
+            }
         };
 
         if current_player != action.player_index {
@@ -312,7 +1131,7 @@ impl GameState {
                 };
                 if let Some(anchor) = self.setup_pending_roads.get(&action.player_index) {
                     if !edge_contains_node(edge, *anchor) {
-                        return Err(GameError::IllegalAction);
+                        return Err(GameError::SetupRoadDisconnected(*anchor));
                     }
                 }
                 self.validate_road_location(action.player_index, edge, false)?;
@@ -366,7 +1185,12 @@ impl GameState {
         action: &mut GameAction,
         outcome: &mut StepOutcome,
     ) -> Result<(), GameError> {
-        if action.player_index != self.current_player {
+        if action.action_type == ActionType::Resign {
+            return self.resign(action.player_index, outcome);
+        }
+        if action.player_index != self.current_player
+            && !self.players_to_act().contains(&action.player_index)
+        {
             return Err(GameError::ActionOutOfTurn {
                 expected: self.current_player,
                 actual: action.player_index,
@@ -376,9 +1200,11 @@ impl GameState {
         match self.pending_prompt {
             ActionPrompt::PlayTurn => self.handle_turn_action(action, outcome)?,
             ActionPrompt::Discard => self.handle_discard_action(action)?,
-            ActionPrompt::MoveRobber => self.handle_move_robber_action(action)?,
-            ActionPrompt::DecideTrade => self.handle_trade_response_action(action)?,
-            ActionPrompt::DecideAcceptees => self.handle_trade_confirmation_action(action)?,
+            ActionPrompt::MoveRobber => self.handle_move_robber_action(action, outcome)?,
+            ActionPrompt::DecideTrade => self.handle_trade_response_action(action, outcome)?,
+            ActionPrompt::DecideAcceptees => {
+                self.handle_trade_confirmation_action(action, outcome)?
+            }
             _ => {
                 return Err(GameError::InvalidPrompt {
                     prompt: self.pending_prompt,
@@ -399,26 +1225,30 @@ impl GameState {
         match action.action_type {
             ActionType::Roll => {
                 if !self.awaiting_roll {
-                    return Err(GameError::IllegalAction);
+                    return Err(GameError::AlreadyRolled);
                 }
-                let (d1, d2) = match action.payload {
-                    ActionPayload::Dice(a, b) => (a.max(1).min(6), b.max(1).min(6)),
-                    _ => (self.roll_die(), self.roll_die()),
+                let roll = match action.payload {
+                    ActionPayload::Dice(mut roll) => {
+                        roll.d1 = roll.d1.clamp(1, 6);
+                        roll.d2 = roll.d2.clamp(1, 6);
+                        roll
+                    }
+                    _ => self.dice.roll(&mut self.rng),
                 };
-                let sum = d1 + d2;
-                self.last_roll = Some((d1, d2));
+                let sum = roll.sum();
+                self.last_roll = Some(roll);
                 self.awaiting_roll = false;
                 if let Some(player) = self.players.get_mut(action.player_index) {
                     player.has_rolled = true;
                 }
-                action.payload = ActionPayload::Dice(d1, d2);
+                action.payload = ActionPayload::Dice(roll);
                 outcome.events.push(GameEvent::DiceRolled {
                     player: action.player_index,
-                    dice: (d1, d2),
+                    dice: roll,
                     sum,
                 });
                 if sum != 7 {
-                    self.distribute_resources(sum, outcome)?;
+                    self.distribute_resources(&roll, outcome)?;
                     self.pending_prompt = ActionPrompt::PlayTurn;
                 } else {
                     self.begin_discard_phase();
@@ -483,7 +1313,17 @@ impl GameState {
             }
             ActionType::BuyDevelopmentCard => {
                 self.ensure_can_act_after_roll()?;
-                self.buy_development_card(action.player_index)?;
+                let requested = match action.payload {
+                    ActionPayload::DevelopmentCard(card) => Some(card),
+                    _ => None,
+                };
+                if let Some(card) = self.buy_development_card(action.player_index, requested)? {
+                    action.payload = ActionPayload::DevelopmentCard(card);
+                    outcome.events.push(GameEvent::DevelopmentCardBought {
+                        player: action.player_index,
+                        card: Some(card),
+                    });
+                }
             }
             ActionType::MaritimeTrade => {
                 self.ensure_can_act_after_roll()?;
@@ -492,6 +1332,14 @@ impl GameState {
                     _ => return Err(GameError::InvalidPayload("expected maritime trade payload")),
                 };
                 self.maritime_trade(action.player_index, give, receive)?;
+                let mut received = ResourceBundle::zero();
+                received.add(receive, 1);
+                outcome.events.push(GameEvent::TradeExecuted {
+                    player: action.player_index,
+                    partner: None,
+                    give,
+                    receive: received,
+                });
             }
             ActionType::OfferTrade => {
                 self.ensure_can_act_after_roll()?;
@@ -500,6 +1348,24 @@ impl GameState {
                     _ => return Err(GameError::InvalidPayload("expected domestic trade payload")),
                 };
                 self.begin_trade(action.player_index, give, receive)?;
+                outcome.events.push(GameEvent::TradeOffered {
+                    player: action.player_index,
+                    give,
+                    receive,
+                });
+            }
+            ActionType::GiftResource => {
+                self.ensure_can_act_after_roll()?;
+                let (resource, partner) = match action.payload {
+                    ActionPayload::Gift { resource, partner } => (resource, partner),
+                    _ => return Err(GameError::InvalidPayload("expected gift payload")),
+                };
+                self.gift_resource(action.player_index, partner, resource)?;
+                outcome.events.push(GameEvent::ResourceGifted {
+                    player: action.player_index,
+                    partner,
+                    resource,
+                });
             }
             ActionType::PlayKnightCard => {
                 self.play_knight_card(action.player_index)?;
@@ -514,6 +1380,10 @@ impl GameState {
                     }
                 };
                 self.play_year_of_plenty(action.player_index, bundle)?;
+                outcome.events.push(GameEvent::YearOfPlentyPlayed {
+                    player: action.player_index,
+                    resources: bundle,
+                });
             }
             ActionType::PlayMonopoly => {
                 let resource = match action.payload {
@@ -524,12 +1394,25 @@ impl GameState {
                         ));
                     }
                 };
-                self.play_monopoly(action.player_index, resource)?;
+                let taken = self.play_monopoly(action.player_index, resource)?;
+                outcome.events.push(GameEvent::MonopolyPlayed {
+                    player: action.player_index,
+                    resource,
+                    taken,
+                });
             }
             ActionType::PlayRoadBuilding => {
                 self.play_road_building(action.player_index)?;
+                outcome.events.push(GameEvent::RoadBuildingPlayed {
+                    player: action.player_index,
+                });
+            }
+            _ => {
+                return Err(GameError::InvalidPrompt {
+                    prompt: self.pending_prompt,
+                    action: action.action_type,
+                });
             }
-            _ => return Err(GameError::IllegalAction),
         }
 
         Ok(())
@@ -543,7 +1426,7 @@ impl GameState {
             });
         }
         let Some(&required) = self.discard_targets.get(&action.player_index) else {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::NothingToDiscard(action.player_index));
         };
         let discarded_resource = if let ActionPayload::Resource(res) = action.payload {
             res
@@ -562,12 +1445,7 @@ impl GameState {
 
         if required == 1 {
             self.discard_targets.remove(&action.player_index);
-            if let Some(next) = self.discard_queue.pop_front() {
-                self.current_player = next;
-            } else {
-                self.pending_prompt = ActionPrompt::MoveRobber;
-                self.current_player = self.turn_owner;
-            }
+            self.continue_discards();
         } else {
             self.discard_targets
                 .insert(action.player_index, required - 1);
@@ -575,29 +1453,60 @@ impl GameState {
         Ok(())
     }
 
-    fn handle_move_robber_action(&mut self, action: &mut GameAction) -> Result<(), GameError> {
+    fn handle_move_robber_action(
+        &mut self,
+        action: &mut GameAction,
+        outcome: &mut StepOutcome,
+    ) -> Result<(), GameError> {
         if action.action_type != ActionType::MoveRobber {
             return Err(GameError::InvalidPrompt {
                 prompt: ActionPrompt::MoveRobber,
                 action: action.action_type,
             });
         }
-        let (tile_id, victim_idx) = match &action.payload {
+        let (tile_id, victim_idx, requested) = match &action.payload {
             ActionPayload::Robber {
-                tile_id, victim, ..
-            } => (*tile_id, *victim),
+                tile_id,
+                victim,
+                resource,
+            } => (*tile_id, *victim, *resource),
             _ => return Err(GameError::InvalidPayload("expected robber payload")),
         };
         if !self.map.tiles_by_id.contains_key(&tile_id) {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::TileNotFound(tile_id));
+        }
+        if tile_id == self.robber_tile {
+            return Err(GameError::RobberNotMoved(tile_id));
+        }
+        if !self.robber_may_enter(tile_id) {
+            return Err(GameError::RobberOnDesert(tile_id));
+        }
+        if let Some(victim) =
+            victim_idx.filter(|victim| self.are_teammates(self.current_player, *victim))
+        {
+            return Err(GameError::CannotRobTeammate(victim));
+        }
+        if let Some(victim) = victim_idx.filter(|victim| *victim >= self.players.len()) {
+            return Err(GameError::InvalidPlayer(victim));
+        }
+        if let Some(victim) = victim_idx.filter(|victim| self.is_protected_from_robber(*victim)) {
+            return Err(GameError::VictimProtected(victim));
         }
         self.robber_tile = tile_id;
+        outcome.events.push(GameEvent::RobberMoved {
+            player: self.current_player,
+            tile: tile_id,
+            victim: victim_idx,
+        });
         if let Some(victim) = victim_idx {
-            if victim >= self.players.len() {
-                return Err(GameError::InvalidPlayer(victim));
-            }
-            if let Some(resource) = self.steal_random_resource(victim) {
+            if let Some(resource) = self.steal_resource(victim, requested) {
                 self.players[self.current_player].resources.add(resource, 1);
+                outcome.events.push(GameEvent::ResourceStolen {
+                    thief: self.current_player,
+                    victim,
+                    amount: 1,
+                    resource: Some(resource),
+                });
                 action.payload = ActionPayload::Robber {
                     tile_id,
                     victim: Some(victim),
@@ -615,9 +1524,14 @@ impl GameState {
         Ok(())
     }
 
-    fn buy_development_card(&mut self, player_idx: usize) -> Result<(), GameError> {
+    /// Draws a random card, or `requested` when given and still in the deck.
+    fn buy_development_card(
+        &mut self,
+        player_idx: usize,
+        requested: Option<DevelopmentCard>,
+    ) -> Result<Option<DevelopmentCard>, GameError> {
         if self.bank.development_deck_len() == 0 {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::DevelopmentDeckEmpty);
         }
         if !self.players[player_idx]
             .resources
@@ -625,14 +1539,16 @@ impl GameState {
         {
             return Err(GameError::InsufficientResources);
         }
-        let card = self
-            .bank
-            .buy_development_card(&mut self.rng, &mut self.players[player_idx].resources)
-            .map_err(|_| GameError::InsufficientResources)?;
+        let resources = &mut self.players[player_idx].resources;
+        let card = match requested.filter(|card| self.bank.development_card_count(*card) > 0) {
+            Some(card) => self.bank.buy_chosen_development_card(card, resources),
+            None => self.bank.buy_development_card(&mut self.rng, resources),
+        }
+        .map_err(|_| GameError::InsufficientResources)?;
         if let Some(card) = card {
             self.players[player_idx].add_dev_card(card);
         }
-        Ok(())
+        Ok(card)
     }
 
     fn ensure_dev_card_available(
@@ -640,11 +1556,10 @@ impl GameState {
         player_idx: usize,
         card: DevelopmentCard,
     ) -> Result<(), GameError> {
-        if !self.players[player_idx].can_play_dev_card(card) {
-            return Err(GameError::IllegalAction);
-        }
-        if !self.players[player_idx].consume_dev_card(card) {
-            return Err(GameError::IllegalAction);
+        if !self.players[player_idx].can_play_dev_card(card)
+            || !self.players[player_idx].consume_dev_card(card)
+        {
+            return Err(GameError::DevCardNotPlayable(card));
         }
         self.players[player_idx].record_dev_card_play(card);
         Ok(())
@@ -677,9 +1592,15 @@ impl GameState {
         Ok(())
     }
 
-    fn play_monopoly(&mut self, player_idx: usize, resource: Resource) -> Result<(), GameError> {
+    /// Returns each opponent who lost cards and how many.
+    fn play_monopoly(
+        &mut self,
+        player_idx: usize,
+        resource: Resource,
+    ) -> Result<Vec<(usize, u8)>, GameError> {
         self.ensure_dev_card_available(player_idx, DevelopmentCard::Monopoly)?;
         let mut stolen = ResourceBundle::zero();
+        let mut taken = Vec::new();
         for (idx, player) in self.players.iter_mut().enumerate() {
             if idx == player_idx {
                 continue;
@@ -691,11 +1612,32 @@ impl GameState {
                     .subtract(resource, amount)
                     .map_err(|_| GameError::InsufficientResources)?;
                 stolen.add(resource, amount);
+                taken.push((idx, amount));
             }
         }
         if !stolen.is_empty() {
             self.players[player_idx].add_resources(&stolen);
         }
+        Ok(taken)
+    }
+
+    fn gift_resource(
+        &mut self,
+        player_idx: usize,
+        partner: usize,
+        resource: Resource,
+    ) -> Result<(), GameError> {
+        if !self.config.team_gifts
+            || !self.are_teammates(player_idx, partner)
+            || !self.is_active(partner)
+        {
+            return Err(GameError::GiftNotAllowed(partner));
+        }
+        self.players[player_idx]
+            .resources
+            .subtract(resource, 1)
+            .map_err(|_| GameError::InsufficientResources)?;
+        self.players[partner].resources.add(resource, 1);
         Ok(())
     }
 
@@ -719,13 +1661,17 @@ impl GameState {
     ) -> Result<(), GameError> {
         let (resource, amount) = self
             .single_resource_bundle(&give)
-            .ok_or(GameError::IllegalAction)?;
+            .ok_or(GameError::InvalidMaritimeTrade)?;
         if resource == receive {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::InvalidMaritimeTrade);
         }
         let rate = self.maritime_rate(player_idx, resource);
         if amount != rate {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::WrongMaritimeRate {
+                resource,
+                rate,
+                offered: amount,
+            });
         }
         if !self.players[player_idx].resources.can_afford(&give) {
             return Err(GameError::InsufficientResources);
@@ -761,14 +1707,27 @@ impl GameState {
         found
     }
 
-    fn maritime_rate(&self, player_idx: usize, resource: Resource) -> u8 {
-        if self.player_has_port(player_idx, Some(resource)) {
-            return 2;
-        }
-        if self.player_has_port(player_idx, None) {
-            return 3;
-        }
-        4
+    /// How many `resource` cards the player has to hand the bank for one
+    /// card of their choice: 2 with a matching port, 3 with a generic port,
+    /// otherwise 4.
+    pub fn maritime_rate(&self, player_idx: usize, resource: Resource) -> u8 {
+        self.trade_rates(player_idx)[resource_index(resource)]
+    }
+
+    /// `maritime_rate` for every resource, in `Resource::ALL` order.
+    pub fn trade_rates(&self, player_idx: usize) -> [u8; Resource::ALL.len()] {
+        let generic = if self.player_has_port(player_idx, None) {
+            3
+        } else {
+            4
+        };
+        Resource::ALL.map(|resource| {
+            if self.player_has_port(player_idx, Some(resource)) {
+                2
+            } else {
+                generic
+            }
+        })
     }
 
     fn player_has_port(&self, player_idx: usize, port: Option<Resource>) -> bool {
@@ -795,18 +1754,27 @@ impl GameState {
         give: ResourceBundle,
         receive: ResourceBundle,
     ) -> Result<(), GameError> {
+        if !self.config.domestic_trades {
+            return Err(GameError::DomesticTradesDisabled);
+        }
+        if self.trade_offers_this_turn >= MAX_TRADE_OFFERS_PER_TURN {
+            return Err(GameError::TooManyTradeOffers);
+        }
         if give.is_empty() || receive.is_empty() {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::EmptyTrade);
         }
         if self.trade_state.is_some() {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::TradeInProgress);
         }
         if !self.players[player_idx].resources.can_afford(&give) {
             return Err(GameError::InsufficientResources);
         }
         let mut queue = VecDeque::new();
         for offset in 1..self.players.len() {
-            queue.push_back((player_idx + offset) % self.players.len());
+            let seat = (player_idx + offset) % self.players.len();
+            if self.is_active(seat) {
+                queue.push_back(seat);
+            }
         }
         self.trade_state = Some(TradeState {
             offerer: player_idx,
@@ -815,6 +1783,7 @@ impl GameState {
             acceptees: HashSet::new(),
         });
         self.trade_queue = queue;
+        self.trade_offers_this_turn += 1;
         self.advance_trade_queue();
         Ok(())
     }
@@ -847,12 +1816,17 @@ impl GameState {
         self.trade_queue.clear();
     }
 
-    fn handle_trade_response_action(&mut self, action: &mut GameAction) -> Result<(), GameError> {
+    fn handle_trade_response_action(
+        &mut self,
+        action: &mut GameAction,
+        outcome: &mut StepOutcome,
+    ) -> Result<(), GameError> {
         let Some(state) = self.trade_state.as_mut() else {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::NoActiveTrade);
         };
-        if action.player_index == state.offerer {
-            return Err(GameError::IllegalAction);
+        let offerer = state.offerer;
+        if action.player_index == offerer {
+            return Err(GameError::NotTradeParticipant(action.player_index));
         }
         match action.action_type {
             ActionType::AcceptTrade => {
@@ -863,30 +1837,45 @@ impl GameState {
                     return Err(GameError::InsufficientResources);
                 }
                 state.acceptees.insert(action.player_index);
-                self.advance_trade_queue();
+                outcome.events.push(GameEvent::TradeAccepted {
+                    player: action.player_index,
+                    offerer,
+                });
+                self.advance_trade_queue();
                 Ok(())
             }
             ActionType::RejectTrade => {
+                outcome.events.push(GameEvent::TradeRejected {
+                    player: action.player_index,
+                    offerer,
+                });
                 self.advance_trade_queue();
                 Ok(())
             }
-            _ => Err(GameError::IllegalAction),
+            _ => Err(GameError::InvalidPrompt {
+                prompt: ActionPrompt::DecideTrade,
+                action: action.action_type,
+            }),
         }
     }
 
     fn handle_trade_confirmation_action(
         &mut self,
         action: &mut GameAction,
+        outcome: &mut StepOutcome,
     ) -> Result<(), GameError> {
         let Some(state) = self.trade_state.clone() else {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::NoActiveTrade);
         };
         if action.player_index != state.offerer {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::NotTradeParticipant(action.player_index));
         }
         match action.action_type {
             ActionType::CancelTrade => {
                 self.clear_trade_state();
+                outcome.events.push(GameEvent::TradeCancelled {
+                    player: state.offerer,
+                });
                 self.pending_prompt = ActionPrompt::PlayTurn;
                 self.current_player = state.offerer;
                 Ok(())
@@ -906,7 +1895,7 @@ impl GameState {
                     .as_ref()
                     .map_or(false, |ts| ts.acceptees.contains(&partner))
                 {
-                    return Err(GameError::IllegalAction);
+                    return Err(GameError::PartnerNotAccepted(partner));
                 }
                 if !self.players[state.offerer]
                     .resources
@@ -925,19 +1914,30 @@ impl GameState {
                     .map_err(|_| GameError::InsufficientResources)?;
                 self.players[state.offerer].add_resources(&state.receive);
                 self.players[partner].add_resources(&state.give);
+                outcome.events.push(GameEvent::TradeExecuted {
+                    player: state.offerer,
+                    partner: Some(partner),
+                    give: state.give,
+                    receive: state.receive,
+                });
                 self.clear_trade_state();
                 self.pending_prompt = ActionPrompt::PlayTurn;
                 self.current_player = state.offerer;
                 Ok(())
             }
-            _ => Err(GameError::IllegalAction),
+            _ => Err(GameError::InvalidPrompt {
+                prompt: ActionPrompt::DecideAcceptees,
+                action: action.action_type,
+            }),
         }
     }
 
+    /// Queues everyone over the hand limit, starting with the roller and
+    /// going clockwise as the rules have it.
     fn begin_discard_phase(&mut self) {
         self.discard_queue.clear();
         self.discard_targets.clear();
-        for idx in 0..self.players.len() {
+        for idx in self.seats_from_roller() {
             let total = self.players[idx].resources.total() as u8;
             if total > 7 {
                 let to_discard = total / 2;
@@ -945,6 +1945,15 @@ impl GameState {
                 self.discard_targets.insert(idx, to_discard);
             }
         }
+        if self.config.simultaneous_discard {
+            // Everyone owes at once; current_player just points at one of them.
+            self.discard_queue.clear();
+            if let Some(first) = self.next_discarder() {
+                self.pending_prompt = ActionPrompt::Discard;
+                self.current_player = first;
+                return;
+            }
+        }
         if let Some(next) = self.discard_queue.pop_front() {
             self.pending_prompt = ActionPrompt::Discard;
             self.current_player = next;
@@ -954,7 +1963,134 @@ impl GameState {
         }
     }
 
-    fn steal_random_resource(&mut self, player_idx: usize) -> Option<Resource> {
+    /// Hands the discard prompt to whoever owes cards next, or moves on to
+    /// the robber once nobody does.
+    fn continue_discards(&mut self) {
+        if self.config.simultaneous_discard
+            && let Some(next) = self.next_discarder()
+        {
+            self.current_player = next;
+        } else if let Some(next) = self.discard_queue.pop_front() {
+            self.current_player = next;
+        } else {
+            self.pending_prompt = ActionPrompt::MoveRobber;
+            self.current_player = self.turn_owner;
+        }
+    }
+
+    /// Takes `player_idx` out of the game: their hand goes back to the
+    /// bank, they leave every queue and their turn, if it is one, ends. The
+    /// game ends once a single team is left.
+    fn resign(&mut self, player_idx: usize, outcome: &mut StepOutcome) -> Result<(), GameError> {
+        if !self.is_active(player_idx) {
+            return Err(GameError::Resigned(player_idx));
+        }
+        outcome.rewards[player_idx] = self
+            .config
+            .rewards
+            .resignation(self.players.len(), self.resigned.len());
+        self.resigned.push(player_idx);
+        outcome
+            .events
+            .push(GameEvent::PlayerResigned { player: player_idx });
+
+        let hand = std::mem::take(&mut self.players[player_idx].resources);
+        self.bank.receive(&hand);
+        if self.config.resign_removes_structures {
+            self.remove_structures(player_idx);
+        }
+        self.discard_targets.remove(&player_idx);
+        self.discard_queue.retain(|seat| *seat != player_idx);
+        self.trade_queue.retain(|seat| *seat != player_idx);
+        if let Some(state) = self.trade_state.as_mut() {
+            state.acceptees.remove(&player_idx);
+        }
+
+        let mut teams_left: SmallVec<[usize; 4]> = (0..self.players.len())
+            .filter(|seat| self.is_active(*seat))
+            .map(|seat| self.team_of(seat))
+            .collect();
+        teams_left.sort_unstable();
+        teams_left.dedup();
+        if teams_left.len() <= 1 {
+            let winner = (0..self.players.len()).find(|seat| self.is_active(*seat));
+            self.phase = GamePhase::Completed { winner };
+            return Ok(());
+        }
+
+        if player_idx == self.turn_owner {
+            self.clear_trade_state();
+            self.discard_targets.clear();
+            self.discard_queue.clear();
+            self.current_player = self.turn_owner;
+            self.advance_turn(outcome);
+        } else if player_idx == self.current_player {
+            match self.pending_prompt {
+                ActionPrompt::Discard => self.continue_discards(),
+                ActionPrompt::DecideTrade => self.advance_trade_queue(),
+                _ => {}
+            }
+        }
+        self.check_victory();
+        Ok(())
+    }
+
+    fn remove_structures(&mut self, player_idx: usize) {
+        let player = &mut self.players[player_idx];
+        let nodes: Vec<NodeId> = player
+            .settlements
+            .drain()
+            .chain(player.cities.drain())
+            .collect();
+        let roads: Vec<EdgeId> = player.roads.drain().collect();
+        for node in nodes {
+            self.node_occupancy.remove(&node);
+        }
+        for edge in roads {
+            self.road_occupancy.remove(&edge);
+        }
+        self.update_longest_road();
+    }
+
+    /// Whether `seat` is still playing, i.e. hasn't resigned.
+    pub fn is_active(&self, seat: usize) -> bool {
+        !self.resigned.contains(&seat)
+    }
+
+    /// Seats that resigned, first to go first.
+    pub fn resigned(&self) -> &[usize] {
+        &self.resigned
+    }
+
+    /// Every seat, starting with the player whose turn it is and going
+    /// clockwise.
+    fn seats_from_roller(&self) -> impl Iterator<Item = usize> + use<> {
+        let num_players = self.players.len();
+        let roller = self.turn_owner;
+        (0..num_players).map(move |offset| (roller + offset) % num_players)
+    }
+
+    /// The first player from the roller on who still owes cards.
+    fn next_discarder(&self) -> Option<usize> {
+        self.seats_from_roller()
+            .find(|seat| self.discard_targets.contains_key(seat))
+    }
+
+    /// Takes `requested` from the player when they hold one, otherwise a
+    /// card picked uniformly at random from their hand.
+    fn steal_resource(
+        &mut self,
+        player_idx: usize,
+        requested: Option<Resource>,
+    ) -> Option<Resource> {
+        if let Some(resource) = requested
+            && self.players[player_idx]
+                .resources
+                .subtract(resource, 1)
+                .is_ok()
+        {
+            return Some(resource);
+        }
         let mut bag = Vec::new();
         for (resource, amount) in self.players[player_idx].resources.iter() {
             for _ in 0..amount {
@@ -974,7 +2110,7 @@ impl GameState {
 
     fn ensure_can_act_after_roll(&self) -> Result<(), GameError> {
         if self.awaiting_roll {
-            Err(GameError::IllegalAction)
+            Err(GameError::MustRollFirst)
         } else {
             Ok(())
         }
@@ -1011,10 +2147,10 @@ impl GameState {
         node_id: NodeId,
     ) -> Result<(), GameError> {
         if self.players[player_idx].city_limit_reached() {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::CityLimitReached);
         }
         if !self.players[player_idx].settlements.contains(&node_id) {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::NoSettlementToUpgrade(node_id));
         }
         self.pay_cost(player_idx, &COST_CITY)?;
         self.players[player_idx].settlements.remove(&node_id);
@@ -1038,11 +2174,17 @@ impl GameState {
         outcome: &mut StepOutcome,
     ) -> Result<(), GameError> {
         let mut bundle = ResourceBundle::zero();
+        let mut tiles = Vec::new();
         if let Some(tile_ids) = self.map.adjacent_tiles.get(&node_id) {
             for tile_id in tile_ids {
                 if let Some(tile) = self.map.tiles_by_id.get(tile_id) {
                     if let Some(resource) = tile.resource {
                         bundle.add(resource, 1);
+                        if self.config.payout_detail {
+                            let mut single = ResourceBundle::zero();
+                            single.add(resource, 1);
+                            tiles.push((*tile_id, single));
+                        }
                     }
                 }
             }
@@ -1050,25 +2192,34 @@ impl GameState {
         if !bundle.is_empty() {
             if self.bank.dispense(&bundle).is_ok() {
                 self.players[player_idx].add_resources(&bundle);
+                tiles.sort_unstable_by_key(|(tile_id, _)| *tile_id);
                 outcome.events.push(GameEvent::ResourcesDistributed {
                     player: player_idx,
                     bundle,
+                    tiles,
                 });
             }
         }
         Ok(())
     }
 
-    fn roll_die(&mut self) -> u8 {
-        self.rng.gen_range(1..=6)
-    }
-
+    /// Pays out the production dice, with one event per player that got
+    /// anything, in seat order. Extra dice are left to the variants that
+    /// roll them.
     fn distribute_resources(
         &mut self,
-        dice_sum: u8,
+        roll: &DiceRoll,
         outcome: &mut StepOutcome,
     ) -> Result<(), GameError> {
-        for tile in self.map.tiles_by_id.values() {
+        let dice_sum = roll.sum();
+        let mut payouts: SmallVec<[ResourceBundle; 4]> =
+            smallvec![ResourceBundle::zero(); self.players.len()];
+        let mut details: Vec<(usize, u16, ResourceBundle)> = Vec::new();
+        // Walk tiles and corners in id order: when the bank runs short, who
+        // gets paid must not depend on hash order.
+        let mut tiles: Vec<_> = self.map.tiles_by_id.values().collect();
+        tiles.sort_unstable_by_key(|tile| tile.id);
+        for tile in tiles {
             if tile.number != Some(dice_sum) {
                 continue;
             }
@@ -1076,7 +2227,9 @@ impl GameState {
                 continue;
             }
 
-            for (_node_ref, node_id) in &tile.nodes {
+            let mut nodes: Vec<NodeId> = tile.nodes.values().copied().collect();
+            nodes.sort_unstable();
+            for node_id in &nodes {
                 if let Some(structure) = self.node_occupancy.get(node_id) {
                     let multiplier = match structure {
                         Structure::Settlement { .. } => 1,
@@ -1089,17 +2242,45 @@ impl GameState {
                             Structure::Settlement { player } => *player,
                             Structure::City { player } => *player,
                         };
+                        // Buildings left behind by a resignation produce
+                        // nothing.
+                        if self.resigned.contains(&owner) {
+                            continue;
+                        }
                         if self.bank.dispense(&bundle).is_ok() {
                             self.players[owner].add_resources(&bundle);
-                            outcome.events.push(GameEvent::ResourcesDistributed {
-                                player: owner,
-                                bundle,
-                            });
+                            self.players[owner].record_dice_income(&bundle);
+                            payouts[owner].add(resource, multiplier);
+                            if self.config.payout_detail {
+                                details.push((owner, tile.id, bundle));
+                            }
                         }
                     }
                 }
             }
         }
+
+        for (player, bundle) in payouts.into_iter().enumerate() {
+            if bundle.is_empty() {
+                continue;
+            }
+            // A tile can pay the same player at several corners.
+            let mut tiles: Vec<(u16, ResourceBundle)> = Vec::new();
+            for (owner, tile_id, paid) in &details {
+                if *owner != player {
+                    continue;
+                }
+                match tiles.last_mut() {
+                    Some((last, total)) if last == tile_id => total.add_bundle(paid),
+                    _ => tiles.push((*tile_id, *paid)),
+                }
+            }
+            outcome.events.push(GameEvent::ResourcesDistributed {
+                player,
+                bundle,
+                tiles,
+            });
+        }
         Ok(())
     }
 
@@ -1110,8 +2291,17 @@ impl GameState {
         require_network: bool,
     ) -> Result<(), GameError> {
         if self.players[player_idx].settlement_limit_reached() {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::SettlementLimitReached);
         }
+        self.check_settlement_site(player_idx, node_id, require_network)
+    }
+
+    fn check_settlement_site(
+        &self,
+        player_idx: usize,
+        node_id: NodeId,
+        require_network: bool,
+    ) -> Result<(), GameError> {
         if self.node_occupancy.contains_key(&node_id) {
             return Err(GameError::NodeOccupied(node_id));
         }
@@ -1135,8 +2325,17 @@ impl GameState {
         require_network: bool,
     ) -> Result<(), GameError> {
         if self.players[player_idx].road_limit_reached() {
-            return Err(GameError::IllegalAction);
+            return Err(GameError::RoadLimitReached);
         }
+        self.check_road_site(player_idx, edge, require_network)
+    }
+
+    fn check_road_site(
+        &self,
+        player_idx: usize,
+        edge: EdgeId,
+        require_network: bool,
+    ) -> Result<(), GameError> {
         let normalized = normalize_edge(edge);
         if self.road_occupancy.contains_key(&normalized) {
             return Err(GameError::EdgeOccupied);
@@ -1152,16 +2351,23 @@ impl GameState {
             return Err(GameError::EdgeNotFound);
         }
         if require_network {
-            let connected = self.players[player_idx].roads.iter().any(|existing| {
-                let nodes = [existing.0, existing.1];
-                nodes.contains(&node_a) || nodes.contains(&node_b)
-            }) || self.players[player_idx].settlements.contains(&node_a)
-                || self.players[player_idx].settlements.contains(&node_b)
-                || self.players[player_idx].cities.contains(&node_a)
-                || self.players[player_idx].cities.contains(&node_b);
-            if !connected {
-                return Err(GameError::MustConnectToNetwork);
+            // The new road has to hang off the network at a node that isn't
+            // another player's building; roads don't continue through those.
+            let mut blocked_at = None;
+            for node in [node_a, node_b] {
+                if !self.node_connected_to_player_network(player_idx, node) {
+                    continue;
+                }
+                match self.node_occupancy.get(&node) {
+                    Some(Structure::Settlement { player } | Structure::City { player })
+                        if *player != player_idx =>
+                    {
+                        blocked_at = Some(node);
+                    }
+                    _ => return Ok(()),
+                }
             }
+            return Err(blocked_at.map_or(GameError::MustConnectToNetwork, GameError::RoadBlocked));
         }
         Ok(())
     }
@@ -1182,8 +2388,12 @@ impl GameState {
             player.reset_for_new_turn();
         }
         self.current_player = (self.current_player + 1) % self.players.len();
+        while !self.is_active(self.current_player) {
+            self.current_player = (self.current_player + 1) % self.players.len();
+        }
         self.turn_owner = self.current_player;
         self.turn += 1;
+        self.trade_offers_this_turn = 0;
         self.awaiting_roll = true;
         self.pending_prompt = ActionPrompt::PlayTurn;
         outcome.events.push(GameEvent::TurnAdvanced {
@@ -1195,11 +2405,8 @@ impl GameState {
         if matches!(self.phase, GamePhase::Completed { .. }) {
             return;
         }
-        for (idx, player) in self.players.iter().enumerate() {
-            if player.total_points() >= self.config.vps_to_win {
-                self.phase = GamePhase::Completed { winner: Some(idx) };
-                break;
-            }
+        if let Some(winner) = self.config.victory.outcome(self) {
+            self.phase = GamePhase::Completed { winner };
         }
     }
 }
@@ -1209,43 +2416,334 @@ impl GameState {
         &self.available_actions
     }
 
+    /// The players with a decision to make right now. This is just
+    /// `current_player` except during a simultaneous discard, where it is
+    /// everyone who still owes cards, from the roller clockwise.
+    pub fn players_to_act(&self) -> Vec<usize> {
+        if self.config.simultaneous_discard
+            && self.pending_prompt == ActionPrompt::Discard
+            && !matches!(self.phase, GamePhase::Setup(_))
+        {
+            self.seats_from_roller()
+                .filter(|seat| self.discard_targets.contains_key(seat))
+                .collect()
+        } else {
+            vec![self.current_player]
+        }
+    }
+
+    /// Players who still owe cards after a 7 and how many, from the roller
+    /// clockwise.
+    pub fn discard_obligations(&self) -> Vec<(usize, u8)> {
+        self.seats_from_roller()
+            .filter_map(|seat| Some((seat, *self.discard_targets.get(&seat)?)))
+            .collect()
+    }
+
+    /// Players still to answer the current trade offer, in the order they
+    /// will be asked. The one deciding now is `current_player`.
+    pub fn trade_responders_pending(&self) -> Vec<usize> {
+        let mut pending = Vec::new();
+        if self.pending_prompt == ActionPrompt::DecideTrade {
+            pending.push(self.current_player);
+        }
+        pending.extend(self.trade_queue.iter().copied());
+        pending
+    }
+
+    /// The player building with Road Building and how many free roads they
+    /// have left.
+    pub fn free_roads(&self) -> Option<(usize, u8)> {
+        self.road_building_player
+            .filter(|_| self.road_building_free_roads > 0)
+            .map(|player| (player, self.road_building_free_roads))
+    }
+
+    /// The legal actions of `player_idx`, empty when they have nothing to
+    /// decide. `legal_actions` only ever covers `current_player`; this also
+    /// answers for the other players in a simultaneous discard.
+    pub fn legal_actions_for(&self, player_idx: usize) -> Vec<GameAction> {
+        if player_idx == self.current_player {
+            self.available_actions.clone()
+        } else if self.players_to_act().contains(&player_idx) {
+            let mut actions = Vec::new();
+            self.legal_discard_actions(player_idx, &mut actions);
+            actions
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Empty land nodes where `player_idx` may place a settlement, ignoring
+    /// cost and piece limits: the distance rule holds and, once setup is
+    /// over, the node touches the player's road network. Sorted by id.
+    pub fn buildable_settlement_nodes(&self, player_idx: usize) -> Vec<NodeId> {
+        let require_network = !matches!(self.phase, GamePhase::Setup(_));
+        let mut nodes: Vec<NodeId> = self
+            .map
+            .land_nodes
+            .iter()
+            .copied()
+            .filter(|node| {
+                self.check_settlement_site(player_idx, *node, require_network)
+                    .is_ok()
+            })
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    /// Free edges `player_idx` may extend their road network onto, ignoring
+    /// cost and piece limits. Normalized and sorted.
+    pub fn buildable_road_edges(&self, player_idx: usize) -> Vec<EdgeId> {
+        self.network_edge_candidates(player_idx)
+            .into_iter()
+            .filter(|edge| self.check_road_site(player_idx, *edge, true).is_ok())
+            .collect()
+    }
+
+    /// Land nodes `player_idx` could settle after building at most
+    /// `max_roads` more roads. Roads grow outward from the current network
+    /// over free edges and never through another player's building; cost
+    /// and piece limits are ignored. With `max_roads == 0` this is the
+    /// settlement spots already on the network.
+    pub fn reachable_nodes(&self, player_idx: usize, max_roads: usize) -> BTreeSet<NodeId> {
+        let blocked = self.blocked_nodes(player_idx);
+        let mut seen: HashSet<NodeId> = self.player_network_nodes(player_idx).into_iter().collect();
+        let mut frontier: Vec<NodeId> = seen.iter().copied().collect();
+        for _ in 0..max_roads {
+            let mut next = Vec::new();
+            for node in frontier {
+                if blocked.contains(&node) {
+                    continue;
+                }
+                for neighbor in self.map.node_neighbors.get(&node).into_iter().flatten() {
+                    if self
+                        .road_occupancy
+                        .contains_key(&normalize_edge((node, *neighbor)))
+                    {
+                        continue;
+                    }
+                    if seen.insert(*neighbor) {
+                        next.push(*neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        seen.into_iter()
+            .filter(|node| {
+                self.map.land_nodes.contains(node)
+                    && self.check_settlement_site(player_idx, *node, false).is_ok()
+            })
+            .collect()
+    }
+
+    /// Fewest new roads connecting `player_idx`'s network to `target`, in
+    /// the order they'd be built. Follows the same rules as
+    /// `reachable_nodes`; whether `target` is a legal settlement spot isn't
+    /// checked. `Some(vec![])` if the network already touches `target`,
+    /// `None` if it can't be reached or there is no network yet. Ties go to
+    /// the lowest node ids, so the answer is stable.
+    pub fn roads_needed(&self, player_idx: usize, target: NodeId) -> Option<Vec<EdgeId>> {
+        let blocked = self.blocked_nodes(player_idx);
+        let network = self.player_network_nodes(player_idx);
+        let mut parents: HashMap<NodeId, Option<NodeId>> =
+            network.iter().map(|node| (*node, None)).collect();
+        let mut queue: VecDeque<NodeId> = network.into_iter().collect();
+        while let Some(node) = queue.pop_front() {
+            if node == target {
+                let mut path = Vec::new();
+                let mut current = node;
+                while let Some(Some(parent)) = parents.get(&current) {
+                    path.push(normalize_edge((*parent, current)));
+                    current = *parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if blocked.contains(&node) {
+                continue;
+            }
+            let mut neighbors: Vec<NodeId> = self
+                .map
+                .node_neighbors
+                .get(&node)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            neighbors.sort_unstable();
+            for neighbor in neighbors {
+                if parents.contains_key(&neighbor)
+                    || self
+                        .road_occupancy
+                        .contains_key(&normalize_edge((node, neighbor)))
+                {
+                    continue;
+                }
+                parents.insert(neighbor, Some(node));
+                queue.push_back(neighbor);
+            }
+        }
+        None
+    }
+
+    /// Seat holding the Longest Road card, if anyone.
+    pub fn longest_road_holder(&self) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player| player.has_longest_road)
+    }
+
+    /// Seat holding the Largest Army card, if anyone.
+    pub fn largest_army_holder(&self) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player| player.has_largest_army)
+    }
+
+    /// Length of `player_idx`'s longest road as counted for the award, i.e.
+    /// not continuing through other players' buildings.
+    pub fn longest_road_length(&self, player_idx: usize) -> usize {
+        self.player_longest_road(player_idx)
+    }
+
+    /// Zobrist hash of everything that shapes play from here on: the board,
+    /// buildings and roads, the robber, every hand and development card, the
+    /// bank's deck, whose turn and decision it is, how far setup has got and
+    /// any trade under way. Equal positions hash equally across games and
+    /// processes.
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+        let mut mix = |kind: u8, fields: &[u64]| hash ^= zobrist_key(kind, fields);
+        let resource_tag = |resource: Option<Resource>| resource.map_or(0, |r| r as u64 + 1);
+
+        for tile in self.map.tiles_by_id.values() {
+            let number = tile.number.unwrap_or(0) as u64;
+            mix(0, &[tile.id as u64, resource_tag(tile.resource), number]);
+        }
+        for port in self.map.ports_by_id.values() {
+            mix(1, &[port.id as u64, resource_tag(port.resource)]);
+        }
+        for (node, structure) in &self.node_occupancy {
+            let (player, level) = match structure {
+                Structure::Settlement { player } => (*player, 1),
+                Structure::City { player } => (*player, 2),
+            };
+            mix(2, &[*node as u64, player as u64, level]);
+        }
+        for ((a, b), player) in &self.road_occupancy {
+            mix(3, &[*a as u64, *b as u64, *player as u64]);
+        }
+        mix(4, &[self.robber_tile as u64]);
+
+        for (idx, player) in self.players.iter().enumerate() {
+            let idx = idx as u64;
+            for (resource, count) in player.resources.iter() {
+                mix(5, &[idx, resource as u64, count as u64]);
+            }
+            for card in DevelopmentCard::ALL {
+                let matured = player.matured_dev_card_count(card) as u64;
+                let fresh = player.fresh_dev_card_count(card) as u64;
+                let played = player.played_dev_cards.get(&card).copied().unwrap_or(0) as u64;
+                mix(6, &[idx, card as u64, matured, fresh, played]);
+            }
+            let flags = player.has_longest_road as u64
+                | (player.has_largest_army as u64) << 1
+                | (player.has_rolled as u64) << 2
+                | (player.has_played_dev_card_this_turn as u64) << 3;
+            mix(7, &[idx, flags]);
+        }
+
+        mix(8, &[self.bank.development_deck_len() as u64]);
+        mix(
+            9,
+            &[
+                self.current_player as u64,
+                self.pending_prompt as u64,
+                self.awaiting_roll as u64,
+                self.road_building_free_roads as u64,
+            ],
+        );
+        for (position, player) in self.discard_queue.iter().enumerate() {
+            mix(10, &[position as u64, *player as u64]);
+        }
+        for (player, owed) in &self.discard_targets {
+            mix(11, &[*player as u64, *owed as u64]);
+        }
+        for (position, player) in self.resigned.iter().enumerate() {
+            mix(12, &[position as u64, *player as u64]);
+        }
+        let (phase, progress) = match &self.phase {
+            GamePhase::Setup(setup) => (0, setup.cursor as u64),
+            GamePhase::Playing => (1, 0),
+            GamePhase::Completed { winner } => (2, winner.map_or(0, |w| w as u64 + 1)),
+        };
+        mix(
+            13,
+            &[
+                phase,
+                progress,
+                self.turn_owner as u64,
+                self.road_building_player.map_or(0, |p| p as u64 + 1),
+                self.trade_offers_this_turn as u64,
+            ],
+        );
+        for (player, node) in &self.setup_pending_roads {
+            mix(14, &[*player as u64, *node as u64]);
+        }
+        if let Some(trade) = &self.trade_state {
+            let mut fields = vec![trade.offerer as u64];
+            fields.extend(trade.give.counts().map(u64::from));
+            fields.extend(trade.receive.counts().map(u64::from));
+            mix(15, &fields);
+            for player in &trade.acceptees {
+                mix(16, &[*player as u64]);
+            }
+        }
+        for (position, player) in self.trade_queue.iter().enumerate() {
+            mix(17, &[position as u64, *player as u64]);
+        }
+        hash
+    }
+
+    /// The Zobrist hash together with the turn and pending decision, for
+    /// deduplicating positions across self-play games.
+    pub fn position_key(&self) -> PositionKey {
+        PositionKey {
+            hash: self.zobrist_hash(),
+            turn: self.turn,
+            current_player: self.current_player,
+            prompt: self.pending_prompt,
+        }
+    }
+
     pub fn action_log(&self) -> &[GameAction] {
         &self.actions
     }
 
-    fn legal_setup_actions(&self, state: &SetupState) -> Vec<GameAction> {
-        let mut actions = Vec::new();
+    fn legal_setup_actions(&self, state: &SetupState, actions: &mut Vec<GameAction>) {
         let Some(player_idx) = state.current_player() else {
-            return actions;
+            return;
         };
         let prompt = state.current_prompt().unwrap_or(ActionPrompt::PlayTurn);
         match prompt {
             ActionPrompt::BuildInitialSettlement => {
-                for node in &self.map.land_nodes {
-                    if self
-                        .validate_settlement_location(player_idx, *node, false)
-                        .is_ok()
-                    {
-                        actions.push(
-                            GameAction::new(player_idx, ActionType::BuildSettlement)
-                                .with_payload(ActionPayload::Node(*node)),
-                        );
-                    }
+                for node in self.buildable_settlement_nodes(player_idx) {
+                    actions.push(GameAction::build_settlement(player_idx, node));
                 }
             }
             ActionPrompt::BuildInitialRoad => {
                 if let Some(&anchor) = self.setup_pending_roads.get(&player_idx) {
                     if let Some(edges) = self.map.node_edges.get(&anchor) {
+                        let mut edges: SmallVec<[EdgeId; 4]> =
+                            edges.iter().map(|edge| normalize_edge(*edge)).collect();
+                        edges.sort_unstable();
+                        edges.dedup();
                         for edge in edges {
-                            let normalized = normalize_edge(*edge);
-                            if self
-                                .validate_road_location(player_idx, normalized, false)
-                                .is_ok()
-                            {
-                                actions.push(
-                                    GameAction::new(player_idx, ActionType::BuildRoad)
-                                        .with_payload(ActionPayload::Edge(normalized)),
-                                );
+                            if self.validate_road_location(player_idx, edge, false).is_ok() {
+                                actions.push(GameAction::build_road(player_idx, edge));
                             }
                         }
                     }
@@ -1253,197 +2751,136 @@ impl GameState {
             }
             _ => {}
         }
-        actions
     }
 
-    fn legal_play_actions(&self) -> Vec<GameAction> {
+    fn legal_play_actions(&self, actions: &mut Vec<GameAction>) {
         match self.pending_prompt {
-            ActionPrompt::PlayTurn => self.legal_play_turn_actions(),
-            ActionPrompt::Discard => self.legal_discard_actions(),
-            ActionPrompt::MoveRobber => self.legal_move_robber_actions(),
-            ActionPrompt::DecideTrade => self.legal_trade_response_actions(),
-            ActionPrompt::DecideAcceptees => self.legal_trade_confirmation_actions(),
-            _ => Vec::new(),
+            ActionPrompt::PlayTurn => self.legal_play_turn_actions(actions),
+            ActionPrompt::Discard => self.legal_discard_actions(self.current_player, actions),
+            ActionPrompt::MoveRobber => self.legal_move_robber_actions(actions),
+            ActionPrompt::DecideTrade => self.legal_trade_response_actions(actions),
+            ActionPrompt::DecideAcceptees => self.legal_trade_confirmation_actions(actions),
+            _ => {}
         }
     }
 
-    fn legal_play_turn_actions(&self) -> Vec<GameAction> {
+    fn legal_play_turn_actions(&self, actions: &mut Vec<GameAction>) {
         if matches!(self.phase, GamePhase::Completed { .. }) {
-            return Vec::new();
+            return;
         }
-        let mut actions = Vec::new();
         if self.awaiting_roll {
-            actions.push(GameAction::new(self.current_player, ActionType::Roll));
+            actions.push(GameAction::roll(self.current_player));
         } else {
-            actions.push(GameAction::new(self.current_player, ActionType::EndTurn));
+            actions.push(GameAction::end_turn(self.current_player));
         }
         let player_idx = self.current_player;
         let player = &self.players[player_idx];
-        let mut edge_cache: Option<Vec<EdgeId>> = None;
 
         let is_road_building = !player.road_limit_reached()
             && self.road_building_player == Some(player_idx)
             && self.road_building_free_roads > 0;
-        if is_road_building {
-            let edges = edge_cache
-                .get_or_insert_with(|| self.network_edge_candidates(player_idx));
-            for &edge in edges.iter() {
-                if self.validate_road_location(player_idx, edge, true).is_ok() {
-                    actions.push(
-                        GameAction::new(player_idx, ActionType::BuildRoad)
-                            .with_payload(ActionPayload::Edge(edge)),
-                    );
-                }
+        let can_buy_road = !self.awaiting_roll
+            && !player.road_limit_reached()
+            && player.resources.can_afford(&COST_ROAD);
+        if is_road_building || can_buy_road {
+            for edge in self.buildable_road_edges(player_idx) {
+                actions.push(GameAction::build_road(player_idx, edge));
             }
         }
 
         if !self.awaiting_roll {
-            if !is_road_building
-                && !player.road_limit_reached()
-                && player.resources.can_afford(&COST_ROAD)
-            {
-                let edges = edge_cache
-                    .get_or_insert_with(|| self.network_edge_candidates(player_idx));
-                for &edge in edges.iter() {
-                    if self.validate_road_location(player_idx, edge, true).is_ok() {
-                        actions.push(
-                            GameAction::new(player_idx, ActionType::BuildRoad)
-                                .with_payload(ActionPayload::Edge(edge)),
-                        );
-                    }
-                }
-            }
-
             if !player.settlement_limit_reached() && player.resources.can_afford(&COST_SETTLEMENT) {
-                for node in &self.map.land_nodes {
-                    if self
-                        .validate_settlement_location(player_idx, *node, true)
-                        .is_ok()
-                    {
-                        actions.push(
-                            GameAction::new(player_idx, ActionType::BuildSettlement)
-                                .with_payload(ActionPayload::Node(*node)),
-                        );
-                    }
+                for node in self.buildable_settlement_nodes(player_idx) {
+                    actions.push(GameAction::build_settlement(player_idx, node));
                 }
             }
 
             if !player.city_limit_reached() && player.resources.can_afford(&COST_CITY) {
-                for node in &player.settlements {
-                    actions.push(
-                        GameAction::new(player_idx, ActionType::BuildCity)
-                            .with_payload(ActionPayload::Node(*node)),
-                    );
+                let mut settlements: SmallVec<[NodeId; 8]> =
+                    player.settlements.iter().copied().collect();
+                settlements.sort_unstable();
+                for node in settlements {
+                    actions.push(GameAction::build_city(player_idx, node));
                 }
             }
 
             if self.bank.development_deck_len() > 0
                 && player.resources.can_afford(&COST_DEVELOPMENT)
             {
-                actions.push(GameAction::new(player_idx, ActionType::BuyDevelopmentCard));
+                actions.push(GameAction::buy_development_card(player_idx));
             }
 
-            actions.extend(self.legal_maritime_trades(player_idx));
-        }
+            self.legal_maritime_trades(player_idx, actions);
 
-        actions.extend(self.legal_dev_card_actions(player_idx));
+            if self.config.team_gifts {
+                self.legal_gift_actions(player_idx, actions);
+            }
 
-        actions
+            if self.config.domestic_trades
+                && self.trade_offers_this_turn < MAX_TRADE_OFFERS_PER_TURN
+            {
+                self.legal_domestic_trades(player_idx, actions);
+            }
+        }
+
+        self.legal_dev_card_actions(player_idx, actions);
     }
 
-    fn legal_discard_actions(&self) -> Vec<GameAction> {
-        let mut actions = Vec::new();
-        let player_resources = self.players[self.current_player].resources;
+    fn legal_discard_actions(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
+        let player_resources = self.players[player_idx].resources;
         for (resource, count) in player_resources.iter() {
             if count > 0 {
-                actions.push(
-                    GameAction::new(self.current_player, ActionType::Discard).
-                    with_payload(ActionPayload::Resource(resource))
-                );
+                actions.push(GameAction::discard(player_idx, resource));
             }
         }
-
-        actions
     }
 
-    fn legal_move_robber_actions(&self) -> Vec<GameAction> {
-        let mut actions = Vec::new();
-        for tile in self.map.tiles_by_id.values() {
-            if tile.id == self.robber_tile {
+    fn legal_move_robber_actions(&self, actions: &mut Vec<GameAction>) {
+        let mut tiles: SmallVec<[_; 32]> = self.map.tiles_by_id.values().collect();
+        tiles.sort_unstable_by_key(|tile| tile.id);
+        for tile in tiles {
+            if tile.id == self.robber_tile || !self.robber_may_enter(tile.id) {
                 continue;
             }
-            let mut victims = HashSet::new();
-            for node_id in tile.nodes.values() {
-                if let Some(structure) = self.node_occupancy.get(node_id) {
-                    let owner = match structure {
-                        Structure::Settlement { player } | Structure::City { player } => *player,
-                    };
-                    if owner != self.current_player && !self.players[owner].resources.is_empty() {
-                        victims.insert(owner);
-                    }
-                }
-            }
+            let victims = self.robber_victims(tile.id);
             if victims.is_empty() {
-                actions.push(
-                    GameAction::new(self.current_player, ActionType::MoveRobber).with_payload(
-                        ActionPayload::Robber {
-                            tile_id: tile.id,
-                            victim: None,
-                            resource: None,
-                        },
-                    ),
-                );
+                actions.push(GameAction::move_robber(self.current_player, tile.id, None));
             } else {
                 for victim in victims {
-                    actions.push(
-                        GameAction::new(self.current_player, ActionType::MoveRobber).with_payload(
-                            ActionPayload::Robber {
-                                tile_id: tile.id,
-                                victim: Some(victim),
-                                resource: None,
-                            },
-                        ),
-                    );
+                    actions.push(GameAction::move_robber(
+                        self.current_player,
+                        tile.id,
+                        Some(victim),
+                    ));
                 }
             }
         }
-        actions
     }
 
-    fn legal_trade_response_actions(&self) -> Vec<GameAction> {
+    fn legal_trade_response_actions(&self, actions: &mut Vec<GameAction>) {
         let Some(state) = &self.trade_state else {
-            return Vec::new();
+            return;
         };
         if self.current_player == state.offerer {
-            return Vec::new();
+            return;
         }
-        let mut actions = vec![GameAction::new(
-            self.current_player,
-            ActionType::RejectTrade,
-        )];
+        actions.push(GameAction::reject_trade(self.current_player));
         if self.players[self.current_player]
             .resources
             .can_afford(&state.receive)
         {
-            actions.push(GameAction::new(
-                self.current_player,
-                ActionType::AcceptTrade,
-            ));
+            actions.push(GameAction::accept_trade(self.current_player));
         }
-        actions
     }
 
-    fn legal_trade_confirmation_actions(&self) -> Vec<GameAction> {
+    fn legal_trade_confirmation_actions(&self, actions: &mut Vec<GameAction>) {
         let Some(state) = &self.trade_state else {
-            return Vec::new();
+            return;
         };
         if self.current_player != state.offerer {
-            return Vec::new();
+            return;
         }
-        let mut actions = vec![GameAction::new(
-            self.current_player,
-            ActionType::CancelTrade,
-        )];
+        actions.push(GameAction::cancel_trade(self.current_player));
         for partner in &state.acceptees {
             actions.push(
                 GameAction::new(self.current_player, ActionType::ConfirmTrade).with_payload(
@@ -1455,17 +2892,12 @@ impl GameState {
                 ),
             );
         }
-        actions
     }
 
-    fn legal_maritime_trades(&self, player_idx: usize) -> Vec<GameAction> {
-        let mut actions = Vec::new();
-        for resource in Resource::ALL {
+    fn legal_maritime_trades(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
+        let rates = self.trade_rates(player_idx);
+        for (resource, rate) in Resource::ALL.into_iter().zip(rates) {
             let available = self.players[player_idx].resources.get(resource);
-            if available == 0 {
-                continue;
-            }
-            let rate = self.maritime_rate(player_idx, resource);
             if available < rate {
                 continue;
             }
@@ -1488,43 +2920,84 @@ impl GameState {
                 );
             }
         }
-        actions
     }
 
-    fn legal_dev_card_actions(&self, player_idx: usize) -> Vec<GameAction> {
+    fn legal_gift_actions(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
+        for partner in 0..self.players.len() {
+            if !self.are_teammates(player_idx, partner) || !self.is_active(partner) {
+                continue;
+            }
+            for (resource, count) in self.players[player_idx].resources.iter() {
+                if count > 0 {
+                    actions.push(
+                        GameAction::new(player_idx, ActionType::GiftResource)
+                            .with_payload(ActionPayload::Gift { resource, partner }),
+                    );
+                }
+            }
+        }
+    }
+
+    /// 1-for-1 and 2-for-1 offers, skipping requests no opponent could fill.
+    fn legal_domestic_trades(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
+        let hand = self.players[player_idx].resources;
+        for target in Resource::ALL {
+            let mut receive = ResourceBundle::zero();
+            receive.add(target, 1);
+            let fillable = self
+                .players
+                .iter()
+                .enumerate()
+                .any(|(idx, other)| idx != player_idx && other.resources.can_afford(&receive));
+            if !fillable {
+                continue;
+            }
+            for resource in Resource::ALL {
+                if resource == target {
+                    continue;
+                }
+                for amount in 1..=2 {
+                    if hand.get(resource) < amount {
+                        break;
+                    }
+                    let mut give = ResourceBundle::zero();
+                    give.add(resource, amount);
+                    actions.push(
+                        GameAction::new(player_idx, ActionType::OfferTrade).with_payload(
+                            ActionPayload::Trade {
+                                give,
+                                receive,
+                                partner: None,
+                            },
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn legal_dev_card_actions(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
         let player = &self.players[player_idx];
         if player.has_played_dev_card_this_turn {
-            return Vec::new();
+            return;
         }
-        let mut actions = Vec::new();
         if player.can_play_dev_card(DevelopmentCard::Knight) {
-            actions.push(
-                GameAction::new(player_idx, ActionType::PlayKnightCard)
-                    .with_payload(ActionPayload::None),
-            );
+            actions.push(GameAction::play_knight(player_idx));
         }
         if player.can_play_dev_card(DevelopmentCard::YearOfPlenty) {
-            actions.extend(self.year_of_plenty_actions(player_idx));
+            self.year_of_plenty_actions(player_idx, actions);
         }
         if player.can_play_dev_card(DevelopmentCard::Monopoly) {
             for resource in Resource::ALL {
-                actions.push(
-                    GameAction::new(player_idx, ActionType::PlayMonopoly)
-                        .with_payload(ActionPayload::Resource(resource)),
-                );
+                actions.push(GameAction::play_monopoly(player_idx, resource));
             }
         }
         if player.can_play_dev_card(DevelopmentCard::RoadBuilding) {
-            actions.push(
-                GameAction::new(player_idx, ActionType::PlayRoadBuilding)
-                    .with_payload(ActionPayload::None),
-            );
+            actions.push(GameAction::play_road_building(player_idx));
         }
-        actions
     }
 
-    fn year_of_plenty_actions(&self, player_idx: usize) -> Vec<GameAction> {
-        let mut actions = Vec::new();
+    fn year_of_plenty_actions(&self, player_idx: usize, actions: &mut Vec<GameAction>) {
         for (i, resource) in Resource::ALL.iter().enumerate() {
             if self.bank.available(*resource) == 0 {
                 continue;
@@ -1555,18 +3028,28 @@ impl GameState {
                 );
             }
         }
-        actions
     }
 
-    fn refresh_available_actions(&mut self) {
-        self.available_actions = self.compute_available_actions();
+    /// Regenerates the legal actions into the same buffer, so stepping a
+    /// game doesn't allocate a fresh list every time.
+    pub(crate) fn refresh_available_actions(&mut self) {
+        let mut actions = std::mem::take(&mut self.available_actions);
+        actions.clear();
+        self.push_available_actions(&mut actions);
+        self.available_actions = actions;
+        debug_assert!(
+            self.available_actions
+                .iter()
+                .all(|action| action.payload == action.payload.canonical()),
+            "legal actions must be generated in canonical form"
+        );
     }
 
-    fn compute_available_actions(&self) -> Vec<GameAction> {
+    fn push_available_actions(&self, actions: &mut Vec<GameAction>) {
         match &self.phase {
-            GamePhase::Setup(state) => self.legal_setup_actions(state),
-            GamePhase::Playing => self.legal_play_actions(),
-            GamePhase::Completed { .. } => Vec::new(),
+            GamePhase::Setup(state) => self.legal_setup_actions(state, actions),
+            GamePhase::Playing => self.legal_play_actions(actions),
+            GamePhase::Completed { .. } => {}
         }
     }
 
@@ -1581,8 +3064,12 @@ impl GameState {
                 .collect();
         }
 
+        let blocked = self.blocked_nodes(player_idx);
         let mut edges = Vec::new();
         for node in nodes {
+            if blocked.contains(&node) {
+                continue;
+            }
             if let Some(list) = self.map.node_edges.get(&node) {
                 for edge in list {
                     let normalized = normalize_edge(*edge);
@@ -1614,6 +3101,29 @@ impl GameState {
         nodes
     }
 
+    fn push_award_changes(
+        &self,
+        (road, army): (Option<usize>, Option<usize>),
+        outcome: &mut StepOutcome,
+    ) {
+        let holder = self.longest_road_holder();
+        if holder != road {
+            outcome.events.push(GameEvent::LongestRoadChanged {
+                previous: road,
+                holder,
+                length: holder.map_or(0, |idx| self.longest_road_length(idx) as u8),
+            });
+        }
+        let holder = self.largest_army_holder();
+        if holder != army {
+            outcome.events.push(GameEvent::LargestArmyChanged {
+                previous: army,
+                holder,
+                knights: holder.map_or(0, |idx| self.players[idx].knights_played),
+            });
+        }
+    }
+
     fn update_longest_road(&mut self) {
         let mut best_len = 0;
         let mut best_idx: Option<usize> = None;
@@ -1717,14 +3227,6 @@ impl GameState {
     }
 }
 
-fn normalize_edge(edge: EdgeId) -> EdgeId {
-    if edge.0 <= edge.1 {
-        edge
-    } else {
-        (edge.1, edge.0)
-    }
-}
-
 fn collect_all_edges(map: &CatanMap) -> Vec<EdgeId> {
     let mut seen = HashSet::new();
     let mut edges = Vec::new();
@@ -1739,6 +3241,26 @@ fn collect_all_edges(map: &CatanMap) -> Vec<EdgeId> {
     edges
 }
 
+/// Whether `action` names how chance resolved: the dice it rolled, the card
+/// it drew or the resource it stole. These come from `chance_outcomes`.
+pub(crate) fn is_chance_outcome(action: &GameAction) -> bool {
+    matches!(
+        (action.action_type, &action.payload),
+        (ActionType::Roll, ActionPayload::Dice(_))
+            | (
+                ActionType::BuyDevelopmentCard,
+                ActionPayload::DevelopmentCard(_)
+            )
+            | (
+                ActionType::MoveRobber,
+                ActionPayload::Robber {
+                    resource: Some(_),
+                    ..
+                }
+            )
+    )
+}
+
 fn edge_contains_node(edge: EdgeId, node: NodeId) -> bool {
     edge.0 == node || edge.1 == node
 }
@@ -1767,7 +3289,7 @@ struct SetupStep {
 }
 
 impl SetupState {
-    fn new(num_players: usize) -> Self {
+    fn new(num_players: usize, mode: SetupMode) -> Self {
         let mut steps = Vec::with_capacity(num_players * 4);
         for player in 0..num_players {
             steps.push(SetupStep {
@@ -1782,7 +3304,11 @@ impl SetupState {
             });
         }
 
-        for player in (0..num_players).rev() {
+        let second_round: Vec<usize> = match mode {
+            SetupMode::Fixed => (0..num_players).collect(),
+            SetupMode::Snake | SetupMode::Random => (0..num_players).rev().collect(),
+        };
+        for player in second_round {
             steps.push(SetupStep {
                 player_index: player,
                 prompt: ActionPrompt::BuildInitialSettlement,
@@ -1825,3 +3351,387 @@ impl SetupState {
         self.cursor >= self.steps.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game past setup where `roller` is about to roll a 7, with seat `i`
+    /// holding `hands[i]` cards.
+    fn about_to_roll_seven(roller: usize, hands: &[u8], simultaneous_discard: bool) -> GameState {
+        let mut rolls = vec![DiceRoll::new(4, 4); roller];
+        rolls.push(DiceRoll::new(3, 4));
+        let mut state = GameState::new(GameConfig {
+            num_players: hands.len(),
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(rolls),
+            simultaneous_discard,
+            ..GameConfig::default()
+        });
+        while state.turn_owner != roller {
+            let player = state.current_player;
+            state.step(GameAction::roll(player)).unwrap();
+            state.step(GameAction::end_turn(player)).unwrap();
+        }
+        for (seat, cards) in hands.iter().enumerate() {
+            let hand = std::mem::take(&mut state.players[seat].resources);
+            state.bank.receive(&hand);
+            let mut hand = ResourceBundle::zero();
+            for card in 0..*cards {
+                hand.add(Resource::ALL[card as usize % Resource::ALL.len()], 1);
+            }
+            state.bank.dispense(&hand).unwrap();
+            state.players[seat].resources = hand;
+        }
+        state.step(GameAction::roll(roller)).unwrap();
+        state
+    }
+
+    /// Plays discards until the robber is due, returning who discarded in
+    /// the order they were asked.
+    fn discard_order(state: &mut GameState) -> Vec<usize> {
+        let mut order = Vec::new();
+        while state.pending_prompt == ActionPrompt::Discard {
+            let player = state.current_player;
+            if order.last() != Some(&player) {
+                order.push(player);
+            }
+            let discard = state.legal_actions()[0].clone();
+            state.step(discard).unwrap();
+        }
+        order
+    }
+
+    #[test]
+    fn discards_start_at_the_roller_and_go_clockwise() {
+        let mut state = about_to_roll_seven(2, &[8, 3, 10, 9], false);
+        assert_eq!(state.discard_obligations(), vec![(2, 5), (3, 4), (0, 4)]);
+        assert_eq!(state.current_player, 2);
+        assert_eq!(state.players_to_act(), vec![2]);
+        assert_eq!(discard_order(&mut state), vec![2, 3, 0]);
+        assert_eq!(state.pending_prompt, ActionPrompt::MoveRobber);
+        assert_eq!(state.current_player, 2);
+    }
+
+    #[test]
+    fn sequential_discards_skip_a_roller_under_the_limit() {
+        let mut state = about_to_roll_seven(1, &[9, 4, 2, 8], false);
+        assert_eq!(state.discard_obligations(), vec![(3, 4), (0, 4)]);
+        assert_eq!(state.players_to_act(), vec![3]);
+        assert_eq!(discard_order(&mut state), vec![3, 0]);
+        assert_eq!(state.current_player, 1);
+    }
+
+    #[test]
+    fn simultaneous_discards_list_everyone_from_the_roller() {
+        let mut state = about_to_roll_seven(3, &[8, 10, 3, 9], true);
+        assert_eq!(state.discard_obligations(), vec![(3, 4), (0, 4), (1, 5)]);
+        assert_eq!(state.players_to_act(), vec![3, 0, 1]);
+        assert_eq!(state.current_player, 3);
+
+        // Settling a later seat first leaves the others in the same order.
+        while state.discard_targets.contains_key(&0) {
+            let discard = state.legal_actions_for(0)[0].clone();
+            state.step(discard).unwrap();
+        }
+        assert_eq!(state.players_to_act(), vec![3, 1]);
+        assert_eq!(discard_order(&mut state), vec![3, 1]);
+        assert_eq!(state.pending_prompt, ActionPrompt::MoveRobber);
+    }
+
+    fn one(resource: Resource) -> ResourceBundle {
+        let mut bundle = ResourceBundle::zero();
+        bundle.add(resource, 1);
+        bundle
+    }
+
+    /// A two player game past setup where the player to move has rolled and
+    /// holds a wood.
+    fn rolled_with_wood(domestic_trades: bool) -> GameState {
+        let mut state = GameState::new(GameConfig {
+            num_players: 2,
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(vec![DiceRoll::new(4, 4)]),
+            domestic_trades,
+            ..GameConfig::default()
+        });
+        let player = state.current_player;
+        state.bank.dispense(&one(Resource::Wood)).unwrap();
+        state.players[player].resources.add(Resource::Wood, 1);
+        state.step(GameAction::roll(player)).unwrap();
+        state
+    }
+
+    #[test]
+    fn gifts_report_the_card_that_changed_hands() {
+        let mut state = GameState::new(GameConfig {
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(vec![DiceRoll::new(4, 4)]),
+            teams: Some(vec![0, 1, 0, 1]),
+            team_gifts: true,
+            ..GameConfig::default()
+        });
+        let player = state.current_player;
+        let partner = (player + 2) % 4;
+        state.bank.dispense(&one(Resource::Wood)).unwrap();
+        state.players[player].resources.add(Resource::Wood, 1);
+        state.step(GameAction::roll(player)).unwrap();
+        let before = state.players[partner].resources.get(Resource::Wood);
+
+        let gift = GameAction::gift(player, Resource::Wood, partner).unwrap();
+        let outcome = state.step(gift).unwrap();
+        assert!(matches!(
+            outcome.events.as_slice(),
+            [GameEvent::ResourceGifted { player: from, partner: to, resource: Resource::Wood }]
+                if *from == player && *to == partner
+        ));
+        assert_eq!(
+            state.players[partner].resources.get(Resource::Wood),
+            before + 1
+        );
+    }
+
+    #[test]
+    fn trade_offers_need_domestic_trades_and_stop_at_the_limit() {
+        let (give, receive) = (one(Resource::Wood), one(Resource::Brick));
+
+        let mut state = rolled_with_wood(false);
+        let offer = GameAction::offer_trade(state.current_player, give, receive).unwrap();
+        assert!(matches!(
+            state.step(offer),
+            Err(GameError::DomesticTradesDisabled)
+        ));
+
+        let mut state = rolled_with_wood(true);
+        let offerer = state.current_player;
+        for _ in 0..MAX_TRADE_OFFERS_PER_TURN {
+            let offer = GameAction::offer_trade(offerer, give, receive).unwrap();
+            state.step(offer).unwrap();
+            state.step(GameAction::reject_trade(1 - offerer)).unwrap();
+            assert_eq!(state.current_player, offerer);
+        }
+        let offer = GameAction::offer_trade(offerer, give, receive).unwrap();
+        assert!(matches!(
+            state.step(offer),
+            Err(GameError::TooManyTradeOffers)
+        ));
+    }
+
+    #[test]
+    fn zobrist_hash_sees_trades() {
+        let mut state = rolled_with_wood(true);
+        let offerer = state.current_player;
+        let before = state.zobrist_hash();
+        let offer = GameAction::offer_trade(offerer, one(Resource::Wood), one(Resource::Brick));
+        state.step(offer.unwrap()).unwrap();
+        assert_ne!(state.zobrist_hash(), before);
+        state.step(GameAction::reject_trade(1 - offerer)).unwrap();
+        // Same board and hands, but one offer fewer left this turn.
+        assert_ne!(state.zobrist_hash(), before);
+    }
+
+    #[test]
+    fn forfeit_is_only_applied_by_the_clock() {
+        let mut state = rolled_with_wood(false);
+        let other = 1 - state.current_player;
+        assert!(matches!(
+            state.step(GameAction::forfeit(other)),
+            Err(GameError::InvalidAction(ActionType::Forfeit))
+        ));
+        assert!(matches!(
+            state.apply_chance_outcome(GameAction::forfeit(other)),
+            Err(GameError::InvalidAction(ActionType::Forfeit))
+        ));
+        assert!(matches!(state.phase, GamePhase::Playing));
+
+        let outcome = state.apply_timeout_forfeit(other).unwrap();
+        assert!(outcome.done);
+        assert!(matches!(
+            state.phase,
+            GamePhase::Completed { winner: Some(winner) } if winner != other
+        ));
+    }
+
+    /// Over a random game, `check_action` says yes to exactly what `step`
+    /// accepts, for the legal actions, the same actions by the wrong seat
+    /// and a few hand-built ones.
+    #[test]
+    fn check_action_agrees_with_step() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut state = GameState::new(GameConfig {
+            num_players: 3,
+            domestic_trades: true,
+            ..GameConfig::default()
+        });
+        for _ in 0..300 {
+            if matches!(state.phase, GamePhase::Completed { .. }) {
+                break;
+            }
+            let player = state.current_player;
+            let mut candidates = state.legal_actions().to_vec();
+            candidates.extend(candidates.clone().into_iter().map(|mut action| {
+                action.player_index = (action.player_index + 1) % 3;
+                action
+            }));
+            candidates.extend([
+                GameAction::roll(player),
+                GameAction::end_turn(player),
+                GameAction::resign(player),
+                GameAction::new(player, ActionType::BuildCity),
+                GameAction::offer_trade(player, one(Resource::Ore), one(Resource::Wood)).unwrap(),
+            ]);
+            for action in &candidates {
+                assert_eq!(
+                    state.check_action(action).is_ok(),
+                    state.clone().step(action.clone()).is_ok(),
+                    "{action:?}"
+                );
+            }
+            let action = state.legal_actions().choose(&mut rng).cloned().unwrap();
+            state.step(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn only_chance_outcomes_choose_the_dice() {
+        let mut state = GameState::new(GameConfig {
+            setup: SetupMode::Random,
+            ..GameConfig::default()
+        });
+        let player = state.current_player;
+        let roll = GameAction::roll(player).with_payload(ActionPayload::Dice(DiceRoll::new(2, 3)));
+        assert!(matches!(
+            state.step(roll.clone()),
+            Err(GameError::ChosenOutcome(ActionType::Roll))
+        ));
+        assert_eq!(state.last_roll, None);
+
+        state.apply_chance_outcome(roll).unwrap();
+        assert_eq!(state.last_roll.map(|roll| roll.sum()), Some(5));
+    }
+
+    /// A four player game past setup where nobody has rolled yet.
+    fn after_setup(teams: Option<Vec<usize>>, rewards: RewardConfig) -> GameState {
+        GameState::new(GameConfig {
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(vec![DiceRoll::new(4, 4)]),
+            teams,
+            rewards,
+            ..GameConfig::default()
+        })
+    }
+
+    #[test]
+    fn resigned_seats_leave_the_rotation() {
+        let mut state = after_setup(None, RewardConfig::default());
+        let first = state.current_player;
+        let next = (first + 1) % 4;
+
+        // Resigning out of turn leaves the turn where it is.
+        state.step(GameAction::resign(next)).unwrap();
+        assert_eq!(state.current_player, first);
+        assert!(matches!(
+            state.step(GameAction::resign(next)),
+            Err(GameError::Resigned(seat)) if seat == next
+        ));
+
+        // Resigning on your turn passes it on, past seats already gone.
+        state.step(GameAction::resign(first)).unwrap();
+        assert_eq!(state.turn_owner, (first + 2) % 4);
+        assert_eq!(state.resigned(), &[next, first]);
+        for _ in 0..4 {
+            let player = state.current_player;
+            assert!(state.is_active(player));
+            state.step(GameAction::roll(player)).unwrap();
+            state.step(GameAction::end_turn(player)).unwrap();
+        }
+        assert!(matches!(state.phase, GamePhase::Playing));
+    }
+
+    #[test]
+    fn the_last_team_left_wins() {
+        let mut state = after_setup(Some(vec![0, 1, 0, 1]), RewardConfig::default());
+        let outcome = state.step(GameAction::resign(1)).unwrap();
+        assert!(!outcome.done);
+        assert_eq!(outcome.rewards[1], -1.0);
+
+        let outcome = state.step(GameAction::resign(3)).unwrap();
+        assert!(outcome.done);
+        assert!(matches!(
+            state.phase,
+            GamePhase::Completed { winner: Some(0) }
+        ));
+        assert_eq!(outcome.rewards, vec![1.0, 0.0, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn resigned_seats_cannot_win() {
+        let mut state = after_setup(None, RewardConfig::default());
+        state.step(GameAction::resign(0)).unwrap();
+        state.players[0].victory_points = 10;
+        assert_eq!(state.config.victory.outcome(&state), None);
+
+        // Their points still count for a teammate who stayed.
+        let mut state = after_setup(Some(vec![0, 1, 0, 1]), RewardConfig::default());
+        state.step(GameAction::resign(0)).unwrap();
+        state.players[0].victory_points = 10;
+        assert_eq!(state.config.victory.outcome(&state), Some(Some(2)));
+    }
+
+    #[test]
+    fn resigning_pays_the_places_left_at_the_bottom() {
+        let mut state = after_setup(None, RewardConfig::ranked(4));
+        let places = [1.0, 1.0 / 3.0, -1.0 / 3.0, -1.0];
+        let first = state.current_player;
+        let seats: Vec<usize> = (0..4).map(|offset| (first + offset) % 4).collect();
+
+        let outcome = state.step(GameAction::resign(seats[1])).unwrap();
+        assert_eq!(outcome.rewards[seats[1]], places[3]);
+        let outcome = state.step(GameAction::resign(seats[2])).unwrap();
+        assert_eq!(outcome.rewards[seats[2]], places[2]);
+
+        // The third resignation leaves one seat, which wins.
+        let outcome = state.step(GameAction::resign(seats[3])).unwrap();
+        assert!(outcome.done);
+        assert_eq!(outcome.rewards[seats[3]], places[1]);
+        assert_eq!(outcome.rewards[seats[0]], places[0]);
+        assert!(matches!(
+            state.phase,
+            GamePhase::Completed { winner: Some(winner) } if winner == seats[0]
+        ));
+    }
+
+    #[test]
+    fn try_new_reports_invalid_configs() {
+        let config = |num_players, teams| GameConfig {
+            num_players,
+            teams,
+            ..GameConfig::default()
+        };
+        assert!(matches!(
+            GameState::try_new(config(5, None)),
+            Err(GameError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            GameState::try_new(config(2, Some(vec![0, 2]))),
+            Err(GameError::InvalidConfig(_))
+        ));
+        assert!(GameState::try_new(config(2, Some(vec![0, 1]))).is_ok());
+    }
+
+    #[test]
+    fn try_new_reports_layouts_that_do_not_fit_the_map() {
+        let layout = MapLayout {
+            numbers: Some(vec![6, 8]),
+            ..MapLayout::default()
+        };
+        let config = GameConfig {
+            map_layout: Some(layout),
+            ..GameConfig::default()
+        };
+        assert!(matches!(
+            GameState::try_new(config),
+            Err(GameError::InvalidMapLayout(_))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::NodeId;
+
+use super::state::{GameState, Structure};
+
+/// How a game is won. Reaching `GameConfig::vps_to_win` always wins; the
+/// scenario variants add another way for the game to end on top of that.
+/// With teams configured, points and nodes are pooled per team and the
+/// lowest seat of the winning team is reported as the winner. Seats that
+/// resigned can't win, though their points still count for their team.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VictoryCondition {
+    #[default]
+    Points,
+    /// Once `turns` turns have been played, whoever has the most VPs wins.
+    /// A tie for first ends the game as a draw.
+    MostPointsAfterTurns { turns: u32 },
+    /// The first player to have a building on `required` of `nodes` wins.
+    ControlNodes { nodes: Vec<NodeId>, required: usize },
+}
+
+impl VictoryCondition {
+    /// `None` while the game goes on, otherwise the winner (`Some(None)` for
+    /// a draw).
+    pub(crate) fn outcome(&self, state: &GameState) -> Option<Option<usize>> {
+        // One entry per seat, holding the points of the seat's whole team.
+        let points: Vec<u32> = (0..state.players.len())
+            .map(|idx| state.team_points(state.team_of(idx)))
+            .collect();
+        if let Some(idx) = (0..points.len())
+            .find(|idx| state.is_active(*idx) && points[*idx] >= state.config.vps_to_win as u32)
+        {
+            return Some(Some(idx));
+        }
+
+        match self {
+            VictoryCondition::Points => None,
+            VictoryCondition::MostPointsAfterTurns { turns } => {
+                if state.turn < *turns {
+                    return None;
+                }
+                let best = (0..points.len())
+                    .filter(|idx| state.is_active(*idx))
+                    .map(|idx| points[idx])
+                    .max()?;
+                let mut leaders =
+                    (0..points.len()).filter(|idx| state.is_active(*idx) && points[*idx] == best);
+                let winner = leaders.next()?;
+                if leaders.any(|idx| state.team_of(idx) != state.team_of(winner)) {
+                    return Some(None);
+                }
+                Some(Some(winner))
+            }
+            VictoryCondition::ControlNodes { nodes, required } => {
+                let mut controlled = vec![0usize; state.players.len()];
+                for node in nodes {
+                    match state.node_occupancy.get(node) {
+                        Some(Structure::Settlement { player })
+                        | Some(Structure::City { player }) => {
+                            controlled[state.team_of(*player)] += 1;
+                        }
+                        None => {}
+                    }
+                }
+                (0..state.players.len())
+                    .find(|idx| {
+                        state.is_active(*idx) && controlled[state.team_of(*idx)] >= *required
+                    })
+                    .map(Some)
+            }
+        }
+    }
+}
+
+impl fmt::Display for VictoryCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VictoryCondition::Points => write!(f, "POINTS"),
+            VictoryCondition::MostPointsAfterTurns { turns } => write!(f, "TURNS:{turns}"),
+            VictoryCondition::ControlNodes { nodes, required } => {
+                let nodes: Vec<String> = nodes.iter().map(|node| node.to_string()).collect();
+                write!(f, "NODES:{required}:{}", nodes.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for VictoryCondition {
+    type Err = String;
+
+    /// Accepts `points`, `turns:N` and `nodes:K:1,2,3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let kind = parts.next().unwrap_or_default();
+        match kind.to_ascii_lowercase().as_str() {
+            "points" => Ok(VictoryCondition::Points),
+            "turns" => {
+                let turns = parts
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| format!("invalid victory condition '{s}', expected TURNS:N"))?;
+                Ok(VictoryCondition::MostPointsAfterTurns { turns })
+            }
+            "nodes" => {
+                let invalid =
+                    || format!("invalid victory condition '{s}', expected NODES:K:N1,N2,...");
+                let required = parts
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(invalid)?;
+                let nodes = parts
+                    .next()
+                    .ok_or_else(invalid)?
+                    .split(',')
+                    .map(|node| node.trim().parse::<NodeId>().map_err(|_| invalid()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if required == 0 || required > nodes.len() {
+                    return Err(invalid());
+                }
+                Ok(VictoryCondition::ControlNodes { nodes, required })
+            }
+            _ => Err(format!("unknown victory condition: {s}")),
+        }
+    }
+}
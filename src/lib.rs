@@ -7,12 +7,38 @@ pub mod coords;
 pub mod env;
 pub mod features;
 pub mod game;
+pub mod manager;
 pub mod players;
 pub mod types;
 
+pub use board::CanonicalNodeId;
 pub use board::CatanMap;
+pub use board::{HarborLayout, MapLayout};
+pub use board::MapRegistry;
 pub use board::MapType;
+pub use board::Production;
 pub use board::Tile;
-pub use env::{Observation, PlayerObservation, RustEnv, StepResult};
+pub use env::{
+    ActionSpace, BoardObservation, Observation, PendingObligations, PlayerObservation, RustEnv,
+    StepResult,
+};
 pub use game::{Game, GameConfig, GameState};
 pub use types::Color;
+
+// Simulators hand games to worker threads and vectorized environments share
+// maps between them; keep these usable from any thread.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CatanMap>();
+    assert_send_sync::<GameState>();
+    assert_send_sync::<game::MapCache>();
+    assert_send_sync::<Game>();
+    assert_send_sync::<RustEnv>();
+    assert_send_sync::<players::RandomPlayer>();
+    assert_send_sync::<players::ValueFunctionPlayer>();
+    assert_send_sync::<players::SameTurnExpandPlayer>();
+    assert_send_sync::<players::MCTSPlayer>();
+    assert_send_sync::<players::OpeningBookPlayer<players::ValueFunctionPlayer>>();
+    assert_send_sync::<players::WinProbEstimator>();
+    assert_send_sync::<cli::players::PlayerInstance>();
+};
@@ -0,0 +1,283 @@
+//! Bookkeeping for many games at once: lobbies that fill seats as players
+//! register, games that advance as seated players act, idle games that get
+//! abandoned, and the results of the ones that ended.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::game::action::GameAction;
+use crate::game::game::{Game, TURNS_LIMIT};
+use crate::game::{GameConfig, GameError};
+use crate::types::{ActionType, Color};
+
+pub type GameId = Uuid;
+
+/// Games abandoned after this long without anyone joining or acting, unless
+/// the manager was given another timeout.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LobbyStatus {
+    /// Seats are still open; the game starts once all are taken.
+    Waiting,
+    Running,
+    Finished,
+    /// Nobody joined or acted for longer than the idle timeout.
+    Abandoned,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManagerError {
+    #[error("no game {0}")]
+    UnknownGame(GameId),
+    #[error("game {0} has no free seat")]
+    GameFull(GameId),
+    #[error("seat {0} is taken or does not exist")]
+    SeatUnavailable(usize),
+    #[error("{0} is already seated")]
+    AlreadySeated(String),
+    #[error("{0} is not seated in this game")]
+    NotSeated(String),
+    #[error("game is {0:?}")]
+    WrongStatus(LobbyStatus),
+    #[error("{player} sits in seat {seat} but acted for seat {acted_for}")]
+    WrongSeat {
+        player: String,
+        seat: usize,
+        acted_for: usize,
+    },
+    #[error("{action:?} is not a legal action for seat {seat} right now")]
+    IllegalAction { seat: usize, action: ActionType },
+    #[error(transparent)]
+    Game(#[from] GameError),
+}
+
+/// How a game ended, kept until `take_results` hands it out.
+#[derive(Debug, Clone)]
+pub struct GameReport {
+    pub id: GameId,
+    pub status: LobbyStatus,
+    /// Who sat where, by seat; `None` for seats an abandoned lobby never
+    /// filled.
+    pub players: Vec<Option<String>>,
+    pub winner: Option<Color>,
+    pub turns: u32,
+}
+
+/// One game and its seats.
+pub struct ManagedGame {
+    pub game: Game,
+    seats: Vec<Option<String>>,
+    status: LobbyStatus,
+    last_activity: Instant,
+}
+
+impl ManagedGame {
+    pub fn status(&self) -> LobbyStatus {
+        self.status
+    }
+
+    /// Registered player names by seat; `None` for open seats.
+    pub fn seats(&self) -> &[Option<String>] {
+        &self.seats
+    }
+
+    pub fn seat_of(&self, player: &str) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|seat| seat.as_deref() == Some(player))
+    }
+
+    /// Name of the player whose decision the game is waiting on.
+    pub fn player_to_act(&self) -> Option<&str> {
+        if self.status != LobbyStatus::Running {
+            return None;
+        }
+        self.seats.get(self.game.state.current_player)?.as_deref()
+    }
+
+    fn report(&self) -> GameReport {
+        GameReport {
+            id: self.game.id,
+            status: self.status,
+            players: self.seats.clone(),
+            winner: self.game.winning_color(),
+            turns: self.game.state.turn,
+        }
+    }
+}
+
+/// Owns the games a server, environment pool or tournament is running.
+pub struct GameManager {
+    games: HashMap<GameId, ManagedGame>,
+    results: Vec<GameReport>,
+    idle_timeout: Duration,
+}
+
+impl Default for GameManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
+impl GameManager {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            games: HashMap::new(),
+            results: Vec::new(),
+            idle_timeout,
+        }
+    }
+
+    /// Opens a lobby with one seat per player of `config`.
+    pub fn create_game(&mut self, config: GameConfig) -> Result<GameId, ManagerError> {
+        let seats = vec![None; config.num_players];
+        let game = Game::try_new(config)?.with_event_log();
+        let id = game.id;
+        self.games.insert(
+            id,
+            ManagedGame {
+                game,
+                seats,
+                status: LobbyStatus::Waiting,
+                last_activity: Instant::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn game(&self, id: GameId) -> Option<&ManagedGame> {
+        self.games.get(&id)
+    }
+
+    pub fn game_ids(&self) -> impl Iterator<Item = GameId> + '_ {
+        self.games.keys().copied()
+    }
+
+    /// Seats `player` in `seat`, or the first open seat when `None`, and
+    /// returns the seat. The game starts once every seat is taken.
+    pub fn join(
+        &mut self,
+        id: GameId,
+        player: impl Into<String>,
+        seat: Option<usize>,
+    ) -> Result<usize, ManagerError> {
+        let player = player.into();
+        let managed = self
+            .games
+            .get_mut(&id)
+            .ok_or(ManagerError::UnknownGame(id))?;
+        if managed.status != LobbyStatus::Waiting {
+            return Err(ManagerError::WrongStatus(managed.status));
+        }
+        if managed.seat_of(&player).is_some() {
+            return Err(ManagerError::AlreadySeated(player));
+        }
+        let seat = match seat {
+            Some(seat) if managed.seats.get(seat).is_some_and(Option::is_none) => seat,
+            Some(seat) => return Err(ManagerError::SeatUnavailable(seat)),
+            None => managed
+                .seats
+                .iter()
+                .position(Option::is_none)
+                .ok_or(ManagerError::GameFull(id))?,
+        };
+        managed.seats[seat] = Some(player);
+        if managed.seats.iter().all(Option::is_some) {
+            managed.status = LobbyStatus::Running;
+        }
+        managed.last_activity = Instant::now();
+        Ok(seat)
+    }
+
+    /// Frees `player`'s seat while the game is still waiting for players.
+    pub fn leave(&mut self, id: GameId, player: &str) -> Result<(), ManagerError> {
+        let managed = self
+            .games
+            .get_mut(&id)
+            .ok_or(ManagerError::UnknownGame(id))?;
+        if managed.status != LobbyStatus::Waiting {
+            return Err(ManagerError::WrongStatus(managed.status));
+        }
+        let seat = managed
+            .seat_of(player)
+            .ok_or_else(|| ManagerError::NotSeated(player.to_string()))?;
+        managed.seats[seat] = None;
+        managed.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Plays `action` for `player`. Only one of the seat's legal actions is
+    /// accepted, besides resigning; chance payloads such as dice can't be
+    /// chosen. A game that ends with it moves to the results.
+    pub fn act(
+        &mut self,
+        id: GameId,
+        player: &str,
+        mut action: GameAction,
+    ) -> Result<(), ManagerError> {
+        let managed = self
+            .games
+            .get_mut(&id)
+            .ok_or(ManagerError::UnknownGame(id))?;
+        if managed.status != LobbyStatus::Running {
+            return Err(ManagerError::WrongStatus(managed.status));
+        }
+        let seat = managed
+            .seat_of(player)
+            .ok_or_else(|| ManagerError::NotSeated(player.to_string()))?;
+        if action.player_index != seat {
+            return Err(ManagerError::WrongSeat {
+                player: player.to_string(),
+                seat,
+                acted_for: action.player_index,
+            });
+        }
+        action.canonicalize();
+        if action.action_type != ActionType::Resign
+            && !managed.game.state.legal_actions_for(seat).contains(&action)
+        {
+            return Err(ManagerError::IllegalAction {
+                seat,
+                action: action.action_type,
+            });
+        }
+        managed.game.try_execute(action)?;
+        managed.last_activity = Instant::now();
+        if managed.game.is_over() || managed.game.state.turn >= TURNS_LIMIT {
+            self.retire(id, LobbyStatus::Finished);
+        }
+        Ok(())
+    }
+
+    /// Abandons every game nobody has joined or acted in for longer than
+    /// the idle timeout, and returns their ids.
+    pub fn expire_idle(&mut self) -> Vec<GameId> {
+        let now = Instant::now();
+        let idle: Vec<GameId> = self
+            .games
+            .iter()
+            .filter(|(_, managed)| now.duration_since(managed.last_activity) > self.idle_timeout)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &idle {
+            self.retire(*id, LobbyStatus::Abandoned);
+        }
+        idle
+    }
+
+    /// Reports of the games that finished or were abandoned since the last
+    /// call, oldest first.
+    pub fn take_results(&mut self) -> Vec<GameReport> {
+        std::mem::take(&mut self.results)
+    }
+
+    fn retire(&mut self, id: GameId, status: LobbyStatus) {
+        if let Some(mut managed) = self.games.remove(&id) {
+            managed.status = status;
+            self.results.push(managed.report());
+        }
+    }
+}
@@ -1,5 +1,93 @@
+use std::future::{Future, ready};
+
+use rand::rngs::StdRng;
+
 use crate::game::{action::GameAction, game::Game};
 
+/// Picks one of `actions` for the current player. `decide` takes `&mut self`
+/// so players can carry state between decisions (caches, opponent models,
+/// their own RNG). Boxed players implement the trait too, so
+/// `Vec<Box<dyn BasePlayer>>` can be handed to `Game::play` to mix player
+/// types, including ones defined outside this crate.
 pub trait BasePlayer {
-    fn decide(&self, game: &Game, actions: &[GameAction]) -> Option<GameAction>;
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction>;
+
+    /// Replaces the player's random generator. `Game::play` calls this at
+    /// the start of every game with a generator derived from the game's
+    /// seed, stream and the player's seat, so bots that sample (random
+    /// moves, playouts, exploration) replay identically for the same seed.
+    /// Deterministic players can ignore it.
+    fn reseed(&mut self, _rng: StdRng) {}
+
+    /// What the search behind the last `decide` looked like, as JSON, for
+    /// bots that search and were asked to keep it. `None` otherwise.
+    fn last_search_stats(&self) -> Option<&str> {
+        None
+    }
+
+    /// `actions` with how much the player likes each, best first, for
+    /// advising someone else on the position. `None` from players that
+    /// pick without scoring their options.
+    fn score_actions(
+        &mut self,
+        _game: &Game,
+        _actions: &[GameAction],
+    ) -> Option<Vec<(GameAction, f64)>> {
+        None
+    }
+}
+
+impl<P: BasePlayer + ?Sized> BasePlayer for Box<P> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+        (**self).decide(game, actions)
+    }
+
+    fn reseed(&mut self, rng: StdRng) {
+        (**self).reseed(rng)
+    }
+
+    fn last_search_stats(&self) -> Option<&str> {
+        (**self).last_search_stats()
+    }
+
+    fn score_actions(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> Option<Vec<(GameAction, f64)>> {
+        (**self).score_actions(game, actions)
+    }
+}
+
+/// A player whose decisions arrive later, such as a human or bot at the
+/// other end of a connection, for `Game::play_async`. Every `BasePlayer` is
+/// one whose decisions are ready straight away.
+///
+/// Players that wait on someone else should give up and resolve to `None`
+/// once the current player's time on `game.state.clock` runs out. The
+/// clock's timeout policy then settles the decision as it does in
+/// `Game::play`; without a clock, the player forfeits.
+pub trait AsyncPlayer {
+    fn decide(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> impl Future<Output = Option<GameAction>> + Send;
+
+    /// See `BasePlayer::reseed`.
+    fn reseed(&mut self, _rng: StdRng) {}
+}
+
+impl<P: BasePlayer> AsyncPlayer for P {
+    fn decide(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> impl Future<Output = Option<GameAction>> + Send {
+        ready(BasePlayer::decide(self, game, actions))
+    }
+
+    fn reseed(&mut self, rng: StdRng) {
+        BasePlayer::reseed(self, rng)
+    }
 }
@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::board::NodeId;
+use crate::game::action::{ActionPayload, GameAction};
+use crate::game::resources::{
+    COST_CITY, COST_DEVELOPMENT, COST_ROAD, COST_SETTLEMENT, ResourceBundle,
+};
+use crate::game::state::GameState;
+use crate::types::{ActionType, Resource};
+
+const NUM_RESOURCES: usize = Resource::ALL.len();
+
+/// What an observer believes about one player's hand, as the expected number
+/// of cards of each resource. The expectations always sum to the hand size,
+/// which is public.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandBelief {
+    pub expected: [f64; NUM_RESOURCES],
+}
+
+impl HandBelief {
+    pub fn exact(hand: &ResourceBundle) -> Self {
+        let mut belief = Self::default();
+        belief.gain(hand);
+        belief
+    }
+
+    pub fn size(&self) -> f64 {
+        self.expected.iter().sum()
+    }
+
+    pub fn expected(&self, resource: Resource) -> f64 {
+        self.expected[resource as usize]
+    }
+
+    /// Chance that a card drawn at random from the hand is `resource`.
+    pub fn probability(&self, resource: Resource) -> f64 {
+        let size = self.size();
+        if size <= 0.0 {
+            return 0.0;
+        }
+        self.expected(resource) / size
+    }
+
+    fn gain(&mut self, bundle: &ResourceBundle) {
+        for (resource, amount) in bundle.iter() {
+            self.expected[resource as usize] += amount as f64;
+        }
+    }
+
+    /// Removes cards the player was seen spending. Spending more of a resource
+    /// than we expected proves the missing cards were attributed elsewhere, so
+    /// the deficit is taken proportionally from the other resources.
+    fn spend(&mut self, bundle: &ResourceBundle) {
+        for (resource, amount) in bundle.iter() {
+            let idx = resource as usize;
+            let deficit = amount as f64 - self.expected[idx];
+            self.expected[idx] = (self.expected[idx] - amount as f64).max(0.0);
+            if deficit > 0.0 {
+                self.shrink_others(idx, deficit);
+            }
+        }
+    }
+
+    fn shrink_others(&mut self, keep: usize, amount: f64) {
+        let others: f64 = (0..NUM_RESOURCES)
+            .filter(|idx| *idx != keep)
+            .map(|idx| self.expected[idx])
+            .sum();
+        if others <= 0.0 {
+            return;
+        }
+        let ratio = (1.0 - amount / others).max(0.0);
+        for idx in (0..NUM_RESOURCES).filter(|idx| *idx != keep) {
+            self.expected[idx] *= ratio;
+        }
+    }
+
+    /// Removes one card the observer didn't see and returns its distribution.
+    fn lose_unknown(&mut self) -> [f64; NUM_RESOURCES] {
+        let mut card = [0.0; NUM_RESOURCES];
+        let size = self.size();
+        if size <= 0.0 {
+            return card;
+        }
+        let take = size.min(1.0);
+        for (idx, expected) in self.expected.iter_mut().enumerate() {
+            card[idx] = *expected / size * take;
+            *expected -= card[idx];
+        }
+        card
+    }
+
+    fn gain_unknown(&mut self, card: &[f64; NUM_RESOURCES]) {
+        for (expected, amount) in self.expected.iter_mut().zip(card) {
+            *expected += amount;
+        }
+    }
+
+    fn take_all(&mut self, resource: Resource) -> f64 {
+        std::mem::take(&mut self.expected[resource as usize])
+    }
+
+    /// Rescales the belief to the publicly known hand size.
+    fn resize(&mut self, size: u32) {
+        let current = self.size();
+        if (current - size as f64).abs() < 1e-9 {
+            return;
+        }
+        if current <= 0.0 {
+            // Nothing to go on: spread the cards evenly.
+            self.expected = [size as f64 / NUM_RESOURCES as f64; NUM_RESOURCES];
+            return;
+        }
+        let ratio = size as f64 / current;
+        for expected in &mut self.expected {
+            *expected *= ratio;
+        }
+    }
+
+    /// Draws a concrete hand of `size` cards consistent with the belief.
+    pub fn sample(&self, size: u32, rng: &mut impl Rng) -> ResourceBundle {
+        let mut weights = self.expected;
+        let mut hand = ResourceBundle::zero();
+        for _ in 0..size {
+            let total: f64 = weights.iter().sum();
+            let idx = if total <= 0.0 {
+                rng.gen_range(0..NUM_RESOURCES)
+            } else {
+                let mut pick = rng.gen_range(0.0..total);
+                let mut chosen = NUM_RESOURCES - 1;
+                for (idx, weight) in weights.iter().enumerate() {
+                    if pick < *weight {
+                        chosen = idx;
+                        break;
+                    }
+                    pick -= weight;
+                }
+                chosen
+            };
+            weights[idx] = (weights[idx] - 1.0).max(0.0);
+            hand.add(Resource::ALL[idx], 1);
+        }
+        hand
+    }
+}
+
+/// Infers opponents' hidden hands from what one seat can observe: dice
+/// income, builds, purchases, trades, discards and robber steals. Feed it the
+/// game with `update` as the game progresses; it replays the action log from
+/// where it left off. `MCTSPlayer::with_determinization` searches over hands
+/// sampled from it.
+#[derive(Debug, Clone)]
+pub struct HandTracker {
+    observer: usize,
+    hands: Vec<HandBelief>,
+    buildings: HashMap<NodeId, (usize, u8)>,
+    settlements_built: Vec<u8>,
+    roads_built: Vec<u8>,
+    free_roads: u8,
+    robber_tile: u16,
+    cursor: usize,
+    /// The action at `cursor - 1`, to notice when it was taken back.
+    last_seen: Option<GameAction>,
+}
+
+impl HandTracker {
+    pub fn new(observer: usize, state: &GameState) -> Self {
+        let num_players = state.players.len();
+        let robber_tile = state
+            .map
+            .tiles_by_id
+            .values()
+            .find(|tile| tile.resource.is_none())
+            .map(|tile| tile.id)
+            .unwrap_or(0);
+        let mut tracker = Self {
+            observer,
+            hands: vec![HandBelief::default(); num_players],
+            buildings: HashMap::new(),
+            settlements_built: vec![0; num_players],
+            roads_built: vec![0; num_players],
+            free_roads: 0,
+            robber_tile,
+            cursor: 0,
+            last_seen: None,
+        };
+        tracker.update(state);
+        tracker
+    }
+
+    pub fn observer(&self) -> usize {
+        self.observer
+    }
+
+    pub fn belief(&self, player_idx: usize) -> &HandBelief {
+        &self.hands[player_idx]
+    }
+
+    /// Whether `update` can carry on for `observer` in `state`: the tracker
+    /// was following that seat, and nothing it saw has been taken back.
+    /// Callers still have to tell games apart.
+    pub fn follows(&self, observer: usize, state: &GameState) -> bool {
+        let log = state.action_log();
+        observer == self.observer
+            && self.hands.len() == state.players.len()
+            && self.cursor <= log.len()
+            && self.cursor.checked_sub(1).map(|last| &log[last]) == self.last_seen.as_ref()
+    }
+
+    /// Processes every action logged since the last update.
+    pub fn update(&mut self, state: &GameState) {
+        let log = state.action_log();
+        for action in &log[self.cursor.min(log.len())..] {
+            self.observe(state, action);
+        }
+        self.cursor = log.len();
+        self.last_seen = log.last().cloned();
+
+        for (idx, player) in state.players.iter().enumerate() {
+            if idx == self.observer {
+                self.hands[idx] = HandBelief::exact(&player.resources);
+            } else {
+                self.hands[idx].resize(player.resources.total());
+            }
+        }
+    }
+
+    /// Samples hidden hands for every opponent; the observer's own hand is
+    /// copied as is.
+    pub fn sample_hands(&self, state: &GameState, rng: &mut impl Rng) -> Vec<ResourceBundle> {
+        state
+            .players
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| {
+                if idx == self.observer {
+                    player.resources
+                } else {
+                    self.hands[idx].sample(player.resources.total(), rng)
+                }
+            })
+            .collect()
+    }
+
+    /// A copy of `state` with opponents' hands replaced by a sample from the
+    /// belief, for searching over determinizations of the hidden information.
+    pub fn determinize(&self, state: &GameState, rng: &mut impl Rng) -> GameState {
+        let hands = self.sample_hands(state, rng);
+        let mut sampled = state.clone();
+        for (player, hand) in sampled.players.iter_mut().zip(hands) {
+            player.resources = hand;
+        }
+        sampled
+    }
+
+    fn observe(&mut self, state: &GameState, action: &GameAction) {
+        let player = action.player_index;
+        match (action.action_type, &action.payload) {
+            (ActionType::Roll, ActionPayload::Dice(roll)) => {
+                self.collect_income(state, roll.sum());
+            }
+            (ActionType::BuildSettlement, ActionPayload::Node(node)) => {
+                let built = self.settlements_built[player];
+                self.settlements_built[player] += 1;
+                self.buildings.insert(*node, (player, 1));
+                if built >= 2 {
+                    self.hands[player].spend(&COST_SETTLEMENT);
+                } else if built == 1 {
+                    self.hands[player].gain(&starting_resources(state, *node));
+                }
+            }
+            (ActionType::BuildCity, ActionPayload::Node(node)) => {
+                self.buildings.insert(*node, (player, 2));
+                self.hands[player].spend(&COST_CITY);
+            }
+            (ActionType::BuildRoad, _) => {
+                let built = self.roads_built[player];
+                self.roads_built[player] += 1;
+                if built < 2 {
+                    // Setup roads are free.
+                } else if self.free_roads > 0 {
+                    self.free_roads -= 1;
+                } else {
+                    self.hands[player].spend(&COST_ROAD);
+                }
+            }
+            (ActionType::PlayRoadBuilding, _) => self.free_roads = 2,
+            (ActionType::EndTurn, _) => self.free_roads = 0,
+            (ActionType::BuyDevelopmentCard, _) => {
+                self.hands[player].spend(&COST_DEVELOPMENT);
+            }
+            (ActionType::MaritimeTrade, ActionPayload::MaritimeTrade { give, receive }) => {
+                self.hands[player].spend(give);
+                let mut bundle = ResourceBundle::zero();
+                bundle.add(*receive, 1);
+                self.hands[player].gain(&bundle);
+            }
+            (
+                ActionType::ConfirmTrade,
+                ActionPayload::Trade {
+                    give,
+                    receive,
+                    partner: Some(partner),
+                },
+            ) => {
+                self.hands[player].spend(give);
+                self.hands[player].gain(receive);
+                self.hands[*partner].spend(receive);
+                self.hands[*partner].gain(give);
+            }
+            (ActionType::GiftResource, ActionPayload::Gift { resource, partner }) => {
+                let mut bundle = ResourceBundle::zero();
+                bundle.add(*resource, 1);
+                self.hands[player].spend(&bundle);
+                self.hands[*partner].gain(&bundle);
+            }
+            (ActionType::Discard, ActionPayload::Resources(bundle)) => {
+                self.hands[player].spend(bundle);
+            }
+            (ActionType::PlayYearOfPlenty, ActionPayload::Resources(bundle)) => {
+                self.hands[player].gain(bundle);
+            }
+            (ActionType::PlayMonopoly, ActionPayload::Resource(resource)) => {
+                let mut taken = 0.0;
+                for (idx, hand) in self.hands.iter_mut().enumerate() {
+                    if idx != player {
+                        taken += hand.take_all(*resource);
+                    }
+                }
+                self.hands[player].expected[*resource as usize] += taken;
+            }
+            (
+                ActionType::MoveRobber,
+                ActionPayload::Robber {
+                    tile_id,
+                    victim,
+                    resource,
+                },
+            ) => {
+                self.robber_tile = *tile_id;
+                let Some(victim) = *victim else {
+                    return;
+                };
+                // Only the thief and the victim get to see the stolen card.
+                match resource {
+                    Some(resource) if self.observer == player || self.observer == victim => {
+                        let mut bundle = ResourceBundle::zero();
+                        bundle.add(*resource, 1);
+                        self.hands[victim].spend(&bundle);
+                        self.hands[player].gain(&bundle);
+                    }
+                    Some(_) => {
+                        let card = self.hands[victim].lose_unknown();
+                        self.hands[player].gain_unknown(&card);
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_income(&mut self, state: &GameState, dice_sum: u8) {
+        for tile in state.map.tiles_by_id.values() {
+            if tile.number != Some(dice_sum) || tile.id == self.robber_tile {
+                continue;
+            }
+            let Some(resource) = tile.resource else {
+                continue;
+            };
+            for node in tile.nodes.values() {
+                if let Some(&(owner, amount)) = self.buildings.get(node) {
+                    let mut bundle = ResourceBundle::zero();
+                    bundle.add(resource, amount);
+                    self.hands[owner].gain(&bundle);
+                }
+            }
+        }
+    }
+}
+
+fn starting_resources(state: &GameState, node: NodeId) -> ResourceBundle {
+    let mut bundle = ResourceBundle::zero();
+    for tile_id in state.map.adjacent_tiles.get(&node).into_iter().flatten() {
+        if let Some(resource) = state.map.tiles_by_id.get(tile_id).and_then(|t| t.resource) {
+            bundle.add(resource, 1);
+        }
+    }
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::dice::{DiceConfig, DiceRoll};
+    use crate::game::setup::SetupMode;
+    use crate::game::state::GameConfig;
+    use crate::types::DevelopmentCard;
+
+    /// Three players past setup who take turns rolling `rolls` over and
+    /// over, stopped right after the `turns`th roll.
+    fn after_rolls(rolls: Vec<DiceRoll>, turns: usize) -> GameState {
+        let mut state = GameState::new(GameConfig {
+            num_players: 3,
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(rolls),
+            ..GameConfig::default()
+        });
+        for turn in 0..turns {
+            let player = state.current_player;
+            if turn > 0 {
+                state.step(GameAction::end_turn(player)).unwrap();
+            }
+            state.step(GameAction::roll(state.current_player)).unwrap();
+        }
+        state
+    }
+
+    fn assert_exact(tracker: &HandTracker, state: &GameState) {
+        for (idx, player) in state.players.iter().enumerate() {
+            assert_eq!(
+                tracker.belief(idx),
+                &HandBelief::exact(&player.resources),
+                "seat {idx} seen by {}",
+                tracker.observer()
+            );
+        }
+    }
+
+    #[test]
+    fn public_income_is_known_exactly() {
+        let rolls = [6, 8, 5, 9, 4, 10].map(|sum| DiceRoll::new(sum / 2, sum - sum / 2));
+        let state = after_rolls(rolls.to_vec(), 6);
+        for observer in 0..3 {
+            assert_exact(&HandTracker::new(observer, &state), &state);
+        }
+    }
+
+    #[test]
+    fn only_the_thief_and_victim_see_what_was_stolen() {
+        let mut state = after_rolls(vec![DiceRoll::new(3, 3), DiceRoll::new(3, 4)], 2);
+        let thief = state.current_player;
+        let steal = state
+            .legal_actions()
+            .iter()
+            .find(|action| {
+                matches!(
+                    action.payload,
+                    ActionPayload::Robber {
+                        victim: Some(_),
+                        ..
+                    }
+                )
+            })
+            .cloned()
+            .unwrap();
+        let ActionPayload::Robber {
+            victim: Some(victim),
+            ..
+        } = steal.payload
+        else {
+            unreachable!()
+        };
+        let bystander = 3 - thief - victim;
+        let before = HandTracker::new(bystander, &state);
+        let (outcome, _) = state.chance_outcomes(&steal).swap_remove(0);
+        state.apply_chance_outcome(outcome).unwrap();
+
+        assert_exact(&HandTracker::new(thief, &state), &state);
+        assert_exact(&HandTracker::new(victim, &state), &state);
+
+        // The bystander only knows a card moved, so it takes its share of
+        // each resource the victim held.
+        let seen = HandTracker::new(bystander, &state);
+        let held = before.belief(victim).size();
+        for resource in Resource::ALL {
+            let share = before.belief(victim).expected(resource) / held;
+            let lost = before.belief(victim).expected(resource) - share;
+            let gained = before.belief(thief).expected(resource) + share;
+            assert!((seen.belief(victim).expected(resource) - lost).abs() < 1e-9);
+            assert!((seen.belief(thief).expected(resource) - gained).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn monopoly_empties_everyone_elses_hand_of_the_resource() {
+        let rolls = [6, 8, 5, 9].map(|sum| DiceRoll::new(sum / 2, sum - sum / 2));
+        let mut state = after_rolls(rolls.to_vec(), 1);
+        let player = state.current_player;
+        state.bank.dispense(&COST_DEVELOPMENT).unwrap();
+        state.players[player]
+            .resources
+            .add_bundle(&COST_DEVELOPMENT);
+        let buy = GameAction::new(player, ActionType::BuyDevelopmentCard)
+            .with_payload(ActionPayload::DevelopmentCard(DevelopmentCard::Monopoly));
+        state.apply_chance_outcome(buy).unwrap();
+        // Wait a round for the card to be playable.
+        for _ in 0..3 {
+            state
+                .step(GameAction::end_turn(state.current_player))
+                .unwrap();
+            state.step(GameAction::roll(state.current_player)).unwrap();
+        }
+        let resource = Resource::ALL
+            .into_iter()
+            .max_by_key(|resource| {
+                let held: u8 = (0..3)
+                    .filter(|seat| *seat != player)
+                    .map(|seat| state.players[seat].resources.get(*resource))
+                    .sum();
+                held
+            })
+            .unwrap();
+        state
+            .step(GameAction::play_monopoly(player, resource))
+            .unwrap();
+
+        assert_exact(&HandTracker::new(player, &state), &state);
+        for observer in 0..3 {
+            let tracker = HandTracker::new(observer, &state);
+            for seat in (0..3).filter(|seat| *seat != player) {
+                assert_eq!(tracker.belief(seat).expected(resource), 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::game::game::{Game, TURNS_LIMIT};
+
+const ROLLOUTS: usize = 32;
+
+/// Anything that can put numbers on a position without playing it out,
+/// such as a trained network. Returns one probability per seat.
+pub trait WinProbModel: Send + Sync {
+    fn win_probabilities(&self, game: &Game) -> Vec<f64>;
+}
+
+/// Each player's chance of winning from here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinEstimate {
+    /// By seat. Rollouts that hit the turn limit count for nobody, so these
+    /// can sum to less than one.
+    pub probabilities: Vec<f64>,
+    /// Rollouts behind the estimate; 0 when it came from a model or the
+    /// game was already over.
+    pub rollouts: usize,
+}
+
+/// Estimates win probabilities by playing the position out at random a
+/// number of times, or by asking a model when one is loaded. Rollouts see
+/// the whole state, hidden hands included.
+pub struct WinProbEstimator {
+    rollouts: usize,
+    model: Option<Box<dyn WinProbModel>>,
+    rng: StdRng,
+}
+
+impl Default for WinProbEstimator {
+    fn default() -> Self {
+        Self::new(ROLLOUTS)
+    }
+}
+
+impl WinProbEstimator {
+    pub fn new(rollouts: usize) -> Self {
+        Self {
+            rollouts: rollouts.max(1),
+            model: None,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    /// Answers from `model` instead of rolling out.
+    pub fn with_model(mut self, model: Box<dyn WinProbModel>) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn reseed(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
+
+    pub fn estimate(&mut self, game: &Game) -> WinEstimate {
+        let num_players = game.state.players.len();
+        if let Some(winner) = game.winning_color() {
+            let probabilities = game
+                .state
+                .players
+                .iter()
+                .map(|player| if player.color == winner { 1.0 } else { 0.0 })
+                .collect();
+            return WinEstimate {
+                probabilities,
+                rollouts: 0,
+            };
+        }
+        if let Some(model) = &self.model {
+            return WinEstimate {
+                probabilities: model.win_probabilities(game),
+                rollouts: 0,
+            };
+        }
+
+        let mut wins = vec![0usize; num_players];
+        for _ in 0..self.rollouts {
+            if let Some(seat) = self.rollout(game) {
+                wins[seat] += 1;
+            }
+        }
+        WinEstimate {
+            probabilities: wins
+                .iter()
+                .map(|wins| *wins as f64 / self.rollouts as f64)
+                .collect(),
+            rollouts: self.rollouts,
+        }
+    }
+
+    /// Plays random legal actions to the end and returns the winner's seat.
+    fn rollout(&mut self, game: &Game) -> Option<usize> {
+        let mut game_copy = game.copy();
+        while game_copy.winning_color().is_none() && game_copy.state.turn < TURNS_LIMIT {
+            let Some(action) = game_copy
+                .state
+                .legal_actions()
+                .choose(&mut self.rng)
+                .cloned()
+            else {
+                break;
+            };
+            let _ = game_copy.state.step_with_rng(action, &mut self.rng);
+        }
+        let winner = game_copy.winning_color()?;
+        game_copy
+            .state
+            .players
+            .iter()
+            .position(|player| player.color == winner)
+    }
+}
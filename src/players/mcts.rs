@@ -1,10 +1,18 @@
 use std::collections::HashMap;
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
+use crate::cli::compressed_actions::compress_actions;
 use crate::game::action::GameAction;
 use crate::game::game::Game;
 use crate::players::BasePlayer;
+use crate::players::belief::HandTracker;
+use crate::players::ordering::order_groups;
+use crate::players::solver::find_observed_forced_win;
 use crate::players::tree_search::{execute_spectrum, list_pruned_actions};
 use crate::types::Color;
 
@@ -15,11 +23,76 @@ fn exp_c() -> f64 {
     2.0_f64.sqrt()
 }
 
+/// Progressive widening: a node only considers as many of its actions as
+/// its visit count justifies, adding more as it is revisited, so search
+/// goes deep instead of spending every simulation on the first ply when
+/// trades and placements give hundreds of options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressiveWidening {
+    pub coefficient: f64,
+    pub exponent: f64,
+}
+
+impl Default for ProgressiveWidening {
+    fn default() -> Self {
+        Self {
+            coefficient: 2.0,
+            exponent: 0.5,
+        }
+    }
+}
+
+impl ProgressiveWidening {
+    /// Actions a node may have expanded once it has been visited `visits`
+    /// times: `ceil(coefficient * (visits + 1)^exponent)`, at least one.
+    pub fn max_children(&self, visits: u32) -> usize {
+        let limit = self.coefficient * (visits as f64 + 1.0).powf(self.exponent);
+        (limit.ceil() as usize).max(1)
+    }
+}
+
+/// The root of the last search, as returned by `last_search_stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchStats {
+    pub player_index: usize,
+    pub simulations: usize,
+    pub root_visits: u32,
+    pub chosen: Option<GameAction>,
+    /// Expanded root actions, most visited first.
+    pub children: Vec<ChildStats>,
+    /// Root actions widening never got to.
+    pub unexpanded: usize,
+}
+
+/// One root action of a search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildStats {
+    pub action: GameAction,
+    pub visits: u32,
+    /// Chance-weighted win rate over the action's outcomes.
+    pub q: f64,
+    /// Weight the action had before searching. There is no policy model
+    /// yet, so this is uniform over the root's candidate actions.
+    pub prior: f64,
+}
+
 #[derive(Clone)]
 pub struct MCTSPlayer {
     pub color: Color,
     pub num_simulations: usize,
     pub prunning: bool,
+    /// `None` expands every action of a node on its first visit.
+    pub widening: Option<ProgressiveWidening>,
+    /// Keep a `SearchStats` of every search for `last_search_stats`.
+    pub record_search_stats: bool,
+    /// Search a copy of the game where opponents' hands are sampled from a
+    /// `HandTracker` instead of read off the true state.
+    pub determinize: bool,
+    /// What this seat has seen of the game being played, by game id, kept
+    /// up to date from one decision to the next.
+    tracker: Option<(Uuid, HandTracker)>,
+    last_search_stats: Option<String>,
+    rng: StdRng,
 }
 
 impl MCTSPlayer {
@@ -28,12 +101,70 @@ impl MCTSPlayer {
             color,
             num_simulations: num_simulations.unwrap_or(SIMULATIONS),
             prunning: prunning.unwrap_or(false),
+            widening: Some(ProgressiveWidening::default()),
+            record_search_stats: false,
+            determinize: false,
+            tracker: None,
+            last_search_stats: None,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    pub fn with_search_stats(mut self) -> Self {
+        self.record_search_stats = true;
+        self
+    }
+
+    pub fn with_widening(mut self, widening: Option<ProgressiveWidening>) -> Self {
+        self.widening = widening;
+        self
+    }
+
+    pub fn with_determinization(mut self) -> Self {
+        self.determinize = true;
+        self
+    }
+
+    /// Runs the configured number of simulations from `game`.
+    fn search(&mut self, game: &Game) -> StateNode {
+        let mut root_game = game.copy();
+        if self.determinize
+            && let Some(seat) = game
+                .state
+                .players
+                .iter()
+                .position(|p| p.color == self.color)
+        {
+            let tracker = follow(&mut self.tracker, seat, game);
+            root_game.state = tracker.determinize(&game.state, &mut self.rng);
+        }
+        let mut root = StateNode::new(self.color, root_game, self.prunning, self.widening);
+        for _ in 0..self.num_simulations {
+            root.run_simulation(&mut self.rng);
         }
+        root
     }
 }
 
+/// The tracker in `slot` following `seat` in `game`, caught up on what was
+/// played since the last decision. Starts over for a new game or a takeback.
+fn follow<'a>(
+    slot: &'a mut Option<(Uuid, HandTracker)>,
+    seat: usize,
+    game: &Game,
+) -> &'a HandTracker {
+    match slot {
+        Some((id, tracker)) if *id == game.id && tracker.follows(seat, &game.state) => {
+            tracker.update(&game.state);
+        }
+        _ => *slot = Some((game.id, HandTracker::new(seat, &game.state))),
+    }
+    &slot.as_ref().expect("tracker set above").1
+}
+
 impl BasePlayer for MCTSPlayer {
-    fn decide(&self, game: &Game, _actions: &[GameAction]) -> Option<GameAction> {
+    fn decide(&mut self, game: &Game, _actions: &[GameAction]) -> Option<GameAction> {
+        self.last_search_stats = None;
         // Mirror Python: choose between raw playable_actions or pruned ones
         let base_actions: Vec<GameAction> = game.state.legal_actions().to_vec();
         let actions = if self.prunning {
@@ -46,12 +177,47 @@ impl BasePlayer for MCTSPlayer {
             return actions.first().cloned();
         }
 
-        let mut root = StateNode::new(self.color, game.copy(), self.prunning);
-        for _ in 0..self.num_simulations {
-            root.run_simulation();
+        if let Some(line) = find_observed_forced_win(&game.state) {
+            return line.into_iter().next();
         }
 
-        root.choose_best_action(&actions)
+        let root = self.search(game);
+        let chosen = root.choose_best_action(&actions);
+        if self.record_search_stats {
+            let stats = root.search_stats(self.num_simulations, chosen.clone());
+            self.last_search_stats = serde_json::to_string(&stats).ok();
+        }
+        chosen
+    }
+
+    fn reseed(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
+
+    fn last_search_stats(&self) -> Option<&str> {
+        self.last_search_stats.as_deref()
+    }
+
+    /// Win rates of the root's children, most visited first. Actions the
+    /// search never expanded are left out.
+    fn score_actions(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> Option<Vec<(GameAction, f64)>> {
+        if actions.is_empty() {
+            return None;
+        }
+        let root = self.search(game);
+        let stats = root.search_stats(self.num_simulations, None);
+        Some(
+            stats
+                .children
+                .into_iter()
+                .filter(|child| actions.contains(&child.action))
+                .map(|child| (child.action, child.q))
+                .collect(),
+        )
     }
 }
 
@@ -60,121 +226,207 @@ struct StateNode {
     color: Color,
     game: Game,
     children: HashMap<GameAction, Vec<(Box<StateNode>, f64)>>,
+    /// Actions with children, in the order they were expanded.
+    expanded: Vec<GameAction>,
+    /// Actions still to expand, next one last. `None` until first needed.
+    unexpanded: Option<Vec<GameAction>>,
     prunning: bool,
+    widening: Option<ProgressiveWidening>,
     wins: u32,
     visits: u32,
 }
 
 impl StateNode {
-    fn new(color: Color, game: Game, prunning: bool) -> Self {
+    fn new(
+        color: Color,
+        game: Game,
+        prunning: bool,
+        widening: Option<ProgressiveWidening>,
+    ) -> Self {
         Self {
             level: 0,
             color,
             game,
             children: HashMap::new(),
+            expanded: Vec::new(),
+            unexpanded: None,
             prunning,
+            widening,
             wins: 0,
             visits: 0,
         }
     }
 
-    fn run_simulation(&mut self) {
-        // Simplified mirror of Python MCTS:
-        // If leaf and non-terminal, expand once; then playout from this node.
-        if self.is_leaf() && !self.is_terminal() {
+    /// Walks down the tree by UCB, sampling chance outcomes by probability,
+    /// plays out from the first unvisited node and backs the result up.
+    fn run_simulation(&mut self, rng: &mut StdRng) -> Option<Color> {
+        let result = if self.is_terminal() {
+            self.game.winning_color()
+        } else if self.visits == 0 && self.level > 0 {
+            self.playout(rng)
+        } else {
             self.expand();
-        }
-
-        // Select best action and run playout
-        let action = self.choose_best_action_for_selection();
-        let result = self.playout();
+            match self.choose_best_action_for_selection() {
+                Some(action) => self.sample_child(&action, rng).run_simulation(rng),
+                None => self.playout(rng),
+            }
+        };
 
-        // Update statistics
         self.visits += 1;
         if result == Some(self.color) {
             self.wins += 1;
         }
-
-        // Update children if they exist
-        if let Some(children) = self.children.get_mut(&action) {
-            for (child, _) in children.iter_mut() {
-                child.visits += 1;
-                if result == Some(self.color) {
-                    child.wins += 1;
-                }
-            }
-        }
-    }
-
-    fn is_leaf(&self) -> bool {
-        self.children.is_empty()
+        result
     }
 
     fn is_terminal(&self) -> bool {
         self.game.winning_color().is_some()
     }
 
-    fn expand(&mut self) {
+    /// Actions in the order widening expands them: round-robin over the
+    /// compressed-action groups (all roads, each trade pattern, each robber
+    /// tile, ...), best ranked group first, so the first few children
+    /// already span different kinds of move rather than being dozens of
+    /// road placements.
+    fn candidate_actions(&self) -> Vec<GameAction> {
         // Use the same pruning rule as the Python list_prunned_actions when enabled
-        let base = self.game.state.legal_actions().to_vec();
         let actions = if self.prunning {
             list_pruned_actions(&self.game)
         } else {
-            base
+            self.game.state.legal_actions().to_vec()
         };
+        let mut groups = compress_actions(&actions);
+        order_groups(&self.game.state, &mut groups);
+        let longest = groups.iter().map(|g| g.actions.len()).max().unwrap_or(0);
+        (0..longest)
+            .flat_map(|rank| {
+                groups
+                    .iter()
+                    .filter_map(move |group| group.actions.get(rank))
+                    .map(|(_, action)| action.clone())
+            })
+            .collect()
+    }
 
-        for action in actions {
+    /// Expands actions until the node has as many as widening allows for
+    /// its visit count, or all of them without widening.
+    fn expand(&mut self) {
+        if self.unexpanded.is_none() {
+            let mut candidates = self.candidate_actions();
+            candidates.reverse();
+            self.unexpanded = Some(candidates);
+        }
+        let limit = self
+            .widening
+            .map_or(usize::MAX, |widening| widening.max_children(self.visits));
+        while self.expanded.len() < limit {
+            let Some(action) = self.unexpanded.as_mut().and_then(Vec::pop) else {
+                break;
+            };
             let outcomes = execute_spectrum(&self.game, &action);
-            for (next_game, p) in outcomes {
-                let child = StateNode::new(self.color, next_game, self.prunning);
-                self.children
-                    .entry(action.clone())
-                    .or_insert_with(Vec::new)
-                    .push((Box::new(child), p));
+            let children = outcomes
+                .into_iter()
+                .map(|(next_game, p)| {
+                    let mut child =
+                        StateNode::new(self.color, next_game, self.prunning, self.widening);
+                    child.level = self.level + 1;
+                    (Box::new(child), p)
+                })
+                .collect();
+            self.children.insert(action.clone(), children);
+            self.expanded.push(action);
+        }
+    }
+
+    fn sample_child(&mut self, action: &GameAction, rng: &mut StdRng) -> &mut StateNode {
+        let children = self
+            .children
+            .get_mut(action)
+            .expect("selected actions are expanded");
+        let mut roll: f64 = rng.r#gen();
+        let mut picked = children.len() - 1;
+        for (idx, (_, proba)) in children.iter().enumerate() {
+            if roll < *proba {
+                picked = idx;
+                break;
             }
+            roll -= proba;
         }
+        &mut children[picked].0
     }
 
+    /// The most visited of `actions`, breaking ties by expected win rate.
+    /// Actions that were never expanded are only picked if none were.
     fn choose_best_action(&self, actions: &[GameAction]) -> Option<GameAction> {
-        let mut best_action = None;
-        let mut best_score = f64::NEG_INFINITY;
+        actions
+            .iter()
+            .filter_map(|action| {
+                let children = self.children.get(action)?;
+                let visits: u32 = children.iter().map(|(child, _)| child.visits).sum();
+                Some((action, visits, self.action_win_rate(action)))
+            })
+            .fold(
+                None,
+                |best: Option<(&GameAction, u32, f64)>, candidate| match best {
+                    Some(best) if (best.1, best.2) >= (candidate.1, candidate.2) => Some(best),
+                    _ => Some(candidate),
+                },
+            )
+            .map(|(action, _, _)| action.clone())
+            .or_else(|| actions.first().cloned())
+    }
 
-        for action in actions {
+    fn choose_best_action_for_selection(&self) -> Option<GameAction> {
+        let mut best_action: Option<&GameAction> = None;
+        let mut best_score = f64::NEG_INFINITY;
+        // Walk children in expansion order rather than hash order so ties
+        // break the same way on every run.
+        for action in &self.expanded {
             let score = self.action_children_expected_score(action);
             if score > best_score {
                 best_score = score;
-                best_action = Some(action.clone());
+                best_action = Some(action);
             }
         }
-
-        best_action
+        best_action.cloned()
     }
 
-    fn choose_best_action_for_selection(&self) -> GameAction {
-        // When children exist, base the choice on them; otherwise fall back to legal actions.
-        if !self.children.is_empty() {
-            let mut best_action: Option<GameAction> = None;
-            let mut best_score = f64::NEG_INFINITY;
-            for (action, _) in &self.children {
-                let score = self.action_children_expected_score(action);
-                if score > best_score {
-                    best_score = score;
-                    best_action = Some(action.clone());
-                }
-            }
-            if let Some(a) = best_action {
-                return a;
-            }
+    fn search_stats(&self, simulations: usize, chosen: Option<GameAction>) -> SearchStats {
+        let unexpanded = self.unexpanded.as_ref().map_or(0, Vec::len);
+        let prior = 1.0 / (self.expanded.len() + unexpanded).max(1) as f64;
+        let mut children: Vec<ChildStats> = self
+            .expanded
+            .iter()
+            .map(|action| ChildStats {
+                action: action.clone(),
+                visits: self.children[action]
+                    .iter()
+                    .map(|(child, _)| child.visits)
+                    .sum(),
+                q: self.action_win_rate(action),
+                prior,
+            })
+            .collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.visits));
+        SearchStats {
+            player_index: self.game.state.current_player,
+            simulations,
+            root_visits: self.visits,
+            chosen,
+            children,
+            unexpanded,
         }
+    }
 
-        let actions: Vec<_> = self.game.state.legal_actions().to_vec();
-        if actions.is_empty() {
-            return GameAction::new(
-                self.game.state.current_player,
-                crate::types::ActionType::EndTurn,
-            );
-        }
-        actions[0].clone()
+    /// Chance-weighted win rate of the outcomes of `action`.
+    fn action_win_rate(&self, action: &GameAction) -> f64 {
+        self.children.get(action).map_or(0.0, |children| {
+            children
+                .iter()
+                .filter(|(child, _)| child.visits > 0)
+                .map(|(child, proba)| proba * child.wins as f64 / child.visits as f64)
+                .sum()
+        })
     }
 
     fn action_children_expected_score(&self, action: &GameAction) -> f64 {
@@ -198,10 +450,9 @@ impl StateNode {
         }
     }
 
-    fn playout(&mut self) -> Option<Color> {
+    fn playout(&mut self, rng: &mut StdRng) -> Option<Color> {
         // Run a random playout to completion
         let mut game_copy = self.game.copy();
-        let mut rng = rand::thread_rng();
 
         // Use RandomPlayer logic for playout
         while game_copy.winning_color().is_none() && game_copy.state.turn < 1000 {
@@ -210,8 +461,9 @@ impl StateNode {
                 break;
             }
 
-            if let Some(action) = legal_actions.choose(&mut rng) {
-                game_copy.execute(action.clone());
+            if let Some(action) = legal_actions.choose(rng) {
+                let action = action.clone();
+                let _ = game_copy.state.step_with_rng(action, rng);
             } else {
                 break;
             }
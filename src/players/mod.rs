@@ -1,10 +1,20 @@
 pub mod base;
+pub mod belief;
+pub mod estimator;
 pub mod mcts;
+pub mod opening_book;
+pub mod ordering;
 pub mod random;
+pub mod same_turn;
+pub mod solver;
 pub mod tree_search;
 pub mod value;
 
-pub use base::BasePlayer;
-pub use mcts::MCTSPlayer;
+pub use base::{AsyncPlayer, BasePlayer};
+pub use belief::{HandBelief, HandTracker};
+pub use estimator::{WinEstimate, WinProbEstimator, WinProbModel};
+pub use mcts::{ChildStats, MCTSPlayer, ProgressiveWidening, SearchStats};
+pub use opening_book::{OpeningBook, OpeningBookPlayer};
 pub use random::RandomPlayer;
-pub use value::{ValueFunctionParams, ValueFunctionPlayer};
+pub use same_turn::SameTurnExpandPlayer;
+pub use value::{ParamsFileError, ValueFunctionParams, ValueFunctionPlayer};
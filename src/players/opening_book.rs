@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use rand::rngs::StdRng;
+
+use crate::board::CatanMap;
+use crate::game::action::GameAction;
+use crate::game::game::Game;
+use crate::game::position::zobrist_key;
+use crate::game::state::{GamePhase, GameState};
+use crate::players::BasePlayer;
+use crate::types::Resource;
+
+/// Identifies a board by its tiles, numbers and ports, so a book built on one
+/// layout is only ever consulted on that same layout.
+pub fn layout_hash(map: &CatanMap) -> u64 {
+    let resource_tag = |resource: Option<Resource>| resource.map_or(0, |r| r as u64 + 1);
+    let tiles = map.tiles_by_id.values().map(|tile| {
+        let number = tile.number.unwrap_or(0) as u64;
+        zobrist_key(0, &[tile.id as u64, resource_tag(tile.resource), number])
+    });
+    let ports = map
+        .ports_by_id
+        .values()
+        .map(|port| zobrist_key(1, &[port.id as u64, resource_tag(port.resource)]));
+    tiles.chain(ports).fold(0, |hash, key| hash ^ key)
+}
+
+/// Which of a seat's setup placements is being made: 0 and 2 are the
+/// settlements, 1 and 3 the roads that follow them.
+pub fn placement_index(state: &GameState, seat: usize) -> u8 {
+    let player = &state.players[seat];
+    (player.settlements.len() + player.cities.len() + player.roads.len()) as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BookKey {
+    pub layout: u64,
+    pub seat: usize,
+    pub placement: u8,
+}
+
+impl BookKey {
+    /// The key for the decision `seat` faces in `state`.
+    pub fn for_state(state: &GameState, seat: usize) -> Self {
+        Self {
+            layout: layout_hash(&state.map),
+            seat,
+            placement: placement_index(state, seat),
+        }
+    }
+}
+
+/// The recommended placement for one key and the evidence behind it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookEntry {
+    pub key: BookKey,
+    pub action: GameAction,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl BookEntry {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.games as f64
+    }
+}
+
+/// Precomputed setup placements keyed by board layout, seat and placement
+/// index. Stored on disk as JSON.
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    entries: HashMap<BookKey, BookEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BookFile {
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let file: BookFile = serde_json::from_str(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut book = Self::new();
+        for entry in file.entries {
+            book.insert(entry);
+        }
+        Ok(book)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut entries: Vec<BookEntry> = self.entries.values().cloned().collect();
+        entries.sort_by_key(|entry| (entry.key.layout, entry.key.seat, entry.key.placement));
+        let json = serde_json::to_string_pretty(&BookFile { entries })
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, json)
+    }
+
+    /// Adds or replaces the entry for `entry.key`.
+    pub fn insert(&mut self, entry: BookEntry) {
+        self.entries.insert(entry.key, entry);
+    }
+
+    pub fn get(&self, key: &BookKey) -> Option<&BookEntry> {
+        self.entries.get(key)
+    }
+
+    /// The book move for the current player, if the game is in setup and
+    /// the book covers this decision.
+    pub fn lookup(&self, state: &GameState) -> Option<&GameAction> {
+        if !matches!(state.phase, GamePhase::Setup(_)) {
+            return None;
+        }
+        let key = BookKey::for_state(state, state.current_player);
+        self.get(&key).map(|entry| &entry.action)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Plays book moves during setup and hands every other decision, including
+/// setup positions the book doesn't cover, to `inner`.
+#[derive(Clone)]
+pub struct OpeningBookPlayer<P> {
+    book: Arc<OpeningBook>,
+    inner: P,
+}
+
+impl<P: BasePlayer> OpeningBookPlayer<P> {
+    pub fn new(book: Arc<OpeningBook>, inner: P) -> Self {
+        Self { book, inner }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: BasePlayer> BasePlayer for OpeningBookPlayer<P> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+        // A stale or foreign book entry must never produce an illegal move.
+        if let Some(action) = self.book.lookup(&game.state)
+            && actions.contains(action)
+        {
+            return Some(action.clone());
+        }
+        self.inner.decide(game, actions)
+    }
+
+    fn reseed(&mut self, rng: StdRng) {
+        self.inner.reseed(rng);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::cli::compressed_actions::CompressedActionGroup;
+use crate::game::action::GameAction;
+use crate::game::game::Game;
+use crate::game::state::GameState;
+use crate::types::ActionType;
+
+/// `actions` ranked by `action_rank`, most promising first. Actions of equal
+/// rank keep their order.
+pub fn order_actions(game: &Game, actions: &[GameAction]) -> Vec<GameAction> {
+    let mut ordered = actions.to_vec();
+    ordered.sort_by_key(|action| action_rank(&game.state, action));
+    ordered
+}
+
+/// Sorts compressed groups by the best rank among their actions. Groups of
+/// equal rank keep their order.
+pub fn order_groups(state: &GameState, groups: &mut [CompressedActionGroup]) {
+    groups.sort_by_key(|group| {
+        group
+            .actions
+            .iter()
+            .map(|(_, action)| action_rank(state, action))
+            .min()
+    });
+}
+
+/// How early a search should look at `action`; lower comes first. Points
+/// come first, then moves that grow the network or hurt opponents, and the
+/// ones that only shuffle cards around or pass come last.
+pub fn action_rank(state: &GameState, action: &GameAction) -> u8 {
+    match action.action_type {
+        ActionType::BuildCity => 0,
+        ActionType::BuildSettlement => 1,
+        // Before rolling, a knight is worth playing first when the robber
+        // sits on our own production.
+        ActionType::PlayKnightCard
+            if !state.players[action.player_index].has_rolled
+                && robber_blocks(state, action.player_index) =>
+        {
+            2
+        }
+        ActionType::Roll => 3,
+        ActionType::PlayKnightCard | ActionType::BuildRoad | ActionType::PlayRoadBuilding => 4,
+        ActionType::MoveRobber => 5,
+        ActionType::PlayYearOfPlenty | ActionType::PlayMonopoly => 6,
+        ActionType::BuyDevelopmentCard => 7,
+        ActionType::Discard
+        | ActionType::MaritimeTrade
+        | ActionType::OfferTrade
+        | ActionType::AcceptTrade
+        | ActionType::RejectTrade
+        | ActionType::ConfirmTrade
+        | ActionType::CancelTrade
+        | ActionType::GiftResource => 8,
+        ActionType::EndTurn => 9,
+        ActionType::Resign | ActionType::Forfeit => 10,
+    }
+}
+
+/// Whether the robber is on a tile next to one of the player's buildings.
+fn robber_blocks(state: &GameState, player_idx: usize) -> bool {
+    let player = &state.players[player_idx];
+    player
+        .settlements
+        .iter()
+        .chain(player.cities.iter())
+        .filter_map(|node| state.map.adjacent_tiles.get(node))
+        .any(|tiles| tiles.contains(&state.robber_tile))
+}
@@ -1,14 +1,35 @@
 use crate::game::action::GameAction;
 use crate::game::game::Game;
 use crate::players::BasePlayer;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 
 #[derive(Clone)]
-pub struct RandomPlayer;
+pub struct RandomPlayer {
+    rng: StdRng,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RandomPlayer {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
 
 impl BasePlayer for RandomPlayer {
-    fn decide(&self, _game: &Game, actions: &[GameAction]) -> Option<GameAction> {
-        let mut rng = rand::thread_rng();
-        actions.choose(&mut rng).cloned()
+    fn decide(&mut self, _game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+        actions.choose(&mut self.rng).cloned()
+    }
+
+    fn reseed(&mut self, rng: StdRng) {
+        self.rng = rng;
     }
 }
@@ -0,0 +1,122 @@
+use crate::game::action::{ActionPayload, GameAction};
+use crate::game::game::Game;
+use crate::players::BasePlayer;
+use crate::players::solver::find_observed_forced_win;
+use crate::players::tree_search::execute_spectrum;
+use crate::players::value::{ValueFunctionParams, apply_hypothetical_trade, evaluate_state};
+use crate::types::{ActionPrompt, ActionType, Color};
+
+/// Follow-up actions tried after each candidate before giving up on a line.
+const MAX_FOLLOW_UPS: usize = 6;
+
+/// Value-function player that looks a little further than one action: after
+/// each candidate it keeps greedily playing whatever else it could do this
+/// turn (e.g. trade, then build with the proceeds) while that raises the
+/// value, and scores the candidate by where the line ends up.
+#[derive(Clone)]
+pub struct SameTurnExpandPlayer {
+    pub color: Color,
+    pub params: ValueFunctionParams,
+}
+
+impl SameTurnExpandPlayer {
+    pub fn new(color: Color, params: Option<ValueFunctionParams>) -> Self {
+        Self {
+            color,
+            params: params.unwrap_or_default(),
+        }
+    }
+
+    /// Plays the best-valued follow-up while it beats stopping here, and
+    /// returns the value of the final position.
+    fn expand(&self, game: &mut Game, player_idx: usize) -> f64 {
+        let mut value = evaluate_state(game, player_idx, &self.params);
+        for _ in 0..MAX_FOLLOW_UPS {
+            if game.is_over()
+                || game.state.current_player != player_idx
+                || game.state.pending_prompt != ActionPrompt::PlayTurn
+            {
+                break;
+            }
+            let mut best: Option<(f64, Game)> = None;
+            for action in game.state.legal_actions() {
+                if !is_follow_up(action) {
+                    continue;
+                }
+                let mut next = game.copy();
+                next.execute(action.clone());
+                let next_value = evaluate_state(&next, player_idx, &self.params);
+                if next_value > best.as_ref().map_or(value, |(best, _)| *best) {
+                    best = Some((next_value, next));
+                }
+            }
+            let Some((next_value, next)) = best else {
+                break;
+            };
+            value = next_value;
+            *game = next;
+        }
+        value
+    }
+}
+
+/// Moves that stay within the turn, don't wait on dice or other players and
+/// have a single known result. Playing a card purchase or a steal out on a
+/// copy would show the search the card or resource the real game is about
+/// to hand over.
+fn is_follow_up(action: &GameAction) -> bool {
+    match action.action_type {
+        ActionType::Roll
+        | ActionType::EndTurn
+        | ActionType::BuyDevelopmentCard
+        | ActionType::OfferTrade
+        | ActionType::AcceptTrade
+        | ActionType::RejectTrade
+        | ActionType::ConfirmTrade
+        | ActionType::CancelTrade => false,
+        ActionType::MoveRobber => {
+            matches!(action.payload, ActionPayload::Robber { victim: None, .. })
+        }
+        _ => true,
+    }
+}
+
+impl BasePlayer for SameTurnExpandPlayer {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+        if actions.len() == 1 {
+            return actions.first().cloned();
+        }
+
+        if let Some(line) = find_observed_forced_win(&game.state) {
+            return line.into_iter().next();
+        }
+
+        let player_idx = game
+            .state
+            .players
+            .iter()
+            .position(|p| p.color == self.color)?;
+
+        let mut best_value = f64::NEG_INFINITY;
+        let mut best_action = None;
+        for action in actions {
+            let mut game_copy = game.copy();
+            let value = if apply_hypothetical_trade(&mut game_copy, player_idx, action) {
+                evaluate_state(&game_copy, player_idx, &self.params)
+            } else {
+                // Averaged over every roll, card or steal the action can
+                // bring rather than the one the game's generator has lined up.
+                execute_spectrum(game, action)
+                    .into_iter()
+                    .map(|(mut next, p)| p * self.expand(&mut next, player_idx))
+                    .sum()
+            };
+            if value > best_value {
+                best_value = value;
+                best_action = Some(action.clone());
+            }
+        }
+
+        best_action
+    }
+}
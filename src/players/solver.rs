@@ -0,0 +1,223 @@
+use crate::game::action::{ActionPayload, GameAction};
+use crate::game::state::{GamePhase, GameState};
+use crate::players::ordering::action_rank;
+use crate::types::{ActionPrompt, ActionType};
+
+/// Only bother searching when the player is at most this many VPs short.
+pub const SOLVER_VP_MARGIN: u8 = 2;
+
+/// Bounds for `find_forced_win_with`.
+#[derive(Debug, Clone)]
+pub struct SolverOptions {
+    /// Longest line of actions tried within the turn.
+    pub max_depth: usize,
+    /// Upper bound on states visited before giving up.
+    pub max_nodes: usize,
+    /// Consider Monopoly, whose haul depends on the opponents' hands. Only
+    /// sound when those hands are known.
+    pub monopoly: bool,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_nodes: 500,
+            monopoly: true,
+        }
+    }
+}
+
+/// Looks for a sequence of the current player's own actions this turn that
+/// wins the game no matter what: builds, bank trades and dev card plays.
+/// Only actions with a single known outcome are considered, so rolling,
+/// buying dev cards and stealing never appear in a returned line.
+///
+/// Searches `state` as is, opponents' hands included, so it is for analysis
+/// and perfect-information play. Bots use `find_observed_forced_win`.
+pub fn find_forced_win(state: &GameState) -> Option<Vec<GameAction>> {
+    find_forced_win_with(state, &SolverOptions::default())
+}
+
+pub fn find_forced_win_with(state: &GameState, options: &SolverOptions) -> Option<Vec<GameAction>> {
+    if !worth_searching(state) {
+        return None;
+    }
+    search_line(state, options)
+}
+
+/// `find_forced_win` for bots, which can't see the opponents' hands. Only
+/// Monopoly's haul depends on them, so it is left out and every line
+/// returned wins whatever the opponents actually hold.
+pub fn find_observed_forced_win(state: &GameState) -> Option<Vec<GameAction>> {
+    let options = SolverOptions {
+        monopoly: false,
+        ..SolverOptions::default()
+    };
+    find_forced_win_with(state, &options)
+}
+
+/// Whether the current player is free to act and close enough to winning
+/// for a search to have a chance.
+fn worth_searching(state: &GameState) -> bool {
+    if !matches!(state.phase, GamePhase::Playing)
+        || state.pending_prompt != ActionPrompt::PlayTurn
+        || state
+            .legal_actions()
+            .iter()
+            .any(|action| action.action_type == ActionType::Roll)
+    {
+        return false;
+    }
+    let points = state.team_points(state.team_of(state.current_player));
+    points + (SOLVER_VP_MARGIN as u32) >= state.config.vps_to_win as u32
+}
+
+fn search_line(state: &GameState, options: &SolverOptions) -> Option<Vec<GameAction>> {
+    let player_idx = state.current_player;
+    let mut line = Vec::new();
+    let mut budget = options.max_nodes;
+    if search(
+        state,
+        player_idx,
+        options,
+        options.max_depth,
+        &mut budget,
+        &mut line,
+    ) {
+        Some(line)
+    } else {
+        None
+    }
+}
+
+fn search(
+    state: &GameState,
+    player_idx: usize,
+    options: &SolverOptions,
+    depth: usize,
+    budget: &mut usize,
+    line: &mut Vec<GameAction>,
+) -> bool {
+    if depth == 0 {
+        return false;
+    }
+    let mut candidates: Vec<&GameAction> = state
+        .legal_actions()
+        .iter()
+        .filter(|action| action.player_index == player_idx && is_deterministic(action, options))
+        .collect();
+    candidates.sort_by_key(|action| action_rank(state, action));
+
+    for action in candidates {
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+
+        let mut next = state.clone();
+        if next.step(action.clone()).is_err() {
+            continue;
+        }
+        line.push(action.clone());
+        if let GamePhase::Completed {
+            winner: Some(winner),
+        } = next.phase
+        {
+            if state.team_of(winner) == state.team_of(player_idx) {
+                return true;
+            }
+        } else if next.current_player == player_idx
+            && search(&next, player_idx, options, depth - 1, budget, line)
+        {
+            return true;
+        }
+        line.pop();
+    }
+    false
+}
+
+fn is_deterministic(action: &GameAction, options: &SolverOptions) -> bool {
+    match action.action_type {
+        ActionType::PlayMonopoly => options.monopoly,
+        ActionType::BuildSettlement
+        | ActionType::BuildCity
+        | ActionType::BuildRoad
+        | ActionType::MaritimeTrade
+        | ActionType::PlayYearOfPlenty
+        | ActionType::PlayRoadBuilding
+        | ActionType::PlayKnightCard => true,
+        // Moving the robber is only certain when nobody gets robbed.
+        ActionType::MoveRobber => {
+            matches!(action.payload, ActionPayload::Robber { victim: None, .. })
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::bank::Bank;
+    use crate::game::dice::{DiceConfig, DiceRoll};
+    use crate::game::resources::ResourceBundle;
+    use crate::game::setup::SetupMode;
+    use crate::game::state::GameConfig;
+    use crate::types::{DevelopmentCard, Resource};
+
+    /// Two players past setup, seat 0 one city short of winning and done
+    /// rolling, with seat `i` holding `hands[i]` and seat 0 a Monopoly card
+    /// ready to play.
+    fn one_city_from_winning(hands: [&[(Resource, u8)]; 2]) -> GameState {
+        let mut state = GameState::new(GameConfig {
+            num_players: 2,
+            setup: SetupMode::Random,
+            dice: DiceConfig::Scripted(vec![DiceRoll::new(1, 1)]),
+            ..GameConfig::default()
+        });
+        state.config.vps_to_win = state.team_points(state.team_of(0)) as u8 + 1;
+        state.step(GameAction::roll(0)).unwrap();
+        for player in &mut state.players {
+            let hand = std::mem::take(&mut player.resources);
+            state.bank.receive(&hand);
+        }
+        let mut deck = state.bank.development_deck().to_vec();
+        let monopoly = deck
+            .iter()
+            .position(|card| *card == DevelopmentCard::Monopoly)
+            .unwrap();
+        let card = deck.remove(monopoly);
+        deck.push(card);
+        state.bank = Bank::from_parts(*state.bank.resources(), deck);
+        let card = state.bank.draw_development_card().unwrap();
+        state.players[0].dev_cards.push(card);
+
+        for (seat, cards) in hands.iter().enumerate() {
+            let mut hand = ResourceBundle::zero();
+            for (resource, count) in cards.iter() {
+                hand.add(*resource, *count);
+            }
+            state.bank.dispense(&hand).unwrap();
+            state.players[seat].resources = hand;
+        }
+        state.refresh_available_actions();
+        state
+    }
+
+    #[test]
+    fn finds_a_city_that_wins_on_the_spot() {
+        let state = one_city_from_winning([&[(Resource::Wheat, 2), (Resource::Ore, 3)], &[]]);
+        let line = find_observed_forced_win(&state).unwrap();
+        assert_eq!(line.last().unwrap().action_type, ActionType::BuildCity);
+        assert_eq!(find_forced_win(&state), Some(line));
+    }
+
+    #[test]
+    fn bots_do_not_count_on_a_monopoly_haul() {
+        // The ore for the city is only there if the opponent really holds it.
+        let state = one_city_from_winning([&[(Resource::Wheat, 2)], &[(Resource::Ore, 3)]]);
+        let line = find_forced_win(&state).unwrap();
+        assert_eq!(line[0], GameAction::play_monopoly(0, Resource::Ore));
+        assert_eq!(find_observed_forced_win(&state), None);
+    }
+}
@@ -3,174 +3,52 @@ use std::collections::HashSet;
 use crate::game::{
     action::{ActionPayload, GameAction},
     game::Game,
-    state::{GamePhase, GameState, Structure},
+    state::{GamePhase, GameState},
 };
 use crate::types::{ActionPrompt, ActionType, Color, Resource};
 
-fn number_probability(number: u8) -> f64 {
-    match number {
-        2 | 12 => 1.0 / 36.0,
-        3 | 11 => 2.0 / 36.0,
-        4 | 10 => 3.0 / 36.0,
-        5 | 9 => 4.0 / 36.0,
-        6 | 8 => 5.0 / 36.0,
-        7 => 6.0 / 36.0,
-        _ => 0.0,
-    }
-}
-
-fn execute_deterministic(game: &Game, action: &GameAction) -> Vec<(Game, f64)> {
-    let mut next = game.copy();
-    let a = action.clone();
-    if next.state.step(a).is_ok() {
-        vec![(next, 1.0)]
-    } else {
-        Vec::new()
-    }
-}
-
-fn execute_roll(game: &Game, action: &GameAction) -> Vec<(Game, f64)> {
-    let mut outcomes = Vec::new();
-    for sum in 2u8..=12 {
-        let p = number_probability(sum);
-        if p == 0.0 {
-            continue;
-        }
-        // Same mapping as Python: (roll//2, ceil(roll/2))
-        let d1 = sum / 2;
-        let d2 = if sum % 2 == 0 { sum / 2 } else { sum / 2 + 1 };
-        let mut next = game.copy();
-        let mut a = action.clone();
-        a.payload = ActionPayload::Dice(d1, d2);
-        if next.state.step(a).is_ok() {
-            outcomes.push((next, p));
-        }
-    }
-    outcomes
-}
-
-fn execute_buy_development(game: &Game, action: &GameAction) -> Vec<(Game, f64)> {
-    // Python uses an inferred dev-deck distribution based on hidden information.
-    // Our Bank API does not expose the dev deck composition, so we approximate
-    // this as a single deterministic branch and let GameState handle the draw.
-    execute_deterministic(game, action)
-}
-
-fn execute_move_robber(game: &Game, action: &GameAction) -> Vec<(Game, f64)> {
-    let mut outcomes = Vec::new();
-    let state = &game.state;
-
-    let (tile_id, victim_opt) = match action.payload {
-        ActionPayload::Robber {
-            tile_id, victim, ..
-        } => (tile_id, victim),
-        _ => return execute_deterministic(game, action),
-    };
-
-    // If there is no victim, the move is deterministic.
-    if victim_opt.is_none() {
-        return execute_deterministic(game, action);
-    }
-
-    let victim_idx = victim_opt.unwrap();
-    if victim_idx >= state.players.len() {
-        return execute_deterministic(game, action);
-    }
-
-    let victim = &state.players[victim_idx];
-    // Count total cards
-    let mut total_cards: u32 = 0;
-    for r in Resource::ALL {
-        total_cards += victim.resources.get(r) as u32;
-    }
-    if total_cards == 0 {
-        return execute_deterministic(game, action);
-    }
-
-    // Enumerate possible stolen resources (uniform over resource types present)
-    let mut candidate_resources = Vec::new();
-    for r in Resource::ALL {
-        if victim.resources.get(r) > 0 {
-            candidate_resources.push(r);
-        }
-    }
-    if candidate_resources.is_empty() {
-        return execute_deterministic(game, action);
-    }
-
-    let p = 1.0 / (candidate_resources.len() as f64);
-    for res in candidate_resources {
-        let mut next = game.copy();
-        let mut a = action.clone();
-        a.payload = ActionPayload::Robber {
-            tile_id,
-            victim: Some(victim_idx),
-            resource: Some(res),
-        };
-        if next.state.step(a).is_ok() {
-            outcomes.push((next, p));
-        }
-    }
-
-    outcomes
-}
-
 /// Mirror of Python `execute_spectrum`: expand a Game+Action into one or more
-/// possible successor states, each with an associated probability.
+/// possible successor states, each with an associated probability. The odds
+/// come from `GameState::chance_outcomes`.
 pub fn execute_spectrum(game: &Game, action: &GameAction) -> Vec<(Game, f64)> {
-    match action.action_type {
-        ActionType::Roll => execute_roll(game, action),
-        ActionType::BuildSettlement
-        | ActionType::BuildRoad
-        | ActionType::BuildCity
-        | ActionType::EndTurn
-        | ActionType::PlayKnightCard
-        | ActionType::PlayYearOfPlenty
-        | ActionType::PlayRoadBuilding
-        | ActionType::MaritimeTrade
-        | ActionType::Discard
-        | ActionType::OfferTrade
-        | ActionType::AcceptTrade
-        | ActionType::RejectTrade
-        | ActionType::ConfirmTrade
-        | ActionType::CancelTrade => execute_deterministic(game, action),
-        ActionType::BuyDevelopmentCard => execute_buy_development(game, action),
-        ActionType::MoveRobber => execute_move_robber(game, action),
-        ActionType::PlayMonopoly => execute_deterministic(game, action),
-    }
+    game.state
+        .chance_outcomes(action)
+        .into_iter()
+        .filter_map(|(outcome, p)| {
+            let mut next = game.copy();
+            next.state
+                .apply_chance_outcome(outcome)
+                .ok()
+                .map(|_| (next, p))
+        })
+        .collect()
 }
 
-fn player_has_port(state: &GameState, player_idx: usize, port: Option<Resource>) -> bool {
-    if let Some(nodes) = state.map.port_nodes.get(&port) {
-        nodes
-            .iter()
-            .any(|node| match state.node_occupancy.get(node) {
-                Some(Structure::Settlement { player }) | Some(Structure::City { player }) => {
-                    *player == player_idx
-                }
-                _ => false,
-            })
-    } else {
-        false
-    }
+/// Knobs for `list_pruned_actions_with`.
+#[derive(Debug, Clone)]
+pub struct PruneOptions {
+    /// How many robber tiles to keep, ranked by how much production they take
+    /// away from the leading opponent.
+    pub robber_top_k: usize,
 }
 
-fn maritime_rate(state: &GameState, player_idx: usize, resource: Resource) -> u8 {
-    if player_has_port(state, player_idx, Some(resource)) {
-        return 2;
-    }
-    if player_has_port(state, player_idx, None) {
-        return 3;
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self { robber_top_k: 1 }
     }
-    4
 }
 
 /// Rough mirror of Python `list_prunned_actions`. We implement the same
 /// high-level pruning rules:
 /// - During initial settlement placement, prune 1-tile locations.
 /// - When a 3:1 port is available, prune clearly dominated 4:1 maritime trades.
-/// Robber pruning based on production-impact is intentionally omitted.
+/// - Only keep robber moves onto the tiles that hurt the leading opponent's
+///   production the most.
 pub fn list_pruned_actions(game: &Game) -> Vec<GameAction> {
+    list_pruned_actions_with(game, &PruneOptions::default())
+}
+
+pub fn list_pruned_actions_with(game: &Game, options: &PruneOptions) -> Vec<GameAction> {
     let state: &GameState = &game.state;
     let mut actions: Vec<GameAction> = state.legal_actions().to_vec();
 
@@ -213,7 +91,7 @@ pub fn list_pruned_actions(game: &Game) -> Vec<GameAction> {
 
     // 2) Prune maritime trades when a 3:1 port is available: drop 4:1 trades.
     if types.contains(&ActionType::MaritimeTrade) {
-        let has_three_to_one = player_has_port(state, current_player, None);
+        let has_three_to_one = state.trade_rates(current_player).contains(&3);
         if has_three_to_one {
             let mut pruned = Vec::with_capacity(actions.len());
             for a in actions.into_iter() {
@@ -237,7 +115,7 @@ pub fn list_pruned_actions(game: &Game) -> Vec<GameAction> {
                     }
                 }
                 if let Some(resource) = given {
-                    if maritime_rate(state, current_player, resource) == 4 {
+                    if state.maritime_rate(current_player, resource) == 4 {
                         // 4:1 trade while a 3:1 port exists: prune
                         continue;
                     }
@@ -248,9 +126,85 @@ pub fn list_pruned_actions(game: &Game) -> Vec<GameAction> {
         }
     }
 
-    // 3) Robber pruning based on production impact is not implemented here.
-    // All robber actions are kept. This preserves core game legality while
-    // simplifying the search heuristic.
+    // 3) Keep only robber moves onto the most damaging tiles.
+    if types.contains(&ActionType::MoveRobber) {
+        actions = prune_robber_actions(state, actions, options.robber_top_k);
+    }
+
+    actions
+}
+
+fn prune_robber_actions(
+    state: &GameState,
+    actions: Vec<GameAction>,
+    top_k: usize,
+) -> Vec<GameAction> {
+    let current_player = state.current_player;
+    let Some(leader) = (0..state.players.len())
+        .filter(|idx| *idx != current_player)
+        .max_by(|a, b| {
+            let points = |idx: usize| state.players[idx].public_points();
+            let income = |idx: usize| state.expected_income(idx).total();
+            points(*a)
+                .cmp(&points(*b))
+                .then(income(*a).total_cmp(&income(*b)))
+        })
+    else {
+        return actions;
+    };
+
+    let mut impacts: Vec<(u16, f64, f64)> = Vec::new();
+    for action in &actions {
+        if let ActionPayload::Robber { tile_id, .. } = action.payload {
+            if impacts.iter().any(|(tile, _, _)| *tile == tile_id) {
+                continue;
+            }
+            impacts.push((
+                tile_id,
+                robber_production_loss(state, leader, tile_id),
+                robber_production_loss(state, current_player, tile_id),
+            ));
+        }
+    }
+    // Most damage to the leader first; among equals, least damage to ourselves.
+    impacts.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then(a.2.total_cmp(&b.2))
+            .then(a.0.cmp(&b.0))
+    });
+    let keep: HashSet<u16> = impacts
+        .iter()
+        .take(top_k.max(1))
+        .map(|(tile, _, _)| *tile)
+        .collect();
 
     actions
+        .into_iter()
+        .filter(|action| match action.payload {
+            ActionPayload::Robber { tile_id, .. } => keep.contains(&tile_id),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Expected cards per roll `player_idx` would lose if the robber moved from
+/// its current tile onto `tile_id`.
+fn robber_production_loss(state: &GameState, player_idx: usize, tile_id: u16) -> f64 {
+    let player = &state.players[player_idx];
+    let buildings = player
+        .settlements
+        .iter()
+        .map(|node| (*node, 1u32))
+        .chain(player.cities.iter().map(|node| (*node, 2u32)));
+    buildings
+        .map(|(node, multiplier)| {
+            let now = state
+                .map
+                .expected_production_with_robber(node, Some(state.robber_tile));
+            let after = state
+                .map
+                .expected_production_with_robber(node, Some(tile_id));
+            (now.total() - after.total()) * multiplier as f64
+        })
+        .sum()
 }
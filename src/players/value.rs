@@ -1,18 +1,28 @@
-use crate::game::action::GameAction;
+use crate::features::{FeatureCollection, collect_features};
+use crate::game::action::{ActionPayload, GameAction};
 use crate::game::game::Game;
 use crate::game::players::PlayerState;
+use crate::game::state::GameState;
 use crate::players::BasePlayer;
-use crate::types::Color;
-use rand::{Rng, seq::SliceRandom};
+use crate::players::solver::find_observed_forced_win;
+use crate::types::{ActionType, Color, Resource};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Clone)]
 pub struct ValueFunctionPlayer {
     pub color: Color,
     pub params: ValueFunctionParams,
     pub epsilon: Option<f64>,
+    rng: StdRng,
 }
 
-#[derive(Debug, Clone)]
+/// Weights of the value function. Files may list only the weights they
+/// change; the rest keep their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ValueFunctionParams {
     pub public_vps: f64,
     pub production: f64,
@@ -49,73 +59,190 @@ impl Default for ValueFunctionParams {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum ParamsFileError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid JSON in {path}: {source}")]
+    Json {
+        path: String,
+        source: serde_json::Error,
+    },
+    #[error("invalid TOML in {path}: {source}")]
+    Toml {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl ValueFunctionParams {
+    /// Loads weights from a `.toml` file, or JSON for any other extension.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ParamsFileError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let text = std::fs::read_to_string(path).map_err(|source| ParamsFileError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+        {
+            toml::from_str(&text).map_err(|source| ParamsFileError::Toml {
+                path: display,
+                source,
+            })
+        } else {
+            serde_json::from_str(&text).map_err(|source| ParamsFileError::Json {
+                path: display,
+                source,
+            })
+        }
+    }
+
+    /// How good `state` looks for `player_idx` under these weights.
+    pub fn value(&self, state: &GameState, player_idx: usize) -> f64 {
+        evaluate_features(state, &collect_features(state, player_idx), self)
+    }
+}
+
 impl ValueFunctionPlayer {
     pub fn new(color: Color, params: Option<ValueFunctionParams>, epsilon: Option<f64>) -> Self {
         Self {
             color,
             params: params.unwrap_or_default(),
             epsilon,
+            rng: StdRng::seed_from_u64(0),
         }
     }
+
+    /// The value of the position after each of `actions`, in their order.
+    /// `None` if the player has no seat in `game`.
+    fn action_values(&self, game: &Game, actions: &[GameAction]) -> Option<Vec<(GameAction, f64)>> {
+        let player_idx = game
+            .state
+            .players
+            .iter()
+            .position(|p| p.color == self.color)?;
+
+        let parent = collect_features(&game.state, player_idx);
+        let values = actions
+            .iter()
+            .map(|action| {
+                let mut game_copy = game.copy();
+                if !apply_hypothetical_trade(&mut game_copy, player_idx, action) {
+                    game_copy.execute(action.clone());
+                }
+                let features = FeatureCollection::update_from(&parent, action, &game_copy.state);
+                let value = evaluate_features(&game_copy.state, &features, &self.params);
+                (action.clone(), value)
+            })
+            .collect();
+        Some(values)
+    }
 }
 
 impl BasePlayer for ValueFunctionPlayer {
-    fn decide(&self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
+    fn decide(&mut self, game: &Game, actions: &[GameAction]) -> Option<GameAction> {
         if actions.len() == 1 {
             return actions.first().cloned();
         }
 
-        // Epsilon-greedy exploration
-        if let Some(epsilon) = self.epsilon {
-            let mut rng = rand::thread_rng();
-            if rng.gen_bool(epsilon) {
-                return actions.choose(&mut rng).cloned();
-            }
+        if let Some(line) = find_observed_forced_win(&game.state) {
+            return line.into_iter().next();
         }
 
-        // Find player index
-        let player_idx = game
-            .state
-            .players
-            .iter()
-            .position(|p| p.color == self.color)?;
+        // Epsilon-greedy exploration
+        if let Some(epsilon) = self.epsilon
+            && self.rng.gen_bool(epsilon)
+        {
+            return actions.choose(&mut self.rng).cloned();
+        }
 
         // Evaluate each action (must match Python implementation exactly)
         let mut best_value = f64::NEG_INFINITY;
         let mut best_action = None;
-
-        for action in actions {
-            let mut game_copy = game.copy();
-            game_copy.execute(action.clone());
-
-            let value = evaluate_state(&game_copy, player_idx, &self.params);
+        for (action, value) in self.action_values(game, actions)? {
             if value > best_value {
                 best_value = value;
-                best_action = Some(action.clone());
+                best_action = Some(action);
             }
         }
 
         best_action
     }
-}
 
-fn evaluate_state(game: &Game, player_idx: usize, params: &ValueFunctionParams) -> f64 {
-    let player = &game.state.players[player_idx];
-    let total_vps = player.total_points() as f64;
+    fn reseed(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
 
-    // Production (simplified - would need feature extraction for full implementation)
-    let production = calculate_production(game, player_idx);
-    let enemy_production = calculate_enemy_production(game, player_idx);
+    fn score_actions(
+        &mut self,
+        game: &Game,
+        actions: &[GameAction],
+    ) -> Option<Vec<(GameAction, f64)>> {
+        let mut scored = self.action_values(game, actions)?;
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Some(scored)
+    }
+}
 
-    // Longest road
-    let longest_road_length = calculate_longest_road_length(game, player_idx) as f64;
+/// Offering or accepting a domestic trade doesn't move any cards until the
+/// offerer confirms, so score those actions as if the exchange went through.
+/// Returns false for every other action.
+pub(super) fn apply_hypothetical_trade(
+    game: &mut Game,
+    player_idx: usize,
+    action: &GameAction,
+) -> bool {
+    let (give, receive) = match (action.action_type, &action.payload) {
+        (ActionType::OfferTrade, ActionPayload::Trade { give, receive, .. }) => (*give, *receive),
+        (ActionType::AcceptTrade, _) => match game.state.trade_state() {
+            // The acceptee hands over what the offerer asked for.
+            Some(trade) => (trade.receive, trade.give),
+            None => return false,
+        },
+        _ => return false,
+    };
+    let hand = &mut game.state.players[player_idx].resources;
+    if hand.subtract_bundle(&give).is_err() {
+        return false;
+    }
+    hand.add_bundle(&receive);
+    true
+}
+
+pub(super) fn evaluate_state(game: &Game, player_idx: usize, params: &ValueFunctionParams) -> f64 {
+    params.value(&game.state, player_idx)
+}
 
-    // Buildable nodes (simplified)
-    let buildable_nodes = count_buildable_nodes(game, player_idx) as f64;
+/// Scores `state` for the player `features` were extracted for. Terms
+/// shared with feature extraction are read from it, so the heuristic scores
+/// exactly what the features describe.
+fn evaluate_features(
+    state: &GameState,
+    features: &FeatureCollection,
+    params: &ValueFunctionParams,
+) -> f64 {
+    let player_idx = features.perspective;
+    let production_of = |relative_idx: usize| -> f64 {
+        Resource::ALL
+            .iter()
+            .map(|resource| features.production(relative_idx, *resource) as f64)
+            .sum()
+    };
 
-    // Hand resources
-    let hand_resources = player.resources.total() as f64;
-    let hand_devs = (player.dev_cards.len() + player.fresh_dev_cards.len()) as f64;
+    let total_vps = features.actual_vps() as f64;
+    let production = production_of(0);
+    let enemy_production: f64 = (1..state.players.len()).map(production_of).sum();
+    let longest_road_length = features.longest_road_length() as f64;
+    let buildable_nodes = features.buildable_nodes() as f64;
+    let hand_resources = features.resources_in_hand() as f64;
+    let hand_devs = features.devs_in_hand() as f64;
+    let army_size = features.knights_played() as f64;
 
     // Discard penalty
     let discard_penalty = if hand_resources > 7.0 {
@@ -124,22 +251,15 @@ fn evaluate_state(game: &Game, player_idx: usize, params: &ValueFunctionParams)
         0.0
     };
 
-    // Hand synergy (simplified)
+    let player = &state.players[player_idx];
     let hand_synergy = calculate_hand_synergy(player);
 
     // Number of tiles controlled
-    let num_tiles = count_controlled_tiles(game, player_idx) as f64;
-
-    // Army size (knights played)
-    let army_size = player
-        .dev_cards
-        .iter()
-        .filter(|card| matches!(card, crate::types::DevelopmentCard::Knight))
-        .count() as f64;
+    let num_tiles = count_controlled_tiles(state, player_idx) as f64;
 
-    // Reachable production (simplified - would need full feature extraction)
-    let reachable_production_0 = 0.0; // Would need reachability features
-    let reachable_production_1 = 0.0; // Would need reachability features
+    // Production of the spots reachable with zero and one more road
+    let reachable_production_0 = calculate_reachable_production(state, player_idx, 0);
+    let reachable_production_1 = calculate_reachable_production(state, player_idx, 1);
 
     let longest_road_factor = if buildable_nodes == 0.0 {
         params.longest_road
@@ -162,93 +282,22 @@ fn evaluate_state(game: &Game, player_idx: usize, params: &ValueFunctionParams)
         + army_size * params.army_size
 }
 
-fn calculate_production(game: &Game, player_idx: usize) -> f64 {
-    let player = &game.state.players[player_idx];
-    let mut production = 0.0;
-
-    // Get all nodes with buildings
-    let mut owned_nodes = player.settlements.clone();
-    owned_nodes.extend(&player.cities);
-
-    for node_id in owned_nodes {
-        if let Some(tile_ids) = game.state.map.adjacent_tiles.get(&node_id) {
-            for tile_id in tile_ids {
-                if let Some(tile) = game.state.map.tiles_by_id.get(tile_id) {
-                    if let (Some(_resource), Some(number)) = (tile.resource, tile.number) {
-                        let proba = number_probability(number);
-                        production += proba;
-                    }
-                }
-            }
-        }
-    }
-
-    production
-}
-
-fn calculate_enemy_production(game: &Game, player_idx: usize) -> f64 {
-    let mut total = 0.0;
-    for idx in 0..game.state.players.len() {
-        if idx != player_idx {
-            total += calculate_production(game, idx);
-        }
-    }
-    total
-}
-
-fn calculate_longest_road_length(game: &Game, player_idx: usize) -> usize {
-    // Simplified - would need full longest road calculation
-    game.state.players[player_idx].roads.len()
-}
-
-fn count_buildable_nodes(game: &Game, player_idx: usize) -> usize {
-    // Simplified - would need full validation logic
-    let player = &game.state.players[player_idx];
-    let mut count = 0;
-    for node_id in &game.state.map.land_nodes {
-        if !player.settlements.contains(node_id) && !player.cities.contains(node_id) {
-            // Check if node is too close to other buildings (simplified)
-            let mut too_close = false;
-            for other_node in &game.state.map.land_nodes {
-                if *other_node != *node_id {
-                    // Check if nodes are adjacent (simplified check)
-                    if are_nodes_adjacent(game, *node_id, *other_node) {
-                        if game.state.node_occupancy.contains_key(other_node) {
-                            too_close = true;
-                            break;
-                        }
-                    }
-                }
-            }
-            if !too_close {
-                count += 1;
-            }
-        }
-    }
-    count
-}
-
-fn are_nodes_adjacent(
-    game: &Game,
-    node_a: crate::board::NodeId,
-    node_b: crate::board::NodeId,
-) -> bool {
-    // Check if nodes share an edge
-    for edge in game.state.map.node_edges.get(&node_a).unwrap_or(&vec![]) {
-        if edge.0 == node_b || edge.1 == node_b {
-            return true;
-        }
-    }
-    false
+fn calculate_reachable_production(state: &GameState, player_idx: usize, max_roads: usize) -> f64 {
+    state
+        .reachable_nodes(player_idx, max_roads)
+        .iter()
+        .filter_map(|node| state.map.node_production.get(node))
+        .flat_map(|production| production.values())
+        .map(|proba| *proba as f64)
+        .sum()
 }
 
 fn calculate_hand_synergy(player: &PlayerState) -> f64 {
-    // Simplified hand synergy calculation
-    let wheat = player.resources.get(crate::types::Resource::Wheat);
-    let ore = player.resources.get(crate::types::Resource::Ore);
-    let sheep = player.resources.get(crate::types::Resource::Sheep);
-    let brick = player.resources.get(crate::types::Resource::Brick);
-    let wood = player.resources.get(crate::types::Resource::Wood);
+    let wheat = player.resources.get(Resource::Wheat);
+    let ore = player.resources.get(Resource::Ore);
+    let sheep = player.resources.get(Resource::Sheep);
+    let brick = player.resources.get(Resource::Brick);
+    let wood = player.resources.get(Resource::Wood);
 
     let distance_to_city = ((2.0 - wheat as f64).max(0.0) + (3.0 - ore as f64).max(0.0)) / 5.0;
     let distance_to_settlement = ((1.0 - wheat as f64).max(0.0)
@@ -260,15 +309,15 @@ fn calculate_hand_synergy(player: &PlayerState) -> f64 {
     (2.0 - distance_to_city - distance_to_settlement) / 2.0
 }
 
-fn count_controlled_tiles(game: &Game, player_idx: usize) -> usize {
-    let player = &game.state.players[player_idx];
+fn count_controlled_tiles(state: &GameState, player_idx: usize) -> usize {
+    let player = &state.players[player_idx];
     let mut owned_tiles = std::collections::HashSet::new();
 
     let mut owned_nodes = player.settlements.clone();
     owned_nodes.extend(&player.cities);
 
     for node_id in owned_nodes {
-        if let Some(tile_ids) = game.state.map.adjacent_tiles.get(&node_id) {
+        if let Some(tile_ids) = state.map.adjacent_tiles.get(&node_id) {
             for tile_id in tile_ids {
                 owned_tiles.insert(*tile_id);
             }
@@ -278,15 +327,103 @@ fn count_controlled_tiles(game: &Game, player_idx: usize) -> usize {
     owned_tiles.len()
 }
 
-fn number_probability(number: u8) -> f64 {
-    // Probability of rolling this number with two dice
-    match number {
-        2 | 12 => 1.0 / 36.0,
-        3 | 11 => 2.0 / 36.0,
-        4 | 10 => 3.0 / 36.0,
-        5 | 9 => 4.0 / 36.0,
-        6 | 8 => 5.0 / 36.0,
-        7 => 6.0 / 36.0,
-        _ => 0.0,
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    use super::*;
+    use crate::game::state::{GameConfig, GamePhase};
+
+    /// Production of `player_idx`'s buildings, straight from the map.
+    fn production(state: &GameState, player_idx: usize, resource: Resource) -> f64 {
+        let player = &state.players[player_idx];
+        let settlements = player.settlements.iter().map(|node| (node, 1.0));
+        let cities = player.cities.iter().map(|node| (node, 2.0));
+        settlements
+            .chain(cities)
+            .filter_map(|(node, multiplier)| {
+                let proba = state.map.node_production.get(node)?.get(&resource)?;
+                Some(*proba as f64 * multiplier)
+            })
+            .sum()
+    }
+
+    fn assert_close(actual: f32, expected: f64, term: &str) {
+        assert!(
+            (actual as f64 - expected).abs() < 1e-5,
+            "{term}: features say {actual}, the engine {expected}"
+        );
+    }
+
+    /// The terms the heuristic reads from the features agree with the
+    /// engine, and with the features of the same name.
+    #[test]
+    fn heuristic_terms_match_the_features() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for seed in 0..4 {
+            let mut state = GameState::new(GameConfig {
+                seed,
+                num_players: 3 + seed as usize % 2,
+                ..GameConfig::default()
+            });
+            let mut step = 0;
+            while !matches!(state.phase, GamePhase::Completed { .. }) && step < 1500 {
+                if step % 25 == 0 {
+                    for seat in 0..state.players.len() {
+                        let features = collect_features(&state, seat);
+                        let named = |name: &str| features.get(name).unwrap();
+                        let player = &state.players[seat];
+
+                        assert_eq!(features.actual_vps(), named("P0_ACTUAL_VPS"));
+                        assert_close(features.actual_vps(), player.total_points() as f64, "vps");
+                        assert_close(
+                            features.longest_road_length(),
+                            state.longest_road_length(seat) as f64,
+                            "longest road",
+                        );
+                        assert_close(
+                            features.buildable_nodes(),
+                            state.buildable_settlement_nodes(seat).len() as f64,
+                            "buildable nodes",
+                        );
+                        assert_close(
+                            features.resources_in_hand(),
+                            player.resources.total() as f64,
+                            "hand",
+                        );
+                        assert_close(
+                            features.devs_in_hand(),
+                            (player.dev_cards.len() + player.fresh_dev_cards.len()) as f64,
+                            "development cards",
+                        );
+                        assert_close(
+                            features.knights_played(),
+                            player.knights_played as f64,
+                            "knights",
+                        );
+                        for relative_idx in 0..state.players.len() {
+                            let other = (seat + relative_idx) % state.players.len();
+                            for resource in Resource::ALL {
+                                let value = features.production(relative_idx, resource);
+                                assert_eq!(
+                                    value,
+                                    named(&format!("P{relative_idx}_{resource:?}_PRODUCTION"))
+                                );
+                                assert_close(
+                                    value,
+                                    production(&state, other, resource),
+                                    "production",
+                                );
+                            }
+                        }
+                    }
+                }
+                let action = state.legal_actions().choose(&mut rng).cloned().unwrap();
+                state.step(action).unwrap();
+                step += 1;
+            }
+        }
     }
 }
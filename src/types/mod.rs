@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter, EnumString};
 
+pub mod probability;
+
 #[derive(
     Debug,
     Clone,
@@ -134,5 +136,39 @@ pub enum ActionType {
     RejectTrade,
     ConfirmTrade,
     CancelTrade,
+    GiftResource,
     EndTurn,
+    /// Leave the game for good. Never among the legal actions, but accepted
+    /// from any player still in the game at any time after setup.
+    Resign,
+    /// Concede the whole game: the player's team loses and the best placed
+    /// opponent wins. Never among the legal actions; played for a player
+    /// whose clock runs out under `TimeoutPolicy::Forfeit`.
+    Forfeit,
+}
+
+impl ActionType {
+    pub const ALL: [ActionType; 21] = [
+        ActionType::Roll,
+        ActionType::MoveRobber,
+        ActionType::Discard,
+        ActionType::BuildRoad,
+        ActionType::BuildSettlement,
+        ActionType::BuildCity,
+        ActionType::BuyDevelopmentCard,
+        ActionType::PlayKnightCard,
+        ActionType::PlayYearOfPlenty,
+        ActionType::PlayMonopoly,
+        ActionType::PlayRoadBuilding,
+        ActionType::MaritimeTrade,
+        ActionType::OfferTrade,
+        ActionType::AcceptTrade,
+        ActionType::RejectTrade,
+        ActionType::ConfirmTrade,
+        ActionType::CancelTrade,
+        ActionType::GiftResource,
+        ActionType::EndTurn,
+        ActionType::Resign,
+        ActionType::Forfeit,
+    ];
 }
@@ -0,0 +1,32 @@
+//! Two-dice roll probabilities. Number tokens, production estimates, bots and
+//! feature extraction all go through these so they agree on the numbers.
+
+/// Number of equally likely outcomes when rolling two six-sided dice.
+pub const DICE_OUTCOMES: u32 = 36;
+
+/// Every sum two dice can roll, in increasing order.
+pub const ROLL_SUMS: std::ops::RangeInclusive<u8> = 2..=12;
+
+/// Ways to roll `number` with two dice, which is also the number of pips
+/// printed on a number token. Anything outside 2..=12 has no ways.
+pub fn number_pips(number: u8) -> u32 {
+    match number {
+        2..=7 => number as u32 - 1,
+        8..=12 => 13 - number as u32,
+        _ => 0,
+    }
+}
+
+/// Probability of rolling `number` with two dice.
+pub fn number_probability(number: u8) -> f64 {
+    pips_to_probability(number_pips(number))
+}
+
+pub fn pips_to_probability(pips: u32) -> f64 {
+    pips as f64 / DICE_OUTCOMES as f64
+}
+
+/// Inverse of `pips_to_probability`, rounded to the nearest pip.
+pub fn probability_to_pips(probability: f64) -> u32 {
+    (probability * DICE_OUTCOMES as f64).round().max(0.0) as u32
+}